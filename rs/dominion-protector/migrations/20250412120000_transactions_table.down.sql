@@ -0,0 +1,5 @@
+DROP TABLE transaction_object_changes;
+DROP TABLE transaction_events;
+DROP TABLE transaction_commands;
+DROP TABLE transaction_inputs;
+DROP TABLE transactions;
@@ -0,0 +1,75 @@
+CREATE TABLE transactions (
+    digest          VARCHAR(64) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    sender          VARCHAR(66) NOT NULL,
+    checkpoint      BIGINT,
+    timestamp_ms    BIGINT,
+    success         BOOLEAN NOT NULL,
+    error           TEXT,
+    raw_transaction BYTEA NOT NULL,
+    effects         JSONB NOT NULL,
+    read_at         TIMESTAMPTZ NOT NULL DEFAULT Now(),
+
+    PRIMARY KEY(digest, network)
+);
+
+CREATE TABLE transaction_inputs (
+    digest      VARCHAR(64) NOT NULL,
+    network     VARCHAR(10) NOT NULL,
+    input_index INTEGER NOT NULL,
+    kind        VARCHAR(20) NOT NULL,
+    object_id   CHAR(66),
+    value       JSONB NOT NULL,
+
+    PRIMARY KEY(digest, network, input_index),
+    FOREIGN KEY(digest, network)
+        REFERENCES transactions(digest, network)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE transaction_commands (
+    digest        VARCHAR(64) NOT NULL,
+    network       VARCHAR(10) NOT NULL,
+    command_index INTEGER NOT NULL,
+    kind          VARCHAR(20) NOT NULL,
+    package_id    CHAR(66),
+    module_name   TEXT,
+    function_name TEXT,
+    command       JSONB NOT NULL,
+
+    PRIMARY KEY(digest, network, command_index),
+    FOREIGN KEY(digest, network)
+        REFERENCES transactions(digest, network)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE transaction_events (
+    digest      VARCHAR(64) NOT NULL,
+    network     VARCHAR(10) NOT NULL,
+    event_seq   BIGINT NOT NULL,
+    package_id  CHAR(66) NOT NULL,
+    module_name TEXT NOT NULL,
+    sender      VARCHAR(66) NOT NULL,
+    event_type  TEXT NOT NULL,
+    parsed_json JSONB NOT NULL,
+
+    PRIMARY KEY(digest, network, event_seq),
+    FOREIGN KEY(digest, network)
+        REFERENCES transactions(digest, network)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE transaction_object_changes (
+    digest      VARCHAR(64) NOT NULL,
+    network     VARCHAR(10) NOT NULL,
+    object_id   CHAR(66) NOT NULL,
+    change_type VARCHAR(20) NOT NULL,
+    object_type TEXT,
+    version     BIGINT NOT NULL,
+    change      JSONB NOT NULL,
+
+    PRIMARY KEY(digest, network, object_id),
+    FOREIGN KEY(digest, network)
+        REFERENCES transactions(digest, network)
+        ON DELETE CASCADE
+);
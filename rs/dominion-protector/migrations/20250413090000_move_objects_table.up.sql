@@ -0,0 +1,13 @@
+CREATE TABLE move_objects (
+    object_id           CHAR(66) NOT NULL,
+    network             VARCHAR(10) NOT NULL,
+    object_type         TEXT NOT NULL,
+    has_public_transfer BOOLEAN NOT NULL,
+    bcs                 BYTEA NOT NULL,
+    contents            JSONB,
+
+    PRIMARY KEY(object_id, network),
+    FOREIGN KEY(object_id, network)
+        REFERENCES objects(object_id, network)
+        ON DELETE CASCADE
+);
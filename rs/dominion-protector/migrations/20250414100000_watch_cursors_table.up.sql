@@ -0,0 +1,6 @@
+CREATE TABLE watch_cursors (
+    network     VARCHAR(10) NOT NULL,
+    checkpoint  BIGINT NOT NULL,
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT Now(),
+    PRIMARY KEY(network)
+);
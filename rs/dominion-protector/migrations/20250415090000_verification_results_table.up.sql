@@ -0,0 +1,17 @@
+CREATE TABLE verification_results (
+    package_id      CHAR(66) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    module_name     TEXT NOT NULL,
+    function_name   TEXT NOT NULL,
+    property        VARCHAR(64) NOT NULL,
+    subject         TEXT NOT NULL,
+    solver          VARCHAR(10) NOT NULL,
+    verdict         VARCHAR(10) NOT NULL,
+    violated        BOOLEAN,
+    model           TEXT,
+    checked_at      TIMESTAMPTZ NOT NULL DEFAULT Now(),
+    PRIMARY KEY(package_id, network, module_name, function_name, property, subject),
+    FOREIGN KEY(package_id, network, module_name)
+        REFERENCES package_modules(package_id, network, module_name)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,6 @@
+DROP TABLE function_entity_descriptions;
+DROP TABLE function_descriptions;
+DROP TABLE struct_descriptions;
+DROP TABLE module_descriptions;
+DROP TYPE entity_kind;
+DROP TYPE security_level;
@@ -0,0 +1,81 @@
+CREATE TYPE security_level AS ENUM (
+    'Critical Risk',
+    'High Risk',
+    'Medium Risk',
+    'Low Risk',
+    'Best Practices Compliant',
+    'Unknown / Unassessed'
+);
+
+CREATE TYPE entity_kind AS ENUM ('parameter', 'created');
+
+CREATE TABLE module_descriptions (
+    package_id      CHAR(66) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    module_name     TEXT NOT NULL,
+    description     TEXT NOT NULL,
+    security_level  security_level NOT NULL DEFAULT 'Unknown / Unassessed',
+    warnings        TEXT[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY(package_id, network, module_name),
+    FOREIGN KEY(package_id, network, module_name)
+        REFERENCES package_modules(package_id, network, module_name)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE struct_descriptions (
+    package_id      CHAR(66) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    module_name     TEXT NOT NULL,
+    struct_name     TEXT NOT NULL,
+    description     TEXT NOT NULL,
+    address_owned   TEXT,
+    object_owned    TEXT,
+    wrapped         TEXT,
+    shared          TEXT,
+    immutable       TEXT,
+    event           TEXT,
+    warnings        TEXT[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY(package_id, network, module_name, struct_name),
+    FOREIGN KEY(package_id, network, module_name)
+        REFERENCES module_descriptions(package_id, network, module_name)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE function_descriptions (
+    package_id      CHAR(66) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    module_name     TEXT NOT NULL,
+    function_name   TEXT NOT NULL,
+    description     TEXT NOT NULL,
+    security_level  security_level NOT NULL DEFAULT 'Unknown / Unassessed',
+    warnings        TEXT[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY(package_id, network, module_name, function_name),
+    FOREIGN KEY(package_id, network, module_name)
+        REFERENCES module_descriptions(package_id, network, module_name)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE function_entity_descriptions (
+    package_id      CHAR(66) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    module_name     TEXT NOT NULL,
+    function_name   TEXT NOT NULL,
+    kind            entity_kind NOT NULL,
+    index           INT,
+    name            TEXT NOT NULL,
+    proper_name     TEXT,
+    description     TEXT NOT NULL,
+    type_name       TEXT NOT NULL,
+    returned        TEXT,
+    transferred     TEXT,
+    shared          TEXT,
+    frozen          TEXT,
+    wrapped         TEXT,
+    modified        TEXT,
+    dropped         TEXT,
+    warnings        TEXT[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY(package_id, network, module_name, function_name, kind, name),
+    FOREIGN KEY(package_id, network, module_name, function_name)
+        REFERENCES function_descriptions(package_id, network, module_name, function_name)
+        ON DELETE CASCADE
+);
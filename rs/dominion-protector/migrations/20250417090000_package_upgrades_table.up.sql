@@ -0,0 +1,11 @@
+CREATE TABLE package_upgrades (
+    original_id     CHAR(66) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    version         BIGINT NOT NULL,
+    package_id      CHAR(66) NOT NULL,
+    digest          VARCHAR(64) NOT NULL,
+    upgrade_cap_id  CHAR(66),
+
+    PRIMARY KEY(original_id, network, version)
+);
+CREATE INDEX package_upgrades_package_id ON package_upgrades(package_id, network);
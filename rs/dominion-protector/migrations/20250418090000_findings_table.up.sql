@@ -0,0 +1,15 @@
+CREATE TABLE findings (
+    package_id      CHAR(66) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    rule            VARCHAR(64) NOT NULL,
+    module_name     TEXT NOT NULL,
+    member_name     TEXT NOT NULL,
+    code_offset     INTEGER,
+    severity        security_level NOT NULL,
+    message         TEXT NOT NULL,
+    found_at        TIMESTAMPTZ NOT NULL DEFAULT Now(),
+    PRIMARY KEY(package_id, network, rule, module_name, member_name),
+    FOREIGN KEY(package_id, network, module_name)
+        REFERENCES package_modules(package_id, network, module_name)
+        ON DELETE CASCADE
+);
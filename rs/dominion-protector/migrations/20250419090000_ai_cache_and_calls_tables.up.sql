@@ -0,0 +1,25 @@
+CREATE TABLE ai_cache (
+    model           TEXT NOT NULL,
+    prompt_hash     CHAR(64) NOT NULL,
+    input_hash      CHAR(64) NOT NULL,
+    response        JSONB NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT Now(),
+    PRIMARY KEY(model, prompt_hash, input_hash)
+);
+
+CREATE TABLE ai_calls (
+    id                  BIGSERIAL PRIMARY KEY,
+    package_id          CHAR(66) NOT NULL,
+    network             VARCHAR(10) NOT NULL,
+    module_name         TEXT NOT NULL,
+    prompt              TEXT NOT NULL,
+    model               TEXT NOT NULL,
+    prompt_tokens       INTEGER,
+    completion_tokens   INTEGER,
+    latency_ms          BIGINT NOT NULL,
+    cost                DOUBLE PRECISION NOT NULL,
+    cached              BOOLEAN NOT NULL,
+    called_at           TIMESTAMPTZ NOT NULL DEFAULT Now()
+);
+
+CREATE INDEX ai_calls_package_idx ON ai_calls(package_id, network);
@@ -0,0 +1,2 @@
+ALTER TABLE struct_descriptions DROP COLUMN prompt_versions;
+ALTER TABLE module_descriptions DROP COLUMN prompt_versions;
@@ -0,0 +1,2 @@
+ALTER TABLE module_descriptions ADD COLUMN prompt_versions JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE struct_descriptions ADD COLUMN prompt_versions JSONB NOT NULL DEFAULT '{}';
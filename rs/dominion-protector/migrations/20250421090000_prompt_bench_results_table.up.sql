@@ -0,0 +1,12 @@
+CREATE TABLE prompt_bench_results (
+    id                      BIGSERIAL PRIMARY KEY,
+    package_name            TEXT NOT NULL,
+    package_id              CHAR(66) NOT NULL,
+    network                 VARCHAR(10) NOT NULL,
+    model                   TEXT NOT NULL,
+    description_version     TEXT NOT NULL,
+    security_level_version  TEXT NOT NULL,
+    is_scam                 BOOLEAN NOT NULL,
+    security_level          security_level NOT NULL,
+    benchmarked_at          TIMESTAMPTZ NOT NULL DEFAULT Now()
+);
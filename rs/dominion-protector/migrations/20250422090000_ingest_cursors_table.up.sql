@@ -0,0 +1,7 @@
+CREATE TABLE ingest_cursors (
+    name            TEXT PRIMARY KEY,
+    network         VARCHAR(10) NOT NULL,
+    last_object_id  CHAR(66) NOT NULL,
+    objects_saved   BIGINT NOT NULL,
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT Now()
+);
@@ -0,0 +1,3 @@
+ALTER TABLE ingest_cursors DROP COLUMN last_file;
+DELETE FROM ingest_cursors WHERE last_object_id IS NULL;
+ALTER TABLE ingest_cursors ALTER COLUMN last_object_id SET NOT NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE ingest_cursors ALTER COLUMN last_object_id DROP NOT NULL;
+ALTER TABLE ingest_cursors ADD COLUMN last_file TEXT;
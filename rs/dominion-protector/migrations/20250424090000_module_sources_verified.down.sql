@@ -0,0 +1 @@
+ALTER TABLE module_sources DROP COLUMN verified;
@@ -0,0 +1 @@
+ALTER TABLE module_sources ADD COLUMN verified BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,2 @@
+DROP TABLE source_verifications;
+DROP TYPE module_match;
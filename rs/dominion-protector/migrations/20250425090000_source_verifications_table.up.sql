@@ -0,0 +1,15 @@
+CREATE TYPE module_match AS ENUM ('matches', 'differs', 'missing', 'extra');
+
+CREATE TABLE source_verifications (
+    package_id      CHAR(66) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    module_name     TEXT NOT NULL,
+    kind            VARCHAR(20) NOT NULL,
+    result          module_match NOT NULL,
+    differences     JSONB NOT NULL DEFAULT '[]',
+    verified_at     TIMESTAMPTZ NOT NULL DEFAULT Now(),
+    PRIMARY KEY(package_id, network, module_name, kind),
+    FOREIGN KEY(package_id, network)
+        REFERENCES objects(object_id, network)
+        ON DELETE CASCADE
+);
@@ -0,0 +1 @@
+DROP TABLE module_source_maps;
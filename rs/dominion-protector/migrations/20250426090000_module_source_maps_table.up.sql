@@ -0,0 +1,13 @@
+CREATE TABLE module_source_maps (
+    package_id      CHAR(66) NOT NULL,
+    network         VARCHAR(10) NOT NULL,
+    module_name     TEXT NOT NULL,
+    kind            VARCHAR(20) NOT NULL,
+    source_map      BYTEA NOT NULL,
+    links           JSONB,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT Now(),
+    PRIMARY KEY(package_id, network, module_name),
+    FOREIGN KEY(package_id, network, module_name)
+        REFERENCES package_modules(package_id, network, module_name)
+        ON DELETE CASCADE
+);
@@ -0,0 +1 @@
+DROP TABLE structures_usage_packages;
@@ -0,0 +1,9 @@
+CREATE TABLE structures_usage_packages (
+    package_id  CHAR(66) NOT NULL,
+    network     VARCHAR(10) NOT NULL,
+    saved_at    TIMESTAMPTZ NOT NULL DEFAULT Now(),
+    PRIMARY KEY(package_id, network),
+    FOREIGN KEY(package_id, network)
+        REFERENCES objects(object_id, network)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use openai_dive::v1::resources::chat::{ChatCompletionParameters, ChatCompletionResponse};
+use serde_json::json;
+
+use super::Provider;
+
+/// Answers without any model, to run the whole pipeline in CI. JSON requests get the emptiest
+/// value their schema allows, everything else the same fixed answer.
+pub struct Mock {
+    answer: String,
+}
+
+impl Mock {
+    pub fn new(answer: &str) -> Self {
+        Self {
+            answer: answer.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for Mock {
+    async fn complete(&self, params: ChatCompletionParameters) -> Result<ChatCompletionResponse> {
+        let format = serde_json::to_value(&params.response_format)?;
+        let content = match format["json_schema"]["schema"]["type"].as_str() {
+            Some("array") => "[]".to_string(),
+            Some("object") => "{}".to_string(),
+            Some(_) => "null".to_string(),
+            None => self.answer.clone(),
+        };
+        Ok(serde_json::from_value(json!({
+            "id": "mock",
+            "object": "chat.completion",
+            "created": 0,
+            "model": params.model,
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": content},
+                "finish_reason": "stop",
+            }],
+            "usage": {"prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0},
+        }))?)
+    }
+}
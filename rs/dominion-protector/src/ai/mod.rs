@@ -0,0 +1,468 @@
+use std::{
+    env,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use jsonschema::Validator;
+use openai_dive::v1::resources::chat::{
+    ChatCompletionParameters, ChatCompletionParametersBuilder, ChatCompletionResponse,
+    ChatCompletionResponseFormat, ChatMessage, ChatMessageContent, JsonSchemaBuilder,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use sui_sdk::types::base_types::ObjectID;
+use tokio::{fs, time::timeout};
+
+use crate::{
+    db::{ai_cache::AiCacheEntry, ai_call::AiCall, Db},
+    prompts::Prompts,
+};
+use anyhow::{anyhow, bail, Context, Result};
+
+pub mod mock;
+pub mod openai;
+pub mod replay;
+
+/// Requests for an answer before giving up.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Backend answering chat completions.
+#[async_trait]
+pub trait Provider: Send + Sync {
+    async fn complete(&self, params: ChatCompletionParameters) -> Result<ChatCompletionResponse>;
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    /// Any OpenAI compatible API: Atoma, OpenAI or a self-hosted llama.cpp server
+    OpenAi,
+    /// Recorded responses only, nothing leaves the machine
+    Replay,
+    /// OpenAI compatible API, recording every response for replaying later
+    Record,
+    /// Fixed answers without any model, for CI
+    Mock,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiConfig {
+    pub provider: ProviderKind,
+    /// A llama.cpp server listens on `http://localhost:8080/v1`
+    pub base_url: String,
+    pub model: String,
+    /// Environment variable holding the API key, self-hosted servers usually need none
+    pub api_key_env: Option<String>,
+    pub temperature: Option<f32>,
+    pub timeout_secs: u64,
+    pub replay_dir: PathBuf,
+    /// What the mock provider answers to text requests
+    pub mock_answer: String,
+    pub prompts: PathBuf,
+    /// Answer requests made before from `ai_cache` instead of the provider
+    pub cache: bool,
+    /// USD per million prompt tokens
+    pub prompt_price: f64,
+    /// USD per million completion tokens
+    pub completion_price: f64,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            provider: ProviderKind::OpenAi,
+            base_url: "https://api.atoma.network/v1".to_owned(),
+            model: "deepseek-ai/DeepSeek-R1".to_owned(),
+            api_key_env: Some("AI_API_KEY".to_owned()),
+            temperature: None,
+            timeout_secs: 300,
+            replay_dir: PathBuf::from("ai_replays"),
+            mock_answer: "Unknown / Unassessed".to_owned(),
+            prompts: PathBuf::from("prompts.yaml"),
+            cache: true,
+            prompt_price: 0.0,
+            completion_price: 0.0,
+        }
+    }
+}
+
+impl AiConfig {
+    /// Reads the file `AI_CONFIG` points to, or `ai.yaml` when there is one.
+    pub async fn load() -> Result<Self> {
+        let path = match env::var("AI_CONFIG") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) if fs::try_exists("ai.yaml").await? => PathBuf::from("ai.yaml"),
+            Err(_) => return Ok(Self::default()),
+        };
+        let contents = fs::read(&path)
+            .await
+            .with_context(|| format!("Can not read {}", path.display()))?;
+        Ok(serde_yml::from_slice(&contents)?)
+    }
+
+    fn api_key(&self) -> Result<String> {
+        match &self.api_key_env {
+            Some(name) => env::var(name).with_context(|| format!("{} is not set", name)),
+            None => Ok(String::new()),
+        }
+    }
+
+    pub fn provider(&self) -> Result<Box<dyn Provider>> {
+        Ok(match self.provider {
+            ProviderKind::OpenAi => Box::new(openai::OpenAiCompatible::new(
+                &self.base_url,
+                self.api_key()?,
+            )),
+            ProviderKind::Replay => Box::new(replay::Replay::new(&self.replay_dir)),
+            ProviderKind::Record => Box::new(replay::Recorder::new(
+                Box::new(openai::OpenAiCompatible::new(
+                    &self.base_url,
+                    self.api_key()?,
+                )),
+                &self.replay_dir,
+            )),
+            ProviderKind::Mock => Box::new(mock::Mock::new(&self.mock_answer)),
+        })
+    }
+}
+
+/// What a request is made for, recorded in `ai_calls` with its usage.
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub package_id: String,
+    pub network: String,
+    pub module_name: String,
+    /// Prompt the request answers, like `module.warnings`
+    pub prompt: String,
+}
+
+impl Call {
+    pub fn new(package_id: &ObjectID, network: &str, module_name: &str, prompt: &str) -> Self {
+        Self {
+            package_id: package_id.to_string(),
+            network: network.to_string(),
+            module_name: module_name.to_string(),
+            prompt: prompt.to_string(),
+        }
+    }
+}
+
+pub struct AI {
+    provider: Box<dyn Provider>,
+    /// Calls are only cached and accounted for with a database
+    db: Option<PgPool>,
+    cache: bool,
+    pub prompts: Prompts,
+    pub model: String,
+    /// Used for requests not setting their own
+    pub temperature: Option<f32>,
+    pub timeout: Duration,
+    pub prompt_price: f64,
+    pub completion_price: f64,
+}
+
+impl AI {
+    pub async fn new(db: &Db) -> Result<Self> {
+        let config = AiConfig::load().await?;
+        Ok(Self::with_provider(
+            config.provider()?,
+            Prompts::load(&config.prompts).await?,
+            &config,
+        )
+        .with_db(db))
+    }
+
+    pub fn with_provider(provider: Box<dyn Provider>, prompts: Prompts, config: &AiConfig) -> Self {
+        Self {
+            provider,
+            db: None,
+            cache: config.cache,
+            prompts,
+            model: config.model.clone(),
+            temperature: config.temperature,
+            timeout: Duration::from_secs(config.timeout_secs),
+            prompt_price: config.prompt_price,
+            completion_price: config.completion_price,
+        }
+    }
+
+    /// Caches and accounts for calls in `db`.
+    pub fn with_db(self, db: &Db) -> Self {
+        Self {
+            db: Some(db.pool.clone()),
+            ..self
+        }
+    }
+
+    fn with_defaults(&self, mut params: ChatCompletionParameters) -> ChatCompletionParameters {
+        if params.temperature.is_none() {
+            params.temperature = self.temperature;
+        }
+        params
+    }
+
+    /// Asks the provider, bypassing the cache. The call is recorded in `ai_calls`.
+    pub async fn complete(
+        &self,
+        call: &Call,
+        params: ChatCompletionParameters,
+    ) -> Result<ChatCompletionResponse> {
+        let params = self.with_defaults(params);
+        let model = params.model.clone();
+        let started = Instant::now();
+        let response = timeout(self.timeout, self.provider.complete(params))
+            .await
+            .context("AI request timed out")??;
+        self.record(call, &model, &response, started.elapsed(), false)
+            .await?;
+        Ok(response)
+    }
+
+    async fn record(
+        &self,
+        call: &Call,
+        model: &str,
+        response: &ChatCompletionResponse,
+        latency: Duration,
+        cached: bool,
+    ) -> Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+        let prompt_tokens = response
+            .usage
+            .as_ref()
+            .and_then(|usage| usage.prompt_tokens);
+        let completion_tokens = response
+            .usage
+            .as_ref()
+            .and_then(|usage| usage.completion_tokens);
+        let cost = if cached {
+            0.0
+        } else {
+            (prompt_tokens.unwrap_or(0) as f64 * self.prompt_price
+                + completion_tokens.unwrap_or(0) as f64 * self.completion_price)
+                / 1_000_000.0
+        };
+        AiCall {
+            package_id: call.package_id.clone(),
+            network: call.network.clone(),
+            module_name: call.module_name.clone(),
+            prompt: call.prompt.clone(),
+            model: model.to_string(),
+            prompt_tokens: prompt_tokens.map(|tokens| tokens as i32),
+            completion_tokens: completion_tokens.map(|tokens| tokens as i32),
+            latency_ms: latency.as_millis() as i64,
+            cost,
+            cached,
+            called_at: Utc::now(),
+        }
+        .save(db)
+        .await
+    }
+
+    /// Response to an identical request answered before, if the cache is on.
+    async fn cached(
+        &self,
+        call: &Call,
+        params: &ChatCompletionParameters,
+        (prompt_hash, input_hash): &(String, String),
+    ) -> Result<Option<ChatCompletionResponse>> {
+        let (Some(db), true) = (&self.db, self.cache) else {
+            return Ok(None);
+        };
+        let started = Instant::now();
+        let Some(entry) = AiCacheEntry::load(db, &params.model, prompt_hash, input_hash).await?
+        else {
+            return Ok(None);
+        };
+        let response = serde_json::from_value(entry.response)?;
+        self.record(call, &params.model, &response, started.elapsed(), true)
+            .await?;
+        Ok(Some(response))
+    }
+
+    async fn store(
+        &self,
+        params: &ChatCompletionParameters,
+        (prompt_hash, input_hash): &(String, String),
+        response: &ChatCompletionResponse,
+    ) -> Result<()> {
+        let (Some(db), true) = (&self.db, self.cache) else {
+            return Ok(());
+        };
+        AiCacheEntry {
+            model: params.model.clone(),
+            prompt_hash: prompt_hash.clone(),
+            input_hash: input_hash.clone(),
+            response: serde_json::to_value(response)?,
+            created_at: Utc::now(),
+        }
+        .save(db)
+        .await
+    }
+
+    /// Text of the answer, without the reasoning of thinking models. Unusable answers are
+    /// requested again, up to `MAX_ATTEMPTS` times, and only usable ones are cached.
+    pub async fn text_request(
+        &self,
+        call: &Call,
+        params: ChatCompletionParameters,
+    ) -> Result<(String, ChatMessage)> {
+        let (text, message) = self
+            .checked_request(call, params, |text| Ok(text.to_string()))
+            .await?;
+        Ok((text?, message))
+    }
+
+    /// Like [`AI::text_request`], with `check` turning the text into the answer. Answers it
+    /// rejects are returned with the reason for the caller to repair, and are neither cached
+    /// nor taken from the cache.
+    async fn checked_request<T>(
+        &self,
+        call: &Call,
+        params: ChatCompletionParameters,
+        check: impl Fn(&str) -> Result<T>,
+    ) -> Result<(Result<T>, ChatMessage)> {
+        let params = self.with_defaults(params);
+        let key = cache_key(&params)?;
+        if let Some(result) = self.cached(call, &params, &key).await? {
+            match answer(&result).and_then(|(text, message)| Ok((check(&text)?, message))) {
+                Ok((value, message)) => return Ok((Ok(value), message)),
+                Err(err) => println!("Unusable cached response: {}", err),
+            }
+        }
+        let mut last_error = anyhow!("No attempts made");
+        for _ in 0..MAX_ATTEMPTS {
+            let result = self.complete(call, params.clone()).await?;
+            match answer(&result) {
+                Ok((text, message)) => {
+                    let checked = check(&text);
+                    if checked.is_ok() {
+                        self.store(&params, &key, &result).await?;
+                    }
+                    return Ok((checked, message));
+                }
+                Err(err) => {
+                    println!("Unusable response {:?}: {}", result, err);
+                    last_error = err;
+                }
+            }
+        }
+        Err(last_error.context(format!("No usable response in {} attempts", MAX_ATTEMPTS)))
+    }
+
+    /// Asks for JSON matching `schema` and deserializes it. Answers not matching the schema are
+    /// sent back with the validation errors, up to `MAX_ATTEMPTS` times. Only the accepted
+    /// answer is added to `messages` and cached.
+    pub async fn json_request<T: DeserializeOwned>(
+        &self,
+        call: &Call,
+        messages: &mut Vec<ChatMessage>,
+        name: &str,
+        description: &str,
+        schema: Value,
+    ) -> Result<T> {
+        let validator = jsonschema::validator_for(&schema)
+            .map_err(|err| anyhow!("Invalid schema {}: {}", name, err))?;
+        let response_format = ChatCompletionResponseFormat::JsonSchema(
+            JsonSchemaBuilder::default()
+                .description(description)
+                .name(name)
+                .schema(schema)
+                .strict(true)
+                .build()?,
+        );
+        let mut conversation = messages.clone();
+        let mut last_error = anyhow!("No attempts made");
+        for _ in 0..MAX_ATTEMPTS {
+            let parameters = ChatCompletionParametersBuilder::default()
+                .model(self.model.clone())
+                .messages(conversation.as_slice())
+                .response_format(response_format.clone())
+                .build()?;
+            let (parsed, message) = self
+                .checked_request(call, parameters, |text| parse(&validator, text))
+                .await?;
+            match parsed {
+                Ok(value) => {
+                    messages.push(message);
+                    return Ok(value);
+                }
+                Err(err) => {
+                    println!("Invalid {}: {}", name, err);
+                    conversation.push(message);
+                    conversation.push(ChatMessage::User {
+                        content: ChatMessageContent::Text(format!(
+                            "Your answer is not valid: {}. Reply with the corrected JSON only.",
+                            err
+                        )),
+                        name: None,
+                    });
+                    last_error = err;
+                }
+            }
+        }
+        Err(last_error.context(format!("No valid {} in {} attempts", name, MAX_ATTEMPTS)))
+    }
+}
+
+pub(crate) fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Hashes of the prompt answered, the last message, and of everything else in the request.
+fn cache_key(params: &ChatCompletionParameters) -> Result<(String, String)> {
+    let mut input = params.clone();
+    let prompt = input.messages.pop().context("Request without messages")?;
+    Ok((
+        sha256(&serde_json::to_vec(&prompt)?),
+        sha256(&serde_json::to_vec(&input)?),
+    ))
+}
+
+fn answer(result: &ChatCompletionResponse) -> Result<(String, ChatMessage)> {
+    let message = &result.choices.first().context("No choices")?.message;
+    let ChatMessage::Assistant {
+        content: Some(ChatMessageContent::Text(text)),
+        ..
+    } = message
+    else {
+        bail!("No text in the response");
+    };
+    let text = if text.starts_with("<think>") {
+        let end = text.find("</think>").context("No </think> tag")?;
+        text[end + 8..].to_string()
+    } else {
+        text.clone()
+    };
+    Ok((text, message.clone()))
+}
+
+fn parse<T: DeserializeOwned>(validator: &Validator, response: &str) -> Result<T> {
+    // Models wrap JSON in markdown fences even when asked for JSON only
+    let response = response.trim();
+    let response = response
+        .strip_prefix("```json")
+        .or_else(|| response.strip_prefix("```"))
+        .and_then(|response| response.strip_suffix("```"))
+        .unwrap_or(response);
+    let value: Value = serde_json::from_str(response)?;
+    let errors = validator
+        .iter_errors(&value)
+        .map(|err| err.to_string())
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        bail!(errors.join("; "));
+    }
+    Ok(serde_json::from_value(value)?)
+}
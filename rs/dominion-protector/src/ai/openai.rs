@@ -0,0 +1,27 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use openai_dive::v1::{
+    api::Client,
+    resources::chat::{ChatCompletionParameters, ChatCompletionResponse},
+};
+
+use super::Provider;
+
+pub struct OpenAiCompatible {
+    client: Client,
+}
+
+impl OpenAiCompatible {
+    pub fn new(base_url: &str, api_key: String) -> Self {
+        let mut client = Client::new(api_key);
+        client.set_base_url(base_url);
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAiCompatible {
+    async fn complete(&self, params: ChatCompletionParameters) -> Result<ChatCompletionResponse> {
+        Ok(self.client.chat().create(params).await?)
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use openai_dive::v1::resources::chat::{ChatCompletionParameters, ChatCompletionResponse};
+use tokio::fs;
+
+use super::{sha256, Provider};
+
+/// Recorded responses are named after a hash of the whole request, so any change to the
+/// prompts, the model or the conversation needs a new recording.
+fn recording(dir: &Path, params: &ChatCompletionParameters) -> Result<PathBuf> {
+    let name = sha256(&serde_json::to_vec(params)?);
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Serves recorded responses and fails on requests nobody recorded.
+pub struct Replay {
+    dir: PathBuf,
+}
+
+impl Replay {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for Replay {
+    async fn complete(&self, params: ChatCompletionParameters) -> Result<ChatCompletionResponse> {
+        let path = recording(&self.dir, &params)?;
+        let contents = fs::read(&path)
+            .await
+            .with_context(|| format!("No recorded response {}", path.display()))?;
+        Ok(serde_json::from_slice(&contents)?)
+    }
+}
+
+/// Passes requests on and records the responses for `Replay`.
+pub struct Recorder {
+    inner: Box<dyn Provider>,
+    dir: PathBuf,
+}
+
+impl Recorder {
+    pub fn new(inner: Box<dyn Provider>, dir: &Path) -> Self {
+        Self {
+            inner,
+            dir: dir.to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for Recorder {
+    async fn complete(&self, params: ChatCompletionParameters) -> Result<ChatCompletionResponse> {
+        let path = recording(&self.dir, &params)?;
+        let response = self.inner.complete(params).await?;
+        fs::create_dir_all(&self.dir).await?;
+        fs::write(&path, serde_json::to_vec_pretty(&response)?).await?;
+        Ok(response)
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Context, Result};
+use move_binary_format::{
+    file_format::{SignatureToken, Visibility},
+    CompiledModule,
+};
+use move_core_types::language_storage::StructTag;
+use serde::Serialize;
+use sui_sdk::{
+    rpc_types::SuiRawData,
+    types::base_types::{ObjectID, ObjectType},
+};
+use sui_types::{
+    gas_coin::GasCoin,
+    transaction::{
+        Argument, CallArg, Command, ObjectArg, ProgrammableMoveCall, TransactionData,
+        TransactionDataAPI, TransactionKind,
+    },
+    SUI_FRAMEWORK_ADDRESS,
+};
+
+use crate::{
+    call_graph::{framework_effect, function_definition, CallGraph, Effect, Modules, Pattern},
+    commands::download::{get_or_download_modules, get_or_download_object},
+    db::{
+        descriptions::{ModuleDescription, SecurityLevel},
+        Db,
+    },
+    simulator::{Simulation, TransactionSimulator},
+    sui_client::SuiClientWithNetwork,
+};
+
+#[derive(Debug, Serialize)]
+pub struct TransactionReport {
+    pub security_level: SecurityLevel,
+    pub commands: Vec<CommandReport>,
+    /// Outcome of executing the transaction locally, `None` when it could not run
+    pub simulation: Option<Simulation>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommandReport {
+    pub index: usize,
+    pub kind: &'static str,
+    /// `package::module::function` of move calls
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub security_level: SecurityLevel,
+    pub objects: Vec<ObjectReport>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectReport {
+    /// Input object, or the input coin a split coin comes from
+    pub object_id: ObjectID,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub effects: Vec<Effect>,
+}
+
+/// Object a command argument refers to.
+#[derive(Clone)]
+struct Value {
+    object_id: ObjectID,
+    pattern: Pattern,
+    type_: String,
+}
+
+impl Value {
+    fn new(object_id: ObjectID, type_: &StructTag) -> Self {
+        Self {
+            object_id,
+            pattern: Pattern::from_struct_tag(type_),
+            type_: type_.to_canonical_string(true),
+        }
+    }
+
+    fn report(&self, effects: Vec<Effect>) -> ObjectReport {
+        ObjectReport {
+            object_id: self.object_id,
+            type_: self.type_.clone(),
+            effects,
+        }
+    }
+}
+
+pub struct TransactionAnalyzer<'a> {
+    client: &'a SuiClientWithNetwork,
+    db: &'a Db,
+    packages: BTreeMap<ObjectID, Modules>,
+}
+
+impl<'a> TransactionAnalyzer<'a> {
+    pub fn new(client: &'a SuiClientWithNetwork, db: &'a Db) -> Self {
+        Self {
+            client,
+            db,
+            packages: BTreeMap::new(),
+        }
+    }
+
+    /// Reports what every command could do with the objects it gets, along with what the
+    /// transaction does when executed locally.
+    pub async fn analyze(&mut self, transaction: &TransactionData) -> Result<TransactionReport> {
+        let TransactionKind::ProgrammableTransaction(transaction_block) = transaction.kind() else {
+            bail!("Only programmable transactions can be analyzed");
+        };
+
+        let mut inputs = Vec::with_capacity(transaction_block.inputs.len());
+        for input in &transaction_block.inputs {
+            let object_id = match input {
+                CallArg::Object(ObjectArg::ImmOrOwnedObject((object_id, _, _)))
+                | CallArg::Object(ObjectArg::Receiving((object_id, _, _)))
+                | CallArg::Object(ObjectArg::SharedObject { id: object_id, .. }) => *object_id,
+                _ => {
+                    inputs.push(None);
+                    continue;
+                }
+            };
+            let object = get_or_download_object(&object_id, self.client, self.db).await?;
+            inputs.push(match object.type_ {
+                Some(ObjectType::Struct(type_)) => Some(Value::new(object_id, &type_.into())),
+                _ => None,
+            });
+        }
+        let gas = transaction
+            .gas()
+            .first()
+            .map(|(object_id, _, _)| Value::new(*object_id, &GasCoin::type_()));
+
+        let mut results: Vec<Vec<Value>> = Vec::with_capacity(transaction_block.commands.len());
+        let mut commands = Vec::with_capacity(transaction_block.commands.len());
+        let value = |results: &Vec<Vec<Value>>, argument: &Argument| -> Option<Value> {
+            match argument {
+                Argument::GasCoin => gas.clone(),
+                Argument::Input(i) => inputs.get(*i as usize).cloned().flatten(),
+                Argument::Result(i) => results.get(*i as usize)?.first().cloned(),
+                Argument::NestedResult(i, j) => results.get(*i as usize)?.get(*j as usize).cloned(),
+            }
+        };
+        for (index, command) in transaction_block.commands.iter().enumerate() {
+            let mut report = CommandReport {
+                index,
+                kind: "",
+                target: None,
+                security_level: SecurityLevel::LowRisk,
+                objects: Vec::new(),
+                warnings: Vec::new(),
+            };
+            let mut result = Vec::new();
+            match command {
+                Command::MoveCall(call) => {
+                    report.kind = "move_call";
+                    let arguments = call
+                        .arguments
+                        .iter()
+                        .map(|argument| value(&results, argument))
+                        .collect::<Vec<_>>();
+                    if let Err(err) = self.analyze_call(call, &arguments, &mut report).await {
+                        report.security_level = SecurityLevel::UnknownUnassessed;
+                        report.warnings.push(format!("{:#}", err));
+                    }
+                }
+                Command::TransferObjects(objects, _) => {
+                    report.kind = "transfer_objects";
+                    for object in objects.iter().filter_map(|object| value(&results, object)) {
+                        report
+                            .objects
+                            .push(object.report(vec![Effect::Transferred]));
+                    }
+                }
+                Command::SplitCoins(coin, amounts) => {
+                    report.kind = "split_coins";
+                    // Split coins are reported as the coin they are taken from
+                    if let Some(coin) = value(&results, coin) {
+                        result = vec![coin; amounts.len()];
+                    }
+                }
+                Command::MergeCoins(_, coins) => {
+                    report.kind = "merge_coins";
+                    for coin in coins.iter().filter_map(|coin| value(&results, coin)) {
+                        report.objects.push(coin.report(vec![Effect::Destroyed]));
+                    }
+                }
+                Command::MakeMoveVec(_, _) => report.kind = "make_move_vec",
+                Command::Publish(_, _) => report.kind = "publish",
+                Command::Upgrade(_, _, _, _) => {
+                    report.kind = "upgrade";
+                    report.security_level = SecurityLevel::MediumRisk;
+                    report
+                        .warnings
+                        .push("Upgrades a package, changing the code it runs".to_string());
+                }
+            }
+            results.push(result);
+            commands.push(report);
+        }
+
+        let security_level = commands
+            .iter()
+            .fold(SecurityLevel::LowRisk, |level, command| {
+                level.max(command.security_level)
+            });
+        let mut warnings = Vec::new();
+        let simulation = match TransactionSimulator::new(self.client, self.db)
+            .simulate(transaction)
+            .await
+        {
+            Ok(simulation) => Some(simulation),
+            Err(err) => {
+                warnings.push(format!("Simulation failed: {:#}", err));
+                None
+            }
+        };
+        Ok(TransactionReport {
+            security_level,
+            commands,
+            simulation,
+            warnings,
+        })
+    }
+
+    async fn analyze_call(
+        &mut self,
+        call: &ProgrammableMoveCall,
+        arguments: &[Option<Value>],
+        report: &mut CommandReport,
+    ) -> Result<()> {
+        report.target = Some(format!(
+            "{}::{}::{}",
+            call.package, call.module, call.function
+        ));
+        let package = get_or_download_object(&call.package, self.client, self.db).await?;
+        let Some(SuiRawData::Package(package)) = package.bcs else {
+            bail!("{} is not a package", call.package);
+        };
+        let module = CompiledModule::deserialize_with_defaults(
+            package
+                .module_map
+                .get(call.module.as_str())
+                .context("Module not found")?,
+        )?;
+        let def =
+            function_definition(&module, call.function.as_str()).context("Function not found")?;
+        if def.visibility != Visibility::Public && !def.is_entry {
+            bail!("Function can not be called from a transaction");
+        }
+        let type_args = call
+            .type_arguments
+            .iter()
+            .map(|type_argument| Ok(Pattern::from_type_tag(&type_argument.as_type_tag()?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let params = &module
+            .signature_at(module.function_handle_at(def.function).parameters)
+            .0;
+
+        let framework = *module.self_id().address() == SUI_FRAMEWORK_ADDRESS;
+        let mut graph = CallGraph::default();
+        if framework {
+            if let Some((effect, subject)) =
+                framework_effect(call.module.as_str(), call.function.as_str())
+            {
+                graph.record(
+                    effect,
+                    subject.pattern(&type_args),
+                    report.target.clone().unwrap_or_default(),
+                );
+            }
+        } else {
+            if !self.packages.contains_key(&call.package) {
+                let modules = get_or_download_modules(&call.package, self.client, self.db)
+                    .await?
+                    .into_iter()
+                    .map(|module| {
+                        let id = module.self_id();
+                        ((*id.address(), id.name().to_string()), module)
+                    })
+                    .collect();
+                self.packages.insert(call.package, modules);
+            }
+            graph.visit(&self.packages[&call.package], &module, def, type_args);
+        }
+
+        let mut level = SecurityLevel::LowRisk;
+        for (argument, param) in arguments.iter().zip(params) {
+            let Some(value) = argument else {
+                continue;
+            };
+            let by_value = !is_reference(param);
+            let mutable = matches!(param, SignatureToken::MutableReference(_));
+            let effects = graph
+                .effects
+                .keys()
+                .filter(|(effect, pattern)| {
+                    pattern.matches(&value.pattern)
+                        && match effect {
+                            Effect::Withdrawn => mutable,
+                            _ => by_value,
+                        }
+                })
+                .map(|(effect, _)| *effect)
+                .collect::<BTreeSet<_>>();
+            // The framework only does what the transaction author asked for
+            if !framework {
+                for effect in &effects {
+                    level = level.max(match effect {
+                        Effect::Transferred => SecurityLevel::HighRisk,
+                        _ => SecurityLevel::MediumRisk,
+                    });
+                }
+            }
+            if !effects.is_empty() || by_value {
+                report
+                    .objects
+                    .push(value.report(effects.into_iter().collect()));
+            }
+        }
+        if !graph.unresolved.is_empty() {
+            level = level.max(SecurityLevel::UnknownUnassessed);
+            report.warnings.extend(graph.unresolved);
+        }
+        if let Some(description) = ModuleDescription::load(
+            &self.db.pool,
+            &call.package,
+            &self.client.network,
+            call.module.as_str(),
+        )
+        .await?
+        {
+            level = level.max(description.security_level);
+            report.warnings.extend(description.warnings);
+        }
+        report.security_level = level;
+        Ok(())
+    }
+}
+
+fn is_reference(token: &SignatureToken) -> bool {
+    matches!(
+        token,
+        SignatureToken::Reference(_) | SignatureToken::MutableReference(_)
+    )
+}
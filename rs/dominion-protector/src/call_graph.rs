@@ -0,0 +1,518 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use anyhow::{bail, Result};
+use move_binary_format::{
+    file_format::{
+        Bytecode, DatatypeHandleIndex, FunctionDefinition, FunctionHandleIndex, SignatureToken,
+        StructDefinitionIndex,
+    },
+    CompiledModule,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{StructTag, TypeTag},
+};
+use serde::Serialize;
+use sui_sdk::{rpc_types::SuiRawData, types::base_types::ObjectID};
+use sui_types::{MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS, SUI_SYSTEM_ADDRESS};
+
+use crate::{
+    commands::download::{get_or_download_modules, get_or_download_object},
+    db::Db,
+    sui_client::SuiClientWithNetwork,
+};
+
+/// Calls deeper than this are not followed and make the analysis incomplete.
+const MAX_CALL_DEPTH: usize = 32;
+
+/// What a call could do with an object it gets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    Transferred,
+    Shared,
+    Frozen,
+    Wrapped,
+    /// Added to another object as a dynamic object field
+    ObjectOwned,
+    Destroyed,
+    /// Part of the balance taken from a coin passed by mutable reference
+    Withdrawn,
+    Emitted,
+}
+
+/// Type in which type parameters nobody instantiated are left as `Any`, matching every type.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Pattern {
+    Any,
+    Primitive,
+    Vector(Box<Pattern>),
+    Datatype {
+        address: AccountAddress,
+        module: String,
+        name: String,
+        type_params: Vec<Pattern>,
+    },
+}
+
+impl Pattern {
+    pub(crate) fn from_token(
+        module: &CompiledModule,
+        token: &SignatureToken,
+        type_args: &[Pattern],
+    ) -> Self {
+        match token {
+            SignatureToken::Vector(inner) => {
+                Pattern::Vector(Box::new(Self::from_token(module, inner, type_args)))
+            }
+            SignatureToken::Datatype(index) => Self::datatype(module, *index, Vec::new()),
+            SignatureToken::DatatypeInstantiation(i) => Self::datatype(
+                module,
+                i.0,
+                i.1.iter()
+                    .map(|t| Self::from_token(module, t, type_args))
+                    .collect(),
+            ),
+            SignatureToken::Reference(inner) | SignatureToken::MutableReference(inner) => {
+                Self::from_token(module, inner, type_args)
+            }
+            SignatureToken::TypeParameter(index) => type_args
+                .get(*index as usize)
+                .cloned()
+                .unwrap_or(Pattern::Any),
+            _ => Pattern::Primitive,
+        }
+    }
+
+    pub(crate) fn datatype(
+        module: &CompiledModule,
+        index: DatatypeHandleIndex,
+        type_params: Vec<Pattern>,
+    ) -> Self {
+        let handle = module.datatype_handle_at(index);
+        let m = module.module_handle_at(handle.module);
+        Pattern::Datatype {
+            address: *module.address_identifier_at(m.address),
+            module: module.identifier_at(m.name).to_string(),
+            name: module.identifier_at(handle.name).to_string(),
+            type_params,
+        }
+    }
+
+    pub(crate) fn from_type_tag(tag: &TypeTag) -> Self {
+        match tag {
+            TypeTag::Vector(inner) => Pattern::Vector(Box::new(Self::from_type_tag(inner))),
+            TypeTag::Struct(s) => Self::from_struct_tag(s),
+            _ => Pattern::Primitive,
+        }
+    }
+
+    pub(crate) fn from_struct_tag(s: &StructTag) -> Self {
+        Pattern::Datatype {
+            address: s.address,
+            module: s.module.to_string(),
+            name: s.name.to_string(),
+            type_params: s.type_params.iter().map(Self::from_type_tag).collect(),
+        }
+    }
+
+    fn coin(type_arg: Pattern) -> Self {
+        Pattern::Datatype {
+            address: SUI_FRAMEWORK_ADDRESS,
+            module: "coin".to_string(),
+            name: "Coin".to_string(),
+            type_params: vec![type_arg],
+        }
+    }
+
+    pub(crate) fn matches(&self, other: &Pattern) -> bool {
+        match (self, other) {
+            (Pattern::Any, _) | (_, Pattern::Any) => true,
+            (Pattern::Primitive, Pattern::Primitive) => true,
+            (Pattern::Vector(a), Pattern::Vector(b)) => a.matches(b),
+            (
+                Pattern::Datatype {
+                    address,
+                    module,
+                    name,
+                    type_params,
+                },
+                Pattern::Datatype {
+                    address: other_address,
+                    module: other_module,
+                    name: other_name,
+                    type_params: other_type_params,
+                },
+            ) => {
+                address == other_address
+                    && module == other_module
+                    && name == other_name
+                    && type_params.len() == other_type_params.len()
+                    && type_params
+                        .iter()
+                        .zip(other_type_params)
+                        .all(|(a, b)| a.matches(b))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Any => write!(f, "_"),
+            Pattern::Primitive => write!(f, "primitive"),
+            Pattern::Vector(inner) => write!(f, "vector<{}>", inner),
+            Pattern::Datatype {
+                address,
+                module,
+                name,
+                type_params,
+            } => {
+                write!(f, "{}::{}::{}", address.to_hex_literal(), module, name)?;
+                if !type_params.is_empty() {
+                    let params = type_params
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>();
+                    write!(f, "<{}>", params.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// What the object a framework function works on is, in terms of its type arguments.
+#[derive(Clone, Copy)]
+pub(crate) enum Subject {
+    TypeArgument(usize),
+    CoinOf(usize),
+}
+
+impl Subject {
+    pub(crate) fn pattern(self, type_args: &[Pattern]) -> Pattern {
+        match self {
+            Subject::TypeArgument(i) => type_args.get(i).cloned().unwrap_or(Pattern::Any),
+            Subject::CoinOf(i) => Pattern::coin(type_args.get(i).cloned().unwrap_or(Pattern::Any)),
+        }
+    }
+}
+
+/// Framework functions that move objects out of the caller's hands or emit them as events.
+/// The framework is not downloaded, so everything else it does is ignored.
+pub(crate) fn framework_effect(module: &str, function: &str) -> Option<(Effect, Subject)> {
+    use Effect::*;
+    use Subject::*;
+    Some(match (module, function) {
+        (
+            "transfer",
+            "transfer" | "public_transfer" | "party_transfer" | "public_party_transfer",
+        ) => (Transferred, TypeArgument(0)),
+        ("transfer", "share_object" | "public_share_object") => (Shared, TypeArgument(0)),
+        ("transfer", "freeze_object" | "public_freeze_object") => (Frozen, TypeArgument(0)),
+        ("dynamic_field", "add") => (Wrapped, TypeArgument(1)),
+        ("dynamic_object_field", "add") => (ObjectOwned, TypeArgument(1)),
+        ("event", "emit") => (Emitted, TypeArgument(0)),
+        ("coin", "destroy_zero" | "burn" | "join" | "into_balance") => (Destroyed, CoinOf(0)),
+        ("coin", "put") => (Wrapped, CoinOf(0)),
+        ("coin", "split" | "take" | "balance_mut" | "divide_into_n") => (Withdrawn, CoinOf(0)),
+        ("pay", "split" | "split_vec" | "split_and_transfer" | "divide_and_keep") => {
+            (Withdrawn, CoinOf(0))
+        }
+        ("pay", "join" | "join_vec" | "join_vec_and_transfer") => (Destroyed, CoinOf(0)),
+        ("pay", "keep") => (Transferred, CoinOf(0)),
+        _ => return None,
+    })
+}
+
+pub(crate) fn is_framework(address: &AccountAddress) -> bool {
+    *address == MOVE_STDLIB_ADDRESS
+        || *address == SUI_FRAMEWORK_ADDRESS
+        || *address == SUI_SYSTEM_ADDRESS
+}
+
+/// Whether the function handle refers to `0x2::<module_name>::<name>`.
+pub(crate) fn is_framework_call(
+    module: &CompiledModule,
+    index: FunctionHandleIndex,
+    module_name: &str,
+    name: &str,
+) -> bool {
+    let handle = module.function_handle_at(index);
+    let callee = module.module_handle_at(handle.module);
+    *module.address_identifier_at(callee.address) == SUI_FRAMEWORK_ADDRESS
+        && module.identifier_at(callee.name).as_str() == module_name
+        && module.identifier_at(handle.name).as_str() == name
+}
+
+pub(crate) type Modules = BTreeMap<(AccountAddress, String), CompiledModule>;
+
+/// Modules of the package, and every module calls from them can reach outside the framework.
+pub(crate) async fn load_modules(
+    package_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<(Vec<CompiledModule>, Modules)> {
+    let package = get_or_download_object(package_id, client, db).await?;
+    let Some(SuiRawData::Package(package)) = package.bcs else {
+        bail!("Object is not a package");
+    };
+    let own_modules = package
+        .module_map
+        .values()
+        .map(|bytecode| CompiledModule::deserialize_with_defaults(bytecode))
+        .collect::<Result<Vec<_>, _>>()?;
+    let modules = get_or_download_modules(package_id, client, db)
+        .await?
+        .into_iter()
+        .map(|module| {
+            let id = module.self_id();
+            ((*id.address(), id.name().to_string()), module)
+        })
+        .collect();
+    Ok((own_modules, modules))
+}
+
+pub(crate) fn function_definition<'m>(
+    module: &'m CompiledModule,
+    name: &str,
+) -> Option<&'m FunctionDefinition> {
+    module.function_defs().iter().find(|def| {
+        module
+            .identifier_at(module.function_handle_at(def.function).name)
+            .as_str()
+            == name
+    })
+}
+
+/// Effects of a function and everything it calls, collected by type rather than by value:
+/// any object of an affected type the function gets could be affected.
+#[derive(Default)]
+pub(crate) struct CallGraph {
+    visited: BTreeSet<(AccountAddress, String, String, Vec<Pattern>)>,
+    /// Functions being visited, from the one the analysis started with
+    stack: Vec<String>,
+    pub(crate) effects: BTreeMap<(Effect, Pattern), Trace>,
+    pub(crate) unresolved: BTreeSet<String>,
+}
+
+/// Where an effect comes from.
+#[derive(Debug, Clone)]
+pub(crate) struct Trace {
+    /// Framework function or wrapping datatype causing the effect
+    pub(crate) via: String,
+    /// Functions from the analysed one to the one causing the effect
+    pub(crate) call_chain: Vec<String>,
+}
+
+impl CallGraph {
+    /// Keeps the shortest known call chain for every effect.
+    pub(crate) fn record(&mut self, effect: Effect, pattern: Pattern, via: String) {
+        let trace = Trace {
+            via,
+            call_chain: self.stack.clone(),
+        };
+        match self.effects.get(&(effect, pattern.clone())) {
+            Some(known) if known.call_chain.len() <= trace.call_chain.len() => {}
+            _ => {
+                self.effects.insert((effect, pattern), trace);
+            }
+        }
+    }
+
+    pub(crate) fn visit(
+        &mut self,
+        modules: &Modules,
+        module: &CompiledModule,
+        def: &FunctionDefinition,
+        type_args: Vec<Pattern>,
+    ) {
+        let handle = module.function_handle_at(def.function);
+        let name = module.identifier_at(handle.name).to_string();
+        let self_id = module.self_id();
+        if !self.visited.insert((
+            *self_id.address(),
+            self_id.name().to_string(),
+            name.clone(),
+            type_args.clone(),
+        )) {
+            return;
+        }
+        if self.stack.len() > MAX_CALL_DEPTH {
+            self.unresolved.insert(format!(
+                "{}::{} is called too deep",
+                self_id.to_canonical_display(true),
+                name
+            ));
+            return;
+        }
+        let Some(code) = &def.code else {
+            return;
+        };
+        self.stack.push(format!(
+            "{}::{}::{}",
+            self_id.address().to_hex_literal(),
+            self_id.name(),
+            name
+        ));
+
+        let mut deletes_uid = false;
+        let mut unpacked = Vec::new();
+        for instruction in &code.code {
+            match instruction {
+                Bytecode::Call(index) => {
+                    deletes_uid |= self.visit_call(modules, module, *index, Vec::new());
+                }
+                Bytecode::CallGeneric(index) => {
+                    let instantiation = module.function_instantiation_at(*index);
+                    let args = module
+                        .signature_at(instantiation.type_parameters)
+                        .0
+                        .iter()
+                        .map(|t| Pattern::from_token(module, t, &type_args))
+                        .collect();
+                    deletes_uid |= self.visit_call(modules, module, instantiation.handle, args);
+                }
+                Bytecode::Pack(index) => self.wrap_fields(module, *index, &[]),
+                Bytecode::PackGeneric(index) => {
+                    let instantiation = module.struct_instantiation_at(*index);
+                    let args = module
+                        .signature_at(instantiation.type_parameters)
+                        .0
+                        .iter()
+                        .map(|t| Pattern::from_token(module, t, &type_args))
+                        .collect::<Vec<_>>();
+                    self.wrap_fields(module, instantiation.def, &args);
+                }
+                Bytecode::Unpack(index) => {
+                    let handle = module.struct_def_at(*index).struct_handle;
+                    unpacked.push(Pattern::datatype(module, handle, Vec::new()));
+                }
+                Bytecode::UnpackGeneric(index) => {
+                    let instantiation = module.struct_instantiation_at(*index);
+                    let handle = module.struct_def_at(instantiation.def).struct_handle;
+                    let args = module
+                        .signature_at(instantiation.type_parameters)
+                        .0
+                        .iter()
+                        .map(|t| Pattern::from_token(module, t, &type_args))
+                        .collect();
+                    unpacked.push(Pattern::datatype(module, handle, args));
+                }
+                _ => {}
+            }
+        }
+        // Objects are destroyed by unpacking them and deleting their UID
+        if deletes_uid {
+            for pattern in unpacked {
+                self.record(
+                    Effect::Destroyed,
+                    pattern,
+                    "0x2::object::delete".to_string(),
+                );
+            }
+        }
+        self.stack.pop();
+    }
+
+    /// Returns whether the callee is `object::delete`.
+    fn visit_call(
+        &mut self,
+        modules: &Modules,
+        module: &CompiledModule,
+        index: FunctionHandleIndex,
+        type_args: Vec<Pattern>,
+    ) -> bool {
+        let handle = module.function_handle_at(index);
+        let callee_handle = module.module_handle_at(handle.module);
+        let address = *module.address_identifier_at(callee_handle.address);
+        let module_name = module.identifier_at(callee_handle.name).as_str();
+        let name = module.identifier_at(handle.name).as_str();
+
+        if address == SUI_FRAMEWORK_ADDRESS {
+            if module_name == "object" && name == "delete" {
+                return true;
+            }
+            if let Some((effect, subject)) = framework_effect(module_name, name) {
+                self.record(
+                    effect,
+                    subject.pattern(&type_args),
+                    format!("0x2::{}::{}", module_name, name),
+                );
+            }
+            return false;
+        }
+
+        let callee = if handle.module == module.self_handle_idx() {
+            module
+        } else if let Some(callee) = modules.get(&(address, module_name.to_string())) {
+            callee
+        } else {
+            if !is_framework(&address) {
+                self.unresolved.insert(format!(
+                    "{}::{} is not downloaded",
+                    address.to_hex_literal(),
+                    module_name
+                ));
+            }
+            return false;
+        };
+        match function_definition(callee, name) {
+            Some(def) => self.visit(modules, callee, def, type_args),
+            None => {
+                self.unresolved.insert(format!(
+                    "{}::{}::{} is not found",
+                    address.to_hex_literal(),
+                    module_name,
+                    name
+                ));
+            }
+        }
+        false
+    }
+
+    /// Objects put into another object are wrapped, including ones inside vectors or generic
+    /// containers. Values without `key` holding them are not objects, so they are left out.
+    fn wrap_fields(
+        &mut self,
+        module: &CompiledModule,
+        index: StructDefinitionIndex,
+        type_args: &[Pattern],
+    ) {
+        let struct_def = module.struct_def_at(index);
+        if !module
+            .datatype_handle_at(struct_def.struct_handle)
+            .abilities
+            .has_key()
+        {
+            return;
+        }
+        let container =
+            Pattern::datatype(module, struct_def.struct_handle, type_args.to_vec()).to_string();
+        for field in struct_def.fields().unwrap_or_default() {
+            self.wrap(
+                Pattern::from_token(module, &field.signature.0, type_args),
+                &container,
+            );
+        }
+    }
+
+    fn wrap(&mut self, pattern: Pattern, container: &str) {
+        match &pattern {
+            Pattern::Primitive => return,
+            Pattern::Vector(inner) => self.wrap(inner.as_ref().clone(), container),
+            Pattern::Datatype { type_params, .. } => {
+                for param in type_params.clone() {
+                    self.wrap(param, container);
+                }
+            }
+            Pattern::Any => {}
+        }
+        self.record(Effect::Wrapped, pattern, container.to_string());
+    }
+}
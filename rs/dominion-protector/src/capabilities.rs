@@ -0,0 +1,396 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use move_binary_format::{
+    file_format::{
+        Bytecode, CodeUnit, FunctionDefinition, JumpTableInner, LocalIndex, SignatureToken,
+        Visibility,
+    },
+    CompiledModule,
+};
+use move_core_types::account_address::AccountAddress;
+use serde::Serialize;
+use sui_sdk::types::base_types::ObjectID;
+use sui_types::SUI_FRAMEWORK_ADDRESS;
+
+use crate::{
+    call_graph::{
+        framework_effect, is_framework_call, load_modules, CallGraph, Effect, Modules, Pattern,
+    },
+    db::{function, Db},
+    sui_client::SuiClientWithNetwork,
+    verifier::{is_capability_type, shared_types},
+};
+
+/// Who gets a capability.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recipient {
+    /// Sender of the transaction, for `init` the publisher of the package
+    Sender,
+    /// Address hardcoded in the package
+    Address(String),
+    /// Address computed at runtime or passed by the caller
+    Computed,
+    /// Shared object, anyone can use it
+    Everyone,
+    /// Frozen object, anyone can use it by immutable reference
+    Frozen,
+    /// Stored inside another value
+    Wrapped(String),
+    /// Dynamic object field of another object
+    ObjectOwned,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Grant {
+    pub recipient: Recipient,
+    /// Functions from `init` or the minting function to the one giving the capability away
+    pub call_chain: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    Reference,
+    MutableReference,
+    Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GatedFunction {
+    pub module_name: String,
+    pub function_name: String,
+    pub visibility: function::Visibility,
+    pub is_entry: bool,
+    pub access: Access,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Minter {
+    pub module_name: String,
+    pub function_name: String,
+    pub grants: Vec<Grant>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Capability {
+    pub module_name: String,
+    pub name: String,
+    /// Holders of capabilities with `store` can pass them on to anyone
+    pub transferable: bool,
+    /// What `init` does with the capabilities it creates
+    pub initial_holders: Vec<Grant>,
+    /// Functions giving away capabilities they did not get by value, other than `init`
+    pub minted_by: Vec<Minter>,
+    pub gates: Vec<GatedFunction>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CapabilityGraph {
+    pub package_id: String,
+    pub network: String,
+    pub capabilities: Vec<Capability>,
+}
+
+fn function_name(module: &CompiledModule, def: &FunctionDefinition) -> String {
+    module
+        .identifier_at(module.function_handle_at(def.function).name)
+        .to_string()
+}
+
+/// Where the sender of the transaction is before an instruction: in locals, or on top of the
+/// stack.
+#[derive(Clone, PartialEq)]
+struct SenderState {
+    locals: BTreeSet<LocalIndex>,
+    on_stack: bool,
+}
+
+/// Where the sender is before every instruction. A value only counts as the sender when it is
+/// on every path reaching the instruction, so locals assigned something else on the way do not.
+fn sender_states(module: &CompiledModule, code: &CodeUnit) -> Vec<Option<SenderState>> {
+    let mut states = vec![None; code.code.len()];
+    if code.code.is_empty() {
+        return states;
+    }
+    states[0] = Some(SenderState {
+        locals: BTreeSet::new(),
+        on_stack: false,
+    });
+    let mut pending = vec![0];
+    while let Some(pc) = pending.pop() {
+        let Some(mut state) = states[pc].clone() else {
+            continue;
+        };
+        let pushes_sender = match &code.code[pc] {
+            Bytecode::Call(index) => is_framework_call(module, *index, "tx_context", "sender"),
+            Bytecode::CopyLoc(local) | Bytecode::MoveLoc(local) => state.locals.contains(local),
+            _ => false,
+        };
+        match &code.code[pc] {
+            Bytecode::StLoc(local) if state.on_stack => {
+                state.locals.insert(*local);
+            }
+            // Anything can be written through a mutable borrow
+            Bytecode::StLoc(local) | Bytecode::MutBorrowLoc(local) => {
+                state.locals.remove(local);
+            }
+            _ => {}
+        }
+        state.on_stack = pushes_sender;
+        for next in successors(code, pc) {
+            let joined = match &states[next] {
+                None => state.clone(),
+                Some(known) => SenderState {
+                    locals: known.locals.intersection(&state.locals).copied().collect(),
+                    on_stack: known.on_stack && state.on_stack,
+                },
+            };
+            if states[next].as_ref() != Some(&joined) {
+                states[next] = Some(joined);
+                pending.push(next);
+            }
+        }
+    }
+    states
+}
+
+fn successors(code: &CodeUnit, pc: usize) -> Vec<usize> {
+    let targets = match &code.code[pc] {
+        Bytecode::Ret | Bytecode::Abort => vec![],
+        Bytecode::Branch(target) => vec![*target as usize],
+        Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => vec![*target as usize, pc + 1],
+        Bytecode::VariantSwitch(index) => code
+            .jump_tables
+            .get(index.0 as usize)
+            .map(|table| {
+                let JumpTableInner::Full(targets) = &table.jump_table;
+                targets.iter().map(|target| *target as usize).collect()
+            })
+            .unwrap_or_default(),
+        _ => vec![pc + 1],
+    };
+    targets
+        .into_iter()
+        .filter(|next| *next < code.code.len())
+        .collect()
+}
+
+/// Finds where the recipient of the transfer at `at` comes from, looking at the instruction
+/// pushing it, which is the last argument.
+pub(crate) fn recipient(module: &CompiledModule, code: &CodeUnit, at: usize) -> Recipient {
+    if sender_states(module, code)
+        .get(at)
+        .and_then(Option::as_ref)
+        .is_some_and(|state| state.on_stack)
+    {
+        return Recipient::Sender;
+    }
+    match at.checked_sub(1).map(|i| &code.code[i]) {
+        Some(Bytecode::LdConst(index)) => {
+            let constant = module.constant_at(*index);
+            match AccountAddress::from_bytes(&constant.data) {
+                Ok(address) if constant.type_ == SignatureToken::Address => {
+                    Recipient::Address(address.to_hex_literal())
+                }
+                _ => Recipient::Computed,
+            }
+        }
+        _ => Recipient::Computed,
+    }
+}
+
+/// Ways the function gives away objects matching `pattern`. Transfers done directly by the
+/// function get their recipient resolved, deeper ones are reported as computed.
+fn grants(
+    modules: &Modules,
+    module: &CompiledModule,
+    def: &FunctionDefinition,
+    pattern: &Pattern,
+) -> Vec<Grant> {
+    let mut graph = CallGraph::default();
+    graph.visit(modules, module, def, Vec::new());
+    let mut grants = Vec::new();
+    for ((effect, effect_pattern), trace) in &graph.effects {
+        if !effect_pattern.matches(pattern) || matches!(effect_pattern, Pattern::Any) {
+            continue;
+        }
+        let recipient = match effect {
+            Effect::Transferred => Recipient::Computed,
+            Effect::Shared => Recipient::Everyone,
+            Effect::Frozen => Recipient::Frozen,
+            Effect::Wrapped => Recipient::Wrapped(trace.via.clone()),
+            Effect::ObjectOwned => Recipient::ObjectOwned,
+            _ => continue,
+        };
+        // Direct transfers are resolved below
+        if *effect == Effect::Transferred && trace.call_chain.len() == 1 {
+            continue;
+        }
+        grants.push(Grant {
+            recipient,
+            call_chain: trace.call_chain.clone(),
+        });
+    }
+
+    let Some(code) = &def.code else {
+        return grants;
+    };
+    let chain = vec![format!(
+        "{}::{}::{}",
+        module.self_id().address().to_hex_literal(),
+        module.self_id().name(),
+        function_name(module, def)
+    )];
+    for (at, instruction) in code.code.iter().enumerate() {
+        let Bytecode::CallGeneric(index) = instruction else {
+            continue;
+        };
+        let instantiation = module.function_instantiation_at(*index);
+        let handle = module.function_handle_at(instantiation.handle);
+        let callee = module.module_handle_at(handle.module);
+        if *module.address_identifier_at(callee.address) != SUI_FRAMEWORK_ADDRESS {
+            continue;
+        }
+        let Some((Effect::Transferred, subject)) = framework_effect(
+            module.identifier_at(callee.name).as_str(),
+            module.identifier_at(handle.name).as_str(),
+        ) else {
+            continue;
+        };
+        let type_args = module
+            .signature_at(instantiation.type_parameters)
+            .0
+            .iter()
+            .map(|token| Pattern::from_token(module, token, &[]))
+            .collect::<Vec<_>>();
+        let transferred = subject.pattern(&type_args);
+        if transferred.matches(pattern) && !matches!(transferred, Pattern::Any) {
+            grants.push(Grant {
+                recipient: recipient(module, code, at),
+                call_chain: chain.clone(),
+            });
+        }
+    }
+    grants
+}
+
+fn access(module: &CompiledModule, token: &SignatureToken, pattern: &Pattern) -> Option<Access> {
+    let (access, inner) = match token {
+        SignatureToken::Reference(inner) => (Access::Reference, inner.as_ref()),
+        SignatureToken::MutableReference(inner) => (Access::MutableReference, inner.as_ref()),
+        _ => (Access::Value, token),
+    };
+    let param = Pattern::from_token(module, inner, &[]);
+    (matches!(param, Pattern::Datatype { .. }) && param.matches(pattern)).then_some(access)
+}
+
+/// Capabilities are objects of the package, of the types [`is_capability_type`] accepts, that
+/// `init` gives away and public or entry functions require.
+pub async fn capability_graph(
+    package_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<CapabilityGraph> {
+    let (own_modules, modules) = load_modules(package_id, client, db).await?;
+    let shared = shared_types(&own_modules);
+
+    // Any module can create the objects of another one with its functions in `init`
+    let inits = own_modules
+        .iter()
+        .filter_map(|module| {
+            module
+                .function_defs()
+                .iter()
+                .find(|def| function_name(module, def) == "init")
+                .map(|init| (module, init))
+        })
+        .collect::<Vec<_>>();
+
+    let mut capabilities = Vec::new();
+    for module in &own_modules {
+        for struct_def in module.struct_defs() {
+            if !is_capability_type(module, struct_def.struct_handle, &shared) {
+                continue;
+            }
+            let handle = module.datatype_handle_at(struct_def.struct_handle);
+            let pattern = Pattern::datatype(
+                module,
+                struct_def.struct_handle,
+                vec![Pattern::Any; handle.type_parameters.len()],
+            );
+
+            // Objects given away by an `init` have to be created by it, it gets no other objects
+            let initial_holders = inits
+                .iter()
+                .flat_map(|(init_module, init)| grants(&modules, init_module, init, &pattern))
+                .collect::<Vec<_>>();
+            if initial_holders.is_empty() {
+                continue;
+            }
+
+            let mut gates = Vec::new();
+            let mut minted_by = Vec::new();
+            for gated_module in &own_modules {
+                for def in gated_module.function_defs() {
+                    let name = function_name(gated_module, def);
+                    let gated_module_name = gated_module.self_id().name().to_string();
+                    if def.visibility == Visibility::Private && !def.is_entry {
+                        continue;
+                    }
+                    let params = &gated_module
+                        .signature_at(gated_module.function_handle_at(def.function).parameters)
+                        .0;
+                    let accesses = params
+                        .iter()
+                        .filter_map(|param| access(gated_module, param, &pattern))
+                        .collect::<Vec<_>>();
+                    if !accesses
+                        .iter()
+                        .any(|access| matches!(access, Access::Value))
+                        && name != "init"
+                    {
+                        let grants = grants(&modules, gated_module, def, &pattern);
+                        if !grants.is_empty() {
+                            minted_by.push(Minter {
+                                module_name: gated_module_name.clone(),
+                                function_name: name.clone(),
+                                grants,
+                            });
+                        }
+                    }
+                    for access in accesses {
+                        gates.push(GatedFunction {
+                            module_name: gated_module_name.clone(),
+                            function_name: name.clone(),
+                            visibility: match def.visibility {
+                                Visibility::Private => function::Visibility::Private,
+                                Visibility::Public => function::Visibility::Public,
+                                Visibility::Friend => function::Visibility::Friend,
+                            },
+                            is_entry: def.is_entry,
+                            access,
+                        });
+                    }
+                }
+            }
+            if gates.is_empty() {
+                continue;
+            }
+            capabilities.push(Capability {
+                module_name: module.self_id().name().to_string(),
+                name: module.identifier_at(handle.name).to_string(),
+                transferable: handle.abilities.has_store(),
+                initial_holders,
+                minted_by,
+                gates,
+            });
+        }
+    }
+    Ok(CapabilityGraph {
+        package_id: package_id.to_string(),
+        network: client.network.clone(),
+        capabilities,
+    })
+}
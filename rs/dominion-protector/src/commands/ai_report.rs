@@ -0,0 +1,66 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::db::{ai_call_total::AiCallTotal, Db};
+
+/// Prints what AI calls cost, per package and prompt.
+#[derive(Args)]
+pub struct AiReportCommand {
+    #[arg(long)]
+    pub network: Option<String>,
+    #[arg(long)]
+    pub package_id: Option<String>,
+}
+
+impl AiReportCommand {
+    pub async fn run(self) -> Result<()> {
+        let db = Db::new().await?;
+        let totals = AiCallTotal::load_all(
+            &db.pool,
+            self.network.as_deref(),
+            self.package_id.as_deref(),
+        )
+        .await?;
+
+        println!(
+            "{:<66} {:<8} {:<24} {:>6} {:>6} {:>10} {:>10} {:>10} {:>10}",
+            "package",
+            "network",
+            "prompt",
+            "calls",
+            "cached",
+            "prompt",
+            "completion",
+            "seconds",
+            "USD"
+        );
+        for total in &totals {
+            println!(
+                "{:<66} {:<8} {:<24} {:>6} {:>6} {:>10} {:>10} {:>10.1} {:>10.4}",
+                total.package_id,
+                total.network,
+                total.prompt,
+                total.calls,
+                total.cached,
+                total.prompt_tokens,
+                total.completion_tokens,
+                total.latency_ms as f64 / 1000.0,
+                total.cost
+            );
+        }
+        let calls = totals.iter().map(|total| total.calls).sum::<i64>();
+        let cached = totals.iter().map(|total| total.cached).sum::<i64>();
+        println!(
+            "{} calls, {} answered from the cache, {} prompt and {} completion tokens, {:.4} USD",
+            calls,
+            cached,
+            totals.iter().map(|total| total.prompt_tokens).sum::<i64>(),
+            totals
+                .iter()
+                .map(|total| total.completion_tokens)
+                .sum::<i64>(),
+            totals.iter().map(|total| total.cost).sum::<f64>()
+        );
+        Ok(())
+    }
+}
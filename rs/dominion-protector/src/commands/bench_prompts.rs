@@ -0,0 +1,310 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::Args;
+use move_binary_format::CompiledModule;
+use move_core_types::account_address::AccountAddress;
+use serde::Deserialize;
+use sui_sdk::{rpc_types::SuiRawData, types::base_types::ObjectID};
+
+use crate::{
+    ai::AI,
+    commands::{
+        decompile::Kind,
+        describe::{full_module::base_messages, module},
+        import::{import_package, read_modules},
+    },
+    db::{
+        descriptions::{FullModuleDescription, SecurityLevel},
+        full_object::load_object,
+        prompt_bench_result::PromptBenchResult,
+        sources::ModuleSource,
+        Db,
+    },
+    decompiler::{
+        decompile_module_to_smt, decompile_module_with_disasm,
+        revela::decompile_module_with_revela_cli,
+    },
+};
+
+/// Packages at this level or above count as flagged as scams.
+pub const FLAGGED_FROM: SecurityLevel = SecurityLevel::HighRisk;
+
+/// Runs the module prompts over labelled packages and reports how well they catch scams.
+#[derive(Args)]
+pub struct BenchPromptsCommand {
+    #[arg(long, default_value = "bench.yaml")]
+    pub labels: PathBuf,
+    /// Source the module prompts are given
+    #[arg(long, default_value = "revela")]
+    pub kind: Kind,
+    /// Only print the results of earlier runs
+    #[arg(long)]
+    pub report: bool,
+}
+
+impl BenchPromptsCommand {
+    pub async fn run(self) -> Result<()> {
+        let db = Db::new().await?;
+        if !self.report {
+            let ai = AI::new(&db).await?;
+            let labels = Labels::load(&self.labels)?;
+            for package in &labels.packages {
+                let Some(result) = bench_package(&db, &ai, &self.kind, package).await? else {
+                    match &package.path {
+                        Some(path) => println!(
+                            "Skipping {}: no compiled modules in {}, build it first",
+                            package.name,
+                            path.display()
+                        ),
+                        None => println!(
+                            "Skipping {}: {} is not stored on {}, download it first",
+                            package.name,
+                            package.id,
+                            package.network()
+                        ),
+                    }
+                    continue;
+                };
+                println!(
+                    "{}: {:?} (labelled {})",
+                    result.package_name,
+                    result.security_level,
+                    if result.is_scam { "scam" } else { "benign" }
+                );
+                result.save(&db.pool).await?;
+            }
+        }
+
+        println!(
+            "{:<32} {:<12} {:<14} {:>4} {:>4} {:>4} {:>4} {:>9} {:>6}",
+            "model", "description", "security_level", "TP", "FP", "FN", "TN", "precision", "recall"
+        );
+        for score in scores(&PromptBenchResult::load_latest(&db.pool).await?) {
+            println!(
+                "{:<32} {:<12} {:<14} {:>4} {:>4} {:>4} {:>4} {:>9} {:>6}",
+                score.model,
+                score.description_version,
+                score.security_level_version,
+                score.true_positives,
+                score.false_positives,
+                score.false_negatives,
+                score.true_negatives,
+                ratio(score.precision()),
+                ratio(score.recall())
+            );
+        }
+        Ok(())
+    }
+}
+
+fn ratio(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |value| format!("{:.2}", value))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Labels {
+    pub packages: Vec<LabelledPackage>,
+}
+
+impl Labels {
+    /// Reads the labels, resolving package paths against the directory of the file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read(path)
+            .with_context(|| format!("Can not read labels {}", path.display()))?;
+        let mut labels: Self = serde_yml::from_slice(&contents)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for package in &mut labels.packages {
+            if let Some(package_path) = &mut package.path {
+                *package_path = dir.join(&*package_path);
+            }
+        }
+        Ok(labels)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LabelledPackage {
+    pub name: String,
+    pub scam: bool,
+    /// Move package imported on the `bench` network, compiled with `sui move build`
+    pub path: Option<PathBuf>,
+    #[serde(default = "zero_id")]
+    pub id: String,
+    /// Network of packages without a path, which must be downloaded beforehand
+    pub network: Option<String>,
+}
+
+fn zero_id() -> String {
+    "0x0".to_string()
+}
+
+impl LabelledPackage {
+    pub fn network(&self) -> &str {
+        match (&self.network, &self.path) {
+            (Some(network), _) => network,
+            (None, Some(_)) => "bench",
+            (None, None) => "mainnet",
+        }
+    }
+}
+
+/// Asks the module prompts about every module of the package, importing it first if it is a
+/// local one. Dependencies are described by their stored descriptions only, so nothing needs
+/// an RPC. `None` when the package is not stored, or is local and not built.
+pub async fn bench_package(
+    db: &Db,
+    ai: &AI,
+    kind: &Kind,
+    package: &LabelledPackage,
+) -> Result<Option<PromptBenchResult>> {
+    let package_id = ObjectID::from_str(&package.id)?;
+    let network = package.network();
+    if let Some(path) = &package.path {
+        if load_object(&db.pool, network, &package_id).await?.is_none() {
+            let Ok(modules) = read_modules(path) else {
+                return Ok(None);
+            };
+            let mut tx = db.pool.begin().await?;
+            import_package(&mut *tx, package_id, network, &modules).await?;
+            tx.commit().await?;
+        }
+    }
+    let Some(object) = load_object(&db.pool, network, &package_id).await? else {
+        return Ok(None);
+    };
+    let Some(SuiRawData::Package(stored)) = object.bcs else {
+        bail!("{} is not a package", package_id);
+    };
+
+    let mut security_level = SecurityLevel::BestPracticesCompliant;
+    for (module_name, bytecode) in &stored.module_map {
+        println!("Benchmarking {}::{}", package.name, module_name);
+        let compiled = CompiledModule::deserialize_with_defaults(bytecode)?;
+        let source = module_source(db, network, package_id, &compiled, bytecode, kind).await?;
+        let mut dependencies = vec![];
+        for dependency in compiled.immediate_dependencies() {
+            if *dependency.address() == AccountAddress::ONE
+                || *dependency.address() == AccountAddress::TWO
+                || ObjectID::from(*dependency.address()) == package_id
+            {
+                continue;
+            }
+            if let Some(description) = FullModuleDescription::load(
+                &db.pool,
+                &ObjectID::from(*dependency.address()),
+                network,
+                dependency.name().as_str(),
+            )
+            .await?
+            {
+                dependencies.push((dependency, description));
+            }
+        }
+        let mut messages = base_messages(&compiled, &source.source, &dependencies, ai)?;
+        let description =
+            module::generate(&package_id, network, module_name, &mut messages, ai).await?;
+        security_level = security_level.max(description.security_level);
+    }
+
+    Ok(Some(PromptBenchResult {
+        package_name: package.name.clone(),
+        package_id: package_id.to_string(),
+        network: network.to_string(),
+        model: ai.model.clone(),
+        description_version: ai.prompts.get("module.description")?.version.clone(),
+        security_level_version: ai.prompts.get("module.security_level")?.version.clone(),
+        is_scam: package.scam,
+        security_level,
+        benchmarked_at: Utc::now(),
+    }))
+}
+
+async fn module_source(
+    db: &Db,
+    network: &str,
+    package_id: ObjectID,
+    compiled: &CompiledModule,
+    bytecode: &[u8],
+    kind: &Kind,
+) -> Result<ModuleSource> {
+    let module_name = compiled.self_id().name().to_string();
+    let stored = ModuleSource::load(
+        &db.pool,
+        &package_id,
+        network,
+        &module_name,
+        &kind.to_string(),
+    )
+    .await?;
+    if let Some(source) = stored {
+        return Ok(source);
+    }
+    match kind {
+        Kind::Revela => {
+            decompile_module_with_revela_cli(&db.pool, network, package_id, &module_name, bytecode)
+                .await
+        }
+        Kind::Disassembled => {
+            decompile_module_with_disasm(&db.pool, network, package_id, compiled).await
+        }
+        Kind::Smt => decompile_module_to_smt(&db.pool, network, package_id, compiled).await,
+    }
+}
+
+/// Confusion matrix of one model with one version of the description and security level
+/// prompts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Score {
+    pub model: String,
+    pub description_version: String,
+    pub security_level_version: String,
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+    pub true_negatives: usize,
+}
+
+impl Score {
+    /// Share of flagged packages that are scams, `None` when nothing was flagged.
+    pub fn precision(&self) -> Option<f64> {
+        let flagged = self.true_positives + self.false_positives;
+        (flagged > 0).then(|| self.true_positives as f64 / flagged as f64)
+    }
+
+    /// Share of scams that were flagged, `None` without labelled scams.
+    pub fn recall(&self) -> Option<f64> {
+        let scams = self.true_positives + self.false_negatives;
+        (scams > 0).then(|| self.true_positives as f64 / scams as f64)
+    }
+}
+
+pub fn scores(results: &[PromptBenchResult]) -> Vec<Score> {
+    let mut scores = BTreeMap::<(String, String, String), Score>::new();
+    for result in results {
+        let key = (
+            result.model.clone(),
+            result.description_version.clone(),
+            result.security_level_version.clone(),
+        );
+        let score = scores.entry(key).or_insert_with(|| Score {
+            model: result.model.clone(),
+            description_version: result.description_version.clone(),
+            security_level_version: result.security_level_version.clone(),
+            ..Default::default()
+        });
+        let flagged = result.security_level.severity() >= FLAGGED_FROM.severity();
+        match (flagged, result.is_scam) {
+            (true, true) => score.true_positives += 1,
+            (true, false) => score.false_positives += 1,
+            (false, true) => score.false_negatives += 1,
+            (false, false) => score.true_negatives += 1,
+        }
+    }
+    scores.into_values().collect()
+}
@@ -0,0 +1,73 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use clap::Args;
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{
+    capabilities::{capability_graph, Recipient},
+    db::Db,
+    sui_client::SuiClientWithNetwork,
+};
+
+#[derive(Args)]
+pub struct CapabilitiesCommand {
+    pub id: String,
+}
+
+impl CapabilitiesCommand {
+    pub async fn run(self) -> Result<()> {
+        let client = SuiClientWithNetwork::with_default_network().await?;
+        let db = Db::new().await?;
+        let package_id = ObjectID::from_str(&self.id)?;
+        let graph = capability_graph(&package_id, &client, &db).await?;
+        for capability in &graph.capabilities {
+            println!(
+                "{}::{}{}",
+                capability.module_name,
+                capability.name,
+                if capability.transferable {
+                    " (transferable)"
+                } else {
+                    ""
+                }
+            );
+            for grant in &capability.initial_holders {
+                println!(
+                    "  received by {} in {}",
+                    describe_recipient(&grant.recipient),
+                    grant.call_chain.join(" -> ")
+                );
+            }
+            for minter in &capability.minted_by {
+                println!(
+                    "  also minted by {}::{}",
+                    minter.module_name, minter.function_name
+                );
+            }
+            for gate in &capability.gates {
+                println!(
+                    "  gates {}::{} ({:?}{}, by {:?})",
+                    gate.module_name,
+                    gate.function_name,
+                    gate.visibility,
+                    if gate.is_entry { " entry" } else { "" },
+                    gate.access
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn describe_recipient(recipient: &Recipient) -> String {
+    match recipient {
+        Recipient::Sender => "the publisher".to_string(),
+        Recipient::Address(address) => address.clone(),
+        Recipient::Computed => "a computed address".to_string(),
+        Recipient::Everyone => "everyone as a shared object".to_string(),
+        Recipient::Frozen => "everyone as a frozen object".to_string(),
+        Recipient::Wrapped(container) => format!("whoever owns {}", container),
+        Recipient::ObjectOwned => "another object".to_string(),
+    }
+}
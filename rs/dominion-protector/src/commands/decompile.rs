@@ -32,6 +32,7 @@ use crate::{
 pub enum Kind {
     Revela,
     Disassembled,
+    Smt,
 }
 
 impl Display for Kind {
@@ -39,6 +40,7 @@ impl Display for Kind {
         match self {
             Kind::Revela => write!(f, "revela"),
             Kind::Disassembled => write!(f, "disassembled"),
+            Kind::Smt => write!(f, "smt"),
         }
     }
 }
@@ -87,6 +89,12 @@ impl DecompileCommand {
                         decompile_module_with_disasm(&mut *db, network, package.id, module).await?;
                 }
             }
+            Kind::Smt => {
+                for module_bytecode in package.module_map.values() {
+                    let module = CompiledModule::deserialize_with_defaults(module_bytecode)?;
+                    let _ = decompile_module_to_smt(&mut *db, network, package.id, &module).await?;
+                }
+            }
         }
         Ok(())
     }
@@ -150,33 +158,37 @@ impl DecompileCommand {
     }
 }
 
-/*
 pub async fn get_or_decompile_module(
-    module_id: ModuleId,
+    package_id: &ObjectID,
+    module_name: &str,
+    kind: &Kind,
     client: &SuiClientWithNetwork,
-    db: &mut Client,
+    db: &Db,
 ) -> Result<ModuleSource> {
-    create_sources_tables_if_needed(&db).await?;
-    let source = read_source_from_db(module_id.clone(), client.network.clone(), db).await?;
+    let source = ModuleSource::load(
+        &db.pool,
+        package_id,
+        &client.network,
+        module_name,
+        &kind.to_string(),
+    )
+    .await?;
     if let Some(source) = source {
-        Ok(source)
-    } else {
-        let object_id: ObjectID = module_id.address().clone().into();
-        let package = get_or_download_object(object_id, client, db).await?;
-        if let SuiRawData::Package(package) = package.bcs.unwrap() {
-            let modules = decompile_package(DecompileParams {
-                network: client.network.clone(),
-                package: &package,
-                db,
-            })
-            .await?;
-            Ok(modules
-                .get(module_id.name())
-                .context("Module not found")?
-                .clone())
-        } else {
-            bail!("Object is not a package");
-        }
+        return Ok(source);
+    }
+    DecompileCommand {
+        id: None,
+        kind: kind.clone(),
     }
+    .process_package(client, db, package_id)
+    .await?;
+    ModuleSource::load(
+        &db.pool,
+        package_id,
+        &client.network,
+        module_name,
+        &kind.to_string(),
+    )
+    .await?
+    .context("Module not found")
 }
-*/
@@ -1,139 +1,241 @@
+use std::{future::Future, pin::Pin};
+
 use crate::{
     ai::AI,
     commands::{
-        decompile::get_or_decompile_module, describe::structure, download::get_or_download_object,
-    },
-    db::{
-        descriptions::{create_description_tables_if_needed, FullModuleDescription},
-        sources::ModuleSource,
+        decompile::{get_or_decompile_module, Kind},
+        describe::structure,
+        download::get_or_download_object,
     },
+    db::{descriptions::FullModuleDescription, structure_usage::StructureUsage, Db},
+    prompts::Prompts,
     sui_client::SuiClientWithNetwork,
+    usage::get_or_save_structures_usage,
 };
 use anyhow::{bail, Context, Result};
 use move_binary_format::CompiledModule;
 use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
 
 use openai_dive::v1::resources::chat::{ChatMessage, ChatMessageContent};
-use sui_sdk::rpc_types::SuiRawData;
-use tokio_postgres::Client;
+use sui_sdk::{rpc_types::SuiRawData, types::base_types::ObjectID};
 
 use super::module;
 
+/// Generates a new description of the module, describing its dependencies first if needed.
 pub async fn describe(
-    compiled: &CompiledModule,
-    module: &ModuleSource,
+    module_id: &ModuleId,
+    kind: &Kind,
     client: &SuiClientWithNetwork,
-    db: &mut Client,
+    db: &Db,
     ai: &AI,
 ) -> Result<FullModuleDescription> {
-    create_description_tables_if_needed(db).await?;
+    let package_id = ObjectID::from(*module_id.address());
+    let module_name = module_id.name().as_str();
+    let package = get_or_download_object(&package_id, client, db).await?;
+    let Some(SuiRawData::Package(package)) = package.bcs else {
+        bail!("Wrong package id")
+    };
+    let compiled = CompiledModule::deserialize_with_defaults(
+        package
+            .module_map
+            .get(module_name)
+            .context("Can not find module")?,
+    )?;
+    let source = get_or_decompile_module(&package_id, module_name, kind, client, db).await?;
 
-    Box::pin(async move {
-        let mut input_message = String::new();
-        for dependency in &module.dependencies {
-            if *dependency.address() == AccountAddress::ONE
-                || *dependency.address() == AccountAddress::TWO
-            {
-                continue;
-            }
-            let description = get_or_describe(dependency.clone(), db, client, ai).await?;
-            input_message.push_str(&format!(
-                "*** Dependency {} ***\n  - description ```{}```\n",
-                &description.module.id.to_canonical_display(true),
-                &description.module.description
+    let mut dependencies = vec![];
+    for dependency in compiled.immediate_dependencies() {
+        if *dependency.address() == AccountAddress::ONE
+            || *dependency.address() == AccountAddress::TWO
+        {
+            continue;
+        }
+        // Boxed to break the recursion through dependencies
+        let description: Pin<Box<dyn Future<Output = Result<FullModuleDescription>> + Send + '_>> =
+            Box::pin(get_or_describe(&dependency, kind, client, db, ai));
+        let description = description.await?;
+        dependencies.push((dependency, description));
+    }
+    let base_messages = base_messages(&compiled, &source.source, &dependencies, ai)?;
+
+    let module_info = module::generate(
+        &package_id,
+        &client.network,
+        module_name,
+        &mut base_messages.clone(),
+        ai,
+    )
+    .await?;
+    let usages = get_or_save_structures_usage(&package_id, client, db).await?;
+    let mut struct_descriptions = vec![];
+    for struct_name in struct_names(&compiled) {
+        println!("Struct name: {:?}", struct_name);
+        // Ownership claims are grounded in what the bytecode does with the struct
+        let mut messages = base_messages.clone();
+        messages.push(ChatMessage::User {
+            content: ChatMessageContent::Text(ai.prompts.render(
+                "structure.usage",
+                &[
+                    ("module_name", module_name),
+                    ("struct_name", struct_name),
+                    (
+                        "usage_facts",
+                        &usage_facts(usages.iter().filter(|usage| {
+                            usage.module_name == module_name && usage.datatype_name == struct_name
+                        })),
+                    ),
+                ],
+            )?),
+            name: None,
+        });
+        struct_descriptions.push(
+            structure::generate(
+                &package_id,
+                &client.network,
+                module_name,
+                struct_name,
+                &messages,
+                ai,
+            )
+            .await?,
+        );
+    }
+    let result = FullModuleDescription {
+        module: module_info,
+        structs: struct_descriptions,
+        functions: vec![], // TODO
+    };
+    let mut tx = db.pool.begin().await?;
+    result.save(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(result)
+}
+
+/// Developer prompt and the module with its dependencies, which every question about the
+/// module follows.
+pub fn base_messages(
+    compiled: &CompiledModule,
+    source: &str,
+    dependencies: &[(ModuleId, FullModuleDescription)],
+    ai: &AI,
+) -> Result<Vec<ChatMessage>> {
+    let mut dependencies_text = String::new();
+    for (dependency, description) in dependencies {
+        dependencies_text.push_str(&format!(
+            "*** Dependency {} ***\n  - description ```{}```\n",
+            dependency.to_canonical_display(true),
+            &description.module.description
+        ));
+        dependencies_text.push_str("  - structs:\n");
+        for struct_description in &description.structs {
+            dependencies_text.push_str(&format!(
+                "    - Struct {} description ```{}```\n",
+                struct_description.struct_name, struct_description.description,
             ));
-            input_message.push_str("  - structs:\n");
-            for struct_description in &description.structs {
-                input_message.push_str(&format!(
-                    "    - Struct {} description ```{}```\n",
-                    struct_description.struct_name, struct_description.description,
-                ));
-            }
-            input_message.push_str("  - functions\n");
-            for function_description in &description.functions {
-                input_message.push_str(&format!(
-                    "    - function {} description ```{}```\n",
-                    function_description.function.function_name,
-                    function_description.function.description,
+        }
+        dependencies_text.push_str("  - functions\n");
+        for function_description in &description.functions {
+            dependencies_text.push_str(&format!(
+                "    - function {} description ```{}```\n",
+                function_description.function.function_name,
+                function_description.function.description,
+            ));
+        }
+    }
+    Ok(vec![
+        ChatMessage::Developer {
+            content: ChatMessageContent::Text(ai.prompts.render("developer", &[])?),
+            name: None,
+        },
+        ChatMessage::User {
+            content: ChatMessageContent::Text(ai.prompts.render(
+                "module.input",
+                &[
+                    ("module_name", compiled.self_id().name().as_str()),
+                    ("structs", &struct_names(compiled).join(", ")),
+                    ("dependencies", &dependencies_text),
+                    ("source", source),
+                ],
+            )?),
+            name: None,
+        },
+    ])
+}
+
+fn struct_names(compiled: &CompiledModule) -> Vec<&str> {
+    compiled
+        .struct_defs()
+        .iter()
+        .map(|def| {
+            compiled
+                .identifier_at(compiled.datatype_handle_at(def.struct_handle).name)
+                .as_str()
+        })
+        .collect()
+}
+
+fn usage_facts<'u>(usages: impl Iterator<Item = &'u StructureUsage>) -> String {
+    let mut facts = String::new();
+    let mut found = false;
+    for usage in usages {
+        for (column, fact) in [
+            (&usage.address_owned, "transfers it to an address"),
+            (
+                &usage.object_owned,
+                "adds it to another object as a dynamic object field",
+            ),
+            (&usage.wrapped, "wraps it into another value"),
+            (&usage.shared, "shares it"),
+            (&usage.immutable, "freezes it"),
+            (&usage.emitted, "emits it as an event"),
+        ] {
+            if column.is_some() {
+                found = true;
+                facts.push_str(&format!(
+                    "  - {}::{} {} (calls {})\n",
+                    usage.target_module_name,
+                    usage.function_name,
+                    fact,
+                    usage.call_chain.join(" -> ")
                 ));
             }
         }
-        input_message.push_str(&format!(
-            "*** Decomplied module for audit ***: ```move {}```\n",
-            &module.source
-        ));
-        let base_messages = vec![
-            ChatMessage::Developer {
-                content: ChatMessageContent::Text(ai.prompts.developer.clone()),
-                name: None,
-            },
-            ChatMessage::User {
-                content: ChatMessageContent::Text(input_message),
-                name: None,
-            },
-        ];
+    }
+    if !found {
+        facts.push_str(
+            "  - no function of the package transfers, wraps, shares, freezes or emits it\n",
+        );
+    }
+    facts
+}
 
-        let module_info = module::generate(
-            module.id.clone(),
-            client.network.clone(),
-            &mut base_messages.clone(),
-            ai,
-        )
-        .await?;
-        let mut structs = vec![];
-        for def in compiled.struct_defs() {
-            let handle = compiled.datatype_handle_at(def.struct_handle);
-            let struct_name = compiled.identifier_at(handle.name).as_str();
-            println!("Struct name: {:?}", struct_name);
-            structs.push(
-                structure::generate(
-                    module.id.clone(),
-                    client.network.clone(),
-                    struct_name,
-                    &mut base_messages.clone(),
-                    ai,
-                )
-                .await?,
-            );
-        }
-        let result = FullModuleDescription {
-            module: module_info,
-            structs,
-            functions: vec![], // TODO
-        };
-        let mut tx = db.transaction().await?;
-        result.save_to_db(&mut tx).await?;
-        tx.commit().await?;
-        Ok(result)
-    })
-    .await
+/// Whether the description was generated with the prompts as they are now.
+pub fn is_current(description: &FullModuleDescription, prompts: &Prompts) -> bool {
+    description.module.prompt_versions.0 == prompts.versions(module::PROMPTS)
+        && description
+            .structs
+            .iter()
+            .all(|structure| structure.prompt_versions.0 == prompts.versions(structure::PROMPTS))
 }
 
+/// Stored description of the module, generating it again if it is missing or stale.
 pub async fn get_or_describe(
-    module_id: ModuleId,
-    db: &mut Client,
+    module_id: &ModuleId,
+    kind: &Kind,
     client: &SuiClientWithNetwork,
+    db: &Db,
     ai: &AI,
 ) -> Result<FullModuleDescription> {
-    create_description_tables_if_needed(db).await?;
-    let module =
-        FullModuleDescription::read_from_db(module_id.clone(), client.network.clone(), db).await?;
-    if let Some(module) = module {
+    let module = FullModuleDescription::load(
+        &db.pool,
+        &ObjectID::from(*module_id.address()),
+        &client.network,
+        module_id.name().as_str(),
+    )
+    .await?;
+    if let Some(module) = module.filter(|module| is_current(module, &ai.prompts)) {
         Ok(module)
     } else {
-        let package =
-            get_or_download_object(module_id.address().clone().into(), client, db).await?;
-        if let Some(SuiRawData::Package(package)) = package.bcs {
-            let compiled = CompiledModule::deserialize_with_defaults(
-                &package
-                    .module_map
-                    .get(module_id.name().as_str())
-                    .context("Can not file module")?,
-            )?;
-            let module = get_or_decompile_module(module_id, client, db).await?;
-            describe(&compiled, &module, client, db, ai).await
-        } else {
-            bail!("Wrong package id")
-        }
+        describe(module_id, kind, client, db, ai).await
     }
 }
@@ -2,16 +2,12 @@ use std::str::FromStr;
 
 use crate::{
     ai::AI,
-    commands::{
-        decompile::{decompile, get_or_decompile_module, DecompileParams},
-        download::get_or_download_object,
-    },
-    db::{build_db, sources::read_source_from_db},
+    commands::{decompile::Kind, download::get_or_download_object},
+    db::Db,
     sui_client::SuiClientWithNetwork,
 };
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, Result};
 use clap::{Args, Subcommand};
-use move_binary_format::CompiledModule;
 use move_core_types::language_storage::ModuleId;
 
 use sui_sdk::{
@@ -27,81 +23,61 @@ pub mod structure;
 pub struct DescribeCommand {
     #[command(subcommand)]
     command: DescribeType,
+    /// Source the descriptions are generated from
+    #[arg(long, default_value = "revela")]
+    kind: Kind,
 }
 
 #[derive(Subcommand)]
 enum DescribeType {
-    Package { package_id: String },
-    Module { full_name: String },
-    Transaction { digest: String },
+    Package {
+        package_id: String,
+        /// Only describe modules without a description made with the current prompts
+        #[arg(long)]
+        stale: bool,
+    },
+    Module {
+        full_name: String,
+    },
+    Transaction {
+        digest: String,
+    },
 }
 
 impl DescribeCommand {
     pub async fn run(self) -> Result<()> {
         let client = SuiClientWithNetwork::with_default_network().await?;
-        let mut db = build_db().await?;
-        let ai = AI::new().await?;
+        let db = Db::new().await?;
+        let ai = AI::new(&db).await?;
 
         match self.command {
-            DescribeType::Package { package_id } => {
+            DescribeType::Package { package_id, stale } => {
                 println!("Describing package with ID: {}", package_id);
-                let object_id = ObjectID::from_str(&package_id)?;
-                let package = get_or_download_object(object_id, &client, &mut db).await?;
-                if let SuiRawData::Package(package) = package.bcs.unwrap() {
-                    for (name, bytecode) in &package.module_map {
-                        let compiled = CompiledModule::deserialize_with_defaults(&bytecode)?;
-                        let module_id =
-                            ModuleId::new(object_id.into(), Identifier::new(name.clone())?);
-                        let module =
-                            read_source_from_db(module_id.clone(), client.network.clone(), &mut db)
-                                .await?;
-                        let module = if let Some(module) = module {
-                            module
-                        } else {
-                            let modules = decompile(DecompileParams {
-                                db: &mut db,
-                                network: client.network.clone(),
-                                package: &package,
-                            })
+                let package_id = ObjectID::from_str(&package_id)?;
+                let package = get_or_download_object(&package_id, &client, &db).await?;
+                let Some(SuiRawData::Package(package)) = package.bcs else {
+                    bail!("Object is not a package");
+                };
+                for module_name in package.module_map.keys() {
+                    let module_id =
+                        ModuleId::new(package_id.into(), Identifier::new(module_name.as_str())?);
+                    if stale {
+                        full_module::get_or_describe(&module_id, &self.kind, &client, &db, &ai)
                             .await?;
-                            modules
-                                .get(module_id.name())
-                                .context("Module not found")?
-                                .clone()
-                        };
-                        full_module::describe(&compiled, &module, &client, &mut db, &ai).await?;
+                    } else {
+                        full_module::describe(&module_id, &self.kind, &client, &db, &ai).await?;
                     }
-                } else {
-                    bail!("Object is not a package");
                 }
                 Ok(())
             }
             DescribeType::Module { full_name } => {
                 let module_id = ModuleId::from_str(&full_name)?;
                 println!("Describing module: {}", &module_id);
-                let package = get_or_download_object(
-                    ObjectID::from_address(module_id.address().clone()),
-                    &client,
-                    &mut db,
-                )
-                .await?;
-                if let SuiRawData::Package(package) = package.bcs.unwrap() {
-                    let compiled = CompiledModule::deserialize_with_defaults(
-                        &package
-                            .module_map
-                            .get(module_id.name().as_str())
-                            .context("Can not file module")?,
-                    )?;
-                    let module = get_or_decompile_module(module_id, &client, &mut db).await?;
-                    full_module::describe(&compiled, &module, &client, &mut db, &ai).await?;
-                } else {
-                    bail!("Wrong package id")
-                }
+                full_module::describe(&module_id, &self.kind, &client, &db, &ai).await?;
                 Ok(())
             }
             DescribeType::Transaction { digest } => {
-                println!("Describing transaction with digest: {}", digest);
-                Ok(())
+                bail!("Describing transactions is not supported yet: {}", digest)
             }
         }
     }
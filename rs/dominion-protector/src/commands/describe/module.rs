@@ -1,18 +1,32 @@
 use crate::{
-    ai::AI,
+    ai::{Call, AI},
     db::descriptions::{ModuleDescription, SecurityLevel},
 };
-use anyhow::{bail, Context, Result};
-use move_core_types::language_storage::ModuleId;
+use anyhow::{bail, Result};
 use openai_dive::v1::resources::chat::{
     ChatCompletionParametersBuilder, ChatCompletionResponseFormat, ChatMessage, ChatMessageContent,
-    JsonSchemaBuilder,
 };
 use serde_json::json;
+use sqlx::types::Json;
+use sui_sdk::types::base_types::ObjectID;
 
-async fn generate_description(messages: &mut Vec<ChatMessage>, ai: &AI) -> Result<String> {
+/// Prompts a module description is generated with.
+pub const PROMPTS: &[&str] = &[
+    "developer",
+    "module.input",
+    "module.description",
+    "module.warnings",
+    "module.security_level",
+];
+
+async fn generate_description(
+    variables: &[(&str, &str)],
+    messages: &mut Vec<ChatMessage>,
+    ai: &AI,
+    call: &Call,
+) -> Result<String> {
     messages.push(ChatMessage::User {
-        content: ChatMessageContent::Text(ai.prompts.module.description.clone()),
+        content: ChatMessageContent::Text(ai.prompts.render("module.description", variables)?),
         name: None,
     });
 
@@ -22,69 +36,49 @@ async fn generate_description(messages: &mut Vec<ChatMessage>, ai: &AI) -> Resul
         .response_format(ChatCompletionResponseFormat::Text)
         .build()?;
 
-    let (description, message) = ai.text_request(parameters).await?;
+    let (description, message) = ai.text_request(call, parameters).await?;
     messages.push(message);
 
     Ok(description)
 }
 
-async fn generate_warnings(messages: &mut Vec<ChatMessage>, ai: &AI) -> Result<Vec<String>> {
+async fn generate_warnings(
+    variables: &[(&str, &str)],
+    messages: &mut Vec<ChatMessage>,
+    ai: &AI,
+    call: &Call,
+) -> Result<Vec<String>> {
     messages.push(ChatMessage::User {
-        content: ChatMessageContent::Text(ai.prompts.module.warnings.clone()),
+        content: ChatMessageContent::Text(ai.prompts.render("module.warnings", variables)?),
         name: None,
     });
 
-    let parameters = ChatCompletionParametersBuilder::default()
-        .model(ai.model.clone())
-        .messages(messages.as_slice())
-        .response_format(ChatCompletionResponseFormat::JsonSchema(
-            JsonSchemaBuilder::default()
-                .description("Array of strings")
-                .name("String[]")
-                .schema(json!({
-                  "$schema": "https://json-schema.org/draft/2020-12/schema",
-                  "type": "array",
-                  "items": {
-                    "type": "string"
-                  },
-                  "example": ["A", "B"]
-                }))
-                .strict(true)
-                .build()?,
-        ))
-        .build()?;
-
-    let result = ai.chat().create(parameters).await?;
-    let mut wranings: Option<Vec<String>> = None;
-
-    for choice in result.choices {
-        if let ChatMessage::Assistant { content, .. } = &choice.message {
-            let response: String = match content.as_ref() {
-                Some(ChatMessageContent::Text(text)) => text.clone(),
-                Some(ChatMessageContent::ContentPart(_)) => continue,
-                Some(ChatMessageContent::None) => continue,
-                None => continue,
-            };
-            println!("Response: {:?}", choice);
-            let result = serde_json::from_str(&response);
-            if let Ok(result) = result {
-                wranings.replace(result);
-                messages.push(choice.message);
-                break;
-            }
-        }
-    }
-
-    Ok(wranings.context("Error getting warnings")?)
+    ai.json_request(
+        call,
+        messages,
+        "String[]",
+        "Array of strings",
+        json!({
+          "$schema": "https://json-schema.org/draft/2020-12/schema",
+          "type": "array",
+          "items": {
+            "type": "string"
+          },
+          "example": ["A", "B"]
+        }),
+    )
+    .await
 }
 
 async fn generate_security_level(
+    variables: &[(&str, &str)],
     messages: &mut Vec<ChatMessage>,
     ai: &AI,
+    call: &Call,
 ) -> Result<SecurityLevel> {
     println!("Security level");
     messages.push(ChatMessage::User {
-        content: ChatMessageContent::Text(ai.prompts.module.security_level.clone()),
+        content: ChatMessageContent::Text(ai.prompts.render("module.security_level", variables)?),
         name: None,
     });
 
@@ -94,7 +88,7 @@ async fn generate_security_level(
         .response_format(ChatCompletionResponseFormat::Text)
         .build()?;
 
-    let (response, message) = ai.text_request(parameters).await?;
+    let (response, message) = ai.text_request(call, parameters).await?;
     let security_level = if response.contains("Critical") {
         SecurityLevel::CriticalRisk
     } else if response.contains("High") {
@@ -115,21 +109,28 @@ async fn generate_security_level(
 }
 
 pub async fn generate(
-    id: ModuleId,
-    network: String,
+    package_id: &ObjectID,
+    network: &str,
+    module_name: &str,
     messages: &mut Vec<ChatMessage>,
     ai: &AI,
 ) -> Result<ModuleDescription> {
     // TODO: FullModuleDescription
-    let description = generate_description(messages, ai).await?;
-    let warnings = generate_warnings(messages, ai).await?;
-    let security_level = generate_security_level(messages, ai).await?;
+    let call = |prompt: &str| Call::new(package_id, network, module_name, prompt);
+    let variables = [("module_name", module_name)];
+    let description =
+        generate_description(&variables, messages, ai, &call("module.description")).await?;
+    let warnings = generate_warnings(&variables, messages, ai, &call("module.warnings")).await?;
+    let security_level =
+        generate_security_level(&variables, messages, ai, &call("module.security_level")).await?;
 
     Ok(ModuleDescription {
-        id,
-        network,
+        package_id: package_id.to_string(),
+        network: network.to_string(),
+        module_name: module_name.to_string(),
         description,
         warnings,
         security_level,
+        prompt_versions: Json(ai.prompts.versions(PROMPTS)),
     })
 }
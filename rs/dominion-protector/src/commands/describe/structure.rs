@@ -1,12 +1,25 @@
-use crate::{ai::AI, db::descriptions::StructDescription};
-use anyhow::{bail, Context, Result};
-use move_core_types::language_storage::ModuleId;
+use crate::{
+    ai::{Call, AI},
+    db::descriptions::StructDescription,
+};
+use anyhow::Result;
 use openai_dive::v1::resources::chat::{
     ChatCompletionParametersBuilder, ChatCompletionResponseFormat, ChatMessage, ChatMessageContent,
-    JsonSchemaBuilder,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sqlx::types::Json;
+use sui_sdk::types::base_types::ObjectID;
+
+/// Prompts a struct description is generated with.
+pub const PROMPTS: &[&str] = &[
+    "developer",
+    "module.input",
+    "structure.usage",
+    "structure.description",
+    "structure.warnings",
+    "structure.ownership",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Ownership {
@@ -25,91 +38,64 @@ struct Ownership {
 }
 
 async fn generate_ownership(
-    struct_name: &str,
+    variables: &[(&str, &str)],
     messages: &mut Vec<ChatMessage>,
     ai: &AI,
+    call: &Call,
 ) -> Result<Ownership> {
     messages.push(ChatMessage::User {
-        content: ChatMessageContent::Text(
-            ai.prompts
-                .structure
-                .ownership
-                .replace("{struct_name}", struct_name),
-        ),
+        content: ChatMessageContent::Text(ai.prompts.render("structure.ownership", variables)?),
         name: None,
     });
 
-    let parameters = ChatCompletionParametersBuilder::default()
-        .model(ai.model.clone())
-        .messages(messages.as_slice())
-        .response_format(ChatCompletionResponseFormat::JsonSchema(
-            JsonSchemaBuilder::default()
-                .description("Ownership conditions")
-                .name("Ownership")
-                .schema(json!({
-                    "$schema": "https://json-schema.org/draft/2020-12/schema",
-                    "type": "object",
-                    "properties": {
-                        "address_owned": {
-                            "type": "string",
-                            "description": "Indicates if the struct may be owned by a user. 'always' means it is always user-owned, undefined means never."
-                        },
-                        "object_owned": {
-                            "type": "string",
-                            "description": "Indicates if the struct may be owned by another object. 'always' means it is always object-owned, undefined means never."
-                        },
-                        "wrapped": {
-                            "type": "string",
-                            "description": "Indicates if the struct may be wrapped inside another struct. Possible values: 'always', specific conditions (as a string), or undefined if not applicable."
-                        },
-                        "shared": {
-                            "type": "string",
-                            "description": "Indicates if the struct may be shared across multiple users. 'always' means it is always shared, undefined means never."
-                        },
-                        "immutable": {
-                            "type": "string",
-                            "description": "Indicates if the struct is immutable. 'always' means it is always immutable, undefined means never."
-                        },
-                        "event": {
-                            "type": "string",
-                            "description": "Indicates if the struct is an event. 'always' means it is always an event, undefined means never."
-                        }
-                    },
-                    "additionalProperties": false,
-                }))
-                .strict(true)
-                .build()?,
-        ))
-        .build()?;
-
-    let result = ai.chat().create(parameters).await?;
-    if let ChatMessage::Assistant { content, .. } = &result.choices[0].message {
-        let response: String = match content.as_ref() {
-            Some(ChatMessageContent::Text(text)) => text.clone(),
-            _ => bail!("Invalid response"),
-        };
-        println!("Response: {:?}", result.choices[0]);
-        let ownership: Ownership = serde_json::from_str(&response)?;
-        messages.push(result.choices[0].message.clone());
-
-        Ok(ownership)
-    } else {
-        bail!("Invalid response")
-    }
+    ai.json_request(
+        call,
+        messages,
+        "Ownership",
+        "Ownership conditions",
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "address_owned": {
+                    "type": "string",
+                    "description": "Indicates if the struct may be owned by a user. 'always' means it is always user-owned, undefined means never."
+                },
+                "object_owned": {
+                    "type": "string",
+                    "description": "Indicates if the struct may be owned by another object. 'always' means it is always object-owned, undefined means never."
+                },
+                "wrapped": {
+                    "type": "string",
+                    "description": "Indicates if the struct may be wrapped inside another struct. Possible values: 'always', specific conditions (as a string), or undefined if not applicable."
+                },
+                "shared": {
+                    "type": "string",
+                    "description": "Indicates if the struct may be shared across multiple users. 'always' means it is always shared, undefined means never."
+                },
+                "immutable": {
+                    "type": "string",
+                    "description": "Indicates if the struct is immutable. 'always' means it is always immutable, undefined means never."
+                },
+                "event": {
+                    "type": "string",
+                    "description": "Indicates if the struct is an event. 'always' means it is always an event, undefined means never."
+                }
+            },
+            "additionalProperties": false,
+        }),
+    )
+    .await
 }
 
 async fn generate_description(
-    struct_name: &str,
+    variables: &[(&str, &str)],
     messages: &mut Vec<ChatMessage>,
     ai: &AI,
+    call: &Call,
 ) -> Result<String> {
     messages.push(ChatMessage::User {
-        content: ChatMessageContent::Text(
-            ai.prompts
-                .structure
-                .description
-                .replace("{struct_name}", struct_name),
-        ),
+        content: ChatMessageContent::Text(ai.prompts.render("structure.description", variables)?),
         name: None,
     });
 
@@ -119,82 +105,64 @@ async fn generate_description(
         .response_format(ChatCompletionResponseFormat::Text)
         .build()?;
 
-    let (description, message) = ai.text_request(parameters).await?;
+    let (description, message) = ai.text_request(call, parameters).await?;
     messages.push(message);
 
     Ok(description)
 }
 
 async fn generate_warnings(
-    struct_name: &str,
+    variables: &[(&str, &str)],
     messages: &mut Vec<ChatMessage>,
     ai: &AI,
+    call: &Call,
 ) -> Result<Vec<String>> {
     messages.push(ChatMessage::User {
-        content: ChatMessageContent::Text(
-            ai.prompts
-                .structure
-                .warnings
-                .replace("{struct_name}", struct_name),
-        ),
+        content: ChatMessageContent::Text(ai.prompts.render("structure.warnings", variables)?),
         name: None,
     });
 
-    let parameters = ChatCompletionParametersBuilder::default()
-        .model(ai.model.clone())
-        .messages(messages.as_slice())
-        .response_format(ChatCompletionResponseFormat::JsonSchema(
-            JsonSchemaBuilder::default()
-                .description("Array of strings")
-                .name("String[]")
-                .schema(json!({
-                  "$schema": "https://json-schema.org/draft/2020-12/schema",
-                  "type": "array",
-                  "items": {
-                    "type": "string"
-                  },
-                  "example": ["A", "B"]
-                }))
-                .strict(true)
-                .build()?,
-        ))
-        .build()?;
-
-    let result = ai.chat().create(parameters).await?;
-    let mut wranings: Option<Vec<String>> = None;
-
-    for choice in result.choices {
-        if let ChatMessage::Assistant { content, .. } = &choice.message {
-            let response: String = match content.as_ref() {
-                Some(ChatMessageContent::Text(text)) => text.clone(),
-                Some(ChatMessageContent::ContentPart(_)) => continue,
-                Some(ChatMessageContent::None) => continue,
-                None => continue,
-            };
-            println!("Response: {:?}", choice);
-            let result = serde_json::from_str(&response);
-            if let Ok(result) = result {
-                wranings.replace(result);
-                messages.push(choice.message);
-                break;
-            }
-        }
-    }
-
-    Ok(wranings.context("Error getting warnings")?)
+    ai.json_request(
+        call,
+        messages,
+        "String[]",
+        "Array of strings",
+        json!({
+          "$schema": "https://json-schema.org/draft/2020-12/schema",
+          "type": "array",
+          "items": {
+            "type": "string"
+          },
+          "example": ["A", "B"]
+        }),
+    )
+    .await
 }
 
 pub async fn generate(
-    module: ModuleId,
-    network: String,
+    package_id: &ObjectID,
+    network: &str,
+    module_name: &str,
     structure_name: &str,
-    messages: &Vec<ChatMessage>,
+    messages: &[ChatMessage],
     ai: &AI,
 ) -> Result<StructDescription> {
+    let call = |prompt: &str| Call::new(package_id, network, module_name, prompt);
+    let variables = [
+        ("module_name", module_name),
+        ("struct_name", structure_name),
+    ];
     let (description, warnings) = {
-        let mut messages = messages.clone();
-        let description = generate_description(structure_name, &mut messages, ai).await?;
-        let warnings = generate_warnings(structure_name, &mut messages, ai).await?;
+        let mut messages = messages.to_vec();
+        let description = generate_description(
+            &variables,
+            &mut messages,
+            ai,
+            &call("structure.description"),
+        )
+        .await?;
+        let warnings =
+            generate_warnings(&variables, &mut messages, ai, &call("structure.warnings")).await?;
         (description, warnings)
     };
 
@@ -206,13 +174,14 @@ pub async fn generate(
         immutable,
         event,
     } = {
-        let mut messages = messages.clone();
-        generate_ownership(structure_name, &mut messages, ai).await?
+        let mut messages = messages.to_vec();
+        generate_ownership(&variables, &mut messages, ai, &call("structure.ownership")).await?
     };
 
     Ok(StructDescription {
-        module,
-        network,
+        package_id: package_id.to_string(),
+        network: network.to_string(),
+        module_name: module_name.to_string(),
         struct_name: structure_name.to_owned(),
         description,
         address_owned,
@@ -222,5 +191,6 @@ pub async fn generate(
         immutable,
         event,
         warnings,
+        prompt_versions: Json(ai.prompts.versions(PROMPTS)),
     })
 }
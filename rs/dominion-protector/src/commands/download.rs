@@ -1,6 +1,6 @@
 use std::{
     cell::{RefCell, RefMut},
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     ops::DerefMut,
     str::FromStr,
 };
@@ -11,18 +11,24 @@ use move_core_types::account_address::AccountAddress;
 use move_model::compiled_model::Model;
 use sqlx::PgConnection;
 use sui_sdk::{
-    rpc_types::{SuiObjectData, SuiObjectDataOptions, SuiRawData},
-    types::base_types::ObjectID,
+    rpc_types::{
+        ObjectChange, SuiCommand, SuiObjectData, SuiObjectDataOptions, SuiRawData,
+        SuiTransactionBlockDataAPI, SuiTransactionBlockKind, SuiTransactionBlockResponse,
+        SuiTransactionBlockResponseOptions,
+    },
+    types::{base_types::ObjectID, digests::TransactionDigest},
 };
 
 use crate::{
     db::{
         full_object::{load_object, save_rpc_object},
+        full_transaction::save_rpc_transaction,
         Db,
     },
+    layout::type_addresses,
     sui_client::SuiClientWithNetwork,
 };
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 
 #[derive(Args)]
 pub struct DownloadCommand {
@@ -49,17 +55,16 @@ impl DownloadCommand {
                 Ok(())
             }
             DownloadType::Transaction { digest } => {
-                todo!("Downloading transaction with digest: {}", digest);
+                let digest = TransactionDigest::from_str(&digest)?;
+                println!("Downloading transaction with digest: {}", &digest);
+                download_transaction(&digest, &client, &db).await?;
+                Ok(())
             }
         }
     }
 }
 
-pub async fn download_object(
-    object_id: &ObjectID,
-    client: &SuiClientWithNetwork,
-    db: &Db,
-) -> Result<SuiObjectData> {
+async fn read_object(object_id: &ObjectID, client: &SuiClientWithNetwork) -> Result<SuiObjectData> {
     let object = client
         .client
         .read_api()
@@ -76,7 +81,24 @@ pub async fn download_object(
             },
         )
         .await?;
-    let data = object.data.unwrap();
+    object.data.context("Object not found")
+}
+
+pub async fn download_object(
+    object_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<SuiObjectData> {
+    let data = read_object(object_id, client).await?;
+
+    if let Some(SuiRawData::MoveObject(move_object)) = data.bcs.as_ref() {
+        // Contents are decoded with the layouts of every package the type mentions
+        let package_ids = type_addresses(&move_object.type_)
+            .into_iter()
+            .map(ObjectID::from_address)
+            .collect();
+        get_or_download_packages(package_ids, client, db).await?;
+    }
 
     // Test code
     /*
@@ -112,6 +134,74 @@ pub async fn download_object(
     Ok(data)
 }
 
+pub async fn download_transaction(
+    digest: &TransactionDigest,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<SuiTransactionBlockResponse> {
+    let transaction = client
+        .client
+        .read_api()
+        .get_transaction_with_options(
+            *digest,
+            SuiTransactionBlockResponseOptions {
+                show_input: true,
+                show_raw_input: true,
+                show_effects: true,
+                show_events: true,
+                show_object_changes: true,
+                show_balance_changes: false,
+                show_raw_effects: false,
+            },
+        )
+        .await?;
+
+    for package_id in touched_packages(&transaction) {
+        println!("Transaction touched package: {}", &package_id);
+        get_or_download_object(&package_id, client, db).await?;
+    }
+
+    let mut tx = db.pool.begin().await?;
+    save_rpc_transaction(&mut *tx, &client.network, &transaction).await?;
+    tx.commit().await?;
+    Ok(transaction)
+}
+
+/// Packages called, published or upgraded by the transaction, plus the ones that emitted its events.
+fn touched_packages(transaction: &SuiTransactionBlockResponse) -> BTreeSet<ObjectID> {
+    let mut packages = BTreeSet::new();
+    if let Some(SuiTransactionBlockKind::ProgrammableTransaction(ptb)) = transaction
+        .transaction
+        .as_ref()
+        .map(|transaction| transaction.data.transaction())
+    {
+        for command in &ptb.commands {
+            match command {
+                SuiCommand::MoveCall(call) => {
+                    packages.insert(call.package);
+                }
+                SuiCommand::Upgrade(_, package_id, _) => {
+                    packages.insert(*package_id);
+                }
+                _ => {}
+            }
+        }
+    }
+    for change in transaction.object_changes.iter().flatten() {
+        if let ObjectChange::Published { package_id, .. } = change {
+            packages.insert(*package_id);
+        }
+    }
+    for event in transaction
+        .events
+        .iter()
+        .flat_map(|events| events.data.iter())
+    {
+        packages.insert(event.package_id);
+    }
+    packages
+}
+
 pub async fn get_or_download_object(
     object_id: &ObjectID,
     client: &SuiClientWithNetwork,
@@ -125,11 +215,52 @@ pub async fn get_or_download_object(
     })
 }
 
+/// Downloads missing packages together with everything in their linkage tables.
+async fn get_or_download_packages(
+    package_ids: Vec<ObjectID>,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<()> {
+    let mut resolved = BTreeSet::new();
+    let mut unresolved = package_ids;
+    while let Some(package_id) = unresolved.pop() {
+        if !resolved.insert(package_id) {
+            continue;
+        }
+        let package = match load_object(&db.pool, &client.network, &package_id).await? {
+            Some(package) => package,
+            None => {
+                let package = read_object(&package_id, client).await?;
+                let mut tx = db.pool.begin().await?;
+                save_rpc_object(&mut *tx, &client.network, &package).await?;
+                tx.commit().await?;
+                package
+            }
+        };
+        if let Some(SuiRawData::Package(package)) = package.bcs {
+            unresolved.extend(package.linkage_table.into_keys());
+        }
+    }
+    Ok(())
+}
+
 pub async fn get_or_download_model(
     package_id: &ObjectID,
     client: &SuiClientWithNetwork,
     db: &Db,
 ) -> Result<Model> {
+    let modules = get_or_download_modules(package_id, client, db).await?;
+    let model = Model::from_compiled(&BTreeMap::new(), modules);
+
+    Ok(model)
+}
+
+/// Modules of the package and of all its dependencies except the Move stdlib and the Sui framework.
+pub async fn get_or_download_modules(
+    package_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<Vec<CompiledModule>> {
     let mut modules = Vec::<CompiledModule>::new();
     let mut unresolved_dependenices = vec![package_id.clone()];
     while !unresolved_dependenices.is_empty() {
@@ -152,7 +283,5 @@ pub async fn get_or_download_model(
             );
         }
     }
-    let model = Model::from_compiled(&BTreeMap::new(), modules);
-
-    Ok(model)
+    Ok(modules)
 }
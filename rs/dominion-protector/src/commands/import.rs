@@ -0,0 +1,171 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::Args;
+use move_binary_format::CompiledModule;
+use move_core_types::account_address::AccountAddress;
+use sqlx::{Acquire, Postgres};
+use sui_sdk::types::{base_types::ObjectID, digests::ObjectDigest};
+
+use crate::db::{
+    full_module::save_module,
+    object::{Object, OwnerType},
+    Db,
+};
+
+/// Stores compiled modules as a package, without any RPC.
+#[derive(Args)]
+pub struct ImportCommand {
+    /// Directory with `.mv` files, a `build/<package>/bytecode_modules` folder or a Move package
+    /// built with `sui move build`
+    pub path: PathBuf,
+    /// Id to store the package under, also replacing the `0x0` address of unpublished modules
+    #[arg(long, default_value = "0x0")]
+    pub id: String,
+    #[arg(long, default_value = "local")]
+    pub network: String,
+}
+
+impl ImportCommand {
+    pub async fn run(self) -> Result<()> {
+        let db = Db::new().await?;
+        let package_id = ObjectID::from_str(&self.id)?;
+        let modules = read_modules(&self.path)?;
+        println!(
+            "Importing {} modules as {} on {}",
+            modules.len(),
+            package_id,
+            self.network
+        );
+        let mut tx = db.pool.begin().await?;
+        import_package(&mut *tx, package_id, &self.network, &modules).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Finds the compiled modules of the package at `path`, skipping its dependencies.
+pub fn read_modules(path: &Path) -> Result<BTreeMap<String, Vec<u8>>> {
+    let mut dir = path.to_path_buf();
+    if !has_modules(&dir)? && dir.join("build").is_dir() {
+        dir = dir.join("build");
+    }
+    if !has_modules(&dir)? && !dir.ends_with("bytecode_modules") {
+        // `build` holds one folder per package built, usually just one
+        let built = fs::read_dir(&dir)?
+            .map(|entry| Ok(entry?.path().join("bytecode_modules")))
+            .filter(|path| path.as_ref().map_or(true, |path| path.is_dir()))
+            .collect::<Result<Vec<_>>>()?;
+        match built.as_slice() {
+            [bytecode_modules] => dir = bytecode_modules.clone(),
+            [] => {}
+            _ => bail!("Several packages are built in {}", dir.display()),
+        }
+    }
+
+    let mut modules = BTreeMap::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "mv") {
+            let module_name = path
+                .file_stem()
+                .context("Module file has no name")?
+                .to_string_lossy()
+                .to_string();
+            modules.insert(module_name, fs::read(&path)?);
+        }
+    }
+    if modules.is_empty() {
+        bail!("No compiled modules in {}", dir.display());
+    }
+    Ok(modules)
+}
+
+fn has_modules(dir: &Path) -> Result<bool> {
+    Ok(fs::read_dir(dir)?
+        .any(|entry| entry.is_ok_and(|entry| entry.path().extension().is_some_and(|e| e == "mv"))))
+}
+
+/// Stores the modules the way downloading a freshly published package does. Modules still at
+/// `0x0` get the package id as their address, like publishing does.
+pub async fn import_package<'a, A>(
+    db: A,
+    package_id: ObjectID,
+    network: &str,
+    modules: &BTreeMap<String, Vec<u8>>,
+) -> Result<()>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut db = db.acquire().await?;
+
+    let mut published = BTreeMap::new();
+    for (module_name, bytecode) in modules {
+        let mut module = CompiledModule::deserialize_with_defaults(bytecode)?;
+        let address = module.self_handle().address;
+        let self_address = &mut module.address_identifiers[address.0 as usize];
+        if *self_address == AccountAddress::ZERO && package_id != ObjectID::ZERO {
+            *self_address = package_id.into();
+        } else if *self_address != AccountAddress::from(package_id) {
+            bail!(
+                "Module {} is compiled for {}, not {}",
+                module_name,
+                self_address.to_hex_literal(),
+                package_id
+            );
+        }
+        let mut bytecode = Vec::new();
+        module.serialize_with_version(module.version, &mut bytecode)?;
+        published.insert(module_name.clone(), (module, bytecode));
+    }
+
+    // Nothing was upgraded yet, every type originates in this package
+    let mut type_origins = BTreeMap::new();
+    for (module_name, (module, _)) in &published {
+        for handle in module
+            .datatype_handles()
+            .iter()
+            .filter(|handle| handle.module == module.self_handle_idx())
+        {
+            type_origins.insert(
+                (
+                    module_name.clone(),
+                    module.identifier_at(handle.name).to_string(),
+                ),
+                package_id,
+            );
+        }
+    }
+
+    Object {
+        object_id: package_id.to_string(),
+        network: network.to_string(),
+        version: 1,
+        digest: ObjectDigest::new([0; 32]).to_string(),
+        object_type: "package".to_string(),
+        owner_type: OwnerType::Immutable,
+        owner: None,
+        initial_shared_version: None,
+        read_at: Utc::now(),
+    }
+    .save(&mut *db)
+    .await?;
+    for (module_name, (_, bytecode)) in &published {
+        save_module(
+            &mut *db,
+            package_id,
+            network,
+            module_name,
+            bytecode,
+            &type_origins,
+        )
+        .await?;
+    }
+    Ok(())
+}
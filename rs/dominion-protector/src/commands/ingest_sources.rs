@@ -0,0 +1,81 @@
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{
+    commands::{download::get_or_download_object, verify_source::print_verification},
+    db::Db,
+    source_providers::{git::Git, ingest_package, mvr::Mvr, welldone::Welldone, SourceProvider},
+    sui_client::SuiClientWithNetwork,
+};
+
+/// Stores sources of packages from a provider, verified by recompiling them.
+#[derive(Args)]
+pub struct IngestSourcesCommand {
+    #[command(subcommand)]
+    pub provider: ProviderCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ProviderCommand {
+    /// Every package verified on WELLDONE Code, or just one
+    Welldone {
+        #[arg(long)]
+        id: Option<String>,
+    },
+    /// Local checkout of a Move package
+    Git { path: PathBuf, id: String },
+    /// Packages registered on the Move registry
+    Mvr {
+        #[arg(required = true)]
+        names: Vec<String>,
+        #[arg(long)]
+        registry: Option<String>,
+    },
+}
+
+impl IngestSourcesCommand {
+    pub async fn run(self) -> Result<()> {
+        let client = SuiClientWithNetwork::with_default_network().await?;
+        self.ingest(&client).await
+    }
+
+    pub async fn ingest(self, client: &SuiClientWithNetwork) -> Result<()> {
+        let db = Db::new().await?;
+        // Everything verified on WELLDONE Code is too much to stop at the first broken package
+        let bulk = matches!(self.provider, ProviderCommand::Welldone { id: None });
+        let (provider, only): (Box<dyn SourceProvider>, Option<String>) = match self.provider {
+            ProviderCommand::Welldone { id } => (Box::new(Welldone), id),
+            ProviderCommand::Git { path, id } => (
+                Box::new(Git {
+                    path,
+                    package_id: ObjectID::from_str(&id)?,
+                }),
+                None,
+            ),
+            ProviderCommand::Mvr { names, registry } => (Box::new(Mvr { names, registry }), None),
+        };
+        let packages = match only {
+            Some(id) => vec![ObjectID::from_str(&id)?],
+            None => provider.packages(&client.network).await?,
+        };
+
+        for package_id in packages {
+            println!("Package: {}", package_id);
+            let ingested = async {
+                get_or_download_object(&package_id, client, &db).await?;
+                ingest_package(&db, &client.network, provider.as_ref(), &package_id).await
+            }
+            .await;
+            match ingested {
+                Ok(Some(verification)) => print_verification(&verification),
+                Ok(None) => println!("No sources from {}", provider.kind()),
+                Err(err) if bulk => println!("Failed to ingest {}: {:#}", package_id, err),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{
+    db::Db,
+    lineage::{diff, get_lineage},
+    sui_client::SuiClientWithNetwork,
+};
+
+#[derive(Args)]
+pub struct LineageCommand {
+    /// Any version of the package
+    pub id: String,
+    /// Compare only this version with `--to`, by default every version is compared with the previous one
+    #[arg(long, requires = "to")]
+    pub from: Option<i64>,
+    #[arg(long, requires = "from")]
+    pub to: Option<i64>,
+}
+
+impl LineageCommand {
+    pub async fn run(self) -> Result<()> {
+        let client = SuiClientWithNetwork::with_default_network().await?;
+        let db = Db::new().await?;
+        let package_id = ObjectID::from_str(&self.id)?;
+        let lineage = get_lineage(&package_id, &client, &db).await?;
+        for upgrade in &lineage {
+            println!(
+                "v{} {} published in {}",
+                upgrade.version, upgrade.package_id, upgrade.digest
+            );
+        }
+
+        let pairs = match (self.from, self.to) {
+            (Some(from), Some(to)) => {
+                let version = |version: i64| {
+                    lineage
+                        .iter()
+                        .find(|upgrade| upgrade.version == version)
+                        .with_context(|| format!("Version {} not found", version))
+                };
+                vec![(version(from)?, version(to)?)]
+            }
+            _ => lineage.iter().zip(lineage.iter().skip(1)).collect(),
+        };
+        for (from, to) in pairs {
+            let diff = diff(
+                &ObjectID::from_str(&from.package_id)?,
+                &ObjectID::from_str(&to.package_id)?,
+                &client,
+                &db,
+            )
+            .await?;
+            println!(
+                "v{} -> v{}: {:?}",
+                from.version, to.version, diff.security_level
+            );
+            for change in diff.changes {
+                println!(
+                    "  [{:?}] {}{}: {}",
+                    change.security_level,
+                    change.module_name,
+                    change
+                        .name
+                        .map(|name| format!("::{}", name))
+                        .unwrap_or_default(),
+                    change.description
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,16 @@
+pub mod ai_report;
+pub mod bench_prompts;
+pub mod capabilities;
 pub mod download;
 pub mod decompile;
-// pub mod describe;
+pub mod describe;
+pub mod import;
+pub mod ingest_sources;
+pub mod lineage;
+pub mod scan;
 pub mod serve;
 pub mod watch;
+pub mod verify;
+pub mod verify_source;
 // pub mod clear;
 pub mod experiment;
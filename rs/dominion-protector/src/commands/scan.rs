@@ -0,0 +1,51 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use clap::Args;
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{
+    db::Db,
+    rules::{all_rules, run_rules, Package},
+    sui_client::SuiClientWithNetwork,
+};
+
+#[derive(Args)]
+pub struct ScanCommand {
+    pub id: String,
+    /// Run only this rule and print its findings without storing them
+    #[arg(long)]
+    pub rule: Option<String>,
+}
+
+impl ScanCommand {
+    pub async fn run(self) -> Result<()> {
+        let client = SuiClientWithNetwork::with_default_network().await?;
+        let db = Db::new().await?;
+        let package_id = ObjectID::from_str(&self.id)?;
+        let findings = match &self.rule {
+            Some(name) => {
+                let Some(rule) = all_rules().into_iter().find(|rule| rule.name() == name) else {
+                    bail!("Unknown rule {}", name);
+                };
+                rule.check(&Package::load(&package_id, &client, &db).await?)
+            }
+            None => run_rules(&package_id, &client, &db).await?,
+        };
+        for finding in &findings {
+            println!(
+                "[{:?}] {} {}::{}{}: {}",
+                finding.severity,
+                finding.rule,
+                finding.module_name,
+                finding.member_name,
+                finding
+                    .code_offset
+                    .map(|offset| format!(" @{}", offset))
+                    .unwrap_or_default(),
+                finding.message
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    Json,
+};
+use sui_types::transaction::TransactionData;
+
+use crate::analyzer::{TransactionAnalyzer, TransactionReport};
+
+use super::{error::AppError, state::ServerState};
+
+/// Takes BCS serialized `TransactionData` before it is signed.
+pub async fn analyze_transaction(
+    State(state): State<Arc<ServerState>>,
+    Path(network): Path<String>,
+    body: Bytes,
+) -> Result<Json<TransactionReport>, AppError> {
+    let client = state.sui_client(&network)?;
+    let transaction: TransactionData = bcs::from_bytes(&body)
+        .map_err(|err| AppError::BadRequest(format!("Invalid transaction data: {}", err)))?;
+    Ok(Json(
+        TransactionAnalyzer::new(client, &state.db)
+            .analyze(&transaction)
+            .await?,
+    ))
+}
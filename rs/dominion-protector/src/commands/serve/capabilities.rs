@@ -0,0 +1,24 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::capabilities::{capability_graph, CapabilityGraph};
+
+use super::{error::AppError, state::ServerState};
+
+/// Capabilities of the package, who gets them and which functions they gate.
+pub async fn capabilities(
+    State(state): State<Arc<ServerState>>,
+    Path((network, package_id)): Path<(String, String)>,
+) -> Result<Json<CapabilityGraph>, AppError> {
+    let client = state.sui_client(&network)?;
+    let package_id = ObjectID::from_str(&package_id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid package id {}", package_id)))?;
+    Ok(Json(
+        capability_graph(&package_id, client, &state.db).await?,
+    ))
+}
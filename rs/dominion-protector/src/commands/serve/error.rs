@@ -1,18 +1,29 @@
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
+    Json,
 };
+use serde_json::json;
 
-pub struct AppError(anyhow::Error);
+pub enum AppError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(anyhow::Error),
+}
 
 // Tell axum how to convert `AppError` into a response.
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Something went wrong: {}", self.0),
-        )
-            .into_response()
+        let (status, error, message) = match self {
+            AppError::BadRequest(message) => (StatusCode::BAD_REQUEST, "bad_request", message),
+            AppError::NotFound(message) => (StatusCode::NOT_FOUND, "not_found", message),
+            AppError::Internal(err) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                err.to_string(),
+            ),
+        };
+        (status, Json(json!({ "error": error, "message": message }))).into_response()
     }
 }
 
@@ -21,6 +32,6 @@ where
     E: Into<anyhow::Error>,
 {
     fn from(err: E) -> Self {
-        Self(err.into())
+        Self::Internal(err.into())
     }
 }
@@ -0,0 +1,26 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{db::finding::Finding, rules::run_rules};
+
+use super::{error::AppError, state::ServerState};
+
+/// Findings of the scam heuristics for the package, running them if it was never scanned.
+pub async fn findings(
+    State(state): State<Arc<ServerState>>,
+    Path((network, package_id)): Path<(String, String)>,
+) -> Result<Json<Vec<Finding>>, AppError> {
+    let client = state.sui_client(&network)?;
+    let package_id = ObjectID::from_str(&package_id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid package id {}", package_id)))?;
+    let findings = Finding::load_all_by_package(&state.db.pool, &package_id, &network).await?;
+    if !findings.is_empty() {
+        return Ok(Json(findings));
+    }
+    Ok(Json(run_rules(&package_id, client, &state.db).await?))
+}
@@ -0,0 +1,40 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{
+    db::package_upgrade::PackageUpgrade,
+    lineage::{self, get_lineage, PackageDiff},
+};
+
+use super::{error::AppError, state::ServerState};
+
+fn parse_package_id(package_id: &str) -> Result<ObjectID, AppError> {
+    ObjectID::from_str(package_id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid package id {}", package_id)))
+}
+
+/// Every version of the package, oldest first.
+pub async fn lineage(
+    State(state): State<Arc<ServerState>>,
+    Path((network, package_id)): Path<(String, String)>,
+) -> Result<Json<Vec<PackageUpgrade>>, AppError> {
+    let client = state.sui_client(&network)?;
+    let package_id = parse_package_id(&package_id)?;
+    Ok(Json(get_lineage(&package_id, client, &state.db).await?))
+}
+
+/// Risky changes between two versions of a package.
+pub async fn package_diff(
+    State(state): State<Arc<ServerState>>,
+    Path((network, from, to)): Path<(String, String, String)>,
+) -> Result<Json<PackageDiff>, AppError> {
+    let client = state.sui_client(&network)?;
+    let from = parse_package_id(&from)?;
+    let to = parse_package_id(&to)?;
+    Ok(Json(lineage::diff(&from, &to, client, &state.db).await?))
+}
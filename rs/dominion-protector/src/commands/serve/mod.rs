@@ -1,40 +1,63 @@
+pub mod analyze_transaction;
+pub mod capabilities;
 pub mod error;
-// pub mod known_packages;
-// pub mod module_description;
-// pub mod package_description;
+pub mod findings;
+pub mod known_packages;
+pub mod lineage;
+pub mod module_description;
+pub mod package_description;
+pub mod source_links;
+pub mod source_verification;
 pub mod state;
 
 use clap::Args;
 
-use axum::extract::{Json, Path, Query, State};
-use axum::http::StatusCode;
-use axum::{routing::get, Router};
-use serde_json::{json, Value};
+use axum::{
+    routing::{get, post},
+    Router,
+};
 use state::ServerState;
-use std::collections::HashMap;
 use std::result::Result;
 use std::sync::Arc;
 
-// use crate::commands::serve::known_packages::known_packages;
-// use crate::commands::serve::module_description::module_description;
+use crate::commands::serve::analyze_transaction::analyze_transaction;
+use crate::commands::serve::capabilities::capabilities;
+use crate::commands::serve::findings::findings;
+use crate::commands::serve::known_packages::known_packages;
+use crate::commands::serve::lineage::{lineage, package_diff};
+use crate::commands::serve::module_description::module_description;
+use crate::commands::serve::package_description::package_description;
+use crate::commands::serve::source_links::source_links;
+use crate::commands::serve::source_verification::source_verification;
 
 #[derive(Args)]
 pub struct ServeCommand {
     address: String,
 }
 
+pub fn router(state: Arc<ServerState>) -> Router {
+    Router::new()
+        .route("/{network}/known_packages", get(known_packages))
+        .route("/{network}/module/{module_id}", get(module_description))
+        .route("/{network}/package/{package_id}", get(package_description))
+        .route("/{network}/lineage/{package_id}", get(lineage))
+        .route("/{network}/diff/{from}/{to}", get(package_diff))
+        .route("/{network}/capabilities/{package_id}", get(capabilities))
+        .route("/{network}/findings/{package_id}", get(findings))
+        .route(
+            "/{network}/source_verification/{package_id}",
+            get(source_verification),
+        )
+        .route("/{network}/source_links/{module_id}", get(source_links))
+        .route("/{network}/analyze_transaction", post(analyze_transaction))
+        .with_state(state)
+}
+
 impl ServeCommand {
     pub async fn run(self) -> Result<(), anyhow::Error> {
         let state = Arc::new(ServerState::new().await?);
-        let app = Router::new()
-            // .route("/{network}/known_packages", get(known_packages))
-            /*.route(
-                "/{network}/module/{module_id}",
-                get(module_description),
-            )*/
-            .with_state(state);
         let listener = tokio::net::TcpListener::bind(self.address).await?;
-        axum::serve(listener, app).await?;
+        axum::serve(listener, router(state)).await?;
         Ok(())
     }
 }
@@ -1,29 +1,27 @@
 use std::{str::FromStr, sync::Arc};
 
-use anyhow::Context;
 use axum::{
     extract::{Path, State},
     Json,
 };
 use move_core_types::language_storage::ModuleId;
-use serde_json::Value;
 
-use crate::commands::describe::full_module;
+use crate::{
+    commands::{decompile::Kind, describe::full_module},
+    db::descriptions::FullModuleDescription,
+};
 
 use super::{error::AppError, state::ServerState};
 
+/// Describes the module on the first request, later requests are served from the database.
 pub async fn module_description(
     State(state): State<Arc<ServerState>>,
     Path((network, module_id)): Path<(String, String)>,
-) -> Result<Json<Value>, AppError> {
-    let module = ModuleId::from_str(&module_id)?;
-    Ok(Json(serde_json::to_value(
-        full_module::get_or_describe(
-            module,
-            &mut *state.db.lock().await,
-            state.sui_clients.get(&network).context("Unknown network")?,
-            &state.ai,
-        )
-        .await?,
-    )?))
+) -> Result<Json<FullModuleDescription>, AppError> {
+    let client = state.sui_client(&network)?;
+    let module = ModuleId::from_str(&module_id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid module id {}", module_id)))?;
+    Ok(Json(
+        full_module::get_or_describe(&module, &Kind::Revela, client, &state.db, &state.ai).await?,
+    ))
 }
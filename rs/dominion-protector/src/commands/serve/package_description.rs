@@ -1,13 +1,50 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 
-use axum::{extract::{Path, State}, Json};
-use serde_json::{json, Value};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::Serialize;
+use sui_sdk::types::base_types::ObjectID;
 
-use super::state::ServerState;
+use crate::db::{descriptions::FullModuleDescription, package_module::PackageModule};
 
-async fn package_description(
+use super::{error::AppError, state::ServerState};
+
+#[derive(Serialize)]
+pub struct PackageDescription {
+    pub package_id: String,
+    pub network: String,
+    pub modules: Vec<FullModuleDescription>,
+}
+
+/// Descriptions of the already described modules of a package.
+pub async fn package_description(
     State(state): State<Arc<ServerState>>,
-    Path((network, id)): Path<(String, String)>,
-) -> Json<Value> {
-    Json(json!({ "data": 42 }))
+    Path((network, package_id)): Path<(String, String)>,
+) -> Result<Json<PackageDescription>, AppError> {
+    state.sui_client(&network)?;
+    let package_id = ObjectID::from_str(&package_id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid package id {}", package_id)))?;
+    let mut db = state.db.pool.acquire().await?;
+    let mut modules = Vec::new();
+    for module in PackageModule::load_all_by_package(&mut *db, &package_id, &network).await? {
+        if let Some(description) =
+            FullModuleDescription::load(&mut *db, &package_id, &network, &module.module_name)
+                .await?
+        {
+            modules.push(description);
+        }
+    }
+    if modules.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "Package {} is not described",
+            package_id
+        )));
+    }
+    Ok(Json(PackageDescription {
+        package_id: package_id.to_string(),
+        network,
+        modules,
+    }))
 }
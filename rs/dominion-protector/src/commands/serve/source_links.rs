@@ -0,0 +1,45 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use move_binary_format::CompiledModule;
+use move_core_types::language_storage::ModuleId;
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{
+    db::{module_source_map::ModuleSourceMap, package_module::PackageModule},
+    source_map::{update_links, SourceLinks},
+};
+
+use super::{error::AppError, state::ServerState};
+
+/// Links between the verified source, disassembly and Revela output of the module, made on the
+/// first request after its source was verified.
+pub async fn source_links(
+    State(state): State<Arc<ServerState>>,
+    Path((network, module_id)): Path<(String, String)>,
+) -> Result<Json<SourceLinks>, AppError> {
+    let module_id = ModuleId::from_str(&module_id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid module id {}", module_id)))?;
+    let package_id = ObjectID::from(*module_id.address());
+    let module_name = module_id.name().as_str();
+    let not_found = || AppError::NotFound(format!("No verified source for {}", module_id));
+
+    let stored = ModuleSourceMap::load(&state.db.pool, &package_id, &network, module_name)
+        .await?
+        .ok_or_else(not_found)?;
+    if let Some(links) = stored.links {
+        return Ok(Json(links.0));
+    }
+    let bytecode = PackageModule::load(&state.db.pool, &package_id, &network, module_name)
+        .await?
+        .ok_or_else(not_found)?;
+    let module = CompiledModule::deserialize_with_defaults(&bytecode.module_bytecode)?;
+    Ok(Json(
+        update_links(&state.db.pool, &network, &package_id, &module)
+            .await?
+            .ok_or_else(not_found)?,
+    ))
+}
@@ -0,0 +1,24 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::db::source_verification::SourceVerification;
+
+use super::{error::AppError, state::ServerState};
+
+/// Outcome of recompiling every source stored for the package, a module's source is verified
+/// when its result is `matches`.
+pub async fn source_verification(
+    State(state): State<Arc<ServerState>>,
+    Path((network, package_id)): Path<(String, String)>,
+) -> Result<Json<Vec<SourceVerification>>, AppError> {
+    let package_id = ObjectID::from_str(&package_id)
+        .map_err(|_| AppError::BadRequest(format!("Invalid package id {}", package_id)))?;
+    Ok(Json(
+        SourceVerification::load_all_by_package(&state.db.pool, &package_id, &network).await?,
+    ))
+}
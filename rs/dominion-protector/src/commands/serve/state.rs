@@ -4,6 +4,8 @@ use anyhow::Result;
 
 use crate::{ai::AI, db::Db, sui_client::SuiClientWithNetwork};
 
+use super::error::AppError;
+
 pub struct ServerState {
     pub db: Db,
     pub sui_clients: HashMap<String, SuiClientWithNetwork>,
@@ -12,8 +14,10 @@ pub struct ServerState {
 
 impl ServerState {
     pub async fn new() -> Result<Self> {
+        let db = Db::new().await?;
         Ok(Self {
-            db: Db::new().await?,
+            ai: AI::new(&db).await?,
+            db,
             sui_clients: HashMap::from_iter([
                 (
                     "mainnet".to_owned(),
@@ -28,7 +32,12 @@ impl ServerState {
                     SuiClientWithNetwork::new("testnet").await?,
                 ),
             ]),
-            ai: AI::new().await?,
         })
     }
+
+    pub fn sui_client(&self, network: &str) -> Result<&SuiClientWithNetwork, AppError> {
+        self.sui_clients
+            .get(network)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown network {}", network)))
+    }
 }
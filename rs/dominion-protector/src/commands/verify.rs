@@ -0,0 +1,107 @@
+use std::{str::FromStr, time::Duration};
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use clap::Args;
+use move_binary_format::CompiledModule;
+use sui_sdk::{rpc_types::SuiRawData, types::base_types::ObjectID};
+
+use crate::{
+    commands::download::get_or_download_object,
+    db::{verification_result::VerificationResult, Db},
+    decompiler::decompile_module_to_smt,
+    sui_client::SuiClientWithNetwork,
+    verifier::{shared_types, targets, Solver},
+};
+
+#[derive(Args)]
+pub struct VerifyCommand {
+    pub id: String,
+    #[arg(long)]
+    pub module: Option<String>,
+    #[arg(long, default_value = "z3")]
+    pub solver: Solver,
+    #[arg(long, default_value = "30")]
+    pub timeout_secs: u64,
+}
+
+impl VerifyCommand {
+    pub async fn run(self) -> Result<()> {
+        let client = SuiClientWithNetwork::with_default_network().await?;
+        let db = Db::new().await?;
+        let package_id = ObjectID::from_str(&self.id)?;
+        let package = get_or_download_object(&package_id, &client, &db).await?;
+        let Some(SuiRawData::Package(package)) = package.bcs else {
+            bail!("Object is not a package");
+        };
+
+        let modules = package
+            .module_map
+            .iter()
+            .map(|(module_name, bytecode)| {
+                Ok((
+                    module_name,
+                    CompiledModule::deserialize_with_defaults(bytecode)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let shared = shared_types(modules.iter().map(|(_, module)| module));
+
+        for (module_name, module) in &modules {
+            if self
+                .module
+                .as_ref()
+                .is_some_and(|module| module != *module_name)
+            {
+                continue;
+            }
+            // Translated again every time, rows saved by older translations can be stale
+            let mut tx = db.pool.begin().await?;
+            let smt = decompile_module_to_smt(&mut *tx, &client.network, package_id, module)
+                .await?
+                .source;
+            tx.commit().await?;
+
+            for target in targets(module, &shared) {
+                let name = format!(
+                    "{}::{} {} {}",
+                    module_name, target.function_name, target.property.name, target.subject
+                );
+                if !target.is_defined_in(&smt) {
+                    println!("{}: skipped, function body is not translated", name);
+                    continue;
+                }
+                let outcome = self
+                    .solver
+                    .check(&target.script(&smt), Duration::from_secs(self.timeout_secs))
+                    .await?;
+                let violated = target.property.violated(&outcome.verdict);
+                println!(
+                    "{}: {}{}",
+                    name,
+                    outcome.verdict,
+                    if violated == Some(true) {
+                        " (violated)"
+                    } else {
+                        ""
+                    }
+                );
+                let result = VerificationResult {
+                    package_id: package_id.to_string(),
+                    network: client.network.clone(),
+                    module_name: module_name.to_string(),
+                    function_name: target.function_name.clone(),
+                    property: target.property.name.to_string(),
+                    subject: target.subject.clone(),
+                    solver: self.solver.to_string(),
+                    verdict: outcome.verdict,
+                    violated,
+                    model: outcome.model,
+                    checked_at: Utc::now(),
+                };
+                result.save(&db.pool).await?;
+            }
+        }
+        Ok(())
+    }
+}
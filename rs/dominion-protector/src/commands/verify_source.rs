@@ -0,0 +1,59 @@
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::Result;
+use clap::Args;
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{
+    commands::download::get_or_download_object,
+    db::{source_verification::ModuleMatch, Db},
+    source_providers::SourcePackage,
+    source_verification::{verify_package, Verification},
+    sui_client::SuiClientWithNetwork,
+};
+
+/// Compiles a local Move package and checks it reproduces the bytecode of a published package,
+/// storing the outcome so the sources show as verified.
+#[derive(Args)]
+pub struct VerifySourceCommand {
+    pub id: String,
+    /// Move package directory, with its `Move.toml`
+    pub path: PathBuf,
+    /// Kind the sources are stored under
+    #[arg(long, default_value = "upload")]
+    pub kind: String,
+}
+
+impl VerifySourceCommand {
+    pub async fn run(self) -> Result<()> {
+        let client = SuiClientWithNetwork::with_default_network().await?;
+        let db = Db::new().await?;
+        let package_id = ObjectID::from_str(&self.id)?;
+        get_or_download_object(&package_id, &client, &db).await?;
+        let package = SourcePackage::read(&self.path)?;
+        let verification =
+            verify_package(&db, &client.network, &package_id, &self.kind, &package).await?;
+        print_verification(&verification);
+        Ok(())
+    }
+}
+
+pub fn print_verification(verification: &Verification) {
+    if let Some(err) = &verification.compile_error {
+        println!("Sources do not compile: {}", err);
+    }
+    for (module_name, comparison) in &verification.modules {
+        let result = match comparison.result {
+            ModuleMatch::Matches => "verified",
+            ModuleMatch::Differs => "bytecode differs",
+            ModuleMatch::Missing => "no source",
+            ModuleMatch::Extra => "not in the package",
+        };
+        println!("  {}: {}", module_name, result);
+        if comparison.result == ModuleMatch::Differs {
+            for difference in &comparison.differences {
+                println!("    {}", difference);
+            }
+        }
+    }
+}
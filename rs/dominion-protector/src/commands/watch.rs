@@ -1,12 +1,265 @@
-use anyhow::Result;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
 use clap::Args;
+use sui_sdk::{
+    rpc_types::{ObjectChange, SuiRawData, SuiTransactionBlockResponseOptions},
+    types::{base_types::ObjectID, sui_serde::BigInt},
+};
+use sui_storage::blob::Blob;
+use sui_types::full_checkpoint_content::CheckpointData;
+use tokio::{fs, time::sleep};
+
+use crate::{
+    commands::{
+        decompile::{DecompileCommand, Kind},
+        download::download_object,
+    },
+    db::{
+        full_object::{load_object, save_object},
+        watch_cursor::WatchCursor,
+        Db,
+    },
+    sui_client::{default_network, SuiClientWithNetwork},
+};
+
+const CHECKPOINTS_PER_PAGE: usize = 100;
+const TRANSACTIONS_PER_REQUEST: usize = 50;
 
 #[derive(Args)]
-pub struct WatchCommand {}
+pub struct WatchCommand {
+    /// Read `<sequence>.chk` files from this directory instead of polling the fullnode
+    #[arg(long)]
+    pub checkpoints_dir: Option<PathBuf>,
+    /// First checkpoint to process when there is no saved cursor, defaults to the latest one
+    /// of the fullnode or the first file of the directory
+    #[arg(long)]
+    pub from: Option<u64>,
+    /// Decompile new packages right away. Failed ones are left for the `decompile` command
+    #[arg(long)]
+    pub decompile: Option<Kind>,
+    #[arg(long, default_value = "5")]
+    pub poll_interval_secs: u64,
+}
 
 impl WatchCommand {
     pub async fn run(self) -> Result<()> {
-        println!("Watching for changes...");
+        // Checkpoint files carry the packages, so following them needs no fullnode
+        let client = match &self.checkpoints_dir {
+            Some(_) => None,
+            None => Some(SuiClientWithNetwork::with_default_network().await?),
+        };
+        let network = match &client {
+            Some(client) => client.network.clone(),
+            None => default_network()?,
+        };
+        let db = Db::new().await?;
+        let mut next = match WatchCursor::load(&db.pool, &network).await? {
+            Some(cursor) => u64::try_from(cursor.checkpoint)? + 1,
+            None => match (self.from, &self.checkpoints_dir, &client) {
+                (Some(from), _, _) => from,
+                (None, Some(dir), _) => first_checkpoint(dir).await?,
+                (None, None, Some(client)) => {
+                    client
+                        .client
+                        .read_api()
+                        .get_latest_checkpoint_sequence_number()
+                        .await?
+                }
+                (None, None, None) => unreachable!("Fullnode mode always has a client"),
+            },
+        };
+        println!("Watching {} from checkpoint {}", &network, next);
+
+        loop {
+            let result = match (&self.checkpoints_dir, &client) {
+                (Some(dir), _) => self.follow_directory(dir, next, &network, &db).await,
+                (None, Some(client)) => self.follow_fullnode(next, client, &db).await,
+                (None, None) => unreachable!("Fullnode mode always has a client"),
+            };
+            match result {
+                Ok(processed) if processed > next => next = processed,
+                Ok(_) => sleep(Duration::from_secs(self.poll_interval_secs)).await,
+                Err(err) => {
+                    // The cursor only moves after a checkpoint is fully saved, so retrying is safe
+                    println!("Failed to process checkpoint {}: {:?}", next, err);
+                    sleep(Duration::from_secs(self.poll_interval_secs)).await;
+                }
+            }
+        }
+    }
+
+    /// Processes checkpoints available on the fullnode and returns the next one to wait for.
+    async fn follow_fullnode(
+        &self,
+        mut next: u64,
+        client: &SuiClientWithNetwork,
+        db: &Db,
+    ) -> Result<u64> {
+        let page = client
+            .client
+            .read_api()
+            .get_checkpoints(
+                next.checked_sub(1).map(BigInt::from),
+                Some(CHECKPOINTS_PER_PAGE),
+                false,
+            )
+            .await?;
+        for checkpoint in page.data {
+            let mut published = Vec::new();
+            for digests in checkpoint.transactions.chunks(TRANSACTIONS_PER_REQUEST) {
+                let transactions = client
+                    .client
+                    .read_api()
+                    .multi_get_transactions_with_options(
+                        digests.to_vec(),
+                        SuiTransactionBlockResponseOptions {
+                            show_input: false,
+                            show_raw_input: false,
+                            show_effects: false,
+                            show_events: false,
+                            show_object_changes: true,
+                            show_balance_changes: false,
+                            show_raw_effects: false,
+                        },
+                    )
+                    .await?;
+                // Both publish and upgrade produce a new package object
+                for change in transactions
+                    .iter()
+                    .flat_map(|transaction| transaction.object_changes.iter().flatten())
+                {
+                    if let ObjectChange::Published { package_id, .. } = change {
+                        published.push(*package_id);
+                    }
+                }
+            }
+
+            let mut new_packages = Vec::new();
+            for package_id in published {
+                if load_object(&db.pool, &client.network, &package_id)
+                    .await?
+                    .is_none()
+                {
+                    println!("Package published: {}", &package_id);
+                    download_object(&package_id, client, db).await?;
+                    new_packages.push(package_id);
+                }
+            }
+            self.finish_checkpoint(
+                checkpoint.sequence_number,
+                &new_packages,
+                &client.network,
+                db,
+            )
+            .await?;
+            next = checkpoint.sequence_number + 1;
+        }
+        Ok(next)
+    }
+
+    /// Processes checkpoint files present in `dir` and returns the next one to wait for.
+    async fn follow_directory(
+        &self,
+        dir: &Path,
+        mut next: u64,
+        network: &str,
+        db: &Db,
+    ) -> Result<u64> {
+        loop {
+            let path = dir.join(format!("{}.chk", next));
+            if !fs::try_exists(&path).await? {
+                return Ok(next);
+            }
+            let checkpoint: CheckpointData = Blob::from_bytes(&fs::read(&path).await?)?;
+
+            let mut new_packages = Vec::new();
+            for object in checkpoint
+                .transactions
+                .iter()
+                .flat_map(|transaction| transaction.output_objects.iter())
+                .filter(|object| object.is_package())
+            {
+                if load_object(&db.pool, network, &object.id())
+                    .await?
+                    .is_none()
+                {
+                    println!("Package published: {}", object.id());
+                    let mut tx = db.pool.begin().await?;
+                    save_object(&mut *tx, network, object).await?;
+                    tx.commit().await?;
+                    new_packages.push(object.id());
+                }
+            }
+            self.finish_checkpoint(next, &new_packages, network, db)
+                .await?;
+            next += 1;
+        }
+    }
+
+    async fn finish_checkpoint(
+        &self,
+        checkpoint: u64,
+        new_packages: &[ObjectID],
+        network: &str,
+        db: &Db,
+    ) -> Result<()> {
+        if let Some(kind) = &self.decompile {
+            let decompiler = DecompileCommand {
+                id: None,
+                kind: kind.clone(),
+            };
+            for package_id in new_packages {
+                if let Err(err) = decompile_saved(&decompiler, package_id, network, db).await {
+                    println!("Failed to decompile package {}: {:?}", package_id, err);
+                }
+            }
+        }
+        WatchCursor {
+            network: network.to_string(),
+            checkpoint: checkpoint.try_into()?,
+            updated_at: Utc::now(),
+        }
+        .save(&db.pool)
+        .await?;
         Ok(())
     }
 }
+
+/// Decompiles a package the checkpoint saved, without going back to the fullnode for it.
+async fn decompile_saved(
+    decompiler: &DecompileCommand,
+    package_id: &ObjectID,
+    network: &str,
+    db: &Db,
+) -> Result<()> {
+    let object = load_object(&db.pool, network, package_id)
+        .await?
+        .with_context(|| format!("Package {} is not saved", package_id))?;
+    let Some(SuiRawData::Package(package)) = object.bcs else {
+        bail!("Object {} is not a package", package_id);
+    };
+    let mut tx = db.pool.begin().await?;
+    decompiler
+        .decompile_package(&mut *tx, network, &package)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Lowest `<sequence>.chk` file of `dir`.
+async fn first_checkpoint(dir: &Path) -> Result<u64> {
+    let mut first = None;
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Some(Ok(number)) = name.strip_suffix(".chk").map(str::parse::<u64>) {
+            first = Some(first.map_or(number, |first: u64| first.min(number)));
+        }
+    }
+    first.with_context(|| format!("No checkpoint file in {}", dir.display()))
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+
+/// Usable AI response, keyed by what was asked so the same request is only paid for once.
+#[derive(Debug, FromRow)]
+pub struct AiCacheEntry {
+    pub model: String,
+    /// SHA256 of the prompt answered, the last message of the request
+    pub prompt_hash: String,
+    /// SHA256 of the rest of the request: the conversation before the prompt and the options
+    pub input_hash: String,
+    pub response: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AiCacheEntry {
+    pub async fn load<'e, E>(
+        executor: E,
+        model: &str,
+        prompt_hash: &str,
+        input_hash: &str,
+    ) -> Result<Option<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            AiCacheEntry,
+            "SELECT * FROM ai_cache WHERE model = $1 AND prompt_hash = $2 AND input_hash = $3",
+            &model,
+            &prompt_hash,
+            &input_hash
+        )
+        .fetch_optional(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO ai_cache (model, prompt_hash, input_hash, response, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (model, prompt_hash, input_hash) DO UPDATE
+            SET response = EXCLUDED.response, created_at = EXCLUDED.created_at",
+            &self.model,
+            &self.prompt_hash,
+            &self.input_hash,
+            &self.response,
+            self.created_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
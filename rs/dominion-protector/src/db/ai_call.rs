@@ -0,0 +1,49 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{query, Executor, FromRow, Postgres};
+
+/// Request made to the AI provider, or answered from `ai_cache`.
+#[derive(Debug, FromRow)]
+pub struct AiCall {
+    pub package_id: String,
+    pub network: String,
+    pub module_name: String,
+    /// Prompt the request answers, like `module.warnings`
+    pub prompt: String,
+    pub model: String,
+    /// Not every provider reports usage
+    pub prompt_tokens: Option<i32>,
+    pub completion_tokens: Option<i32>,
+    pub latency_ms: i64,
+    /// In USD, from the prices in the AI config
+    pub cost: f64,
+    pub cached: bool,
+    pub called_at: DateTime<Utc>,
+}
+
+impl AiCall {
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO ai_calls
+            (package_id, network, module_name, prompt, model, prompt_tokens, completion_tokens, latency_ms, cost, cached, called_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            &self.package_id,
+            &self.network,
+            &self.module_name,
+            &self.prompt,
+            &self.model,
+            self.prompt_tokens,
+            self.completion_tokens,
+            self.latency_ms,
+            self.cost,
+            self.cached,
+            self.called_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use sqlx::{query_as, Executor, FromRow, Postgres};
+
+/// `ai_calls` added up for one prompt of a package.
+#[derive(Debug, FromRow)]
+pub struct AiCallTotal {
+    pub package_id: String,
+    pub network: String,
+    pub prompt: String,
+    pub calls: i64,
+    /// Calls answered from `ai_cache`
+    pub cached: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub latency_ms: i64,
+    pub cost: f64,
+}
+
+impl AiCallTotal {
+    /// Totals per package and prompt, optionally for one network or package only.
+    pub async fn load_all<'e, E>(
+        executor: E,
+        network: Option<&str>,
+        package_id: Option<&str>,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            AiCallTotal,
+            "SELECT
+                package_id,
+                network,
+                prompt,
+                COUNT(*) as \"calls!\",
+                COUNT(*) FILTER (WHERE cached) as \"cached!\",
+                COALESCE(SUM(prompt_tokens), 0)::BIGINT as \"prompt_tokens!\",
+                COALESCE(SUM(completion_tokens), 0)::BIGINT as \"completion_tokens!\",
+                SUM(latency_ms)::BIGINT as \"latency_ms!\",
+                SUM(cost) as \"cost!\"
+            FROM ai_calls
+            WHERE ($1::TEXT IS NULL OR network = $1) AND ($2::TEXT IS NULL OR package_id = $2)
+            GROUP BY package_id, network, prompt
+            ORDER BY package_id, network, prompt",
+            network,
+            package_id
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+}
@@ -1,15 +1,10 @@
-use std::str::FromStr;
-
-use anyhow::{Context, Result};
-use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
-use postgres_types::{FromSql, ToSql};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sui_sdk::types::Identifier;
-use tokio_postgres::{Client, Row, Transaction};
+use sqlx::{Acquire, Postgres};
+use sui_sdk::types::base_types::ObjectID;
 
 use super::{function::FunctionDescription, function_entity::FunctionEntityDescription};
 
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FullFunctionDescription {
     #[serde(flatten)]
@@ -18,39 +13,55 @@ pub struct FullFunctionDescription {
 }
 
 impl FullFunctionDescription {
-    pub async fn read_from_db(
-        module_id: ModuleId,
-        network: String,
-        function_name: String,
-        db: &mut Client,
-    ) -> Result<Option<Self>> {
-        let function = FunctionDescription::read_from_db(
-            module_id.clone(),
-            network.clone(),
-            function_name.clone(),
-            db,
+    pub async fn load<'a, A>(
+        db: A,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+        function_name: &str,
+    ) -> Result<Option<Self>>
+    where
+        A: Acquire<'a, Database = Postgres>,
+    {
+        let mut db = db.acquire().await?;
+        let Some(function) =
+            FunctionDescription::load(&mut *db, package_id, network, module_name, function_name)
+                .await?
+        else {
+            return Ok(None);
+        };
+        let entities = FunctionEntityDescription::load_all_by_function(
+            &mut *db,
+            package_id,
+            network,
+            module_name,
+            function_name,
         )
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("Function not found"))?;
-        let entities =
-            FunctionEntityDescription::read_from_db(module_id, network, function_name, db).await?;
+        .await?;
         Ok(Some(Self { function, entities }))
     }
 
-    pub async fn read_all_from_db(
-        module_id: ModuleId,
-        network: String,
-        db: &mut Client,
-    ) -> Result<Vec<Self>> {
+    pub async fn load_all_by_module<'a, A>(
+        db: A,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+    ) -> Result<Vec<Self>>
+    where
+        A: Acquire<'a, Database = Postgres>,
+    {
+        let mut db = db.acquire().await?;
         let functions =
-            FunctionDescription::read_all_from_db(module_id.clone(), network.clone(), db).await?;
-        let mut result = vec![];
+            FunctionDescription::load_all_by_module(&mut *db, package_id, network, module_name)
+                .await?;
+        let mut result = Vec::with_capacity(functions.len());
         for function in functions {
-            let entities = FunctionEntityDescription::read_from_db(
-                module_id.clone(),
-                network.clone(),
-                function.function_name.clone(),
-                db,
+            let entities = FunctionEntityDescription::load_all_by_function(
+                &mut *db,
+                package_id,
+                network,
+                module_name,
+                &function.function_name,
             )
             .await?;
             result.push(Self { function, entities });
@@ -58,19 +69,24 @@ impl FullFunctionDescription {
         Ok(result)
     }
 
-    pub async fn save_to_db(&self, tx: &mut Transaction<'_>) -> Result<bool> {
-        let function_saved = self.function.save_to_db(tx).await?;
-        tx.execute("DELETE FROM function_entity_descriptions
-        WHERE package_id = $1 AND network = $2 AND module = $3 AND function = $4", 
-        &[
-            &self.function.module.address().to_hex_literal(),
+    /// Replaces the function description and all of its entity descriptions.
+    pub async fn save<'a, A>(&self, db: A) -> Result<()>
+    where
+        A: Acquire<'a, Database = Postgres>,
+    {
+        let mut db = db.acquire().await?;
+        self.function.save(&mut *db).await?;
+        FunctionEntityDescription::delete_all_by_function(
+            &mut *db,
+            &ObjectID::from_hex_literal(&self.function.package_id)?,
             &self.function.network,
-            &self.function.module.name().as_str(),
+            &self.function.module_name,
             &self.function.function_name,
-        ]).await?;
+        )
+        .await?;
         for entity in &self.entities {
-            entity.save_to_db(tx).await?;
+            entity.save(&mut *db).await?;
         }
-        Ok(function_saved)
+        Ok(())
     }
 }
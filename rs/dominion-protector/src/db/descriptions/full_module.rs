@@ -1,11 +1,7 @@
-use std::str::FromStr;
-
-use anyhow::{Context, Result};
-use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
-use postgres_types::{FromSql, ToSql};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sui_sdk::types::Identifier;
-use tokio_postgres::{Client, Row, Transaction};
+use sqlx::{Acquire, Postgres};
+use sui_sdk::types::base_types::ObjectID;
 
 use super::{
     full_function::FullFunctionDescription, module::ModuleDescription, structure::StructDescription,
@@ -20,48 +16,54 @@ pub struct FullModuleDescription {
 }
 
 impl FullModuleDescription {
-    pub async fn read_from_db(
-        module_id: ModuleId,
-        network: String,
-        db: &mut Client,
-    ) -> Result<Option<Self>> {
-        let module =
-            ModuleDescription::read_from_db(module_id.clone(), network.clone(), db).await?;
-        if let Some(module) = module {
-            let structs =
-                StructDescription::read_all_from_db(module_id.clone(), network.clone(), db).await?;
-            let functions =
-                FullFunctionDescription::read_all_from_db(module_id.clone(), network.clone(), db)
-                    .await?;
-            return Ok(Some(Self {
-                module,
-                structs,
-                functions,
-            }));
-        } else {
-            Ok(None)
-        }
+    pub async fn load<'a, A>(
+        db: A,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+    ) -> Result<Option<Self>>
+    where
+        A: Acquire<'a, Database = Postgres>,
+    {
+        let mut db = db.acquire().await?;
+        let Some(module) =
+            ModuleDescription::load(&mut *db, package_id, network, module_name).await?
+        else {
+            return Ok(None);
+        };
+        let structs =
+            StructDescription::load_all_by_module(&mut *db, package_id, network, module_name)
+                .await?;
+        let functions =
+            FullFunctionDescription::load_all_by_module(&mut *db, package_id, network, module_name)
+                .await?;
+        Ok(Some(Self {
+            module,
+            structs,
+            functions,
+        }))
     }
 
-    pub async fn save_to_db(&self, tx: &mut Transaction<'_>) -> Result<()> {
-        println!("Saving module: {:?}", self.module);
-        tx.execute(
-            "DELETE FROM module_descriptions
-        WHERE package_id = $1 AND network = $2 AND module = $3",
-        &[
-            &self.module.id.address().to_hex_literal(),
+    /// Replaces the previous description of the module, dropping structs and functions
+    /// that are not described anymore.
+    pub async fn save<'a, A>(&self, db: A) -> Result<()>
+    where
+        A: Acquire<'a, Database = Postgres>,
+    {
+        let mut db = db.acquire().await?;
+        ModuleDescription::delete(
+            &mut *db,
+            &ObjectID::from_hex_literal(&self.module.package_id)?,
             &self.module.network,
-            &self.module.id.name().as_str(),
-        ]
-        ).await?;
-        self.module.save_to_db(tx).await?;
-        println!("Saving new struct descriptions");
-        for struct_ in &self.structs {
-            struct_.save_to_db(tx).await?;
+            &self.module.module_name,
+        )
+        .await?;
+        self.module.save(&mut *db).await?;
+        for structure in &self.structs {
+            structure.save(&mut *db).await?;
         }
-        println!("Saving new function descriptions");
         for function in &self.functions {
-            function.save_to_db(tx).await?;
+            function.save(&mut *db).await?;
         }
         Ok(())
     }
@@ -1,34 +1,29 @@
-use std::str::FromStr;
-
-use anyhow::{Context, Result};
-use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
-use postgres_types::{FromSql, ToSql};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sui_sdk::types::Identifier;
-use tokio_postgres::{Client, Row, Transaction};
-
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
 
-#[derive(Debug, Clone, Copy, ToSql, FromSql, Serialize, Deserialize)]
-#[postgres(name = "entity_kind")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "entity_kind", rename_all = "lowercase")]
 pub enum EntityKind {
-    #[postgres(name = "parameter")]
     Parameter,
-    #[postgres(name = "created")]
     Created,
 }
 
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What a function does with one of its parameters or with an object it creates.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct FunctionEntityDescription {
-    pub module: ModuleId,
+    pub package_id: String,
     pub network: String,
-    pub function: String,
-    pub description: String,
+    pub module_name: String,
+    pub function_name: String,
     pub kind: EntityKind,
+    /// Parameter position, `None` for created objects
     pub index: Option<i32>,
     pub name: String,
     pub proper_name: Option<String>,
-    pub type_: String,
+    pub description: String,
+    pub type_name: String,
     pub returned: Option<String>,
     pub transferred: Option<String>,
     pub shared: Option<String>,
@@ -39,263 +34,118 @@ pub struct FunctionEntityDescription {
     pub warnings: Vec<String>,
 }
 
-impl TryFrom<&Row> for FunctionEntityDescription {
-    type Error = anyhow::Error;
-
-    fn try_from(value: &Row) -> Result<Self> {
-        let columns = value.columns();
-        let package_id: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "package_id")
-                .context("package_id column not found")?,
-        );
-        let network: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "network")
-                .context("network column not found")?,
-        );
-        let module: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "module")
-                .context("module column not found")?,
-        );
-        let function: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "function")
-                .context("function column not found")?,
-        );
-        let kind: EntityKind = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "kind")
-                .context("kind column not found")?,
-        );
-        let index: Option<i32> = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "index")
-                .context("index column not found")?,
-        );
-
-        let description: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "description")
-                .context("description column not found")?,
-        );
-
-        let name = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "name")
-                .context("name column not found")?,
-        );
-
-        let proper_name = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "proper_name")
-                .context("proper_name column not found")?,
-        );
-
-        let type_ = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "type")
-                .context("type column not found")?,
-        );
-
-        let returned = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "returned")
-                .context("returned column not found")?,
-        );
-
-        let transferred = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "transferred")
-                .context("transferred column not found")?,
-        );
-
-        let shared = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "shared")
-                .context("shared column not found")?,
-        );
-
-        let frozen = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "frozen")
-                .context("frozen column not found")?,
-        );
-
-        let wrapped = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "wrapped")
-                .context("wrapped column not found")?,
-        );
-
-        let modified = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "modified")
-                .context("modified column not found")?,
-        );
-
-        let dropped = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "dropped")
-                .context("dropped column not found")?,
-        );
-
-        let warnings = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "warnings")
-                .context("warnings column not found")?,
-        );
-
-        Ok(Self {
-            module: ModuleId::new(
-                AccountAddress::from_hex_literal(&package_id)?,
-                Identifier::from_str(&module)?,
-            ),
-            network,
-            function,
-            kind,
-            index,
-            name,
-            proper_name,
-            description,
-            type_,
-            returned,
-            transferred,
-            shared,
-            frozen,
-            wrapped,
-            modified,
-            dropped,
-            warnings,
-        })
-    }
-}
-
 impl FunctionEntityDescription {
-    const COLUMNS: &'static [&'static str] = &[
-        "package_id",
-        "network",
-        "module",
-        "function",
-        "kind",
-        "index",
-        "name",
-        "proper_name",
-        "description",
-        "type",
-        "returned",
-        "transferred",
-        "shared",
-        "frozen",
-        "wrapped",
-        "modified",
-        "dropped",
-        "warnings",
-    ];
-
-    pub async fn create_table_if_needed(client: &Client) -> Result<()> {
-        client.batch_execute("
-            CREATE TABLE IF NOT EXISTS function_entity_descriptions (
-                package_id      CHAR(66) NOT NULL,
-                network         VARCHAR(10) NOT NULL,
-                module          TEXT NOT NULL,
-                function        TEXT NOT NULL,
-                kind            entity_kind NOT NULL,
-                index           INT,
-                name            TEXT NOT NULL,
-                proper_name     TEXT,
-                description     TEXT NOT NULL,
-                type            TEXT NOT NULL,
-                returned        TEXT,
-                transferred     TEXT,
-                shared          TEXT,
-                frozen          TEXT,
-                wrapped         TEXT,
-                modified        TEXT,
-                dropped         TEXT,
-                warnings        TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[],
-                FOREIGN KEY(package_id, network) REFERENCES objects(id, network) ON DELETE CASCADE,
-                FOREIGN KEY(package_id, network, module) REFERENCES package_modules(package_id, network, name) ON DELETE CASCADE,
-                FOREIGN KEY(package_id, network, module) REFERENCES module_descriptions(package_id, network, module) ON DELETE CASCADE,
-                FOREIGN KEY(package_id, network, module, function) REFERENCES function_descriptions(package_id, network, module, function) ON DELETE CASCADE
-            );
-        ").await?;
-        Ok(())
+    pub async fn load_all_by_function<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+        function_name: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            FunctionEntityDescription,
+            "SELECT
+                package_id,
+                network,
+                module_name,
+                function_name,
+                kind as \"kind: EntityKind\",
+                index,
+                name,
+                proper_name,
+                description,
+                type_name,
+                returned,
+                transferred,
+                shared,
+                frozen,
+                wrapped,
+                modified,
+                dropped,
+                warnings
+            FROM function_entity_descriptions
+            WHERE package_id = $1 AND network = $2 AND module_name = $3 AND function_name = $4
+            ORDER BY kind, index, name",
+            &package_id.to_string(),
+            &network,
+            &module_name,
+            &function_name
+        )
+        .fetch_all(executor)
+        .await?)
     }
 
-    pub async fn read_from_db(
-        module_id: ModuleId,
-        network: String,
-        function_name: String,
-        db: &mut Client,
-    ) -> Result<Vec<Self>> {
-        let rows = db
-            .query(
-                &format!("SELECT {}
-        FROM function_entity_descriptions WHERE package_id = $1 AND network = $2 AND module = $3 AND function = $4",
-                FunctionEntityDescription::COLUMNS.join(", ")),
-                &[
-                    &module_id.address().to_hex_literal(),
-                    &network,
-                    &module_id.name().as_str(),
-                    &function_name,
-                ],
-            )
-            .await?;
-
-        return rows
-            .into_iter()
-            .map(|row| FunctionEntityDescription::try_from(&row))
-            .collect();
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO function_entity_descriptions
+            (package_id, network, module_name, function_name, kind, index, name, proper_name,
+             description, type_name, returned, transferred, shared, frozen, wrapped, modified,
+             dropped, warnings)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            ON CONFLICT (package_id, network, module_name, function_name, kind, name) DO UPDATE
+            SET
+                index = EXCLUDED.index,
+                proper_name = EXCLUDED.proper_name,
+                description = EXCLUDED.description,
+                type_name = EXCLUDED.type_name,
+                returned = EXCLUDED.returned,
+                transferred = EXCLUDED.transferred,
+                shared = EXCLUDED.shared,
+                frozen = EXCLUDED.frozen,
+                wrapped = EXCLUDED.wrapped,
+                modified = EXCLUDED.modified,
+                dropped = EXCLUDED.dropped,
+                warnings = EXCLUDED.warnings",
+            &self.package_id,
+            &self.network,
+            &self.module_name,
+            &self.function_name,
+            self.kind as _,
+            self.index,
+            &self.name,
+            self.proper_name.as_ref(),
+            &self.description,
+            &self.type_name,
+            self.returned.as_ref(),
+            self.transferred.as_ref(),
+            self.shared.as_ref(),
+            self.frozen.as_ref(),
+            self.wrapped.as_ref(),
+            self.modified.as_ref(),
+            self.dropped.as_ref(),
+            &self.warnings
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
     }
 
-    pub async fn save_to_db(&self, tx: &mut Transaction<'_>) -> Result<bool> {
-        let rows = tx.execute(
-            &format!(
-                "INSERT INTO function_entity_descriptions ({})
-    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)",
-                FunctionEntityDescription::COLUMNS.join(", ")
-            ),
-            &[
-                &self.module.address().to_hex_literal(),
-                &self.network,
-                &self.module.name().as_str(),
-                &self.function,
-                &self.kind,
-                &self.index,
-                &self.name,
-                &self.proper_name,
-                &self.description,
-                &self.type_,
-                &self.returned,
-                &self.transferred,
-                &self.shared,
-                &self.frozen,
-                &self.wrapped,
-                &self.modified,
-                &self.dropped,
-                &self.warnings,
-            ],
-        ).await?;
-        Ok(rows > 0)
+    pub async fn delete_all_by_function<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+        function_name: &str,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "DELETE FROM function_entity_descriptions
+            WHERE package_id = $1 AND network = $2 AND module_name = $3 AND function_name = $4",
+            &package_id.to_string(),
+            &network,
+            &module_name,
+            &function_name
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
     }
 }
@@ -1,152 +1,99 @@
-use std::str::FromStr;
-
-use anyhow::{Context, Result};
-use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
-use postgres_types::{FromSql, ToSql};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sui_sdk::types::Identifier;
-use tokio_postgres::{Client, Row, Transaction};
+use sqlx::{query, query_as, types::Json, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
 
 use super::security_level::SecurityLevel;
+use crate::prompts::PromptVersions;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct ModuleDescription {
-    pub id: ModuleId,
+    pub package_id: String,
     pub network: String,
+    pub module_name: String,
     pub description: String,
     pub security_level: SecurityLevel,
     pub warnings: Vec<String>,
-}
-
-impl TryFrom<&Row> for ModuleDescription {
-    type Error = anyhow::Error;
-
-    fn try_from(value: &Row) -> Result<Self> {
-        let columns = value.columns();
-        let package_id: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "package_id")
-                .context("package_id column not found")?,
-        );
-        let network: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "network")
-                .context("network column not found")?,
-        );
-        let module: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "module")
-                .context("module column not found")?,
-        );
-        let description: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "description")
-                .context("description column not found")?,
-        );
-        let security_level: SecurityLevel = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "security_level")
-                .context("security_level column not found")?,
-        );
-        let warnings: Vec<String> = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "warnings")
-                .context("warnings column not found")?,
-        );
-        Ok(Self {
-            id: ModuleId::new(
-                AccountAddress::from_hex_literal(&package_id)?,
-                Identifier::from_str(&module)?,
-            ),
-            network,
-            description,
-            security_level,
-            warnings,
-        })
-    }
+    /// Prompts the description was generated with
+    pub prompt_versions: Json<PromptVersions>,
 }
 
 impl ModuleDescription {
-    const COLUMNS: &'static [&'static str] = &[
-        "package_id",
-        "network",
-        "module",
-        "description",
-        "security_level",
-        "warnings",
-    ];
-
-    pub async fn create_table_if_needed(client: &Client) -> Result<()> {
-        client.batch_execute("
-            CREATE TABLE IF NOT EXISTS module_descriptions (
-                package_id      CHAR(66) NOT NULL,
-                network         VARCHAR(10) NOT NULL,
-                module          TEXT NOT NULL,
-                description     TEXT NOT NULL,
-                security_level  security_level NOT NULL DEFAULT 'Unknown / Unassessed',
-                warnings        TEXT[] NOT NULL DEFAULT '{}',
-                PRIMARY KEY(package_id, network, module),
-                FOREIGN KEY(package_id, network) REFERENCES objects(id, network) ON DELETE CASCADE,
-                FOREIGN KEY(package_id, network, module) REFERENCES package_modules(package_id, network, name) ON DELETE CASCADE
-            );
-        ").await?;
-        Ok(())
+    pub async fn load<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+    ) -> Result<Option<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            ModuleDescription,
+            "SELECT
+                package_id,
+                network,
+                module_name,
+                description,
+                security_level as \"security_level: SecurityLevel\",
+                warnings,
+                prompt_versions as \"prompt_versions: Json<PromptVersions>\"
+            FROM module_descriptions
+            WHERE package_id = $1 AND network = $2 AND module_name = $3",
+            &package_id.to_string(),
+            &network,
+            &module_name
+        )
+        .fetch_optional(executor)
+        .await?)
     }
 
-    pub async fn read_from_db(
-        module_id: ModuleId,
-        network: String,
-        db: &mut Client,
-    ) -> Result<Option<ModuleDescription>> {
-        let rows = db
-            .query(
-                &format!(
-                    "SELECT {}
-    FROM module_descriptions
-    WHERE package_id = $1 AND network = $2 AND module = $3",
-                    ModuleDescription::COLUMNS.join(", ")
-                ),
-                &[
-                    &module_id.address().to_hex_literal(),
-                    &network,
-                    &module_id.name().as_str(),
-                ],
-            )
-            .await?;
-        if rows.is_empty() {
-            return Ok(None);
-        }
-        return Ok(Some(ModuleDescription::try_from(&rows[0])?));
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO module_descriptions
+            (package_id, network, module_name, description, security_level, warnings, prompt_versions)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (package_id, network, module_name) DO UPDATE
+            SET
+                description = EXCLUDED.description,
+                security_level = EXCLUDED.security_level,
+                warnings = EXCLUDED.warnings,
+                prompt_versions = EXCLUDED.prompt_versions",
+            &self.package_id,
+            &self.network,
+            &self.module_name,
+            &self.description,
+            self.security_level as _,
+            &self.warnings,
+            &self.prompt_versions as _
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
     }
 
-    pub async fn save_to_db(&self, tx: &mut Transaction<'_>) -> Result<bool> {
-        let rows = tx
-            .execute(
-                &format!(
-                    "INSERT INTO module_descriptions ({})
-                    VALUES ($1, $2, $3, $4, $5, $6)
-                    ON CONFLICT (package_id, network, module) DO UPDATE
-                    SET description = excluded.description,
-                        security_level = excluded.security_level,
-                        warnings = excluded.warnings",
-                    ModuleDescription::COLUMNS.join(", ")
-                ),
-                &[
-                    &self.id.address().to_hex_literal(),
-                    &self.network,
-                    &self.id.name().as_str(),
-                    &self.description,
-                    &self.security_level,
-                    &self.warnings,
-                ],
-            )
-            .await?;
-        Ok(rows > 0)
+    /// Removes the description together with its struct and function descriptions.
+    pub async fn delete<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "DELETE FROM module_descriptions
+            WHERE package_id = $1 AND network = $2 AND module_name = $3",
+            &package_id.to_string(),
+            &network,
+            &module_name
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
     }
 }
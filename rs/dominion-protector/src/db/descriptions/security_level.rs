@@ -1,19 +1,41 @@
-use postgres_types::{FromSql, ToSql};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, ToSql, FromSql, Serialize, Deserialize)]
-#[postgres(name = "security_level")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "security_level")]
 pub enum SecurityLevel {
-    #[postgres(name = "Critical Risk")]
+    #[sqlx(rename = "Critical Risk")]
     CriticalRisk,
-    #[postgres(name = "High Risk")]
+    #[sqlx(rename = "High Risk")]
     HighRisk,
-    #[postgres(name = "Medium Risk")]
+    #[sqlx(rename = "Medium Risk")]
     MediumRisk,
-    #[postgres(name = "Low Risk")]
+    #[sqlx(rename = "Low Risk")]
     LowRisk,
-    #[postgres(name = "Best Practices Compliant")]
+    #[sqlx(rename = "Best Practices Compliant")]
     BestPracticesCompliant,
-    #[postgres(name = "Unknown / Unassessed")]
+    #[sqlx(rename = "Unknown / Unassessed")]
     UnknownUnassessed,
-}
\ No newline at end of file
+}
+
+impl SecurityLevel {
+    /// Unassessed code is riskier than code known to be low risk, but not as alarming
+    /// as code known to be dangerous.
+    pub fn severity(self) -> u8 {
+        match self {
+            SecurityLevel::BestPracticesCompliant => 0,
+            SecurityLevel::LowRisk => 1,
+            SecurityLevel::UnknownUnassessed => 2,
+            SecurityLevel::MediumRisk => 3,
+            SecurityLevel::HighRisk => 4,
+            SecurityLevel::CriticalRisk => 5,
+        }
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
+        }
+    }
+}
@@ -1,17 +1,15 @@
-use std::str::FromStr;
-
-use anyhow::{Context, Result};
-use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
-use postgres_types::{FromSql, ToSql};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sui_sdk::types::Identifier;
-use tokio_postgres::{Client, Row, Transaction};
+use sqlx::{query, query_as, types::Json, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
 
+use crate::prompts::PromptVersions;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct StructDescription {
-    pub module: ModuleId,
+    pub package_id: String,
     pub network: String,
+    pub module_name: String,
     pub struct_name: String,
     pub description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -27,229 +25,120 @@ pub struct StructDescription {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub event: Option<String>,
     pub warnings: Vec<String>,
+    /// Prompts the description was generated with
+    pub prompt_versions: Json<PromptVersions>,
 }
 
 impl StructDescription {
-    const COLUMNS: &'static [&'static str] = &[
-        "package_id",
-        "network",
-        "module",
-        "struct",
-        "description",
-        "address_owned",
-        "object_owned",
-        "wrapped",
-        "shared",
-        "immutable",
-        "event",
-        "warnings",
-    ];
-
-    pub async fn create_table_if_needed(client: &Client) -> Result<()> {
-        client.batch_execute("
-            CREATE TABLE IF NOT EXISTS struct_descriptions (
-                package_id      CHAR(66) NOT NULL,
-                network         VARCHAR(10) NOT NULL,
-                module          TEXT NOT NULL,
-                struct          TEXT NOT NULL,
-                description     TEXT NOT NULL,
-                address_owned   TEXT,
-                object_owned    TEXT,
-                wrapped         TEXT,
-                shared          TEXT,
-                immutable       TEXT,
-                event           TEXT,
-                warnings        TEXT[] NOT NULL DEFAULT '{}',
-                PRIMARY KEY(package_id, network, module, struct),
-                FOREIGN KEY(package_id, network) REFERENCES objects(id, network) ON DELETE CASCADE,
-                FOREIGN KEY(package_id, network, module) REFERENCES package_modules(package_id, network, name) ON DELETE CASCADE,
-                FOREIGN KEY(package_id, network, module) REFERENCES module_descriptions(package_id, network, module) ON DELETE CASCADE
-            );
-        ").await?;
-        Ok(())
-    }
-
-    pub async fn read_from_db(
-        module_id: ModuleId,
-        network: String,
-        struct_name: String,
-        db: &mut Client,
-    ) -> Result<Option<StructDescription>> {
-        let rows = db
-            .query(
-                &format!(
-                    "SELECT {}
-                    FROM struct_descriptions
-                    WHERE package_id = $1 AND network = $2 AND module = $3 AND struct = $4",
-                    StructDescription::COLUMNS.join(", ")
-                ),
-                &[
-                    &module_id.address().to_hex_literal(),
-                    &network,
-                    &module_id.name().as_str(),
-                    &struct_name,
-                ],
-            )
-            .await?;
-        if rows.is_empty() {
-            return Ok(None);
-        }
-        return Ok(Some(StructDescription::try_from(&rows[0])?));
+    pub async fn load<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+        struct_name: &str,
+    ) -> Result<Option<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            StructDescription,
+            "SELECT
+                package_id,
+                network,
+                module_name,
+                struct_name,
+                description,
+                address_owned,
+                object_owned,
+                wrapped,
+                shared,
+                immutable,
+                event,
+                warnings,
+                prompt_versions as \"prompt_versions: Json<PromptVersions>\"
+            FROM struct_descriptions
+            WHERE package_id = $1 AND network = $2 AND module_name = $3 AND struct_name = $4",
+            &package_id.to_string(),
+            &network,
+            &module_name,
+            &struct_name
+        )
+        .fetch_optional(executor)
+        .await?)
     }
 
-    pub async fn read_all_from_db(
-        module_id: ModuleId,
-        network: String,
-        db: &mut Client,
-    ) -> Result<Vec<StructDescription>> {
-        let rows = db
-            .query(
-                &format!(
-                    "SELECT {}
-                    FROM struct_descriptions
-                    WHERE package_id = $1 AND network = $2 AND module = $3",
-                    StructDescription::COLUMNS.join(", ")
-                ),
-                &[
-                    &module_id.address().to_hex_literal(),
-                    &network,
-                    &module_id.name().as_str(),
-                ],
-            )
-            .await?;
-        return rows
-            .into_iter()
-            .map(|row| StructDescription::try_from(&row))
-            .collect();
+    pub async fn load_all_by_module<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            StructDescription,
+            "SELECT
+                package_id,
+                network,
+                module_name,
+                struct_name,
+                description,
+                address_owned,
+                object_owned,
+                wrapped,
+                shared,
+                immutable,
+                event,
+                warnings,
+                prompt_versions as \"prompt_versions: Json<PromptVersions>\"
+            FROM struct_descriptions
+            WHERE package_id = $1 AND network = $2 AND module_name = $3
+            ORDER BY struct_name",
+            &package_id.to_string(),
+            &network,
+            &module_name
+        )
+        .fetch_all(executor)
+        .await?)
     }
 
-    pub async fn save_to_db(&self, tx: &mut Transaction<'_>) -> Result<bool> {
-        let rows = tx.execute(
-            &format!(
-                "INSERT INTO struct_descriptions ({})
-                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-                 ON CONFLICT (package_id, network, module, struct) DO UPDATE
-                 SET description = excluded.description,
-                     address_owned = excluded.address_owned,
-                     object_owned = excluded.object_owned,
-                     wrapped = excluded.wrapped,
-                     shared = excluded.shared,
-                     immutable = excluded.immutable,
-                     event = excluded.event,
-                     warnings = excluded.warnings",
-                StructDescription::COLUMNS.join(", ")
-            ),
-            &[
-                &self.module.address().to_hex_literal(),
-                &self.network,
-                &self.module.name().as_str(),
-                &self.struct_name,
-                &self.description,
-                &self.address_owned,
-                &self.object_owned,
-                &self.wrapped,
-                &self.shared,
-                &self.immutable,
-                &self.event,
-                &self.warnings,
-            ],
-        ).await?;
-        Ok(rows > 0)
-    }
-}
-
-impl TryFrom<&Row> for StructDescription {
-    type Error = anyhow::Error;
-
-    fn try_from(value: &Row) -> Result<Self> {
-        let columns = value.columns();
-        let package_id: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "package_id")
-                .context("package_id column not found")?,
-        );
-        let network: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "network")
-                .context("network column not found")?,
-        );
-        let module: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "module")
-                .context("module column not found")?,
-        );
-        let struct_: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "struct")
-                .context("struct column not found")?,
-        );
-        let description: String = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "description")
-                .context("description column not found")?,
-        );
-        let address_owned: Option<String> = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "address_owned")
-                .context("address_owned column not found")?,
-        );
-        let object_owned: Option<String> = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "object_owned")
-                .context("object_owned column not found")?,
-        );
-        let wrapped: Option<String> = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "wrapped")
-                .context("wrapped column not found")?,
-        );
-        let shared: Option<String> = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "shared")
-                .context("shared column not found")?,
-        );
-        let immutable: Option<String> = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "immutable")
-                .context("immutable column not found")?,
-        );
-        let event: Option<String> = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "event")
-                .context("event column not found")?,
-        );
-        let warnings: Vec<String> = value.get(
-            columns
-                .iter()
-                .position(|c| c.name() == "warnings")
-                .context("warnings column not found")?,
-        );
-        Ok(Self {
-            module: ModuleId::new(
-                AccountAddress::from_hex_literal(&package_id)?,
-                Identifier::from_str(&module)?,
-            ),
-            network,
-            struct_name: struct_,
-            description,
-            address_owned,
-            object_owned,
-            wrapped,
-            shared,
-            immutable,
-            event,
-            warnings,
-        })
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO struct_descriptions
+            (package_id, network, module_name, struct_name, description, address_owned,
+             object_owned, wrapped, shared, immutable, event, warnings, prompt_versions)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (package_id, network, module_name, struct_name) DO UPDATE
+            SET
+                description = EXCLUDED.description,
+                address_owned = EXCLUDED.address_owned,
+                object_owned = EXCLUDED.object_owned,
+                wrapped = EXCLUDED.wrapped,
+                shared = EXCLUDED.shared,
+                immutable = EXCLUDED.immutable,
+                event = EXCLUDED.event,
+                warnings = EXCLUDED.warnings,
+                prompt_versions = EXCLUDED.prompt_versions",
+            &self.package_id,
+            &self.network,
+            &self.module_name,
+            &self.struct_name,
+            &self.description,
+            self.address_owned.as_ref(),
+            self.object_owned.as_ref(),
+            self.wrapped.as_ref(),
+            self.shared.as_ref(),
+            self.immutable.as_ref(),
+            self.event.as_ref(),
+            &self.warnings,
+            &self.prompt_versions as _
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
     }
 }
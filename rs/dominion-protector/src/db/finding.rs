@@ -0,0 +1,102 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
+
+use super::descriptions::SecurityLevel;
+
+/// Problem a scam heuristic found in a package.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Finding {
+    pub package_id: String,
+    pub network: String,
+    pub rule: String,
+    pub module_name: String,
+    /// Function or datatype the finding is about
+    pub member_name: String,
+    /// Instruction in the function body, `None` for datatypes
+    pub code_offset: Option<i32>,
+    pub severity: SecurityLevel,
+    pub message: String,
+    pub found_at: DateTime<Utc>,
+}
+
+impl Finding {
+    pub async fn load_all_by_package<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            Finding,
+            "SELECT
+                package_id,
+                network,
+                rule,
+                module_name,
+                member_name,
+                code_offset,
+                severity as \"severity: SecurityLevel\",
+                message,
+                found_at
+            FROM findings
+            WHERE package_id = $1 AND network = $2
+            ORDER BY module_name, member_name, rule",
+            &package_id.to_string(),
+            &network
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO findings
+            (package_id, network, rule, module_name, member_name, code_offset, severity, message, found_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (package_id, network, rule, module_name, member_name) DO UPDATE
+            SET
+                code_offset = EXCLUDED.code_offset,
+                severity = EXCLUDED.severity,
+                message = EXCLUDED.message,
+                found_at = EXCLUDED.found_at",
+            &self.package_id,
+            &self.network,
+            &self.rule,
+            &self.module_name,
+            &self.member_name,
+            self.code_offset,
+            self.severity as _,
+            &self.message,
+            self.found_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_all_by_package<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "DELETE FROM findings WHERE package_id = $1 AND network = $2",
+            &package_id.to_string(),
+            &network
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
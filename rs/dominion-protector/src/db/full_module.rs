@@ -80,20 +80,21 @@ where
 
     for function in compiled.function_defs() {
         let handle = compiled.function_handle_at(function.function);
+        let function_name = compiled.identifier_at(handle.name).as_str();
         let params = compiled.signature_at(handle.parameters);
         let return_ = compiled.signature_at(handle.return_);
         let db_function = Function {
             package_id: package_id.to_string(),
             network: network.to_string(),
             module_name: module_name.to_string(),
-            function_name: handle.name.to_string(),
+            function_name: function_name.to_string(),
             visibility: match function.visibility {
                 Visibility::Private => super::function::Visibility::Private,
                 Visibility::Public => super::function::Visibility::Public,
                 Visibility::Friend => super::function::Visibility::Friend,
             },
             is_entry: function.is_entry,
-            is_initializer: &handle.name.to_string() == "init",
+            is_initializer: function_name == "init",
             type_argument_count: handle.type_parameters.len() as i32,
             parameter_count: params.len() as i32,
             return_count: return_.len() as i32,
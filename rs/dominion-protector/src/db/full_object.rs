@@ -1,13 +1,15 @@
 use std::{
     collections::{BTreeMap, HashMap},
+    str::FromStr,
     u64,
 };
 
-use anyhow::{bail, Context, Result};
+use anyhow::{Context, Result};
 use move_binary_format::{file_format::Visibility, CompiledModule};
+use move_core_types::language_storage::StructTag;
 use sqlx::{Acquire, Postgres};
 use sui_sdk::{
-    rpc_types::{SuiObjectData, SuiRawData, SuiRawMovePackage},
+    rpc_types::{SuiObjectData, SuiRawData, SuiRawMoveObject, SuiRawMovePackage},
     types::{
         base_types::{ObjectID, SequenceNumber},
         move_package::{TypeOrigin, UpgradeInfo},
@@ -15,10 +17,11 @@ use sui_sdk::{
 };
 
 use super::{
-    full_module::save_module, function::Function, object::Object, package_linkage::PackageLinkage,
-    package_module::PackageModule, structure::Structure,
+    full_module::save_module, function::Function, move_object::MoveObject, object::Object,
+    package_linkage::PackageLinkage, package_module::PackageModule, structure::Structure,
 };
-use sui_types::object::Data;
+use crate::layout::PackageLayouts;
+use sui_types::object::{Data, Object as StoredObject};
 
 pub async fn load_object<'a, A>(
     db: A,
@@ -77,18 +80,77 @@ where
                 })
                 .collect::<Result<BTreeMap<_, _>>>()?,
         }));
+    } else if let Some(move_object) = MoveObject::load(&mut *db, object_id, network).await? {
+        object.bcs = Some(SuiRawData::MoveObject(SuiRawMoveObject {
+            type_: StructTag::from_str(&move_object.object_type)?,
+            has_public_transfer: move_object.has_public_transfer,
+            version: object.version,
+            bcs_bytes: move_object.bcs,
+        }));
     }
     Ok(Some(object))
 }
 
-pub async fn save_object<'a, A>(db: A, network: &str, object: &Data) -> Result<()>
+async fn save_move_object<'a, A>(
+    db: A,
+    network: &str,
+    object_id: &ObjectID,
+    type_: &StructTag,
+    has_public_transfer: bool,
+    bcs: &[u8],
+) -> Result<()>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut db = db.acquire().await?;
+    let layouts = PackageLayouts::load(&mut *db, network, type_).await?;
+    move_object_row(
+        &layouts,
+        network,
+        object_id,
+        type_,
+        has_public_transfer,
+        bcs,
+    )
+    .save(&mut *db)
+    .await?;
+    Ok(())
+}
+
+/// Row of the object, with its contents decoded when the layouts of its type are known.
+fn move_object_row(
+    layouts: &PackageLayouts,
+    network: &str,
+    object_id: &ObjectID,
+    type_: &StructTag,
+    has_public_transfer: bool,
+    bcs: &[u8],
+) -> MoveObject {
+    let contents = match layouts.to_json(type_, bcs) {
+        Ok(contents) => Some(contents),
+        Err(err) => {
+            println!("Failed to decode object {}: {}", object_id, err);
+            None
+        }
+    };
+    MoveObject {
+        object_id: object_id.to_string(),
+        network: network.to_string(),
+        object_type: type_.to_canonical_string(true),
+        has_public_transfer,
+        bcs: bcs.to_vec(),
+        contents,
+    }
+}
+
+pub async fn save_object<'a, A>(db: A, network: &str, object: &StoredObject) -> Result<()>
 where
     A: Acquire<'a, Database = Postgres>,
 {
     let mut db = db.acquire().await?;
     let db_object: Object = (object, network).try_into()?;
     db_object.save(&mut *db).await?;
-    match object {
+    match &object.data {
         Data::Package(package) => {
             let type_origins = package.type_origin_map();
             for (module_name, module_bytecode) in package.serialized_module_map() {
@@ -114,13 +176,96 @@ where
                 linkage.save(&mut *db).await?;
             }
         }
-        Data::Move(_) => {
-            bail!("MoveObject not supported yet");
+        Data::Move(move_object) => {
+            save_move_object(
+                &mut *db,
+                network,
+                &object.id(),
+                &move_object.type_().clone().into(),
+                move_object.has_public_transfer(),
+                move_object.contents(),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Saves objects read in bulk. Move objects are inserted with one statement per table,
+/// packages one by one since their modules are analysed as they are saved.
+pub async fn save_objects<'a, A>(db: A, network: &str, objects: &[StoredObject]) -> Result<()>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut db = db.acquire().await?;
+    // Packages first, so that the layouts of objects of their types are known
+    for object in objects {
+        if let Data::Package(_) = &object.data {
+            save_object(&mut *db, network, object).await?;
+        }
+    }
+    let mut rows = vec![];
+    let mut move_objects = vec![];
+    let mut layouts = HashMap::<StructTag, PackageLayouts>::new();
+    for object in objects {
+        match &object.data {
+            Data::Package(_) => {}
+            Data::Move(move_object) => {
+                let type_: StructTag = move_object.type_().clone().into();
+                if !layouts.contains_key(&type_) {
+                    let loaded = PackageLayouts::load(&mut *db, network, &type_).await?;
+                    layouts.insert(type_.clone(), loaded);
+                }
+                rows.push(Object::from((object, network)));
+                move_objects.push(move_object_row(
+                    &layouts[&type_],
+                    network,
+                    &object.id(),
+                    &type_,
+                    move_object.has_public_transfer(),
+                    move_object.contents(),
+                ));
+            }
         }
     }
+    Object::save_all(&mut *db, &rows).await?;
+    MoveObject::save_all(&mut *db, &move_objects).await?;
     Ok(())
 }
 
+/// Decodes the contents of Move objects saved before the package of their type, like objects
+/// saved in parallel with their package. Returns how many were decoded.
+pub async fn decode_saved_objects<'a, A>(db: A, network: &str) -> Result<usize>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut db = db.acquire().await?;
+    let mut layouts = HashMap::<StructTag, PackageLayouts>::new();
+    let mut after = String::new();
+    let mut decoded = 0;
+    loop {
+        let objects = MoveObject::load_undecoded(&mut *db, network, &after, 500).await?;
+        let Some(last) = objects.last() else {
+            break;
+        };
+        after = last.object_id.clone();
+        for object in &objects {
+            let type_ = StructTag::from_str(&object.object_type)?;
+            if !layouts.contains_key(&type_) {
+                let loaded = PackageLayouts::load(&mut *db, network, &type_).await?;
+                layouts.insert(type_.clone(), loaded);
+            }
+            // Type arguments from packages still missing keep it undecoded
+            let Ok(contents) = layouts[&type_].to_json(&type_, &object.bcs) else {
+                continue;
+            };
+            MoveObject::save_contents(&mut *db, &object.object_id, network, &contents).await?;
+            decoded += 1;
+        }
+    }
+    Ok(decoded)
+}
+
 pub async fn save_rpc_object<'a, A>(db: A, network: &str, object: &SuiObjectData) -> Result<()>
 where
     A: Acquire<'a, Database = Postgres>,
@@ -131,8 +276,16 @@ where
     db_object.save(&mut *db).await?;
 
     match object.bcs.as_ref().unwrap() {
-        SuiRawData::MoveObject(_) => {
-            bail!("MoveObject not supported yet");
+        SuiRawData::MoveObject(move_object) => {
+            save_move_object(
+                &mut *db,
+                network,
+                &object.object_id,
+                &move_object.type_,
+                move_object.has_public_transfer,
+                &move_object.bcs_bytes,
+            )
+            .await?;
         }
         SuiRawData::Package(package) => {
             let package = package.to_move_package(u64::MAX)?;
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use sqlx::{Acquire, Postgres};
+use sui_sdk::rpc_types::{
+    ObjectChange, SuiCallArg, SuiCommand, SuiTransactionBlockDataAPI, SuiTransactionBlockKind,
+    SuiTransactionBlockResponse,
+};
+
+use super::{
+    transaction::Transaction, transaction_command::TransactionCommand,
+    transaction_event::TransactionEvent, transaction_input::TransactionInput,
+    transaction_object_change::TransactionObjectChange,
+};
+
+pub async fn save_rpc_transaction<'a, A>(
+    db: A,
+    network: &str,
+    transaction: &SuiTransactionBlockResponse,
+) -> Result<()>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut db = db.acquire().await?;
+
+    let db_transaction: Transaction = (transaction, network).try_into()?;
+    db_transaction.save(&mut *db).await?;
+
+    let digest = transaction.digest.to_string();
+
+    if let Some(SuiTransactionBlockKind::ProgrammableTransaction(ptb)) = transaction
+        .transaction
+        .as_ref()
+        .map(|transaction| transaction.data.transaction())
+    {
+        for (input_index, input) in ptb.inputs.iter().enumerate() {
+            let input = TransactionInput {
+                digest: digest.clone(),
+                network: network.to_string(),
+                input_index: input_index.try_into()?,
+                kind: match input {
+                    SuiCallArg::Object(_) => "object",
+                    SuiCallArg::Pure(_) => "pure",
+                }
+                .to_string(),
+                object_id: input.object().map(ToString::to_string),
+                value: serde_json::to_value(input)?,
+            };
+            input.save(&mut *db).await?;
+        }
+
+        for (command_index, command) in ptb.commands.iter().enumerate() {
+            let (kind, call) = match command {
+                SuiCommand::MoveCall(call) => ("move_call", Some(call)),
+                SuiCommand::TransferObjects(..) => ("transfer_objects", None),
+                SuiCommand::SplitCoins(..) => ("split_coins", None),
+                SuiCommand::MergeCoins(..) => ("merge_coins", None),
+                SuiCommand::Publish(..) => ("publish", None),
+                SuiCommand::Upgrade(..) => ("upgrade", None),
+                SuiCommand::MakeMoveVec(..) => ("make_move_vec", None),
+            };
+            let command = TransactionCommand {
+                digest: digest.clone(),
+                network: network.to_string(),
+                command_index: command_index.try_into()?,
+                kind: kind.to_string(),
+                package_id: call.map(|call| call.package.to_string()),
+                module_name: call.map(|call| call.module.clone()),
+                function_name: call.map(|call| call.function.clone()),
+                command: serde_json::to_value(command)?,
+            };
+            command.save(&mut *db).await?;
+        }
+    }
+
+    for event in transaction
+        .events
+        .iter()
+        .flat_map(|events| events.data.iter())
+    {
+        let event = TransactionEvent {
+            digest: digest.clone(),
+            network: network.to_string(),
+            event_seq: event.id.event_seq.try_into()?,
+            package_id: event.package_id.to_string(),
+            module_name: event.transaction_module.to_string(),
+            sender: event.sender.to_string(),
+            event_type: event.type_.to_canonical_string(true),
+            parsed_json: event.parsed_json.clone(),
+        };
+        event.save(&mut *db).await?;
+    }
+
+    for change in transaction.object_changes.iter().flatten() {
+        let (change_type, object_id, object_type, version) = match change {
+            ObjectChange::Published {
+                package_id,
+                version,
+                ..
+            } => ("published", package_id, None, version),
+            ObjectChange::Transferred {
+                object_id,
+                object_type,
+                version,
+                ..
+            } => ("transferred", object_id, Some(object_type), version),
+            ObjectChange::Mutated {
+                object_id,
+                object_type,
+                version,
+                ..
+            } => ("mutated", object_id, Some(object_type), version),
+            ObjectChange::Deleted {
+                object_id,
+                object_type,
+                version,
+                ..
+            } => ("deleted", object_id, Some(object_type), version),
+            ObjectChange::Wrapped {
+                object_id,
+                object_type,
+                version,
+                ..
+            } => ("wrapped", object_id, Some(object_type), version),
+            ObjectChange::Created {
+                object_id,
+                object_type,
+                version,
+                ..
+            } => ("created", object_id, Some(object_type), version),
+        };
+        let change = TransactionObjectChange {
+            digest: digest.clone(),
+            network: network.to_string(),
+            object_id: object_id.to_string(),
+            change_type: change_type.to_string(),
+            object_type: object_type.map(|t| t.to_canonical_string(true)),
+            version: version.value().try_into()?,
+            change: serde_json::to_value(change)?,
+        };
+        change.save(&mut *db).await?;
+    }
+
+    Ok(())
+}
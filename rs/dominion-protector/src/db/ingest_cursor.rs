@@ -0,0 +1,67 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+
+/// How far a bulk ingest got: everything up to the last object or file is saved.
+#[derive(Debug, FromRow)]
+pub struct IngestCursor {
+    /// Several ingests of the same network can run with different filters
+    pub name: String,
+    pub network: String,
+    /// When reading the live object set of a store, in object id order
+    pub last_object_id: Option<String>,
+    /// When reading snapshot or checkpoint files, in the order they are read
+    pub last_file: Option<String>,
+    pub objects_saved: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IngestCursor {
+    pub async fn load<'e, E>(executor: E, name: &str) -> Result<Option<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            IngestCursor,
+            "SELECT * FROM ingest_cursors WHERE name = $1",
+            &name
+        )
+        .fetch_optional(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO ingest_cursors (name, network, last_object_id, last_file, objects_saved)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (name) DO UPDATE
+             SET
+                network = EXCLUDED.network,
+                last_object_id = EXCLUDED.last_object_id,
+                last_file = EXCLUDED.last_file,
+                objects_saved = EXCLUDED.objects_saved,
+                updated_at = Now()",
+            &self.name,
+            &self.network,
+            self.last_object_id.as_ref(),
+            self.last_file.as_ref(),
+            self.objects_saved
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete<'e, E>(executor: E, name: &str) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!("DELETE FROM ingest_cursors WHERE name = $1", &name)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+}
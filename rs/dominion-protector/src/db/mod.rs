@@ -1,15 +1,35 @@
 use anyhow::Result;
 use std::env;
 
-// pub mod descriptions;
+pub mod ai_cache;
+pub mod ai_call;
+pub mod ai_call_total;
+pub mod descriptions;
+pub mod finding;
 pub mod full_object;
 pub mod full_module;
+pub mod full_transaction;
 pub mod function;
+pub mod ingest_cursor;
+pub mod module_source_map;
+pub mod move_object;
 pub mod object;
 pub mod package_linkage;
 pub mod package_module;
+pub mod package_upgrade;
+pub mod prompt_bench_result;
 pub mod structure;
+pub mod structure_usage;
+pub mod structure_usage_package;
+pub mod source_verification;
 pub mod sources;
+pub mod transaction;
+pub mod transaction_command;
+pub mod transaction_event;
+pub mod transaction_input;
+pub mod transaction_object_change;
+pub mod verification_result;
+pub mod watch_cursor;
 
 pub struct Db {
     pub pool: sqlx::PgPool,
@@ -18,7 +38,12 @@ pub struct Db {
 impl Db {
     pub async fn new() -> Result<Self> {
         let database_url = env::var("DATABASE_URL")?;
-        let pool = sqlx::PgPool::connect(&database_url).await?;
+        Self::connect(&database_url).await
+    }
+
+    /// Connects to the database and brings its schema up to date.
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let pool = sqlx::PgPool::connect(database_url).await?;
         sqlx::migrate!("./migrations").run(&pool).await?;
         Ok(Self { pool })
     }
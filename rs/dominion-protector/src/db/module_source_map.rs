@@ -0,0 +1,92 @@
+use anyhow::Result;
+use sqlx::{query, query_as, types::Json, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::source_map::SourceLinks;
+
+/// Source map the compiler produced for a module whose source was verified.
+#[derive(Debug, Clone, FromRow)]
+pub struct ModuleSourceMap {
+    pub package_id: String,
+    pub network: String,
+    pub module_name: String,
+    /// Kind of the module source the spans point into
+    pub kind: String,
+    /// BCS of the `SourceMap`
+    pub source_map: Vec<u8>,
+    /// Cross-links with the disassembly and Revela output, made when the module is decompiled
+    pub links: Option<Json<SourceLinks>>,
+}
+
+impl ModuleSourceMap {
+    pub async fn load<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+    ) -> Result<Option<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            ModuleSourceMap,
+            "SELECT
+                package_id,
+                network,
+                module_name,
+                kind,
+                source_map,
+                links as \"links: Json<SourceLinks>\"
+            FROM module_source_maps
+            WHERE package_id = $1 AND network = $2 AND module_name = $3",
+            &package_id.to_string(),
+            &network,
+            &module_name
+        )
+        .fetch_optional(executor)
+        .await?)
+    }
+
+    /// Saves the source map, links made with an earlier one are dropped.
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO module_source_maps (package_id, network, module_name, kind, source_map, links)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (package_id, network, module_name) DO UPDATE
+            SET
+                kind = EXCLUDED.kind,
+                source_map = EXCLUDED.source_map,
+                links = EXCLUDED.links,
+                created_at = Now()",
+            &self.package_id,
+            &self.network,
+            &self.module_name,
+            &self.kind,
+            &self.source_map,
+            self.links.as_ref() as _
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn save_links<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "UPDATE module_source_maps SET links = $4
+            WHERE package_id = $1 AND network = $2 AND module_name = $3",
+            &self.package_id,
+            &self.network,
+            &self.module_name,
+            self.links.as_ref() as _
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
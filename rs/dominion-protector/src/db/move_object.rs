@@ -0,0 +1,160 @@
+use anyhow::Result;
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
+
+#[derive(Debug, FromRow)]
+pub struct MoveObject {
+    pub object_id: String,
+    pub network: String,
+    pub object_type: String,
+    pub has_public_transfer: bool,
+    pub bcs: Vec<u8>,
+    pub contents: Option<serde_json::Value>,
+}
+
+impl MoveObject {
+    pub async fn load<'e, E>(
+        executor: E,
+        object_id: &ObjectID,
+        network: &str,
+    ) -> Result<Option<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            MoveObject,
+            "SELECT * FROM move_objects WHERE object_id = $1 AND network = $2",
+            &object_id.to_string(),
+            &network
+        )
+        .fetch_optional(executor)
+        .await?)
+    }
+
+    /// Objects saved without contents while the package of their type is stored, after
+    /// `after` in object id order.
+    pub async fn load_undecoded<'e, E>(
+        executor: E,
+        network: &str,
+        after: &str,
+        limit: i64,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            MoveObject,
+            "SELECT m.* FROM move_objects m
+             WHERE m.network = $1 AND m.contents IS NULL AND m.object_id > $2
+                AND EXISTS (
+                    SELECT 1 FROM package_modules p
+                    WHERE p.package_id = split_part(m.object_type, '::', 1)
+                        AND p.network = m.network
+                )
+             ORDER BY m.object_id
+             LIMIT $3",
+            &network,
+            &after,
+            limit
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+
+    pub async fn save_contents<'e, E>(
+        executor: E,
+        object_id: &str,
+        network: &str,
+        contents: &serde_json::Value,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "UPDATE move_objects SET contents = $3 WHERE object_id = $1 AND network = $2",
+            &object_id,
+            &network,
+            contents
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO move_objects (object_id, network, object_type, has_public_transfer, bcs, contents)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (object_id, network) DO UPDATE
+             SET
+                object_type = EXCLUDED.object_type,
+                has_public_transfer = EXCLUDED.has_public_transfer,
+                bcs = EXCLUDED.bcs,
+                contents = EXCLUDED.contents",
+            &self.object_id,
+            &self.network,
+            &self.object_type,
+            self.has_public_transfer,
+            &self.bcs,
+            self.contents.as_ref()
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Saves many objects with a single statement, their `objects` rows must be saved first.
+    pub async fn save_all<'e, E>(executor: E, objects: &[MoveObject]) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let object_ids = objects
+            .iter()
+            .map(|object| object.object_id.clone())
+            .collect::<Vec<_>>();
+        let networks = objects
+            .iter()
+            .map(|object| object.network.clone())
+            .collect::<Vec<_>>();
+        let object_types = objects
+            .iter()
+            .map(|object| object.object_type.clone())
+            .collect::<Vec<_>>();
+        let has_public_transfers = objects
+            .iter()
+            .map(|object| object.has_public_transfer)
+            .collect::<Vec<_>>();
+        let bcs = objects
+            .iter()
+            .map(|object| object.bcs.clone())
+            .collect::<Vec<_>>();
+        let contents = objects
+            .iter()
+            .map(|object| object.contents.clone())
+            .collect::<Vec<_>>();
+        query!(
+            "INSERT INTO move_objects (object_id, network, object_type, has_public_transfer, bcs, contents)
+             SELECT * FROM UNNEST(
+                $1::CHAR(66)[], $2::VARCHAR(10)[], $3::TEXT[], $4::BOOLEAN[], $5::BYTEA[], $6::JSONB[]
+             )
+             ON CONFLICT (object_id, network) DO UPDATE
+             SET
+                object_type = EXCLUDED.object_type,
+                has_public_transfer = EXCLUDED.has_public_transfer,
+                bcs = EXCLUDED.bcs,
+                contents = EXCLUDED.contents",
+            &object_ids,
+            &networks,
+            &object_types,
+            &has_public_transfers,
+            &bcs,
+            &contents as &[Option<serde_json::Value>]
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
@@ -14,7 +14,7 @@ use sui_sdk::{
 use anyhow::{Context, Result};
 use sqlx::{query, query_as, Executor};
 use sqlx::{FromRow, Postgres};
-use sui_types::object::Data;
+use sui_types::object::{Data, Object as StoredObject};
 
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type,
@@ -93,13 +93,52 @@ impl Object {
         Ok(())
     }
 
-    pub async fn last_id<'c, E: Executor<'c, Database = Postgres>>(db: E) -> Result<Option<ObjectID>> {
-        let row = query!(
-            "SELECT object_id FROM objects ORDER BY read_at DESC LIMIT 1"
+    /// Saves many objects with a single statement.
+    pub async fn save_all<'c, E: Executor<'c, Database = Postgres>>(
+        db: E,
+        objects: &[Object],
+    ) -> Result<()> {
+        let object_ids = objects.iter().map(|object| object.object_id.clone()).collect::<Vec<_>>();
+        let networks = objects.iter().map(|object| object.network.clone()).collect::<Vec<_>>();
+        let versions = objects.iter().map(|object| object.version).collect::<Vec<_>>();
+        let digests = objects.iter().map(|object| object.digest.clone()).collect::<Vec<_>>();
+        let object_types = objects
+            .iter()
+            .map(|object| object.object_type.clone())
+            .collect::<Vec<_>>();
+        let owner_types = objects.iter().map(|object| object.owner_type).collect::<Vec<_>>();
+        let owners = objects.iter().map(|object| object.owner.clone()).collect::<Vec<_>>();
+        let initial_shared_versions = objects
+            .iter()
+            .map(|object| object.initial_shared_version)
+            .collect::<Vec<_>>();
+        query!(
+            "INSERT INTO objects
+            (object_id, network, version, digest, object_type, owner_type, owner, initial_shared_version)
+            SELECT * FROM UNNEST(
+                $1::CHAR(66)[], $2::VARCHAR(10)[], $3::BIGINT[], $4::VARCHAR(64)[], $5::TEXT[],
+                $6::ownertype[], $7::VARCHAR(66)[], $8::BIGINT[]
+            )
+            ON CONFLICT (object_id, network) DO UPDATE
+            SET
+                version = EXCLUDED.version,
+                digest = EXCLUDED.digest,
+                object_type = EXCLUDED.object_type,
+                owner_type = EXCLUDED.owner_type,
+                owner = EXCLUDED.owner,
+                initial_shared_version = EXCLUDED.initial_shared_version",
+            &object_ids,
+            &networks,
+            &versions,
+            &digests,
+            &object_types,
+            &owner_types as &[OwnerType],
+            &owners as &[Option<String>],
+            &initial_shared_versions as &[Option<i64>]
         )
-        .fetch_optional(db)
+        .execute(db)
         .await?;
-        Ok(row.map(|r| ObjectID::from_hex_literal(&r.object_id)).transpose()?)
+        Ok(())
     }
 }
 
@@ -155,23 +194,8 @@ impl From<(&SuiObjectData, &str)> for Object {
         let version = i64::try_from(data.version.value()).unwrap();
         let digest = data.digest.to_string();
         let object_type = data.type_.as_ref().unwrap().to_string();
-        let (owner_type, owner, initial_shared_version) = match data.owner.as_ref().unwrap() {
-            Owner::AddressOwner(sui_address) => {
-                (OwnerType::AddressOwner, Some(sui_address.to_string()), None)
-            }
-            Owner::ObjectOwner(sui_address) => {
-                (OwnerType::ObjectOwner, Some(sui_address.to_string()), None)
-            }
-            Owner::Shared {
-                initial_shared_version,
-            } => (
-                OwnerType::Shared,
-                None,
-                Some(i64::try_from(initial_shared_version.value()).unwrap()),
-            ),
-            Owner::Immutable => (OwnerType::Immutable, None, None),
-            Owner::ConsensusV2 { .. } => (OwnerType::ConsensusV2, None, None),
-        };
+        let (owner_type, owner, initial_shared_version) =
+            owner_columns(data.owner.as_ref().unwrap());
         let read_at = Utc::now();
         Self {
             object_id,
@@ -187,17 +211,16 @@ impl From<(&SuiObjectData, &str)> for Object {
     }
 }
 
-impl From<(&Data, &str)> for Object {
-    fn from((data, network): (&Data, &str)) -> Self {
+impl From<(&StoredObject, &str)> for Object {
+    fn from((object, network): (&StoredObject, &str)) -> Self {
+        let data: &Data = &object.data;
         let object_id = data.id().to_string();
-        let version =
-            i64::try_from(data.try_as_package().map_or(0, |p| p.version().value())).unwrap();
-        let digest = data.try_as_package().map_or("".to_string(), |p| {
-            ObjectDigest::new(p.digest(false)).to_string()
-        });
+        let version = i64::try_from(object.version().value()).unwrap();
+        let digest = object.digest().to_string();
         let object_type = data
             .type_()
             .map_or("package".to_string(), |t| t.to_canonical_string(true));
+        let (owner_type, owner, initial_shared_version) = owner_columns(&object.owner);
         let read_at = Utc::now();
         Self {
             object_id,
@@ -205,13 +228,33 @@ impl From<(&Data, &str)> for Object {
             version,
             digest,
             object_type,
-            owner_type: OwnerType::Immutable, // Todo
-            owner: None,
-            initial_shared_version: None,
+            owner_type,
+            owner,
+            initial_shared_version,
             read_at,
         }
     }
 }
+
+fn owner_columns(owner: &Owner) -> (OwnerType, Option<String>, Option<i64>) {
+    match owner {
+        Owner::AddressOwner(sui_address) => {
+            (OwnerType::AddressOwner, Some(sui_address.to_string()), None)
+        }
+        Owner::ObjectOwner(sui_address) => {
+            (OwnerType::ObjectOwner, Some(sui_address.to_string()), None)
+        }
+        Owner::Shared {
+            initial_shared_version,
+        } => (
+            OwnerType::Shared,
+            None,
+            Some(i64::try_from(initial_shared_version.value()).unwrap()),
+        ),
+        Owner::Immutable => (OwnerType::Immutable, None, None),
+        Owner::ConsensusV2 { .. } => (OwnerType::ConsensusV2, None, None),
+    }
+}
 /*
 pub async fn read_object_from_db(
     object_id: ObjectID,
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
+
+/// One version in the upgrade chain of a package.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PackageUpgrade {
+    /// Id of the first version, shared by all types of the package
+    pub original_id: String,
+    pub network: String,
+    pub version: i64,
+    pub package_id: String,
+    /// Transaction that published this version
+    pub digest: String,
+    /// `None` when the package was made immutable when published
+    pub upgrade_cap_id: Option<String>,
+}
+
+impl PackageUpgrade {
+    pub async fn load_all_by_original<'e, E>(
+        executor: E,
+        original_id: &ObjectID,
+        network: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            PackageUpgrade,
+            "SELECT * FROM package_upgrades
+            WHERE original_id = $1 AND network = $2
+            ORDER BY version",
+            &original_id.to_string(),
+            &network
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO package_upgrades
+            (original_id, network, version, package_id, digest, upgrade_cap_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (original_id, network, version) DO UPDATE
+            SET
+                package_id = EXCLUDED.package_id,
+                digest = EXCLUDED.digest,
+                upgrade_cap_id = EXCLUDED.upgrade_cap_id",
+            &self.original_id,
+            &self.network,
+            self.version,
+            &self.package_id,
+            &self.digest,
+            self.upgrade_cap_id.as_ref()
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+
+use super::descriptions::SecurityLevel;
+
+/// Verdict the module prompts reached on a labelled package.
+#[derive(Debug, Clone, FromRow)]
+pub struct PromptBenchResult {
+    pub package_name: String,
+    pub package_id: String,
+    pub network: String,
+    pub model: String,
+    pub description_version: String,
+    pub security_level_version: String,
+    /// Label of the package
+    pub is_scam: bool,
+    /// Highest security level of its modules
+    pub security_level: SecurityLevel,
+    pub benchmarked_at: DateTime<Utc>,
+}
+
+impl PromptBenchResult {
+    /// Latest result of every package for each model and prompt versions.
+    pub async fn load_latest<'e, E>(executor: E) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            PromptBenchResult,
+            "SELECT DISTINCT ON (model, description_version, security_level_version, package_name)
+                package_name,
+                package_id,
+                network,
+                model,
+                description_version,
+                security_level_version,
+                is_scam,
+                security_level as \"security_level: SecurityLevel\",
+                benchmarked_at
+            FROM prompt_bench_results
+            ORDER BY model, description_version, security_level_version, package_name,
+                benchmarked_at DESC"
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO prompt_bench_results
+            (package_name, package_id, network, model, description_version,
+             security_level_version, is_scam, security_level, benchmarked_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            &self.package_name,
+            &self.package_id,
+            &self.network,
+            &self.model,
+            &self.description_version,
+            &self.security_level_version,
+            self.is_scam,
+            self.security_level as _,
+            self.benchmarked_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, types::Json, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
+
+/// How a module compiled from source compares with the one stored for the package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "module_match", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleMatch {
+    Matches,
+    Differs,
+    /// Stored for the package but not compiled from the source
+    Missing,
+    /// Compiled from the source but not part of the package
+    Extra,
+}
+
+/// Outcome of recompiling the source of one module, the source is verified when it matches.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SourceVerification {
+    pub package_id: String,
+    pub network: String,
+    pub module_name: String,
+    /// Kind of the module source checked
+    pub kind: String,
+    pub result: ModuleMatch,
+    /// What differs between the stored and the compiled module
+    pub differences: Json<Vec<String>>,
+    pub verified_at: DateTime<Utc>,
+}
+
+impl SourceVerification {
+    pub async fn load_all_by_package<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            SourceVerification,
+            "SELECT
+                package_id,
+                network,
+                module_name,
+                kind,
+                result as \"result: ModuleMatch\",
+                differences as \"differences: Json<Vec<String>>\",
+                verified_at
+            FROM source_verifications
+            WHERE package_id = $1 AND network = $2
+            ORDER BY module_name, kind",
+            &package_id.to_string(),
+            &network
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO source_verifications
+            (package_id, network, module_name, kind, result, differences)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (package_id, network, module_name, kind) DO UPDATE
+            SET
+                result = EXCLUDED.result,
+                differences = EXCLUDED.differences,
+                verified_at = Now()",
+            &self.package_id,
+            &self.network,
+            &self.module_name,
+            &self.kind,
+            self.result as _,
+            &self.differences as _
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
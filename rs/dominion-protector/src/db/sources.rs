@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
-use sqlx::{query_as, query, Executor, FromRow, Postgres};
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
 use sui_types::base_types::ObjectID;
 
 #[derive(Debug, FromRow)]
@@ -11,6 +11,8 @@ pub struct ModuleSource {
     pub module_name: String,
     pub source: String,
     pub kind: String,
+    /// Recompiling the source gives the bytecode stored for the module
+    pub verified: bool,
 }
 
 impl ModuleSource {
@@ -19,6 +21,7 @@ impl ModuleSource {
         package_id: &ObjectID,
         network: &str,
         module_name: &str,
+        kind: &str,
     ) -> Result<Option<Self>>
     where
         E: Executor<'e, Database = Postgres>,
@@ -26,10 +29,11 @@ impl ModuleSource {
         Ok(query_as!(
             ModuleSource,
             "SELECT * FROM module_sources
-            WHERE package_id = $1 AND network = $2 AND module_name = $3",
+            WHERE package_id = $1 AND network = $2 AND module_name = $3 AND kind = $4",
             &package_id.to_string(),
             &network,
-            &module_name
+            &module_name,
+            &kind
         )
         .fetch_optional(executor)
         .await?)
@@ -41,15 +45,18 @@ impl ModuleSource {
     {
         query!(
             "INSERT INTO module_sources (
-            package_id, network, module_name, source, kind
+            package_id, network, module_name, source, kind, verified
         ) VALUES (
-            $1, $2, $3, $4, $5
-        )",
+            $1, $2, $3, $4, $5, $6
+        )
+        ON CONFLICT (package_id, network, module_name, kind) DO UPDATE
+        SET source = EXCLUDED.source, verified = EXCLUDED.verified",
             &self.package_id,
             &self.network,
             &self.module_name,
             &self.source,
-            &self.kind
+            &self.kind,
+            self.verified
         )
         .execute(executor)
         .await?;
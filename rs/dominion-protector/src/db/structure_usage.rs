@@ -0,0 +1,126 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
+
+/// What a function of the package does with one of its datatypes, found in the bytecode.
+/// Every ownership column holds the list of ways it happens, `None` when it never does.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct StructureUsage {
+    pub package_id: String,
+    pub network: String,
+    pub module_name: String,
+    pub datatype_name: String,
+    pub target_module_name: String,
+    /// Shortest chain of calls from the function to the one doing it
+    pub call_chain: Vec<String>,
+    pub function_name: String,
+    pub address_owned: Option<serde_json::Value>,
+    pub object_owned: Option<serde_json::Value>,
+    pub wrapped: Option<serde_json::Value>,
+    pub shared: Option<serde_json::Value>,
+    pub immutable: Option<serde_json::Value>,
+    pub emitted: Option<serde_json::Value>,
+}
+
+impl StructureUsage {
+    pub async fn load_all_by_structure<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+        datatype_name: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            StructureUsage,
+            "SELECT * FROM structures_usage
+            WHERE package_id = $1 AND network = $2 AND module_name = $3 AND datatype_name = $4
+            ORDER BY target_module_name, function_name",
+            &package_id.to_string(),
+            &network,
+            &module_name,
+            &datatype_name
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+
+    pub async fn load_all_by_package<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            StructureUsage,
+            "SELECT * FROM structures_usage
+            WHERE package_id = $1 AND network = $2
+            ORDER BY module_name, datatype_name, target_module_name, function_name",
+            &package_id.to_string(),
+            &network
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO structures_usage (
+                package_id, network, module_name, datatype_name, target_module_name, call_chain,
+                function_name, address_owned, object_owned, wrapped, shared, immutable, emitted
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13
+            ) ON CONFLICT (package_id, network, module_name, datatype_name, target_module_name, function_name)
+            DO UPDATE SET
+                call_chain = EXCLUDED.call_chain,
+                address_owned = EXCLUDED.address_owned,
+                object_owned = EXCLUDED.object_owned,
+                wrapped = EXCLUDED.wrapped,
+                shared = EXCLUDED.shared,
+                immutable = EXCLUDED.immutable,
+                emitted = EXCLUDED.emitted",
+            &self.package_id,
+            &self.network,
+            &self.module_name,
+            &self.datatype_name,
+            &self.target_module_name,
+            &self.call_chain,
+            &self.function_name,
+            self.address_owned.as_ref(),
+            self.object_owned.as_ref(),
+            self.wrapped.as_ref(),
+            self.shared.as_ref(),
+            self.immutable.as_ref(),
+            self.emitted.as_ref()
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_all_by_package<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "DELETE FROM structures_usage WHERE package_id = $1 AND network = $2",
+            &package_id.to_string(),
+            &network
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
+
+/// Package whose structures usage is saved, including the ones where no function does
+/// anything with its datatypes. Packages never change, so usage is computed once.
+#[derive(Debug, FromRow)]
+pub struct StructureUsagePackage {
+    pub package_id: String,
+    pub network: String,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl StructureUsagePackage {
+    pub async fn load<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+    ) -> Result<Option<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            StructureUsagePackage,
+            "SELECT * FROM structures_usage_packages WHERE package_id = $1 AND network = $2",
+            &package_id.to_string(),
+            &network
+        )
+        .fetch_optional(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(executor: E, package_id: &ObjectID, network: &str) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO structures_usage_packages (package_id, network)
+            VALUES ($1, $2)
+            ON CONFLICT (package_id, network) DO UPDATE SET saved_at = Now()",
+            &package_id.to_string(),
+            &network
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
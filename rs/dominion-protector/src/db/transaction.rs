@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::{
+    rpc_types::{
+        SuiExecutionStatus, SuiTransactionBlockDataAPI, SuiTransactionBlockEffectsAPI,
+        SuiTransactionBlockResponse,
+    },
+    types::digests::TransactionDigest,
+};
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Transaction {
+    pub digest: String,             // VARCHAR(64)
+    pub network: String,            // VARCHAR(10)
+    pub sender: String,             // VARCHAR(66)
+    pub checkpoint: Option<i64>,    // BIGINT, nullable
+    pub timestamp_ms: Option<i64>,  // BIGINT, nullable
+    pub success: bool,              // BOOLEAN
+    pub error: Option<String>,      // TEXT, nullable
+    pub raw_transaction: Vec<u8>,   // BYTEA
+    pub effects: serde_json::Value, // JSONB
+    pub read_at: DateTime<Utc>,     // TIMESTAMPTZ, default Now()
+}
+
+impl Transaction {
+    pub async fn load<'c, E: Executor<'c, Database = Postgres>>(
+        db: E,
+        digest: &TransactionDigest,
+        network: &str,
+    ) -> Result<Option<Self>> {
+        Ok(query_as!(
+            Transaction,
+            "SELECT * FROM transactions WHERE digest = $1 AND network = $2",
+            &digest.to_string(),
+            &network
+        )
+        .fetch_optional(db)
+        .await?)
+    }
+
+    pub async fn save<'c, E: Executor<'c, Database = Postgres>>(&self, db: E) -> Result<()> {
+        query!(
+            "INSERT INTO transactions
+            (digest, network, sender, checkpoint, timestamp_ms, success, error, raw_transaction, effects)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (digest, network) DO UPDATE
+            SET
+                sender = EXCLUDED.sender,
+                checkpoint = EXCLUDED.checkpoint,
+                timestamp_ms = EXCLUDED.timestamp_ms,
+                success = EXCLUDED.success,
+                error = EXCLUDED.error,
+                raw_transaction = EXCLUDED.raw_transaction,
+                effects = EXCLUDED.effects",
+            &self.digest,
+            &self.network,
+            &self.sender,
+            self.checkpoint,
+            self.timestamp_ms,
+            self.success,
+            self.error.as_ref(),
+            &self.raw_transaction,
+            &self.effects
+        )
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+impl TryFrom<(&SuiTransactionBlockResponse, &str)> for Transaction {
+    type Error = anyhow::Error;
+
+    fn try_from((response, network): (&SuiTransactionBlockResponse, &str)) -> Result<Self> {
+        let transaction = response
+            .transaction
+            .as_ref()
+            .context("Transaction input is required")?;
+        let effects = response
+            .effects
+            .as_ref()
+            .context("Transaction effects are required")?;
+        let (success, error) = match effects.status() {
+            SuiExecutionStatus::Success => (true, None),
+            SuiExecutionStatus::Failure { error } => (false, Some(error.clone())),
+        };
+        Ok(Self {
+            digest: response.digest.to_string(),
+            network: network.to_owned(),
+            sender: transaction.data.sender().to_string(),
+            checkpoint: response.checkpoint.map(i64::try_from).transpose()?,
+            timestamp_ms: response.timestamp_ms.map(i64::try_from).transpose()?,
+            success,
+            error,
+            raw_transaction: response.raw_transaction.clone(),
+            effects: serde_json::to_value(effects)?,
+            read_at: Utc::now(),
+        })
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::digests::TransactionDigest;
+
+#[derive(Debug, FromRow)]
+pub struct TransactionCommand {
+    pub digest: String,
+    pub network: String,
+    pub command_index: i32,
+    pub kind: String,
+    pub package_id: Option<String>,
+    pub module_name: Option<String>,
+    pub function_name: Option<String>,
+    pub command: serde_json::Value,
+}
+
+impl TransactionCommand {
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO transaction_commands (
+                digest, network, command_index, kind, package_id, module_name, function_name, command
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8
+            ) ON CONFLICT (digest, network, command_index)
+            DO UPDATE SET
+                kind = EXCLUDED.kind,
+                package_id = EXCLUDED.package_id,
+                module_name = EXCLUDED.module_name,
+                function_name = EXCLUDED.function_name,
+                command = EXCLUDED.command",
+            &self.digest,
+            &self.network,
+            self.command_index,
+            &self.kind,
+            self.package_id.as_ref(),
+            self.module_name.as_ref(),
+            self.function_name.as_ref(),
+            &self.command
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load_all_by_transaction<'e, E>(
+        executor: E,
+        digest: &TransactionDigest,
+        network: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            TransactionCommand,
+            "SELECT * FROM transaction_commands
+            WHERE digest = $1 AND network = $2
+            ORDER BY command_index",
+            &digest.to_string(),
+            &network
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+}
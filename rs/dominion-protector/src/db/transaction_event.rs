@@ -0,0 +1,67 @@
+use anyhow::Result;
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::digests::TransactionDigest;
+
+#[derive(Debug, FromRow)]
+pub struct TransactionEvent {
+    pub digest: String,
+    pub network: String,
+    pub event_seq: i64,
+    pub package_id: String,
+    pub module_name: String,
+    pub sender: String,
+    pub event_type: String,
+    pub parsed_json: serde_json::Value,
+}
+
+impl TransactionEvent {
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO transaction_events (
+                digest, network, event_seq, package_id, module_name, sender, event_type, parsed_json
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8
+            ) ON CONFLICT (digest, network, event_seq)
+            DO UPDATE SET
+                package_id = EXCLUDED.package_id,
+                module_name = EXCLUDED.module_name,
+                sender = EXCLUDED.sender,
+                event_type = EXCLUDED.event_type,
+                parsed_json = EXCLUDED.parsed_json",
+            &self.digest,
+            &self.network,
+            self.event_seq,
+            &self.package_id,
+            &self.module_name,
+            &self.sender,
+            &self.event_type,
+            &self.parsed_json
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load_all_by_transaction<'e, E>(
+        executor: E,
+        digest: &TransactionDigest,
+        network: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            TransactionEvent,
+            "SELECT * FROM transaction_events
+            WHERE digest = $1 AND network = $2
+            ORDER BY event_seq",
+            &digest.to_string(),
+            &network
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+}
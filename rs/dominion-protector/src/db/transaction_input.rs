@@ -0,0 +1,56 @@
+use anyhow::Result;
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::digests::TransactionDigest;
+
+#[derive(Debug, FromRow)]
+pub struct TransactionInput {
+    pub digest: String,
+    pub network: String,
+    pub input_index: i32,
+    pub kind: String,
+    pub object_id: Option<String>,
+    pub value: serde_json::Value,
+}
+
+impl TransactionInput {
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO transaction_inputs (digest, network, input_index, kind, object_id, value)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (digest, network, input_index) DO UPDATE
+             SET kind = EXCLUDED.kind, object_id = EXCLUDED.object_id, value = EXCLUDED.value",
+            &self.digest,
+            &self.network,
+            self.input_index,
+            &self.kind,
+            self.object_id.as_ref(),
+            &self.value
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load_all_by_transaction<'e, E>(
+        executor: E,
+        digest: &TransactionDigest,
+        network: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            TransactionInput,
+            "SELECT * FROM transaction_inputs
+            WHERE digest = $1 AND network = $2
+            ORDER BY input_index",
+            &digest.to_string(),
+            &network
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+}
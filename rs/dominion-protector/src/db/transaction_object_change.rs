@@ -0,0 +1,63 @@
+use anyhow::Result;
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::digests::TransactionDigest;
+
+#[derive(Debug, FromRow)]
+pub struct TransactionObjectChange {
+    pub digest: String,
+    pub network: String,
+    pub object_id: String,
+    pub change_type: String,
+    pub object_type: Option<String>,
+    pub version: i64,
+    pub change: serde_json::Value,
+}
+
+impl TransactionObjectChange {
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO transaction_object_changes (
+                digest, network, object_id, change_type, object_type, version, change
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7
+            ) ON CONFLICT (digest, network, object_id)
+            DO UPDATE SET
+                change_type = EXCLUDED.change_type,
+                object_type = EXCLUDED.object_type,
+                version = EXCLUDED.version,
+                change = EXCLUDED.change",
+            &self.digest,
+            &self.network,
+            &self.object_id,
+            &self.change_type,
+            self.object_type.as_ref(),
+            self.version,
+            &self.change
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load_all_by_transaction<'e, E>(
+        executor: E,
+        digest: &TransactionDigest,
+        network: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            TransactionObjectChange,
+            "SELECT * FROM transaction_object_changes
+            WHERE digest = $1 AND network = $2",
+            &digest.to_string(),
+            &network
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+use sui_sdk::types::base_types::ObjectID;
+
+/// Solver verdict for one property of one function.
+#[derive(Debug, FromRow)]
+pub struct VerificationResult {
+    pub package_id: String,
+    pub network: String,
+    pub module_name: String,
+    pub function_name: String,
+    pub property: String,
+    /// Parameter the property is about, empty when it is about the whole function
+    pub subject: String,
+    pub solver: String,
+    /// `sat`, `unsat` or `unknown`
+    pub verdict: String,
+    /// `None` when the solver gave up
+    pub violated: Option<bool>,
+    /// Counterexample for `sat` verdicts
+    pub model: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl VerificationResult {
+    pub async fn load_all_by_package<'e, E>(
+        executor: E,
+        package_id: &ObjectID,
+        network: &str,
+    ) -> Result<Vec<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            VerificationResult,
+            "SELECT * FROM verification_results
+            WHERE package_id = $1 AND network = $2
+            ORDER BY module_name, function_name, property, subject",
+            &package_id.to_string(),
+            &network
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO verification_results
+            (package_id, network, module_name, function_name, property, subject, solver, verdict, violated, model)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (package_id, network, module_name, function_name, property, subject) DO UPDATE
+            SET
+                solver = EXCLUDED.solver,
+                verdict = EXCLUDED.verdict,
+                violated = EXCLUDED.violated,
+                model = EXCLUDED.model,
+                checked_at = Now()",
+            &self.package_id,
+            &self.network,
+            &self.module_name,
+            &self.function_name,
+            &self.property,
+            &self.subject,
+            &self.solver,
+            &self.verdict,
+            self.violated,
+            self.model.as_ref()
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Executor, FromRow, Postgres};
+
+/// Last checkpoint fully processed by `watch` on a network.
+#[derive(Debug, FromRow)]
+pub struct WatchCursor {
+    pub network: String,
+    pub checkpoint: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WatchCursor {
+    pub async fn load<'e, E>(executor: E, network: &str) -> Result<Option<Self>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Ok(query_as!(
+            WatchCursor,
+            "SELECT * FROM watch_cursors WHERE network = $1",
+            &network
+        )
+        .fetch_optional(executor)
+        .await?)
+    }
+
+    pub async fn save<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        query!(
+            "INSERT INTO watch_cursors (network, checkpoint)
+             VALUES ($1, $2)
+             ON CONFLICT (network) DO UPDATE
+             SET checkpoint = EXCLUDED.checkpoint, updated_at = Now()",
+            &self.network,
+            self.checkpoint
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
@@ -1,12 +1,15 @@
 use anyhow::Result;
 use move_binary_format::CompiledModule;
-use move_bytecode_source_map::mapping::SourceMapping;
+use move_bytecode_source_map::{mapping::SourceMapping, source_map::SourceMap};
 use move_disassembler::disassembler::{Disassembler, DisassemblerOptions};
 use move_ir_types::location::Spanned;
 use sqlx::{Acquire, Postgres};
 use sui_types::base_types::ObjectID;
 
-use crate::db::sources::ModuleSource;
+use crate::{
+    db::{module_source_map::ModuleSourceMap, sources::ModuleSource},
+    source_map::update_links,
+};
 
 pub mod revela;
 pub mod smt;
@@ -16,12 +19,14 @@ pub async fn decompile_module_to_smt<'a, A>(
     network: &str,
     package_id: ObjectID,
     module: &CompiledModule,
-) -> Result<ModuleSource> {
-    // let mut db = db.acquire().await?;
+) -> Result<ModuleSource>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut db = db.acquire().await?;
 
     let mut smt = String::new();
-    smt::decompile_module(&package_id, module, &mut smt)?;
-    println!("{}", &smt);
+    smt::decompile_module(module, &mut smt)?;
 
     let sources = ModuleSource {
         package_id: package_id.to_string(),
@@ -29,8 +34,9 @@ pub async fn decompile_module_to_smt<'a, A>(
         network: network.to_string(),
         source: smt,
         kind: "smt".to_string(),
+        verified: false,
     };
-    // sources.save(&mut *db).await?;
+    sources.save(&mut *db).await?;
     Ok(sources)
 }
 
@@ -49,10 +55,19 @@ where
     disassembler_options.print_basic_blocks = true;
     disassembler_options.print_locals = true;
 
-    // TODO: make source mapping work with the Move source language
-    let no_loc = Spanned::unsafe_no_loc(()).loc;
-
-    let source_mapping = SourceMapping::new_without_source_map(&module, no_loc)?;
+    let mut db = db.acquire().await?;
+    let module_name = module.name().to_string();
+    // Modules with verified sources are disassembled with the names and spans of the source
+    let source_mapping =
+        match ModuleSourceMap::load(&mut *db, &package_id, network, &module_name).await? {
+            Some(stored) => {
+                SourceMapping::new(bcs::from_bytes::<SourceMap>(&stored.source_map)?, module)
+            }
+            None => {
+                let no_loc = Spanned::unsafe_no_loc(()).loc;
+                SourceMapping::new_without_source_map(&module, no_loc)?
+            }
+        };
 
     let disassembler = Disassembler::new(source_mapping, disassembler_options);
 
@@ -74,8 +89,9 @@ where
         network: network.to_string(),
         source: dissassemble_string,
         kind: "disassembled".to_string(),
+        verified: false,
     };
-    let mut db = db.acquire().await?;
     sources.save(&mut *db).await?;
+    update_links(&mut *db, network, &package_id, module).await?;
     Ok(sources)
 }
@@ -1,12 +1,13 @@
 use std::{fs::File, io::Write, str::from_utf8};
 
 use anyhow::{bail, Result};
+use move_binary_format::CompiledModule;
 use sqlx::{Acquire, Postgres};
 use sui_sdk::types::base_types::ObjectID;
 use tempfile::tempdir;
 use tokio::{fs, process::Command};
 
-use crate::db::sources::ModuleSource;
+use crate::{db::sources::ModuleSource, source_map::update_links};
 
 pub async fn decompile_module_with_revela_cli<'a, A>(
     db: A,
@@ -38,8 +39,11 @@ where
             network: network.to_string(),
             source: from_utf8(&source.stdout)?.to_string(),
             kind: "revela".to_string(),
+            verified: false,
         };
         sources.save(&mut *db).await?;
+        let module = CompiledModule::deserialize_with_defaults(module_bytecode)?;
+        update_links(&mut *db, network, &package_id, &module).await?;
         Ok(sources)
     } else {
         bail!(
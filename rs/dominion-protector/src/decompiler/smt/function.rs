@@ -0,0 +1,1354 @@
+//! Function bodies as transition relations.
+//!
+//! A function `f(p0: S0, p1: &mut S1): R0` becomes
+//! `(define-fun |f| (($p0 S0) ($p1 S1) ($r0 R0) ($m1 S1) ($aborted Bool)) Bool ...)`,
+//! which holds when `f` started with the parameters may return `$r0`, leave `$m1` behind
+//! `p1`, and abort or not. Loop-free bodies are executed symbolically, everything else
+//! (natives, loops, recursion, calls into other modules) stays an uninterpreted relation
+//! with the same signature.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use anyhow::{bail, Context, Result};
+use move_binary_format::{
+    file_format::{
+        Bytecode, CodeUnit, DatatypeHandleIndex, EnumDefinitionIndex, FieldHandleIndex,
+        FunctionDefinition, FunctionDefinitionIndex, FunctionHandleIndex, JumpTableInner,
+        SignatureIndex, SignatureToken, StructDefinitionIndex, VariantTag,
+    },
+    CompiledModule,
+};
+use move_core_types::runtime_value::MoveValue;
+
+use super::{datatype_symbol, field_symbol, function_symbol, member_symbol, sort, TypeParams};
+
+/// Instructions executed over all paths of one function before it is left uninterpreted.
+const MAX_STEPS: usize = 100_000;
+
+const U256_MAX: &str =
+    "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+pub(super) fn decompile_functions<W: fmt::Write>(module: &CompiledModule, mut w: W) -> Result<()> {
+    let own = module
+        .function_defs()
+        .iter()
+        .enumerate()
+        .map(|(index, def)| (def.function.0, index))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut declarations = BTreeMap::new();
+    let mut bodies = BTreeMap::new();
+    let mut uninterpreted = BTreeMap::new();
+    for (index, def) in module.function_defs().iter().enumerate() {
+        let Some(code) = &def.code else {
+            uninterpreted.insert(index, "native".to_string());
+            continue;
+        };
+        let mut translator = Translator::new(module, def, code, &own);
+        match translator.translate() {
+            Ok(body) => {
+                declarations.extend(translator.declarations);
+                bodies.insert(index, (body, translator.calls));
+            }
+            Err(err) => {
+                uninterpreted.insert(index, err.to_string());
+            }
+        }
+    }
+
+    // `define-fun` can only use relations defined before it
+    let mut pending = bodies.keys().copied().collect::<BTreeSet<_>>();
+    let mut order = Vec::new();
+    while let Some(&first) = pending.first() {
+        let ready = pending.iter().copied().find(|index| {
+            bodies[index]
+                .1
+                .iter()
+                .all(|callee| !pending.contains(callee))
+        });
+        if let Some(index) = ready {
+            pending.remove(&index);
+            order.push(index);
+            continue;
+        }
+        // Every pending function waits for another pending one, following callees ends in a cycle
+        let mut index = first;
+        for _ in 0..pending.len() {
+            index = *bodies[&index]
+                .1
+                .iter()
+                .find(|callee| pending.contains(callee))
+                .context("Pending function without pending callees")?;
+        }
+        pending.remove(&index);
+        bodies.remove(&index);
+        uninterpreted.insert(index, "recursive".to_string());
+    }
+
+    for declaration in declarations.values() {
+        writeln!(w, "{}", declaration)?;
+    }
+    for (index, reason) in &uninterpreted {
+        let handle = module.function_handle_at(module.function_defs()[*index].function);
+        writeln!(w, "; {}", reason.replace('\n', " "))?;
+        writeln!(
+            w,
+            "{}",
+            declare_relation(
+                &function_symbol(module, module.function_defs()[*index].function),
+                &relation_sorts(
+                    module,
+                    &module.signature_at(handle.parameters).0,
+                    &module.signature_at(handle.return_).0,
+                ),
+            )
+        )?;
+    }
+    for index in order {
+        writeln!(w, "{}", bodies[&index].0)?;
+    }
+    Ok(())
+}
+
+/// Translates the function at `index` keeping only the paths to `offset`, which end there
+/// without aborting. Paths returning before are dropped.
+pub(super) fn decompile_reachability(
+    module: &CompiledModule,
+    index: FunctionDefinitionIndex,
+    offset: usize,
+) -> Result<(String, String)> {
+    let def = &module.function_defs()[index.0 as usize];
+    let code = def.code.as_ref().context("Native function")?;
+    let own = module
+        .function_defs()
+        .iter()
+        .enumerate()
+        .map(|(index, def)| (def.function.0, index))
+        .collect::<BTreeMap<_, _>>();
+    let relation = format!(
+        "{}@{}|",
+        function_symbol(module, def.function).trim_end_matches('|'),
+        offset
+    );
+    let mut translator = Translator::new(module, def, code, &own);
+    translator.goal = Some(offset);
+    let definition = translator.translate_as(&relation)?;
+    Ok((relation, definition))
+}
+
+/// Sorts of parameters, returns, values left behind `&mut` parameters and the abort flag.
+fn relation_sorts(
+    module: &CompiledModule,
+    params: &[SignatureToken],
+    returns: &[SignatureToken],
+) -> Vec<String> {
+    let mut sorts = params
+        .iter()
+        .chain(returns)
+        .map(|t| sort(module, t, TypeParams::Function))
+        .collect::<Vec<_>>();
+    sorts.extend(params.iter().filter_map(|t| match t {
+        SignatureToken::MutableReference(inner) => Some(sort(module, inner, TypeParams::Function)),
+        _ => None,
+    }));
+    sorts.push("Bool".to_string());
+    sorts
+}
+
+/// Names the `define-fun` of a function gives to the [`relation_sorts`].
+pub(super) fn relation_arguments(
+    module: &CompiledModule,
+    params: &[SignatureToken],
+    returns: &[SignatureToken],
+) -> Vec<(String, String)> {
+    let mut names = (0..params.len())
+        .map(|i| format!("$p{}", i))
+        .collect::<Vec<_>>();
+    names.extend((0..returns.len()).map(|i| format!("$r{}", i)));
+    names.extend(
+        params
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| matches!(t, SignatureToken::MutableReference(_)))
+            .map(|(i, _)| format!("$m{}", i)),
+    );
+    names.push("$aborted".to_string());
+    names
+        .into_iter()
+        .zip(relation_sorts(module, params, returns))
+        .collect()
+}
+
+fn declare_relation(name: &str, sorts: &[String]) -> String {
+    format!("(declare-fun {} ({}) Bool)", name, sorts.join(" "))
+}
+
+fn subst(token: &SignatureToken, type_args: &[SignatureToken]) -> SignatureToken {
+    if type_args.is_empty() {
+        return token.clone();
+    }
+    match token {
+        SignatureToken::TypeParameter(index) => type_args
+            .get(*index as usize)
+            .cloned()
+            .unwrap_or_else(|| token.clone()),
+        SignatureToken::Vector(inner) => SignatureToken::Vector(Box::new(subst(inner, type_args))),
+        SignatureToken::Reference(inner) => {
+            SignatureToken::Reference(Box::new(subst(inner, type_args)))
+        }
+        SignatureToken::MutableReference(inner) => {
+            SignatureToken::MutableReference(Box::new(subst(inner, type_args)))
+        }
+        SignatureToken::DatatypeInstantiation(i) => SignatureToken::DatatypeInstantiation(
+            Box::new((i.0, i.1.iter().map(|t| subst(t, type_args)).collect())),
+        ),
+        _ => token.clone(),
+    }
+}
+
+fn datatype_type(index: DatatypeHandleIndex, type_args: &[SignatureToken]) -> SignatureToken {
+    if type_args.is_empty() {
+        SignatureToken::Datatype(index)
+    } else {
+        SignatureToken::DatatypeInstantiation(Box::new((index, type_args.to_vec())))
+    }
+}
+
+/// Move syntax of a type, used to name instantiations of generic functions.
+fn type_name(module: &CompiledModule, token: &SignatureToken) -> String {
+    match token {
+        SignatureToken::Bool => "bool".to_string(),
+        SignatureToken::U8 => "u8".to_string(),
+        SignatureToken::U16 => "u16".to_string(),
+        SignatureToken::U32 => "u32".to_string(),
+        SignatureToken::U64 => "u64".to_string(),
+        SignatureToken::U128 => "u128".to_string(),
+        SignatureToken::U256 => "u256".to_string(),
+        SignatureToken::Address => "address".to_string(),
+        SignatureToken::Signer => "signer".to_string(),
+        SignatureToken::Vector(inner) => format!("vector<{}>", type_name(module, inner)),
+        SignatureToken::Datatype(index) => datatype_symbol(module, *index)
+            .trim_matches('|')
+            .to_string(),
+        SignatureToken::DatatypeInstantiation(i) => format!(
+            "{}<{}>",
+            datatype_symbol(module, i.0).trim_matches('|'),
+            i.1.iter()
+                .map(|t| type_name(module, t))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        SignatureToken::Reference(inner) => format!("&{}", type_name(module, inner)),
+        SignatureToken::MutableReference(inner) => format!("&mut {}", type_name(module, inner)),
+        SignatureToken::TypeParameter(index) => format!("$T{}", index),
+    }
+}
+
+fn width(ty: &SignatureToken) -> Result<u32> {
+    Ok(match ty {
+        SignatureToken::U8 => 8,
+        SignatureToken::U16 => 16,
+        SignatureToken::U32 => 32,
+        SignatureToken::U64 => 64,
+        SignatureToken::U128 => 128,
+        SignatureToken::U256 => 256,
+        _ => bail!("Integer operation on {:?}", ty),
+    })
+}
+
+fn max_value(width: u32) -> String {
+    if width == 256 {
+        U256_MAX.to_string()
+    } else {
+        (u128::MAX >> (128 - width)).to_string()
+    }
+}
+
+#[derive(Clone)]
+enum Root {
+    Local(usize),
+    Cell(usize),
+}
+
+#[derive(Clone)]
+enum Step {
+    Field {
+        constructor: String,
+        fields: Vec<String>,
+        index: usize,
+    },
+    Index(String),
+}
+
+impl Step {
+    fn read(&self, value: &str) -> String {
+        match self {
+            Step::Field { fields, index, .. } => format!("({} {})", fields[*index], value),
+            Step::Index(i) => format!("(select (data {}) {})", value, i),
+        }
+    }
+
+    fn write(&self, value: &str, new: String) -> String {
+        match self {
+            Step::Field {
+                constructor,
+                fields,
+                index,
+            } => {
+                let mut args = Vec::new();
+                for (i, field) in fields.iter().enumerate() {
+                    if i == *index {
+                        args.push(new.clone());
+                    } else {
+                        args.push(format!("({} {})", field, value));
+                    }
+                }
+                format!("({} {})", constructor, args.join(" "))
+            }
+            Step::Index(i) => format!(
+                "(Vector (store (data {}) {} {}) (length {}))",
+                value, i, new, value
+            ),
+        }
+    }
+}
+
+/// Place a reference points to: a local or a cell, then fields and vector elements inside it.
+#[derive(Clone)]
+struct Path {
+    root: Root,
+    steps: Vec<Step>,
+}
+
+#[derive(Clone)]
+enum Slot {
+    Value(String),
+    Ref(Path),
+}
+
+/// Stack or local slot, `ty` of references is the type they point to.
+#[derive(Clone)]
+struct Entry {
+    slot: Slot,
+    ty: SignatureToken,
+}
+
+#[derive(Clone)]
+struct State {
+    stack: Vec<Entry>,
+    locals: Vec<Option<Entry>>,
+    /// Values behind reference parameters and references returned by calls
+    cells: Vec<String>,
+}
+
+/// Context around the rest of a path, applied innermost last.
+enum Wrapper {
+    Guard(String),
+    Let(String, String),
+    Call { relation: String, aborted: String },
+}
+
+impl Wrapper {
+    fn wrap(self, inner: String) -> String {
+        match self {
+            Wrapper::Guard(condition) => format!("(ite {} {} $aborted)", condition, inner),
+            Wrapper::Let(name, value) => format!("(let (({} {})) {})", name, value, inner),
+            Wrapper::Call { relation, aborted } => {
+                format!("(and {} (ite {} $aborted {}))", relation, aborted, inner)
+            }
+        }
+    }
+}
+
+struct Translator<'m> {
+    module: &'m CompiledModule,
+    def: &'m FunctionDefinition,
+    code: &'m CodeUnit,
+    own: &'m BTreeMap<u16, usize>,
+    local_types: Vec<SignatureToken>,
+    returns: Vec<SignatureToken>,
+    /// `&mut` parameters with the cells holding their values
+    mut_params: Vec<(usize, usize)>,
+    /// Existential variables of the body
+    fresh: Vec<(String, String)>,
+    names: usize,
+    steps: usize,
+    declarations: BTreeMap<String, String>,
+    /// Functions of this module the body relation refers to
+    calls: BTreeSet<usize>,
+    /// Offset whose paths are the only ones kept, see [`decompile_reachability`]
+    goal: Option<usize>,
+}
+
+impl<'m> Translator<'m> {
+    fn new(
+        module: &'m CompiledModule,
+        def: &'m FunctionDefinition,
+        code: &'m CodeUnit,
+        own: &'m BTreeMap<u16, usize>,
+    ) -> Self {
+        let handle = module.function_handle_at(def.function);
+        let mut local_types = module.signature_at(handle.parameters).0.clone();
+        local_types.extend(module.signature_at(code.locals).0.iter().cloned());
+        Self {
+            module,
+            def,
+            code,
+            own,
+            local_types,
+            returns: module.signature_at(handle.return_).0.clone(),
+            mut_params: Vec::new(),
+            fresh: Vec::new(),
+            names: 0,
+            steps: 0,
+            declarations: BTreeMap::new(),
+            calls: BTreeSet::new(),
+            goal: None,
+        }
+    }
+
+    fn translate(&mut self) -> Result<String> {
+        self.translate_as(&function_symbol(self.module, self.def.function))
+    }
+
+    fn translate_as(&mut self, relation: &str) -> Result<String> {
+        let handle = self.module.function_handle_at(self.def.function);
+        let params = &self.module.signature_at(handle.parameters).0;
+
+        let mut state = State {
+            stack: Vec::new(),
+            locals: vec![None; self.local_types.len()],
+            cells: Vec::new(),
+        };
+        for (i, param) in params.iter().enumerate() {
+            let name = format!("$p{}", i);
+            state.locals[i] = Some(match param {
+                SignatureToken::Reference(inner) | SignatureToken::MutableReference(inner) => {
+                    state.cells.push(name);
+                    let cell = state.cells.len() - 1;
+                    if let SignatureToken::MutableReference(_) = param {
+                        self.mut_params.push((i, cell));
+                    }
+                    Entry {
+                        slot: Slot::Ref(Path {
+                            root: Root::Cell(cell),
+                            steps: Vec::new(),
+                        }),
+                        ty: inner.as_ref().clone(),
+                    }
+                }
+                _ => Entry {
+                    slot: Slot::Value(name),
+                    ty: param.clone(),
+                },
+            });
+        }
+        let arguments = relation_arguments(self.module, params, &self.returns);
+
+        let mut body = self.exec(0, state)?;
+        if !self.fresh.is_empty() {
+            body = format!("(exists ({}) {})", Self::bindings(&self.fresh), body);
+        }
+        Ok(format!(
+            "(define-fun {} ({}) Bool\n  {})",
+            relation,
+            Self::bindings(&arguments),
+            body
+        ))
+    }
+
+    fn bindings(variables: &[(String, String)]) -> String {
+        variables
+            .iter()
+            .map(|(name, sort)| format!("({} {})", name, sort))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn sort(&self, ty: &SignatureToken) -> String {
+        sort(self.module, ty, TypeParams::Function)
+    }
+
+    fn fresh(&mut self, sort: String) -> String {
+        let name = format!("$v{}", self.names);
+        self.names += 1;
+        self.fresh.push((name.clone(), sort));
+        name
+    }
+
+    /// Names a compound value so that it is not repeated in every term using it.
+    fn bind(&mut self, wrappers: &mut Vec<Wrapper>, value: String) -> String {
+        if !value.starts_with('(') {
+            return value;
+        }
+        let name = format!("$l{}", self.names);
+        self.names += 1;
+        wrappers.push(Wrapper::Let(name.clone(), value));
+        name
+    }
+
+    /// Formula for every path starting at `pc`.
+    fn exec(&mut self, mut pc: usize, mut state: State) -> Result<String> {
+        let mut wrappers = Vec::new();
+        let result = loop {
+            self.steps += 1;
+            if self.steps > MAX_STEPS {
+                bail!("too many paths");
+            }
+            let instruction = self
+                .code
+                .code
+                .get(pc)
+                .context("Execution fell off the end of the code")?;
+            if self.goal == Some(pc) {
+                break "(not $aborted)".to_string();
+            }
+            match instruction {
+                Bytecode::Nop | Bytecode::FreezeRef => {}
+                Bytecode::Pop => {
+                    pop(&mut state)?;
+                }
+                Bytecode::Ret if self.goal.is_some() => break "false".to_string(),
+                Bytecode::Ret => {
+                    let mut values = Vec::new();
+                    for _ in &self.returns {
+                        values.push(pop(&mut state)?);
+                    }
+                    values.reverse();
+                    let mut terms = vec!["(not $aborted)".to_string()];
+                    for (i, entry) in values.iter().enumerate() {
+                        terms.push(format!("(= $r{} {})", i, value(&state, entry)?));
+                    }
+                    for (param, cell) in &self.mut_params {
+                        terms.push(format!("(= $m{} {})", param, state.cells[*cell]));
+                    }
+                    break format!("(and {})", terms.join(" "));
+                }
+                Bytecode::Abort => {
+                    pop(&mut state)?;
+                    break "$aborted".to_string();
+                }
+                Bytecode::Branch(target) => {
+                    pc = self.forward(pc, *target)?;
+                    continue;
+                }
+                Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+                    let condition = pop(&mut state)?;
+                    let condition = value(&state, &condition)?;
+                    let target = self.forward(pc, *target)?;
+                    let jump = self.exec(target, state.clone())?;
+                    let next = self.exec(pc + 1, state)?;
+                    break match instruction {
+                        Bytecode::BrTrue(_) => format!("(ite {} {} {})", condition, jump, next),
+                        _ => format!("(ite {} {} {})", condition, next, jump),
+                    };
+                }
+                Bytecode::VariantSwitch(index) => {
+                    let table = self
+                        .code
+                        .jump_tables
+                        .get(index.0 as usize)
+                        .context("Unknown jump table")?;
+                    let JumpTableInner::Full(targets) = &table.jump_table;
+                    let entry = pop(&mut state)?;
+                    let enum_value = value(&state, &entry)?;
+                    let enum_value = self.bind(&mut wrappers, enum_value);
+                    let mut arms = Vec::new();
+                    for (tag, target) in targets.iter().enumerate() {
+                        let target = self.forward(pc, *target)?;
+                        let constructor =
+                            self.variant_constructor(table.head_enum, tag as VariantTag)?;
+                        arms.push((constructor, self.exec(target, state.clone())?));
+                    }
+                    let (_, mut result) = arms.pop().context("Empty jump table")?;
+                    for (constructor, arm) in arms.into_iter().rev() {
+                        result = format!(
+                            "(ite ((_ is {}) {}) {} {})",
+                            constructor, enum_value, arm, result
+                        );
+                    }
+                    break result;
+                }
+
+                Bytecode::LdTrue => push(&mut state, "true".to_string(), SignatureToken::Bool),
+                Bytecode::LdFalse => push(&mut state, "false".to_string(), SignatureToken::Bool),
+                Bytecode::LdU8(v) => push(&mut state, v.to_string(), SignatureToken::U8),
+                Bytecode::LdU16(v) => push(&mut state, v.to_string(), SignatureToken::U16),
+                Bytecode::LdU32(v) => push(&mut state, v.to_string(), SignatureToken::U32),
+                Bytecode::LdU64(v) => push(&mut state, v.to_string(), SignatureToken::U64),
+                Bytecode::LdU128(v) => push(&mut state, v.to_string(), SignatureToken::U128),
+                Bytecode::LdU256(v) => push(&mut state, v.to_string(), SignatureToken::U256),
+                Bytecode::LdConst(index) => {
+                    let constant = self.module.constant_at(*index);
+                    let decoded = constant
+                        .deserialize_constant()
+                        .context("Malformed constant")?;
+                    let value = self.constant(&decoded, &constant.type_)?;
+                    push(&mut state, value, constant.type_.clone());
+                }
+
+                Bytecode::CopyLoc(index) => {
+                    let entry = state.locals[*index as usize]
+                        .clone()
+                        .context("Copy of an unset local")?;
+                    state.stack.push(entry);
+                }
+                Bytecode::MoveLoc(index) => {
+                    let entry = state.locals[*index as usize]
+                        .take()
+                        .context("Move of an unset local")?;
+                    state.stack.push(entry);
+                }
+                Bytecode::StLoc(index) => {
+                    let mut entry = pop(&mut state)?;
+                    if let Slot::Value(value) = entry.slot {
+                        entry.slot = Slot::Value(self.bind(&mut wrappers, value));
+                    }
+                    state.locals[*index as usize] = Some(entry);
+                }
+                Bytecode::MutBorrowLoc(index) | Bytecode::ImmBorrowLoc(index) => {
+                    state.stack.push(Entry {
+                        slot: Slot::Ref(Path {
+                            root: Root::Local(*index as usize),
+                            steps: Vec::new(),
+                        }),
+                        ty: self.local_types[*index as usize].clone(),
+                    });
+                }
+                Bytecode::ReadRef => {
+                    let entry = pop(&mut state)?;
+                    push(&mut state, value(&state, &entry)?, entry.ty);
+                }
+                Bytecode::WriteRef => {
+                    let path = pop_ref(&mut state)?;
+                    let entry = pop(&mut state)?;
+                    let new = value(&state, &entry)?;
+                    self.write(&mut state, &mut wrappers, &path, new)?;
+                }
+
+                Bytecode::MutBorrowField(index) | Bytecode::ImmBorrowField(index) => {
+                    self.borrow_field(&mut state, *index, &[])?;
+                }
+                Bytecode::MutBorrowFieldGeneric(index) | Bytecode::ImmBorrowFieldGeneric(index) => {
+                    let instantiation = self.module.field_instantiation_at(*index);
+                    let type_args = self.signature(instantiation.type_parameters);
+                    self.borrow_field(&mut state, instantiation.handle, &type_args)?;
+                }
+                Bytecode::Pack(index) => self.pack(&mut state, *index, &[])?,
+                Bytecode::PackGeneric(index) => {
+                    let instantiation = self.module.struct_instantiation_at(*index);
+                    let type_args = self.signature(instantiation.type_parameters);
+                    self.pack(&mut state, instantiation.def, &type_args)?;
+                }
+                Bytecode::Unpack(index) => self.unpack(&mut state, &mut wrappers, *index, &[])?,
+                Bytecode::UnpackGeneric(index) => {
+                    let instantiation = self.module.struct_instantiation_at(*index);
+                    let type_args = self.signature(instantiation.type_parameters);
+                    self.unpack(&mut state, &mut wrappers, instantiation.def, &type_args)?;
+                }
+
+                Bytecode::PackVariant(index) => {
+                    let handle = self.module.variant_handle_at(*index);
+                    self.pack_variant(&mut state, handle.enum_def, handle.variant, &[])?;
+                }
+                Bytecode::PackVariantGeneric(index) => {
+                    let handle = self.module.variant_instantiation_handle_at(*index);
+                    let instantiation = self.module.enum_instantiation_at(handle.enum_def);
+                    let type_args = self.signature(instantiation.type_parameters);
+                    self.pack_variant(&mut state, instantiation.def, handle.variant, &type_args)?;
+                }
+                Bytecode::UnpackVariant(index)
+                | Bytecode::UnpackVariantImmRef(index)
+                | Bytecode::UnpackVariantMutRef(index) => {
+                    let handle = self.module.variant_handle_at(*index);
+                    let by_ref = !matches!(instruction, Bytecode::UnpackVariant(_));
+                    self.unpack_variant(
+                        &mut state,
+                        &mut wrappers,
+                        handle.enum_def,
+                        handle.variant,
+                        &[],
+                        by_ref,
+                    )?;
+                }
+                Bytecode::UnpackVariantGeneric(index)
+                | Bytecode::UnpackVariantGenericImmRef(index)
+                | Bytecode::UnpackVariantGenericMutRef(index) => {
+                    let handle = self.module.variant_instantiation_handle_at(*index);
+                    let instantiation = self.module.enum_instantiation_at(handle.enum_def);
+                    let type_args = self.signature(instantiation.type_parameters);
+                    let by_ref = !matches!(instruction, Bytecode::UnpackVariantGeneric(_));
+                    self.unpack_variant(
+                        &mut state,
+                        &mut wrappers,
+                        instantiation.def,
+                        handle.variant,
+                        &type_args,
+                        by_ref,
+                    )?;
+                }
+
+                Bytecode::Call(index) => self.call(&mut state, &mut wrappers, *index, &[])?,
+                Bytecode::CallGeneric(index) => {
+                    let instantiation = self.module.function_instantiation_at(*index);
+                    let type_args = self.signature(instantiation.type_parameters);
+                    self.call(&mut state, &mut wrappers, instantiation.handle, &type_args)?;
+                }
+
+                Bytecode::Add | Bytecode::Sub | Bytecode::Mul | Bytecode::Div | Bytecode::Mod => {
+                    let (lhs, rhs, ty) = self.operands(&mut state, &mut wrappers)?;
+                    let max = max_value(width(&ty)?);
+                    let (result, guard) = match instruction {
+                        Bytecode::Add => (
+                            format!("(+ {} {})", lhs, rhs),
+                            format!("(<= (+ {} {}) {})", lhs, rhs, max),
+                        ),
+                        Bytecode::Sub => (
+                            format!("(- {} {})", lhs, rhs),
+                            format!("(>= {} {})", lhs, rhs),
+                        ),
+                        Bytecode::Mul => (
+                            format!("(* {} {})", lhs, rhs),
+                            format!("(<= (* {} {}) {})", lhs, rhs, max),
+                        ),
+                        Bytecode::Div => (
+                            format!("(div {} {})", lhs, rhs),
+                            format!("(not (= {} 0))", rhs),
+                        ),
+                        _ => (
+                            format!("(mod {} {})", lhs, rhs),
+                            format!("(not (= {} 0))", rhs),
+                        ),
+                    };
+                    wrappers.push(Wrapper::Guard(guard));
+                    push(&mut state, result, ty);
+                }
+                Bytecode::BitOr | Bytecode::BitAnd | Bytecode::Xor => {
+                    let (lhs, rhs, ty) = self.operands(&mut state, &mut wrappers)?;
+                    let bits = width(&ty)?;
+                    let op = match instruction {
+                        Bytecode::BitOr => "bvor",
+                        Bytecode::BitAnd => "bvand",
+                        _ => "bvxor",
+                    };
+                    let result = format!(
+                        "(bv2nat ({} ((_ int2bv {}) {}) ((_ int2bv {}) {})))",
+                        op, bits, lhs, bits, rhs
+                    );
+                    push(&mut state, result, ty);
+                }
+                Bytecode::Shl | Bytecode::Shr => {
+                    let shift = pop(&mut state)?;
+                    let shift = value(&state, &shift)?;
+                    let shift = self.bind(&mut wrappers, shift);
+                    let entry = pop(&mut state)?;
+                    let lhs = value(&state, &entry)?;
+                    let bits = width(&entry.ty)?;
+                    let op = match instruction {
+                        Bytecode::Shl => "bvshl",
+                        _ => "bvlshr",
+                    };
+                    wrappers.push(Wrapper::Guard(format!("(< {} {})", shift, bits)));
+                    let result = format!(
+                        "(bv2nat ({} ((_ int2bv {}) {}) ((_ int2bv {}) {})))",
+                        op, bits, lhs, bits, shift
+                    );
+                    push(&mut state, result, entry.ty);
+                }
+                Bytecode::Lt | Bytecode::Gt | Bytecode::Le | Bytecode::Ge => {
+                    let (lhs, rhs, _) = self.operands(&mut state, &mut wrappers)?;
+                    let op = match instruction {
+                        Bytecode::Lt => "<",
+                        Bytecode::Gt => ">",
+                        Bytecode::Le => "<=",
+                        _ => ">=",
+                    };
+                    push(
+                        &mut state,
+                        format!("({} {} {})", op, lhs, rhs),
+                        SignatureToken::Bool,
+                    );
+                }
+                Bytecode::Eq | Bytecode::Neq => {
+                    let (lhs, rhs, _) = self.operands(&mut state, &mut wrappers)?;
+                    let result = match instruction {
+                        Bytecode::Eq => format!("(= {} {})", lhs, rhs),
+                        _ => format!("(not (= {} {}))", lhs, rhs),
+                    };
+                    push(&mut state, result, SignatureToken::Bool);
+                }
+                Bytecode::And | Bytecode::Or => {
+                    let (lhs, rhs, _) = self.operands(&mut state, &mut wrappers)?;
+                    let op = match instruction {
+                        Bytecode::And => "and",
+                        _ => "or",
+                    };
+                    push(
+                        &mut state,
+                        format!("({} {} {})", op, lhs, rhs),
+                        SignatureToken::Bool,
+                    );
+                }
+                Bytecode::Not => {
+                    let entry = pop(&mut state)?;
+                    let result = format!("(not {})", value(&state, &entry)?);
+                    push(&mut state, result, SignatureToken::Bool);
+                }
+                Bytecode::CastU8
+                | Bytecode::CastU16
+                | Bytecode::CastU32
+                | Bytecode::CastU64
+                | Bytecode::CastU128
+                | Bytecode::CastU256 => {
+                    let ty = match instruction {
+                        Bytecode::CastU8 => SignatureToken::U8,
+                        Bytecode::CastU16 => SignatureToken::U16,
+                        Bytecode::CastU32 => SignatureToken::U32,
+                        Bytecode::CastU64 => SignatureToken::U64,
+                        Bytecode::CastU128 => SignatureToken::U128,
+                        _ => SignatureToken::U256,
+                    };
+                    let entry = pop(&mut state)?;
+                    let v = value(&state, &entry)?;
+                    let v = self.bind(&mut wrappers, v);
+                    wrappers.push(Wrapper::Guard(format!(
+                        "(<= {} {})",
+                        v,
+                        max_value(width(&ty)?)
+                    )));
+                    push(&mut state, v, ty);
+                }
+
+                Bytecode::VecPack(index, count) => {
+                    let element = self.element_type(*index)?;
+                    let mut elements = Vec::new();
+                    for _ in 0..*count {
+                        let entry = pop(&mut state)?;
+                        elements.push(value(&state, &entry)?);
+                    }
+                    elements.reverse();
+                    let mut data = self.fresh(format!("(Array Int {})", self.sort(&element)));
+                    for (i, element) in elements.iter().enumerate() {
+                        data = format!("(store {} {} {})", data, i, element);
+                    }
+                    push(
+                        &mut state,
+                        format!("(Vector {} {})", data, count),
+                        SignatureToken::Vector(Box::new(element)),
+                    );
+                }
+                Bytecode::VecLen(_) => {
+                    let entry = pop(&mut state)?;
+                    let result = format!("(length {})", value(&state, &entry)?);
+                    push(&mut state, result, SignatureToken::U64);
+                }
+                Bytecode::VecImmBorrow(index) | Bytecode::VecMutBorrow(index) => {
+                    let element = self.element_type(*index)?;
+                    let i = pop(&mut state)?;
+                    let i = value(&state, &i)?;
+                    let i = self.bind(&mut wrappers, i);
+                    let mut path = pop_ref(&mut state)?;
+                    let vector = read(&state, &path)?;
+                    wrappers.push(Wrapper::Guard(format!("(< {} (length {}))", i, vector)));
+                    path.steps.push(Step::Index(i));
+                    state.stack.push(Entry {
+                        slot: Slot::Ref(path),
+                        ty: element,
+                    });
+                }
+                Bytecode::VecPushBack(_) => {
+                    let entry = pop(&mut state)?;
+                    let element = value(&state, &entry)?;
+                    let path = pop_ref(&mut state)?;
+                    let vector = read(&state, &path)?;
+                    let vector = self.bind(&mut wrappers, vector);
+                    let new = format!(
+                        "(Vector (store (data {}) (length {}) {}) (+ (length {}) 1))",
+                        vector, vector, element, vector
+                    );
+                    self.write(&mut state, &mut wrappers, &path, new)?;
+                }
+                Bytecode::VecPopBack(index) => {
+                    let element = self.element_type(*index)?;
+                    let path = pop_ref(&mut state)?;
+                    let vector = read(&state, &path)?;
+                    let vector = self.bind(&mut wrappers, vector);
+                    wrappers.push(Wrapper::Guard(format!("(> (length {}) 0)", vector)));
+                    push(
+                        &mut state,
+                        format!("(select (data {}) (- (length {}) 1))", vector, vector),
+                        element,
+                    );
+                    let new = format!("(Vector (data {}) (- (length {}) 1))", vector, vector);
+                    self.write(&mut state, &mut wrappers, &path, new)?;
+                }
+                Bytecode::VecUnpack(index, count) => {
+                    let element = self.element_type(*index)?;
+                    let entry = pop(&mut state)?;
+                    let vector = value(&state, &entry)?;
+                    let vector = self.bind(&mut wrappers, vector);
+                    wrappers.push(Wrapper::Guard(format!("(= (length {}) {})", vector, count)));
+                    for i in 0..*count {
+                        push(
+                            &mut state,
+                            format!("(select (data {}) {})", vector, i),
+                            element.clone(),
+                        );
+                    }
+                }
+                Bytecode::VecSwap(_) => {
+                    let j = pop(&mut state)?;
+                    let j = value(&state, &j)?;
+                    let j = self.bind(&mut wrappers, j);
+                    let i = pop(&mut state)?;
+                    let i = value(&state, &i)?;
+                    let i = self.bind(&mut wrappers, i);
+                    let path = pop_ref(&mut state)?;
+                    let vector = read(&state, &path)?;
+                    let vector = self.bind(&mut wrappers, vector);
+                    wrappers.push(Wrapper::Guard(format!(
+                        "(and (< {} (length {})) (< {} (length {})))",
+                        i, vector, j, vector
+                    )));
+                    let new = format!(
+                        "(Vector (store (store (data {v}) {i} (select (data {v}) {j})) {j} (select (data {v}) {i})) (length {v}))",
+                        v = vector,
+                        i = i,
+                        j = j
+                    );
+                    self.write(&mut state, &mut wrappers, &path, new)?;
+                }
+
+                _ => bail!("unsupported instruction {:?}", instruction),
+            }
+            pc += 1;
+        };
+        Ok(wrappers
+            .into_iter()
+            .rev()
+            .fold(result, |inner, wrapper| wrapper.wrap(inner)))
+    }
+
+    /// Loops are the only backward jumps in verified bytecode.
+    fn forward(&self, pc: usize, target: u16) -> Result<usize> {
+        let target = target as usize;
+        if target <= pc {
+            bail!("loop at offset {}", target);
+        }
+        Ok(target)
+    }
+
+    fn signature(&self, index: SignatureIndex) -> Vec<SignatureToken> {
+        self.module.signature_at(index).0.clone()
+    }
+
+    fn element_type(&self, index: SignatureIndex) -> Result<SignatureToken> {
+        self.module
+            .signature_at(index)
+            .0
+            .first()
+            .cloned()
+            .context("Vector instruction without element type")
+    }
+
+    /// Pops two operands, naming them because they usually appear twice in the result.
+    fn operands(
+        &mut self,
+        state: &mut State,
+        wrappers: &mut Vec<Wrapper>,
+    ) -> Result<(String, String, SignatureToken)> {
+        let rhs = pop(state)?;
+        let rhs = value(state, &rhs)?;
+        let rhs = self.bind(wrappers, rhs);
+        let lhs = pop(state)?;
+        let ty = lhs.ty.clone();
+        let lhs = value(state, &lhs)?;
+        let lhs = self.bind(wrappers, lhs);
+        Ok((lhs, rhs, ty))
+    }
+
+    fn constant(&mut self, value: &MoveValue, ty: &SignatureToken) -> Result<String> {
+        Ok(match (value, ty) {
+            (MoveValue::Bool(v), _) => v.to_string(),
+            (MoveValue::U8(v), _) => v.to_string(),
+            (MoveValue::U16(v), _) => v.to_string(),
+            (MoveValue::U32(v), _) => v.to_string(),
+            (MoveValue::U64(v), _) => v.to_string(),
+            (MoveValue::U128(v), _) => v.to_string(),
+            (MoveValue::U256(v), _) => v.to_string(),
+            (MoveValue::Address(v), _) => {
+                let mut bytes = v.into_bytes();
+                bytes.reverse();
+                move_core_types::u256::U256::from_le_bytes(&bytes).to_string()
+            }
+            (MoveValue::Vector(elements), SignatureToken::Vector(element)) => {
+                let mut data = self.fresh(format!("(Array Int {})", self.sort(element)));
+                for (i, e) in elements.iter().enumerate() {
+                    data = format!("(store {} {} {})", data, i, self.constant(e, element)?);
+                }
+                format!("(Vector {} {})", data, elements.len())
+            }
+            _ => bail!("unsupported constant of type {:?}", ty),
+        })
+    }
+
+    fn write(
+        &mut self,
+        state: &mut State,
+        wrappers: &mut Vec<Wrapper>,
+        path: &Path,
+        new: String,
+    ) -> Result<()> {
+        // Values each step is applied to, from the root inwards
+        let mut values = vec![read(
+            state,
+            &Path {
+                root: path.root.clone(),
+                steps: Vec::new(),
+            },
+        )?];
+        for step in path.steps.iter().take(path.steps.len().saturating_sub(1)) {
+            let inner = step.read(values.last().unwrap());
+            values.push(inner);
+        }
+        let mut new = new;
+        for (step, value) in path.steps.iter().zip(values).rev() {
+            new = step.write(&value, new);
+        }
+        let new = self.bind(wrappers, new);
+        match path.root {
+            Root::Local(index) => {
+                state.locals[index] = Some(Entry {
+                    slot: Slot::Value(new),
+                    ty: self.local_types[index].clone(),
+                });
+            }
+            Root::Cell(index) => state.cells[index] = new,
+        }
+        Ok(())
+    }
+
+    fn borrow_field(
+        &mut self,
+        state: &mut State,
+        index: FieldHandleIndex,
+        type_args: &[SignatureToken],
+    ) -> Result<()> {
+        let handle = self.module.field_handle_at(index);
+        let struct_def = self.module.struct_def_at(handle.owner);
+        let fields = struct_def.fields().context("Field of a native struct")?;
+        let field = fields.get(handle.field as usize).context("Unknown field")?;
+        let mut path = pop_ref(state)?;
+        path.steps.push(Step::Field {
+            constructor: datatype_symbol(self.module, struct_def.struct_handle),
+            fields: fields
+                .iter()
+                .map(|f| field_symbol(self.module, struct_def, f))
+                .collect(),
+            index: handle.field as usize,
+        });
+        state.stack.push(Entry {
+            slot: Slot::Ref(path),
+            ty: subst(&field.signature.0, type_args),
+        });
+        Ok(())
+    }
+
+    fn pack(
+        &mut self,
+        state: &mut State,
+        index: StructDefinitionIndex,
+        type_args: &[SignatureToken],
+    ) -> Result<()> {
+        let struct_def = self.module.struct_def_at(index);
+        let fields = struct_def.fields().context("Pack of a native struct")?;
+        let mut values = Vec::new();
+        for _ in fields {
+            let entry = pop(state)?;
+            values.push(value(state, &entry)?);
+        }
+        values.reverse();
+        push(
+            state,
+            format!(
+                "({} {})",
+                datatype_symbol(self.module, struct_def.struct_handle),
+                values.join(" ")
+            ),
+            datatype_type(struct_def.struct_handle, type_args),
+        );
+        Ok(())
+    }
+
+    fn unpack(
+        &mut self,
+        state: &mut State,
+        wrappers: &mut Vec<Wrapper>,
+        index: StructDefinitionIndex,
+        type_args: &[SignatureToken],
+    ) -> Result<()> {
+        let struct_def = self.module.struct_def_at(index);
+        let fields = struct_def.fields().context("Unpack of a native struct")?;
+        let entry = pop(state)?;
+        let packed = value(state, &entry)?;
+        let packed = self.bind(wrappers, packed);
+        for field in fields {
+            push(
+                state,
+                format!(
+                    "({} {})",
+                    field_symbol(self.module, struct_def, field),
+                    packed
+                ),
+                subst(&field.signature.0, type_args),
+            );
+        }
+        Ok(())
+    }
+
+    fn variant_constructor(&self, index: EnumDefinitionIndex, tag: VariantTag) -> Result<String> {
+        let enum_def = self.module.enum_def_at(index);
+        let variant = enum_def
+            .variants
+            .get(tag as usize)
+            .context("Unknown variant")?;
+        Ok(member_symbol(
+            &datatype_symbol(self.module, enum_def.enum_handle),
+            self.module.identifier_at(variant.variant_name).as_str(),
+        ))
+    }
+
+    fn pack_variant(
+        &mut self,
+        state: &mut State,
+        index: EnumDefinitionIndex,
+        tag: VariantTag,
+        type_args: &[SignatureToken],
+    ) -> Result<()> {
+        let enum_def = self.module.enum_def_at(index);
+        let variant = enum_def
+            .variants
+            .get(tag as usize)
+            .context("Unknown variant")?;
+        let constructor = self.variant_constructor(index, tag)?;
+        let ty = datatype_type(enum_def.enum_handle, type_args);
+        let mut values = Vec::new();
+        for _ in &variant.fields {
+            let entry = pop(state)?;
+            values.push(value(state, &entry)?);
+        }
+        values.reverse();
+        let packed = if !values.is_empty() {
+            format!("({} {})", constructor, values.join(" "))
+        } else if !type_args.is_empty() {
+            // Nothing to infer type parameters of a generic constant constructor from
+            format!("(as {} {})", constructor, self.sort(&ty))
+        } else {
+            constructor
+        };
+        push(state, packed, ty);
+        Ok(())
+    }
+
+    fn unpack_variant(
+        &mut self,
+        state: &mut State,
+        wrappers: &mut Vec<Wrapper>,
+        index: EnumDefinitionIndex,
+        tag: VariantTag,
+        type_args: &[SignatureToken],
+        by_ref: bool,
+    ) -> Result<()> {
+        let enum_def = self.module.enum_def_at(index);
+        let variant = enum_def
+            .variants
+            .get(tag as usize)
+            .context("Unknown variant")?;
+        let constructor = self.variant_constructor(index, tag)?;
+        let fields = variant
+            .fields
+            .iter()
+            .map(|f| member_symbol(&constructor, self.module.identifier_at(f.name).as_str()))
+            .collect::<Vec<_>>();
+
+        let entry = pop(state)?;
+        let packed = value(state, &entry)?;
+        let packed = self.bind(wrappers, packed);
+        wrappers.push(Wrapper::Guard(format!(
+            "((_ is {}) {})",
+            constructor, packed
+        )));
+        for (i, field) in variant.fields.iter().enumerate() {
+            let ty = subst(&field.signature.0, type_args);
+            match (&entry.slot, by_ref) {
+                (Slot::Ref(path), true) => {
+                    let mut path = path.clone();
+                    path.steps.push(Step::Field {
+                        constructor: constructor.clone(),
+                        fields: fields.clone(),
+                        index: i,
+                    });
+                    state.stack.push(Entry {
+                        slot: Slot::Ref(path),
+                        ty,
+                    });
+                }
+                (Slot::Value(_), false) => {
+                    push(state, format!("({} {})", fields[i], packed), ty);
+                }
+                _ => bail!("Variant unpacked from a wrong kind of value"),
+            }
+        }
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        state: &mut State,
+        wrappers: &mut Vec<Wrapper>,
+        index: FunctionHandleIndex,
+        type_args: &[SignatureToken],
+    ) -> Result<()> {
+        let handle = self.module.function_handle_at(index);
+        let params = self
+            .module
+            .signature_at(handle.parameters)
+            .0
+            .iter()
+            .map(|t| subst(t, type_args))
+            .collect::<Vec<_>>();
+        let returns = self
+            .module
+            .signature_at(handle.return_)
+            .0
+            .iter()
+            .map(|t| subst(t, type_args))
+            .collect::<Vec<_>>();
+        let mut arguments = Vec::new();
+        for _ in &params {
+            arguments.push(pop(state)?);
+        }
+        arguments.reverse();
+
+        let relation = if type_args.is_empty() {
+            function_symbol(self.module, index)
+        } else {
+            format!(
+                "{}<{}>|",
+                function_symbol(self.module, index).trim_end_matches('|'),
+                type_args
+                    .iter()
+                    .map(|t| type_name(self.module, t))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        match self.own.get(&index.0) {
+            Some(def) if type_args.is_empty() => {
+                self.calls.insert(*def);
+            }
+            _ => {
+                let declaration =
+                    declare_relation(&relation, &relation_sorts(self.module, &params, &returns));
+                self.declarations
+                    .entry(relation.clone())
+                    .or_insert(declaration);
+            }
+        }
+
+        let mut terms = Vec::new();
+        for argument in &arguments {
+            terms.push(value(state, argument)?);
+        }
+        let mut results = Vec::new();
+        for ret in &returns {
+            let result = self.fresh(self.sort(ret));
+            terms.push(result.clone());
+            results.push(match ret {
+                // The callee may return a reference into anything, writes through it are dropped
+                SignatureToken::Reference(inner) | SignatureToken::MutableReference(inner) => {
+                    state.cells.push(result);
+                    Entry {
+                        slot: Slot::Ref(Path {
+                            root: Root::Cell(state.cells.len() - 1),
+                            steps: Vec::new(),
+                        }),
+                        ty: inner.as_ref().clone(),
+                    }
+                }
+                _ => Entry {
+                    slot: Slot::Value(result),
+                    ty: ret.clone(),
+                },
+            });
+        }
+        let mut updates = Vec::new();
+        for (param, argument) in params.iter().zip(&arguments) {
+            if let SignatureToken::MutableReference(inner) = param {
+                let Slot::Ref(path) = &argument.slot else {
+                    bail!("Value passed as a mutable reference");
+                };
+                let updated = self.fresh(self.sort(inner));
+                terms.push(updated.clone());
+                updates.push((path.clone(), updated));
+            }
+        }
+        let aborted = self.fresh("Bool".to_string());
+        terms.push(aborted.clone());
+        wrappers.push(Wrapper::Call {
+            relation: format!("({} {})", relation, terms.join(" ")),
+            aborted,
+        });
+        for (path, updated) in updates {
+            self.write(state, wrappers, &path, updated)?;
+        }
+        state.stack.extend(results);
+        Ok(())
+    }
+}
+
+fn push(state: &mut State, value: String, ty: SignatureToken) {
+    state.stack.push(Entry {
+        slot: Slot::Value(value),
+        ty,
+    });
+}
+
+fn pop(state: &mut State) -> Result<Entry> {
+    state.stack.pop().context("Stack underflow")
+}
+
+fn pop_ref(state: &mut State) -> Result<Path> {
+    match pop(state)?.slot {
+        Slot::Ref(path) => Ok(path),
+        Slot::Value(_) => bail!("Expected a reference on the stack"),
+    }
+}
+
+fn read(state: &State, path: &Path) -> Result<String> {
+    let mut value = match path.root {
+        Root::Local(index) => match state.locals.get(index).and_then(Option::as_ref) {
+            Some(Entry {
+                slot: Slot::Value(value),
+                ..
+            }) => value.clone(),
+            _ => bail!("Borrowed local {} holds no value", index),
+        },
+        Root::Cell(index) => state.cells[index].clone(),
+    };
+    for step in &path.steps {
+        value = step.read(&value);
+    }
+    Ok(value)
+}
+
+/// Value of a stack entry, references are read.
+fn value(state: &State, entry: &Entry) -> Result<String> {
+    match &entry.slot {
+        Slot::Value(value) => Ok(value.clone()),
+        Slot::Ref(path) => read(state, path),
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Write},
+};
+
+use anyhow::Result;
+use move_binary_format::{
+    file_format::{
+        DatatypeHandleIndex, FieldDefinition, FunctionDefinitionIndex, FunctionHandleIndex,
+        SignatureToken, StructDefinition,
+    },
+    CompiledModule,
+};
+use sui_types::base_types::ObjectID;
+
+mod function;
+
+/// Vector theory, `0x1` datatypes and `Balance` that every translated module is checked together
+/// with.
+pub const SYSTEM_THEORY: &str = include_str!("../../../../../smt/system.smt2");
+
+#[derive(Clone, Copy)]
+enum TypeParams {
+    /// `T0`, bound by `par` in datatype declarations
+    Datatype,
+    /// `$T0`, abstract sorts declared once per module for generic function bodies
+    Function,
+}
+
+pub fn decompile_symbol(package_id: &ObjectID, module_name: &str, name: &str) -> String {
+    format!("|{}::{}::{}|", package_id, module_name, name)
+}
+
+/// Symbol of something that belongs to an already quoted symbol, like an enum variant.
+fn member_symbol(owner: &str, name: &str) -> String {
+    format!("{}::{}|", owner.trim_end_matches('|'), name)
+}
+
+fn datatype_symbol(module: &CompiledModule, index: DatatypeHandleIndex) -> String {
+    let handle = module.datatype_handle_at(index);
+    let m = module.module_handle_at(handle.module);
+    decompile_symbol(
+        &ObjectID::from_address(*module.address_identifier_at(m.address)),
+        module.identifier_at(m.name).as_str(),
+        module.identifier_at(handle.name).as_str(),
+    )
+}
+
+/// Selector of a struct field. Field names repeat across structs and the system theory, so they
+/// are qualified like the accessors of enum variants.
+pub(crate) fn field_symbol(
+    module: &CompiledModule,
+    struct_def: &StructDefinition,
+    field: &FieldDefinition,
+) -> String {
+    member_symbol(
+        &datatype_symbol(module, struct_def.struct_handle),
+        module.identifier_at(field.name).as_str(),
+    )
+}
+
+fn function_symbol(module: &CompiledModule, index: FunctionHandleIndex) -> String {
+    let handle = module.function_handle_at(index);
+    let m = module.module_handle_at(handle.module);
+    decompile_symbol(
+        &ObjectID::from_address(*module.address_identifier_at(m.address)),
+        module.identifier_at(m.name).as_str(),
+        module.identifier_at(handle.name).as_str(),
+    )
+}
+
+fn sort(module: &CompiledModule, s: &SignatureToken, type_params: TypeParams) -> String {
+    match &s {
+        SignatureToken::Bool => "Bool".to_string(),
+        SignatureToken::U8
+        | SignatureToken::U16
+        | SignatureToken::U32
+        | SignatureToken::U64
+        | SignatureToken::U128
+        | SignatureToken::U256
+        | SignatureToken::Address
+        | SignatureToken::Signer => "Int".to_string(),
+        SignatureToken::Vector(signature_token) => format!(
+            "(Vector {})",
+            sort(module, signature_token.as_ref(), type_params)
+        ),
+        SignatureToken::Datatype(datatype_handle_index) => {
+            datatype_symbol(module, *datatype_handle_index)
+        }
+        SignatureToken::DatatypeInstantiation(i) => {
+            let mut r = format!("({}", datatype_symbol(module, i.0));
+            for t in &i.1 {
+                write!(&mut r, " {}", sort(module, t, type_params)).unwrap();
+            }
+            r.push(')');
+            r
+        }
+        // References are translated as the values they point to, writes through
+        // `&mut` are returned from functions as updated values
+        SignatureToken::Reference(signature_token)
+        | SignatureToken::MutableReference(signature_token) => {
+            sort(module, signature_token.as_ref(), type_params)
+        }
+        SignatureToken::TypeParameter(index) => match type_params {
+            TypeParams::Datatype => format!("T{}", index),
+            TypeParams::Function => format!("$T{}", index),
+        },
+    }
+}
+
+/// Symbol of the relation a function is translated to, with its argument names and sorts.
+pub fn function_relation(
+    module: &CompiledModule,
+    index: FunctionHandleIndex,
+) -> (String, Vec<(String, String)>) {
+    let handle = module.function_handle_at(index);
+    (
+        function_symbol(module, index),
+        function::relation_arguments(
+            module,
+            &module.signature_at(handle.parameters).0,
+            &module.signature_at(handle.return_).0,
+        ),
+    )
+}
+
+pub fn decompile_type_signature(module: &CompiledModule, s: &SignatureToken) -> String {
+    sort(module, s, TypeParams::Datatype)
+}
+
+/// Symbols declared by [`SYSTEM_THEORY`], modules must not declare them again.
+fn system_symbols() -> BTreeSet<String> {
+    SYSTEM_THEORY
+        .lines()
+        .map(|line| line.split(';').next().unwrap_or_default())
+        .flat_map(|line| line.split('|').skip(1).step_by(2))
+        .map(|symbol| format!("|{}|", symbol))
+        .collect()
+}
+
+/// Relation over the arguments of a function that holds without `$aborted` only for the paths
+/// reaching the instruction at `offset`, as its symbol and `define-fun`. It refers to the
+/// relations [`decompile_module`] defines, which have to come first.
+pub fn decompile_reachability(
+    module: &CompiledModule,
+    index: FunctionDefinitionIndex,
+    offset: usize,
+) -> Result<(String, String)> {
+    function::decompile_reachability(module, index, offset)
+}
+
+/// Translates a module on its own: datatypes of other modules become uninterpreted sorts
+/// and their functions uninterpreted relations, so the result only needs [`SYSTEM_THEORY`].
+pub fn decompile_module<W: fmt::Write>(module: &CompiledModule, mut w: W) -> Result<()> {
+    let system = system_symbols();
+
+    for (index, handle) in module.datatype_handles().iter().enumerate() {
+        let name = datatype_symbol(module, DatatypeHandleIndex(index as u16));
+        if handle.module != module.self_handle_idx() && !system.contains(&name) {
+            writeln!(
+                w,
+                "(declare-sort {} {})",
+                name,
+                handle.type_parameters.len()
+            )?;
+        }
+    }
+    let type_parameters = module
+        .function_handles()
+        .iter()
+        .map(|handle| handle.type_parameters.len())
+        .max()
+        .unwrap_or(0);
+    for i in 0..type_parameters {
+        writeln!(w, "(declare-sort $T{} 0)", i)?;
+    }
+
+    decompile_datatypes(module, &system, &mut w)?;
+    function::decompile_functions(module, &mut w)
+}
+
+fn decompile_datatypes<W: fmt::Write>(
+    module: &CompiledModule,
+    system: &BTreeSet<String>,
+    mut w: W,
+) -> Result<()> {
+    let mut sorts = Vec::new();
+    let mut constructors = Vec::new();
+    for s in module.struct_defs() {
+        let handle = module.datatype_handle_at(s.struct_handle);
+        let name = datatype_symbol(module, s.struct_handle);
+        if system.contains(&name) {
+            continue;
+        }
+        let mut fields = String::new();
+        for f in s.fields().unwrap_or_default() {
+            write!(
+                &mut fields,
+                " ({} {})",
+                field_symbol(module, s, f),
+                decompile_type_signature(module, &f.signature.0),
+            )?;
+        }
+        constructors.push(with_type_parameters(
+            handle.type_parameters.len(),
+            format!("(({}{}))", name, fields),
+        ));
+        sorts.push((name, handle.type_parameters.len()));
+    }
+    for e in module.enum_defs() {
+        let handle = module.datatype_handle_at(e.enum_handle);
+        let name = datatype_symbol(module, e.enum_handle);
+        if system.contains(&name) {
+            continue;
+        }
+        let mut variants = Vec::new();
+        for v in &e.variants {
+            // Variants of one enum may repeat field names, so accessors are qualified
+            let constructor = member_symbol(&name, module.identifier_at(v.variant_name).as_str());
+            let mut variant = format!("({}", constructor);
+            for f in &v.fields {
+                write!(
+                    &mut variant,
+                    " ({} {})",
+                    member_symbol(&constructor, module.identifier_at(f.name).as_str()),
+                    decompile_type_signature(module, &f.signature.0),
+                )?;
+            }
+            variant.push(')');
+            variants.push(variant);
+        }
+        constructors.push(with_type_parameters(
+            handle.type_parameters.len(),
+            format!("({})", variants.join(" ")),
+        ));
+        sorts.push((name, handle.type_parameters.len()));
+    }
+
+    if sorts.is_empty() {
+        return Ok(());
+    }
+    write!(w, "(declare-datatypes (")?;
+    for (name, arity) in &sorts {
+        write!(w, "\n  ({} {})", name, arity)?;
+    }
+    write!(w, ")\n  (")?;
+    for constructor in &constructors {
+        write!(w, "\n  {}", constructor)?;
+    }
+    writeln!(w, "))")?;
+    Ok(())
+}
+
+fn with_type_parameters(count: usize, constructors: String) -> String {
+    if count == 0 {
+        return constructors;
+    }
+    let params = (0..count)
+        .map(|i| format!("T{}", i))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("(par ({}) {})", params, constructors)
+}
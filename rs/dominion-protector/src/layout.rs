@@ -0,0 +1,318 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Context, Result};
+use move_binary_format::{
+    file_format::{DatatypeHandleIndex, FieldDefinition, SignatureToken},
+    CompiledModule,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    u256::U256,
+};
+use serde_json::{json, Map, Value};
+use sqlx::{Acquire, Postgres};
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::db::{package_linkage::PackageLinkage, package_module::PackageModule};
+
+/// Modules needed to decode values of a Move type, loaded from `package_modules`.
+pub struct PackageLayouts {
+    /// Modules by the id they have in bytecode, at the original id of their package. Every
+    /// version loaded of a package is kept, types added in upgrades being only in later ones.
+    modules: BTreeMap<ModuleId, Vec<CompiledModule>>,
+    /// Original ids of the packages loaded, by package id
+    originals: BTreeMap<AccountAddress, AccountAddress>,
+}
+
+impl PackageLayouts {
+    pub async fn load<'a, A>(db: A, network: &str, type_: &StructTag) -> Result<Self>
+    where
+        A: Acquire<'a, Database = Postgres>,
+    {
+        let mut db = db.acquire().await?;
+        let mut modules = BTreeMap::<_, Vec<_>>::new();
+        let mut originals = BTreeMap::new();
+        let mut loaded = BTreeSet::new();
+        let mut unresolved = type_addresses(type_);
+        while let Some(address) = unresolved.pop() {
+            if !loaded.insert(address) {
+                continue;
+            }
+            let package_id = ObjectID::from_address(address);
+            // Bytecode names dependencies by their original id, the linkage says which version
+            // of them the package uses
+            let linkage = PackageLinkage::load_all_by_package(&mut *db, &package_id, network)
+                .await?
+                .into_iter()
+                .map(|linkage| {
+                    Ok((
+                        AccountAddress::from_hex_literal(&linkage.dependency_id)?,
+                        AccountAddress::from_hex_literal(&linkage.upgraded_id)?,
+                    ))
+                })
+                .collect::<Result<BTreeMap<_, _>>>()?;
+            let package_modules =
+                PackageModule::load_all_by_package(&mut *db, &package_id, network).await?;
+            for module in package_modules {
+                let compiled = CompiledModule::deserialize_with_defaults(&module.module_bytecode)?;
+                let original = *compiled.self_id().address();
+                originals.insert(address, original);
+                unresolved.extend(
+                    compiled
+                        .module_handles()
+                        .iter()
+                        .map(|handle| *compiled.address_identifier_at(handle.address))
+                        .filter(|dependency| *dependency != original)
+                        .map(|dependency| *linkage.get(&dependency).unwrap_or(&dependency)),
+                );
+                modules
+                    .entry(compiled.self_id())
+                    .or_default()
+                    .push(compiled);
+            }
+        }
+        Ok(Self { modules, originals })
+    }
+
+    /// Decodes BCS `bytes` of `type_` into the same JSON shape the fullnode uses for object content.
+    pub fn to_json(&self, type_: &StructTag, bytes: &[u8]) -> Result<Value> {
+        let mut reader = BcsReader { bytes, offset: 0 };
+        let value = self.decode_struct(type_, &mut reader)?;
+        if reader.offset != bytes.len() {
+            bail!(
+                "{} trailing bytes after decoding {}",
+                bytes.len() - reader.offset,
+                type_.to_canonical_string(true)
+            );
+        }
+        Ok(value)
+    }
+
+    fn decode(&self, type_: &TypeTag, reader: &mut BcsReader) -> Result<Value> {
+        Ok(match type_ {
+            TypeTag::Bool => Value::Bool(reader.read_byte()? != 0),
+            TypeTag::U8 => json!(reader.read_byte()?),
+            TypeTag::U16 => json!(u16::from_le_bytes(reader.read_array()?)),
+            TypeTag::U32 => json!(u32::from_le_bytes(reader.read_array()?)),
+            TypeTag::U64 => json!(u64::from_le_bytes(reader.read_array()?).to_string()),
+            TypeTag::U128 => json!(u128::from_le_bytes(reader.read_array()?).to_string()),
+            TypeTag::U256 => json!(U256::from_le_bytes(&reader.read_array()?).to_string()),
+            TypeTag::Address | TypeTag::Signer => {
+                json!(AccountAddress::new(reader.read_array()?).to_hex_literal())
+            }
+            TypeTag::Vector(element) => {
+                let length = reader.read_uleb128()?;
+                let mut elements = Vec::new();
+                for _ in 0..length {
+                    elements.push(self.decode(element, reader)?);
+                }
+                Value::Array(elements)
+            }
+            TypeTag::Struct(tag) => self.decode_struct(tag, reader)?,
+        })
+    }
+
+    fn decode_struct(&self, type_: &StructTag, reader: &mut BcsReader) -> Result<Value> {
+        let module_name = type_.module.as_str();
+        let name = type_.name.as_str();
+        if type_.address == AccountAddress::ONE {
+            match (module_name, name) {
+                ("string", "String") | ("ascii", "String") => {
+                    let length = reader.read_uleb128()?;
+                    let bytes = reader.read_bytes(length)?;
+                    return Ok(json!(String::from_utf8_lossy(bytes)));
+                }
+                ("option", "Option") => {
+                    return Ok(match reader.read_uleb128()? {
+                        0 => Value::Null,
+                        1 => {
+                            let element =
+                                type_.type_params.first().context("Option without type")?;
+                            self.decode(element, reader)?
+                        }
+                        length => bail!("Option with {} elements", length),
+                    });
+                }
+                _ => {}
+            }
+        }
+        if type_.address == AccountAddress::TWO && module_name == "object" {
+            match name {
+                "UID" => {
+                    return Ok(json!({
+                        "id": AccountAddress::new(reader.read_array()?).to_hex_literal()
+                    }))
+                }
+                "ID" => {
+                    return Ok(json!(
+                        AccountAddress::new(reader.read_array()?).to_hex_literal()
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        // Types are named after the package defining them, which is an upgrade of the package
+        // for types added by upgrades
+        let address = self.originals.get(&type_.address).unwrap_or(&type_.address);
+        let module_id = ModuleId::new(*address, type_.module.clone());
+        let versions = self
+            .modules
+            .get(&module_id)
+            .with_context(|| format!("Module {} is not loaded", module_id))?;
+
+        for module in versions {
+            for struct_def in module.struct_defs() {
+                let handle = module.datatype_handle_at(struct_def.struct_handle);
+                if module.identifier_at(handle.name).as_str() == name {
+                    let fields = struct_def
+                        .fields()
+                        .with_context(|| format!("Native struct {}", name))?;
+                    return self.decode_fields(module, fields, &type_.type_params, reader);
+                }
+            }
+
+            for enum_def in module.enum_defs() {
+                let handle = module.datatype_handle_at(enum_def.enum_handle);
+                if module.identifier_at(handle.name).as_str() == name {
+                    let tag = reader.read_uleb128()?;
+                    let variant = enum_def
+                        .variants
+                        .get(tag)
+                        .with_context(|| format!("Unknown variant {} of {}", tag, name))?;
+                    return Ok(json!({
+                        "variant": module.identifier_at(variant.variant_name).as_str(),
+                        "fields": self.decode_fields(module, &variant.fields, &type_.type_params, reader)?,
+                    }));
+                }
+            }
+        }
+
+        bail!("Datatype {} not found", type_.to_canonical_string(true))
+    }
+
+    fn decode_fields<'f>(
+        &self,
+        module: &CompiledModule,
+        fields: impl IntoIterator<Item = &'f FieldDefinition>,
+        type_params: &[TypeTag],
+        reader: &mut BcsReader,
+    ) -> Result<Value> {
+        let mut result = Map::new();
+        for field in fields {
+            let field_type = instantiate(module, &field.signature.0, type_params)?;
+            result.insert(
+                module.identifier_at(field.name).to_string(),
+                self.decode(&field_type, reader)?,
+            );
+        }
+        Ok(Value::Object(result))
+    }
+}
+
+/// Addresses of every package mentioned in `type_`, including its type arguments.
+pub fn type_addresses(type_: &StructTag) -> Vec<AccountAddress> {
+    let mut addresses = vec![type_.address];
+    for mut param in &type_.type_params {
+        while let TypeTag::Vector(element) = param {
+            param = element;
+        }
+        if let TypeTag::Struct(tag) = param {
+            addresses.extend(type_addresses(tag));
+        }
+    }
+    addresses
+}
+
+fn instantiate(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    type_params: &[TypeTag],
+) -> Result<TypeTag> {
+    Ok(match token {
+        SignatureToken::Bool => TypeTag::Bool,
+        SignatureToken::U8 => TypeTag::U8,
+        SignatureToken::U16 => TypeTag::U16,
+        SignatureToken::U32 => TypeTag::U32,
+        SignatureToken::U64 => TypeTag::U64,
+        SignatureToken::U128 => TypeTag::U128,
+        SignatureToken::U256 => TypeTag::U256,
+        SignatureToken::Address => TypeTag::Address,
+        SignatureToken::Signer => TypeTag::Signer,
+        SignatureToken::Vector(element) => {
+            TypeTag::Vector(Box::new(instantiate(module, element, type_params)?))
+        }
+        SignatureToken::Datatype(index) => {
+            TypeTag::Struct(Box::new(datatype_tag(module, *index, vec![])))
+        }
+        SignatureToken::DatatypeInstantiation(instantiation) => {
+            let args = instantiation
+                .1
+                .iter()
+                .map(|arg| instantiate(module, arg, type_params))
+                .collect::<Result<Vec<_>>>()?;
+            TypeTag::Struct(Box::new(datatype_tag(module, instantiation.0, args)))
+        }
+        SignatureToken::TypeParameter(index) => type_params
+            .get(*index as usize)
+            .with_context(|| format!("Type parameter T{} is not instantiated", index))?
+            .clone(),
+        SignatureToken::Reference(_) | SignatureToken::MutableReference(_) => {
+            bail!("References can not be stored in objects")
+        }
+    })
+}
+
+fn datatype_tag(
+    module: &CompiledModule,
+    index: DatatypeHandleIndex,
+    type_params: Vec<TypeTag>,
+) -> StructTag {
+    let handle = module.datatype_handle_at(index);
+    let module_handle = module.module_handle_at(handle.module);
+    StructTag {
+        address: *module.address_identifier_at(module_handle.address),
+        module: module.identifier_at(module_handle.name).to_owned(),
+        name: module.identifier_at(handle.name).to_owned(),
+        type_params,
+    }
+}
+
+struct BcsReader<'b> {
+    bytes: &'b [u8],
+    offset: usize,
+}
+
+impl<'b> BcsReader<'b> {
+    fn read_bytes(&mut self, length: usize) -> Result<&'b [u8]> {
+        let end = self
+            .offset
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .context("Unexpected end of BCS data")?;
+        let bytes = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn read_byte(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.read_bytes(N)?.try_into()?)
+    }
+
+    fn read_uleb128(&mut self) -> Result<usize> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(usize::try_from(value)?);
+            }
+        }
+        bail!("ULEB128 value is too long")
+    }
+}
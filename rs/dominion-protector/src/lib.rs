@@ -1,11 +1,23 @@
 use clap::{Parser, Subcommand};
 
 pub mod ai;
+pub mod analyzer;
+pub mod call_graph;
+pub mod capabilities;
 pub mod commands;
 pub mod db;
+pub mod layout;
+pub mod lineage;
 pub mod prompts;
+pub mod rules;
+pub mod simulator;
+pub mod source_map;
+pub mod source_providers;
+pub mod source_verification;
 pub mod sui_client;
+pub mod usage;
 pub mod decompiler;
+pub mod verifier;
 
 use anyhow::Result;
 use commands::*;
@@ -22,9 +34,18 @@ pub struct Cli {
 enum Commands {
     Download(download::DownloadCommand),
     Decompile(decompile::DecompileCommand),
-    // Describe(describe::DescribeCommand),
+    Describe(describe::DescribeCommand),
+    Import(import::ImportCommand),
+    IngestSources(ingest_sources::IngestSourcesCommand),
     Serve(serve::ServeCommand),
     Watch(watch::WatchCommand),
+    Verify(verify::VerifyCommand),
+    VerifySource(verify_source::VerifySourceCommand),
+    Lineage(lineage::LineageCommand),
+    Capabilities(capabilities::CapabilitiesCommand),
+    Scan(scan::ScanCommand),
+    AiReport(ai_report::AiReportCommand),
+    BenchPrompts(bench_prompts::BenchPromptsCommand),
     // Clear(clear::ClearCommand),
     Experiment(experiment::ExperimentCommand),
 }
@@ -34,9 +55,18 @@ impl Cli {
         match self.command {
             Commands::Download(cmd) => cmd.run().await,
             Commands::Decompile(cmd) => cmd.run().await,
-            // Commands::Describe(cmd) => cmd.run().await,
+            Commands::Describe(cmd) => cmd.run().await,
+            Commands::Import(cmd) => cmd.run().await,
+            Commands::IngestSources(cmd) => cmd.run().await,
             Commands::Serve(cmd) => cmd.run().await,
             Commands::Watch(cmd) => cmd.run().await,
+            Commands::Verify(cmd) => cmd.run().await,
+            Commands::VerifySource(cmd) => cmd.run().await,
+            Commands::Lineage(cmd) => cmd.run().await,
+            Commands::Capabilities(cmd) => cmd.run().await,
+            Commands::Scan(cmd) => cmd.run().await,
+            Commands::AiReport(cmd) => cmd.run().await,
+            Commands::BenchPrompts(cmd) => cmd.run().await,
             // Commands::Clear(cmd) => cmd.run().await,
             Commands::Experiment(cmd) => cmd.run().await,
         }
@@ -0,0 +1,587 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Context, Result};
+use move_binary_format::{
+    file_format::{Bytecode, FieldHandleIndex, FunctionHandleIndex, StructDefinitionIndex},
+    CompiledModule,
+};
+use serde::Serialize;
+use sui_sdk::{
+    rpc_types::{
+        ObjectChange, SuiObjectDataOptions, SuiRawData, SuiTransactionBlockResponseOptions,
+        SuiTransactionBlockResponseQuery, TransactionFilter,
+    },
+    types::base_types::ObjectID,
+};
+use sui_types::SUI_FRAMEWORK_ADDRESS;
+
+use crate::{
+    call_graph::function_definition,
+    commands::download::get_or_download_object,
+    db::{
+        descriptions::SecurityLevel,
+        function::{Function, Visibility},
+        package_module::PackageModule,
+        package_upgrade::PackageUpgrade,
+        structure::Structure,
+        Db,
+    },
+    sui_client::SuiClientWithNetwork,
+};
+
+const TRANSACTIONS_PER_PAGE: usize = 50;
+
+/// Modules keep the address of the first version of their package in every upgrade.
+pub async fn original_id(
+    package_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<ObjectID> {
+    let package = get_or_download_object(package_id, client, db).await?;
+    let Some(SuiRawData::Package(package)) = package.bcs else {
+        bail!("Object is not a package");
+    };
+    let bytecode = package
+        .module_map
+        .values()
+        .next()
+        .context("Package has no modules")?;
+    let module = CompiledModule::deserialize_with_defaults(bytecode)?;
+    Ok(ObjectID::from(*module.self_id().address()))
+}
+
+/// Rebuilds the upgrade chain from the transactions that touched the `UpgradeCap` created
+/// together with the first version.
+pub async fn refresh_lineage(
+    original_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<Vec<PackageUpgrade>> {
+    let original = client
+        .client
+        .read_api()
+        .get_object_with_options(
+            *original_id,
+            SuiObjectDataOptions {
+                show_type: false,
+                show_owner: false,
+                show_previous_transaction: true,
+                show_display: false,
+                show_content: false,
+                show_bcs: false,
+                show_storage_rebate: false,
+            },
+        )
+        .await?
+        .data
+        .context("Package not found")?;
+    // Packages are immutable, so their previous transaction is the one that published them
+    let publish_digest = original
+        .previous_transaction
+        .context("Package has no publish transaction")?;
+    let options = SuiTransactionBlockResponseOptions {
+        show_input: false,
+        show_raw_input: false,
+        show_effects: false,
+        show_events: false,
+        show_object_changes: true,
+        show_balance_changes: false,
+        show_raw_effects: false,
+    };
+    let publish = client
+        .client
+        .read_api()
+        .get_transaction_with_options(publish_digest, options.clone())
+        .await?;
+    let upgrade_cap_id = publish
+        .object_changes
+        .iter()
+        .flatten()
+        .find_map(|change| match change {
+            ObjectChange::Created {
+                object_id,
+                object_type,
+                ..
+            } if object_type.address == SUI_FRAMEWORK_ADDRESS
+                && object_type.module.as_str() == "package"
+                && object_type.name.as_str() == "UpgradeCap" =>
+            {
+                Some(*object_id)
+            }
+            _ => None,
+        });
+
+    let mut upgrades = BTreeMap::new();
+    let mut record = |package_id: ObjectID, version: i64, digest: String| {
+        upgrades.insert(
+            version,
+            PackageUpgrade {
+                original_id: original_id.to_string(),
+                network: client.network.clone(),
+                version,
+                package_id: package_id.to_string(),
+                digest,
+                upgrade_cap_id: upgrade_cap_id.map(|cap_id| cap_id.to_string()),
+            },
+        );
+    };
+    record(
+        *original_id,
+        original.version.value().try_into()?,
+        publish_digest.to_string(),
+    );
+
+    if let Some(upgrade_cap_id) = upgrade_cap_id {
+        let mut cursor = None;
+        loop {
+            let page = client
+                .client
+                .read_api()
+                .query_transaction_blocks(
+                    SuiTransactionBlockResponseQuery::new(
+                        Some(TransactionFilter::ChangedObject(upgrade_cap_id)),
+                        Some(options.clone()),
+                    ),
+                    cursor,
+                    Some(TRANSACTIONS_PER_PAGE),
+                    false,
+                )
+                .await?;
+            for transaction in &page.data {
+                for change in transaction.object_changes.iter().flatten() {
+                    if let ObjectChange::Published {
+                        package_id,
+                        version,
+                        ..
+                    } = change
+                    {
+                        record(
+                            *package_id,
+                            version.value().try_into()?,
+                            transaction.digest.to_string(),
+                        );
+                    }
+                }
+            }
+            if !page.has_next_page {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+    }
+
+    let upgrades = upgrades.into_values().collect::<Vec<_>>();
+    let mut tx = db.pool.begin().await?;
+    for upgrade in &upgrades {
+        upgrade.save(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    Ok(upgrades)
+}
+
+/// All versions of the package `package_id` belongs to, oldest first. Falls back to the
+/// saved chain when the fullnode is not reachable.
+pub async fn get_lineage(
+    package_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<Vec<PackageUpgrade>> {
+    let original_id = original_id(package_id, client, db).await?;
+    match refresh_lineage(&original_id, client, db).await {
+        Ok(upgrades) => Ok(upgrades),
+        Err(err) => {
+            println!("Using saved lineage of {}: {:?}", original_id, err);
+            let upgrades =
+                PackageUpgrade::load_all_by_original(&db.pool, &original_id, &client.network)
+                    .await?;
+            if upgrades.is_empty() {
+                bail!("Lineage of {} is unknown", original_id);
+            }
+            Ok(upgrades)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    ModuleAdded,
+    ModuleRemoved,
+    FunctionAdded,
+    FunctionRemoved,
+    VisibilityChanged,
+    EntryChanged,
+    InitAdded,
+    InitChanged,
+    StructAdded,
+    StructRemoved,
+    AbilitiesChanged,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub module_name: String,
+    /// Function or datatype, `None` for module changes
+    pub name: Option<String>,
+    pub security_level: SecurityLevel,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PackageDiff {
+    pub from: String,
+    pub to: String,
+    /// The riskiest change
+    pub security_level: SecurityLevel,
+    pub changes: Vec<Change>,
+}
+
+/// How far outside of its module a function can be called from.
+fn exposure(visibility: Visibility) -> u8 {
+    match visibility {
+        Visibility::Private => 0,
+        Visibility::Friend | Visibility::Package => 1,
+        Visibility::Public => 2,
+    }
+}
+
+fn abilities(structure: &Structure) -> BTreeSet<&'static str> {
+    [
+        (structure.has_key, "key"),
+        (structure.has_store, "store"),
+        (structure.has_copy, "copy"),
+        (structure.has_drop, "drop"),
+    ]
+    .into_iter()
+    .filter_map(|(has, ability)| has.then_some(ability))
+    .collect()
+}
+
+/// Gaining `store` lets anyone wrap or freely transfer the objects, gaining `copy` lets
+/// them be duplicated.
+fn ability_level(ability: &str) -> SecurityLevel {
+    match ability {
+        "store" | "copy" => SecurityLevel::HighRisk,
+        _ => SecurityLevel::MediumRisk,
+    }
+}
+
+/// Instructions of the module initializer with the indices they hold replaced by what they
+/// point to, since recompiling reorders the module tables.
+fn init_instructions(bytecode: Option<&Vec<u8>>) -> Result<Option<Vec<String>>> {
+    let Some(bytecode) = bytecode else {
+        return Ok(None);
+    };
+    let module = CompiledModule::deserialize_with_defaults(bytecode)?;
+    let Some(code) = function_definition(&module, "init").and_then(|def| def.code.as_ref()) else {
+        return Ok(None);
+    };
+    Ok(Some(
+        code.code
+            .iter()
+            .map(|instruction| resolve_instruction(&module, instruction))
+            .collect(),
+    ))
+}
+
+fn resolve_instruction(module: &CompiledModule, instruction: &Bytecode) -> String {
+    // Functions of the module itself are named without the address, which differs between
+    // a package and its upgrades
+    let function = |index: FunctionHandleIndex| {
+        let handle = module.function_handle_at(index);
+        let callee = module.module_handle_at(handle.module);
+        let name = format!(
+            "{}::{}",
+            module.identifier_at(callee.name),
+            module.identifier_at(handle.name)
+        );
+        if handle.module == module.self_handle_idx() {
+            name
+        } else {
+            format!(
+                "{}::{}",
+                module
+                    .address_identifier_at(callee.address)
+                    .to_hex_literal(),
+                name
+            )
+        }
+    };
+    let datatype = |index: StructDefinitionIndex| {
+        let handle = module.datatype_handle_at(module.struct_def_at(index).struct_handle);
+        module.identifier_at(handle.name).to_string()
+    };
+    let field = |index: FieldHandleIndex| {
+        let handle = module.field_handle_at(index);
+        let name = module
+            .struct_def_at(handle.owner)
+            .fields()
+            .and_then(|fields| fields.get(handle.field as usize))
+            .map_or("?", |field| module.identifier_at(field.name).as_str());
+        format!("{}.{}", datatype(handle.owner), name)
+    };
+    match instruction {
+        Bytecode::Call(index) => format!("Call({})", function(*index)),
+        Bytecode::CallGeneric(index) => format!(
+            "CallGeneric({})",
+            function(module.function_instantiation_at(*index).handle)
+        ),
+        Bytecode::Pack(index) => format!("Pack({})", datatype(*index)),
+        Bytecode::Unpack(index) => format!("Unpack({})", datatype(*index)),
+        Bytecode::PackGeneric(index) => format!(
+            "PackGeneric({})",
+            datatype(module.struct_instantiation_at(*index).def)
+        ),
+        Bytecode::UnpackGeneric(index) => format!(
+            "UnpackGeneric({})",
+            datatype(module.struct_instantiation_at(*index).def)
+        ),
+        Bytecode::ImmBorrowField(index) => format!("ImmBorrowField({})", field(*index)),
+        Bytecode::MutBorrowField(index) => format!("MutBorrowField({})", field(*index)),
+        Bytecode::ImmBorrowFieldGeneric(index) => format!(
+            "ImmBorrowFieldGeneric({})",
+            field(module.field_instantiation_at(*index).handle)
+        ),
+        Bytecode::MutBorrowFieldGeneric(index) => format!(
+            "MutBorrowFieldGeneric({})",
+            field(module.field_instantiation_at(*index).handle)
+        ),
+        Bytecode::LdConst(index) => format!("LdConst({:?})", module.constant_at(*index).data),
+        instruction => format!("{:?}", instruction),
+    }
+}
+
+/// Semantic difference between two versions of a package, flagging changes that can turn
+/// an upgrade into a rug pull.
+pub async fn diff(
+    from: &ObjectID,
+    to: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<PackageDiff> {
+    // Downloading a package also fills its functions and structures
+    get_or_download_object(from, client, db).await?;
+    get_or_download_object(to, client, db).await?;
+    let network = &client.network;
+
+    let modules = |package_modules: Vec<PackageModule>| {
+        package_modules
+            .into_iter()
+            .map(|module| (module.module_name, module.module_bytecode))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let old_modules = modules(PackageModule::load_all_by_package(&db.pool, from, network).await?);
+    let new_modules = modules(PackageModule::load_all_by_package(&db.pool, to, network).await?);
+    let functions = |functions: Vec<Function>| {
+        functions
+            .into_iter()
+            .map(|function| {
+                (
+                    (function.module_name.clone(), function.function_name.clone()),
+                    function,
+                )
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    let old_functions =
+        functions(Function::load_all_by_package(&db.pool, &from.to_string(), network).await?);
+    let new_functions =
+        functions(Function::load_all_by_package(&db.pool, &to.to_string(), network).await?);
+    let structures = |structures: Vec<Structure>| {
+        structures
+            .into_iter()
+            .map(|structure| {
+                (
+                    (
+                        structure.module_name.clone(),
+                        structure.datatype_name.clone(),
+                    ),
+                    structure,
+                )
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    let old_structures =
+        structures(Structure::load_all_by_package(&db.pool, &from.to_string(), network).await?);
+    let new_structures =
+        structures(Structure::load_all_by_package(&db.pool, &to.to_string(), network).await?);
+
+    let mut changes = Vec::new();
+    let mut change = |kind, module_name: &str, name: Option<&str>, security_level, description| {
+        changes.push(Change {
+            kind,
+            module_name: module_name.to_string(),
+            name: name.map(str::to_string),
+            security_level,
+            description,
+        })
+    };
+
+    for module_name in new_modules
+        .keys()
+        .filter(|module_name| !old_modules.contains_key(*module_name))
+    {
+        change(
+            ChangeKind::ModuleAdded,
+            module_name,
+            None,
+            SecurityLevel::LowRisk,
+            "Module added".to_string(),
+        );
+    }
+    for module_name in old_modules
+        .keys()
+        .filter(|module_name| !new_modules.contains_key(*module_name))
+    {
+        change(
+            ChangeKind::ModuleRemoved,
+            module_name,
+            None,
+            SecurityLevel::MediumRisk,
+            "Module removed".to_string(),
+        );
+    }
+
+    for ((module_name, function_name), new) in &new_functions {
+        let Some(old) = old_functions.get(&(module_name.clone(), function_name.clone())) else {
+            if new.is_initializer {
+                change(
+                    ChangeKind::InitAdded,
+                    module_name,
+                    Some(function_name),
+                    SecurityLevel::MediumRisk,
+                    "Initializer added, check what it creates and who gets it".to_string(),
+                );
+            } else if new.visibility == Visibility::Public || new.is_entry {
+                change(
+                    ChangeKind::FunctionAdded,
+                    module_name,
+                    Some(function_name),
+                    SecurityLevel::LowRisk,
+                    format!(
+                        "{:?}{} function added",
+                        new.visibility,
+                        if new.is_entry { " entry" } else { "" }
+                    ),
+                );
+            }
+            continue;
+        };
+        if new.is_initializer
+            && init_instructions(old_modules.get(module_name))?
+                != init_instructions(new_modules.get(module_name))?
+        {
+            change(
+                ChangeKind::InitChanged,
+                module_name,
+                Some(function_name),
+                SecurityLevel::MediumRisk,
+                "Initializer changed".to_string(),
+            );
+        }
+        if old.visibility != new.visibility {
+            let widened = exposure(new.visibility) > exposure(old.visibility);
+            change(
+                ChangeKind::VisibilityChanged,
+                module_name,
+                Some(function_name),
+                if widened && new.visibility == Visibility::Public {
+                    SecurityLevel::HighRisk
+                } else if widened {
+                    SecurityLevel::MediumRisk
+                } else {
+                    SecurityLevel::LowRisk
+                },
+                format!(
+                    "Visibility changed from {:?} to {:?}",
+                    old.visibility, new.visibility
+                ),
+            );
+        }
+        if old.is_entry != new.is_entry {
+            change(
+                ChangeKind::EntryChanged,
+                module_name,
+                Some(function_name),
+                if new.is_entry {
+                    SecurityLevel::MediumRisk
+                } else {
+                    SecurityLevel::LowRisk
+                },
+                if new.is_entry {
+                    "Became callable from transactions".to_string()
+                } else {
+                    "No longer callable from transactions".to_string()
+                },
+            );
+        }
+    }
+    for (module_name, function_name) in old_functions.keys() {
+        if !new_functions.contains_key(&(module_name.clone(), function_name.clone())) {
+            change(
+                ChangeKind::FunctionRemoved,
+                module_name,
+                Some(function_name),
+                SecurityLevel::MediumRisk,
+                "Function removed".to_string(),
+            );
+        }
+    }
+
+    for ((module_name, datatype_name), new) in &new_structures {
+        let Some(old) = old_structures.get(&(module_name.clone(), datatype_name.clone())) else {
+            change(
+                ChangeKind::StructAdded,
+                module_name,
+                Some(datatype_name),
+                SecurityLevel::LowRisk,
+                "Datatype added".to_string(),
+            );
+            continue;
+        };
+        let old_abilities = abilities(old);
+        let new_abilities = abilities(new);
+        if old_abilities == new_abilities {
+            continue;
+        }
+        let security_level = new_abilities
+            .difference(&old_abilities)
+            .map(|ability| ability_level(ability))
+            .fold(SecurityLevel::MediumRisk, SecurityLevel::max);
+        change(
+            ChangeKind::AbilitiesChanged,
+            module_name,
+            Some(datatype_name),
+            security_level,
+            format!(
+                "Abilities changed from {{{}}} to {{{}}}",
+                old_abilities.into_iter().collect::<Vec<_>>().join(", "),
+                new_abilities.into_iter().collect::<Vec<_>>().join(", ")
+            ),
+        );
+    }
+    for (module_name, datatype_name) in old_structures.keys() {
+        if !new_structures.contains_key(&(module_name.clone(), datatype_name.clone())) {
+            change(
+                ChangeKind::StructRemoved,
+                module_name,
+                Some(datatype_name),
+                SecurityLevel::MediumRisk,
+                "Datatype removed".to_string(),
+            );
+        }
+    }
+
+    let security_level = changes
+        .iter()
+        .fold(SecurityLevel::LowRisk, |level, change| {
+            level.max(change.security_level)
+        });
+    Ok(PackageDiff {
+        from: from.to_string(),
+        to: to.to_string(),
+        security_level,
+        changes,
+    })
+}
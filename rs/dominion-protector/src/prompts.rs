@@ -1,31 +1,149 @@
-use anyhow::Result;
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Prompts {
-    pub developer: String,
-    pub module: ModulePrompts,
-    pub structure: StructPrompts,
+use crate::ai::sha256;
+
+/// Version of every prompt something was generated with, by prompt name.
+pub type PromptVersions = BTreeMap<String, String>;
+
+/// Prompts the describe commands can not do without.
+const REQUIRED: &[&str] = &[
+    "developer",
+    "module.input",
+    "module.description",
+    "module.security_level",
+    "module.warnings",
+    "structure.usage",
+    "structure.description",
+    "structure.ownership",
+    "structure.warnings",
+];
+
+/// Template with `{variable}` placeholders.
+#[derive(Debug, Clone, Serialize)]
+pub struct Prompt {
+    /// Set in the prompts file, or a hash of the template so any edit makes a new version
+    pub version: String,
+    pub template: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ModulePrompts {
-    pub description: String,
-    pub security_level: String,
-    pub warnings: String,
+impl Prompt {
+    /// Fills in the placeholders. Braces not around a variable name are kept as they are, so
+    /// JSON and code examples need no escaping.
+    pub fn render(&self, variables: &[(&str, &str)]) -> Result<String> {
+        let mut rendered = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let name = rest[1..]
+                .find('}')
+                .map(|end| &rest[1..end + 1])
+                .filter(|name| {
+                    !name.is_empty()
+                        && name
+                            .chars()
+                            .all(|char| char.is_ascii_alphanumeric() || char == '_')
+                });
+            let Some(name) = name else {
+                rendered.push('{');
+                rest = &rest[1..];
+                continue;
+            };
+            let (_, value) = variables
+                .iter()
+                .find(|(variable, _)| *variable == name)
+                .with_context(|| format!("Unknown variable {{{}}}", name))?;
+            rendered.push_str(value);
+            rest = &rest[name.len() + 2..];
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StructPrompts {
-    pub description: String,
-    pub ownership: String,
-    pub warnings: String,
+/// Prompts file entry: a bare template, a versioned one or a group of prompts.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Template(String),
+    #[serde(deny_unknown_fields)]
+    Versioned {
+        version: String,
+        template: String,
+    },
+    Group(BTreeMap<String, Entry>),
+}
+
+/// Prompt templates by dotted name, like `module.warnings` for `warnings` under `module`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Prompts {
+    prompts: BTreeMap<String, Prompt>,
 }
 
 impl Prompts {
-    pub async fn load() -> Result<Self> {
-        let contents = fs::read("prompts.yaml").await?;
-        Ok(serde_yml::from_slice(&contents)?)
+    pub async fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read(path)
+            .await
+            .with_context(|| format!("Can not read prompts {}", path.display()))?;
+        Self::from_yaml(&contents)
+    }
+
+    pub fn from_yaml(contents: &[u8]) -> Result<Self> {
+        let entries: BTreeMap<String, Entry> = serde_yml::from_slice(contents)?;
+        let mut prompts = BTreeMap::new();
+        flatten("", entries, &mut prompts);
+        for name in REQUIRED {
+            if !prompts.contains_key(*name) {
+                bail!("Prompt {} is missing", name);
+            }
+        }
+        Ok(Self { prompts })
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Prompt> {
+        self.prompts
+            .get(name)
+            .with_context(|| format!("Unknown prompt {}", name))
+    }
+
+    pub fn render(&self, name: &str, variables: &[(&str, &str)]) -> Result<String> {
+        self.get(name)?
+            .render(variables)
+            .with_context(|| format!("Can not render prompt {}", name))
+    }
+
+    /// Current versions of the prompts, unknown prompts are left out.
+    pub fn versions(&self, names: &[&str]) -> PromptVersions {
+        names
+            .iter()
+            .filter_map(|name| {
+                let prompt = self.prompts.get(*name)?;
+                Some((name.to_string(), prompt.version.clone()))
+            })
+            .collect()
+    }
+}
+
+fn flatten(prefix: &str, entries: BTreeMap<String, Entry>, prompts: &mut BTreeMap<String, Prompt>) {
+    for (key, entry) in entries {
+        let name = if prefix.is_empty() {
+            key
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match entry {
+            Entry::Template(template) => {
+                let version = sha256(template.as_bytes())[..12].to_string();
+                prompts.insert(name, Prompt { version, template });
+            }
+            Entry::Versioned { version, template } => {
+                prompts.insert(name, Prompt { version, template });
+            }
+            Entry::Group(entries) => flatten(&name, entries, prompts),
+        }
     }
 }
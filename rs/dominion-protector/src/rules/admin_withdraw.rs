@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+
+use move_binary_format::{file_format::Bytecode, CompiledModule};
+use sui_types::SUI_FRAMEWORK_ADDRESS;
+
+use crate::{
+    db::{descriptions::SecurityLevel, finding::Finding},
+    verifier::{datatype_name, is_capability, shared_types, DatatypeName},
+};
+
+use super::{callee, function_name, producer, stack_effect, Package, Rule};
+
+/// Callable functions letting capability holders take funds out of a shared object of the
+/// package, where users' deposits usually end up.
+pub struct AdminWithdraw;
+
+impl Rule for AdminWithdraw {
+    fn name(&self) -> &'static str {
+        "admin_withdraw"
+    }
+
+    fn severity(&self) -> SecurityLevel {
+        SecurityLevel::HighRisk
+    }
+
+    fn check(&self, package: &Package) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let shared = shared_types(&package.modules);
+        for (module, def) in package.callable_functions() {
+            let Some(code) = &def.code else {
+                continue;
+            };
+            let params = &module
+                .signature_at(module.function_handle_at(def.function).parameters)
+                .0;
+            if !params
+                .iter()
+                .any(|param| is_capability(module, param, &shared))
+            {
+                continue;
+            }
+            let withdrawal = code.code.iter().enumerate().find_map(|(at, instruction)| {
+                let withdraws = callee(module, instruction).is_some_and(|callee| {
+                    callee.is(SUI_FRAMEWORK_ADDRESS, "balance", &["split", "withdraw_all"])
+                        || callee.is(SUI_FRAMEWORK_ADDRESS, "coin", &["take", "split"])
+                });
+                if !withdraws {
+                    return None;
+                }
+                // The balance or coin taken from is the first argument
+                let (arity, _) = stack_effect(module, instruction)?;
+                let from = producer(module, &code.code, at, arity.checked_sub(1)?)?;
+                Some((at, shared_owner(module, &code.code, from, &shared)?))
+            });
+            if let Some((at, object)) = withdrawal {
+                findings.push(package.finding(
+                    self,
+                    module,
+                    &function_name(module, def),
+                    Some(at),
+                    format!(
+                        "Capability holders can withdraw funds from the shared {}",
+                        object
+                    ),
+                ));
+            }
+        }
+        findings
+    }
+}
+
+/// Name of the shared object the reference pushed at `at` borrows a field of, following
+/// fields of fields and references kept in locals.
+fn shared_owner(
+    module: &CompiledModule,
+    code: &[Bytecode],
+    at: usize,
+    shared: &BTreeSet<DatatypeName>,
+) -> Option<String> {
+    let field = match &code[at] {
+        Bytecode::MutBorrowField(index) => *index,
+        Bytecode::MutBorrowFieldGeneric(index) => module.field_instantiation_at(*index).handle,
+        Bytecode::CopyLoc(local) | Bytecode::MoveLoc(local) => {
+            let stored = code[..at]
+                .iter()
+                .rposition(|instruction| *instruction == Bytecode::StLoc(*local))?;
+            return shared_owner(module, code, producer(module, code, stored, 0)?, shared);
+        }
+        _ => return None,
+    };
+    let owner = module.struct_def_at(module.field_handle_at(field).owner);
+    let owner = datatype_name(module, owner.struct_handle);
+    if shared.contains(&owner) {
+        return Some(owner.2);
+    }
+    shared_owner(module, code, producer(module, code, at, 0)?, shared)
+}
@@ -0,0 +1,62 @@
+use move_binary_format::file_format::Bytecode;
+use sui_types::SUI_FRAMEWORK_ADDRESS;
+
+use crate::db::{descriptions::SecurityLevel, finding::Finding};
+
+use super::{callee, function_name, is_datatype, Package, Rule};
+
+/// Functions aborting for objects found in a set of ids, which lets whoever fills the
+/// set lock holders out, like `operator_balcklist` in the scamtest package.
+pub struct Blacklist;
+
+impl Rule for Blacklist {
+    fn name(&self) -> &'static str {
+        "blacklist"
+    }
+
+    fn severity(&self) -> SecurityLevel {
+        SecurityLevel::MediumRisk
+    }
+
+    fn check(&self, package: &Package) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for module in &package.modules {
+            for def in module.function_defs() {
+                let Some(code) = &def.code else {
+                    continue;
+                };
+                if !code
+                    .code
+                    .iter()
+                    .any(|instruction| matches!(instruction, Bytecode::Abort))
+                {
+                    continue;
+                }
+                let lookup = code.code.iter().enumerate().find_map(|(at, instruction)| {
+                    let callee = callee(module, instruction)?;
+                    let keyed_by_id = callee
+                        .type_args
+                        .first()
+                        .is_some_and(|key| is_datatype(key, SUI_FRAMEWORK_ADDRESS, "object", "ID"));
+                    (keyed_by_id
+                        && (callee.is(SUI_FRAMEWORK_ADDRESS, "vec_set", &["contains"])
+                            || callee.is(SUI_FRAMEWORK_ADDRESS, "table", &["contains"])))
+                    .then_some((at, callee.module))
+                });
+                if let Some((at, collection)) = lookup {
+                    findings.push(package.finding(
+                        self,
+                        module,
+                        &function_name(module, def),
+                        Some(at),
+                        format!(
+                            "Aborts depending on a {} of object ids, which can be used as a blacklist",
+                            collection
+                        ),
+                    ));
+                }
+            }
+        }
+        findings
+    }
+}
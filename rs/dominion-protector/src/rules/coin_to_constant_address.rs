@@ -0,0 +1,82 @@
+use move_binary_format::file_format::SignatureToken;
+use sui_types::SUI_FRAMEWORK_ADDRESS;
+
+use crate::{
+    call_graph::Pattern,
+    capabilities::{recipient, Recipient},
+    db::{descriptions::SecurityLevel, finding::Finding},
+};
+
+use super::{callee, function_name, is_datatype, Package, Rule};
+
+/// Callable functions taking a `Coin<T>` by value and sending a coin to an address
+/// hardcoded in the package, usually the scammer's.
+pub struct CoinToConstantAddress;
+
+impl Rule for CoinToConstantAddress {
+    fn name(&self) -> &'static str {
+        "coin_to_constant_address"
+    }
+
+    fn severity(&self) -> SecurityLevel {
+        SecurityLevel::CriticalRisk
+    }
+
+    fn check(&self, package: &Package) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for (module, def) in package.callable_functions() {
+            let Some(code) = &def.code else {
+                continue;
+            };
+            let takes_coin = module
+                .signature_at(module.function_handle_at(def.function).parameters)
+                .0
+                .iter()
+                .filter(|token| {
+                    !matches!(
+                        token,
+                        SignatureToken::Reference(_) | SignatureToken::MutableReference(_)
+                    )
+                })
+                .any(|token| {
+                    is_datatype(
+                        &Pattern::from_token(module, token, &[]),
+                        SUI_FRAMEWORK_ADDRESS,
+                        "coin",
+                        "Coin",
+                    )
+                });
+            if !takes_coin {
+                continue;
+            }
+            for (at, instruction) in code.code.iter().enumerate() {
+                let Some(callee) = callee(module, instruction) else {
+                    continue;
+                };
+                if !callee.is(
+                    SUI_FRAMEWORK_ADDRESS,
+                    "transfer",
+                    &["transfer", "public_transfer"],
+                ) || !callee.type_args.first().is_some_and(|transferred| {
+                    is_datatype(transferred, SUI_FRAMEWORK_ADDRESS, "coin", "Coin")
+                }) {
+                    continue;
+                }
+                if let Recipient::Address(address) = recipient(module, code, at) {
+                    findings.push(package.finding(
+                        self,
+                        module,
+                        &function_name(module, def),
+                        Some(at),
+                        format!(
+                            "Sends coins it is given to the hardcoded address {}",
+                            address
+                        ),
+                    ));
+                    break;
+                }
+            }
+        }
+        findings
+    }
+}
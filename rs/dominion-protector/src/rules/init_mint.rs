@@ -0,0 +1,82 @@
+use sui_types::SUI_FRAMEWORK_ADDRESS;
+
+use crate::{
+    capabilities::{recipient, Recipient},
+    db::{descriptions::SecurityLevel, finding::Finding},
+};
+
+use super::{callee, function_name, is_datatype, Package, Rule};
+
+/// `init` minting coins to the publisher, the whole supply when it also freezes the
+/// `TreasuryCap`.
+pub struct InitMint;
+
+impl Rule for InitMint {
+    fn name(&self) -> &'static str {
+        "init_mint"
+    }
+
+    fn severity(&self) -> SecurityLevel {
+        SecurityLevel::HighRisk
+    }
+
+    fn check(&self, package: &Package) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for (module, def) in package.initializers() {
+            let Some(code) = &def.code else {
+                continue;
+            };
+            let mut mints = false;
+            let mut minted_to_sender = None;
+            let mut cap_given_up = false;
+            for (at, instruction) in code.code.iter().enumerate() {
+                let Some(callee) = callee(module, instruction) else {
+                    continue;
+                };
+                if callee.is(SUI_FRAMEWORK_ADDRESS, "coin", &["mint"])
+                    || callee.is(SUI_FRAMEWORK_ADDRESS, "balance", &["increase_supply"])
+                {
+                    mints = true;
+                } else if callee.is(SUI_FRAMEWORK_ADDRESS, "coin", &["mint_and_transfer"]) {
+                    if matches!(recipient(module, code, at), Recipient::Sender) {
+                        minted_to_sender.get_or_insert(at);
+                    }
+                } else if callee.is(
+                    SUI_FRAMEWORK_ADDRESS,
+                    "transfer",
+                    &["transfer", "public_transfer"],
+                ) {
+                    let coin = callee.type_args.first().is_some_and(|transferred| {
+                        is_datatype(transferred, SUI_FRAMEWORK_ADDRESS, "coin", "Coin")
+                    });
+                    if mints && coin && matches!(recipient(module, code, at), Recipient::Sender) {
+                        minted_to_sender.get_or_insert(at);
+                    }
+                } else if callee.is(
+                    SUI_FRAMEWORK_ADDRESS,
+                    "transfer",
+                    &["freeze_object", "public_freeze_object"],
+                ) && callee.type_args.first().is_some_and(|frozen| {
+                    is_datatype(frozen, SUI_FRAMEWORK_ADDRESS, "coin", "TreasuryCap")
+                }) {
+                    // Nobody can mint with a frozen `TreasuryCap`
+                    cap_given_up = true;
+                }
+            }
+            if let Some(at) = minted_to_sender {
+                findings.push(package.finding(
+                    self,
+                    module,
+                    &function_name(module, def),
+                    Some(at),
+                    if cap_given_up {
+                        "Mints the whole supply to the publisher".to_string()
+                    } else {
+                        "Mints coins to the publisher on deployment".to_string()
+                    },
+                ));
+            }
+        }
+        findings
+    }
+}
@@ -0,0 +1,341 @@
+use anyhow::Result;
+use chrono::Utc;
+use move_binary_format::{
+    file_format::{Bytecode, FunctionDefinition, FunctionHandleIndex, StructDefinitionIndex},
+    CompiledModule,
+};
+use move_core_types::account_address::AccountAddress;
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{
+    call_graph::{function_definition, load_modules, Modules, Pattern},
+    db::{
+        descriptions::SecurityLevel,
+        finding::Finding,
+        function::{Function, Visibility},
+        package_module::PackageModule,
+        structure::Structure,
+        Db,
+    },
+    sui_client::SuiClientWithNetwork,
+};
+
+pub mod admin_withdraw;
+pub mod blacklist;
+pub mod coin_to_constant_address;
+pub mod init_mint;
+pub mod shared_treasury_cap;
+
+/// Deterministic check for a known scam pattern.
+pub trait Rule: Send + Sync {
+    /// Stable identifier, stored with every finding
+    fn name(&self) -> &'static str;
+    fn severity(&self) -> SecurityLevel;
+    fn check(&self, package: &Package) -> Vec<Finding>;
+}
+
+pub fn all_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(coin_to_constant_address::CoinToConstantAddress),
+        Box::new(admin_withdraw::AdminWithdraw),
+        Box::new(init_mint::InitMint),
+        Box::new(shared_treasury_cap::SharedTreasuryCap),
+        Box::new(blacklist::Blacklist),
+    ]
+}
+
+/// Everything rules look at: the bytecode of the package and what was stored about it.
+pub struct Package {
+    pub package_id: ObjectID,
+    pub network: String,
+    pub modules: Vec<CompiledModule>,
+    /// Modules reachable from the package outside the framework, for following calls
+    pub linked: Modules,
+    pub functions: Vec<Function>,
+    pub structures: Vec<Structure>,
+}
+
+impl Package {
+    pub async fn load(
+        package_id: &ObjectID,
+        client: &SuiClientWithNetwork,
+        db: &Db,
+    ) -> Result<Self> {
+        // Downloading a package also fills its functions and structures
+        let (_, linked) = load_modules(package_id, client, db).await?;
+        let mut package = Self::load_stored(package_id, &client.network, db).await?;
+        package.linked = linked;
+        Ok(package)
+    }
+
+    /// Package as stored, without following calls out of it.
+    pub async fn load_stored(package_id: &ObjectID, network: &str, db: &Db) -> Result<Self> {
+        let modules = PackageModule::load_all_by_package(&db.pool, package_id, network)
+            .await?
+            .iter()
+            .map(|module| CompiledModule::deserialize_with_defaults(&module.module_bytecode))
+            .collect::<Result<Vec<_>, _>>()?;
+        let linked = modules
+            .iter()
+            .map(|module| {
+                let id = module.self_id();
+                ((*id.address(), id.name().to_string()), module.clone())
+            })
+            .collect();
+        let functions =
+            Function::load_all_by_package(&db.pool, &package_id.to_string(), network).await?;
+        let structures =
+            Structure::load_all_by_package(&db.pool, &package_id.to_string(), network).await?;
+        Ok(Self {
+            package_id: *package_id,
+            network: network.to_string(),
+            modules,
+            linked,
+            functions,
+            structures,
+        })
+    }
+
+    /// Runs every rule and replaces the findings stored for the package.
+    pub async fn scan(&self, db: &Db) -> Result<Vec<Finding>> {
+        let findings = all_rules()
+            .iter()
+            .flat_map(|rule| rule.check(self))
+            .collect::<Vec<_>>();
+        let mut tx = db.pool.begin().await?;
+        Finding::delete_all_by_package(&mut *tx, &self.package_id, &self.network).await?;
+        for finding in &findings {
+            finding.save(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(findings)
+    }
+
+    pub fn module(&self, name: &str) -> Option<&CompiledModule> {
+        self.modules
+            .iter()
+            .find(|module| module.self_id().name().as_str() == name)
+    }
+
+    /// Stored functions anyone can call from a transaction, with their definitions.
+    pub(crate) fn callable_functions(
+        &self,
+    ) -> impl Iterator<Item = (&CompiledModule, &FunctionDefinition)> {
+        self.functions
+            .iter()
+            .filter(|function| function.is_entry || function.visibility == Visibility::Public)
+            .filter_map(|function| {
+                let module = self.module(&function.module_name)?;
+                Some((
+                    module,
+                    function_definition(module, &function.function_name)?,
+                ))
+            })
+    }
+
+    /// The `init` functions of the package, with their definitions.
+    pub(crate) fn initializers(
+        &self,
+    ) -> impl Iterator<Item = (&CompiledModule, &FunctionDefinition)> {
+        self.functions
+            .iter()
+            .filter(|function| function.is_initializer)
+            .filter_map(|function| {
+                let module = self.module(&function.module_name)?;
+                Some((
+                    module,
+                    function_definition(module, &function.function_name)?,
+                ))
+            })
+    }
+
+    pub(crate) fn finding(
+        &self,
+        rule: &dyn Rule,
+        module: &CompiledModule,
+        member_name: &str,
+        code_offset: Option<usize>,
+        message: String,
+    ) -> Finding {
+        Finding {
+            package_id: self.package_id.to_string(),
+            network: self.network.clone(),
+            rule: rule.name().to_string(),
+            module_name: module.self_id().name().to_string(),
+            member_name: member_name.to_string(),
+            code_offset: code_offset.map(|offset| offset as i32),
+            severity: rule.severity(),
+            message,
+            found_at: Utc::now(),
+        }
+    }
+}
+
+/// Function called by an instruction, with the types it is instantiated with.
+pub(crate) struct Callee {
+    pub address: AccountAddress,
+    pub module: String,
+    pub name: String,
+    pub type_args: Vec<Pattern>,
+}
+
+impl Callee {
+    pub(crate) fn is(&self, address: AccountAddress, module: &str, names: &[&str]) -> bool {
+        self.address == address && self.module == module && names.contains(&self.name.as_str())
+    }
+}
+
+pub(crate) fn callee(module: &CompiledModule, instruction: &Bytecode) -> Option<Callee> {
+    let (handle, type_args) = match instruction {
+        Bytecode::Call(index) => (module.function_handle_at(*index), Vec::new()),
+        Bytecode::CallGeneric(index) => {
+            let instantiation = module.function_instantiation_at(*index);
+            (
+                module.function_handle_at(instantiation.handle),
+                module
+                    .signature_at(instantiation.type_parameters)
+                    .0
+                    .iter()
+                    .map(|token| Pattern::from_token(module, token, &[]))
+                    .collect(),
+            )
+        }
+        _ => return None,
+    };
+    let module_handle = module.module_handle_at(handle.module);
+    Some(Callee {
+        address: *module.address_identifier_at(module_handle.address),
+        module: module.identifier_at(module_handle.name).to_string(),
+        name: module.identifier_at(handle.name).to_string(),
+        type_args,
+    })
+}
+
+/// Values an instruction pops and pushes. Unknown for instructions ending a block and for
+/// variants, which the rules do not follow.
+pub(crate) fn stack_effect(
+    module: &CompiledModule,
+    instruction: &Bytecode,
+) -> Option<(usize, usize)> {
+    let call = |handle: FunctionHandleIndex| {
+        let handle = module.function_handle_at(handle);
+        (
+            module.signature_at(handle.parameters).0.len(),
+            module.signature_at(handle.return_).0.len(),
+        )
+    };
+    let fields = |def: StructDefinitionIndex| module.struct_def_at(def).fields().map(Vec::len);
+    Some(match instruction {
+        Bytecode::Nop => (0, 0),
+        Bytecode::Pop | Bytecode::StLoc(_) => (1, 0),
+        Bytecode::LdU8(_)
+        | Bytecode::LdU16(_)
+        | Bytecode::LdU32(_)
+        | Bytecode::LdU64(_)
+        | Bytecode::LdU128(_)
+        | Bytecode::LdU256(_)
+        | Bytecode::LdConst(_)
+        | Bytecode::LdTrue
+        | Bytecode::LdFalse
+        | Bytecode::CopyLoc(_)
+        | Bytecode::MoveLoc(_)
+        | Bytecode::MutBorrowLoc(_)
+        | Bytecode::ImmBorrowLoc(_) => (0, 1),
+        Bytecode::CastU8
+        | Bytecode::CastU16
+        | Bytecode::CastU32
+        | Bytecode::CastU64
+        | Bytecode::CastU128
+        | Bytecode::CastU256
+        | Bytecode::Not
+        | Bytecode::ReadRef
+        | Bytecode::FreezeRef
+        | Bytecode::MutBorrowField(_)
+        | Bytecode::ImmBorrowField(_)
+        | Bytecode::MutBorrowFieldGeneric(_)
+        | Bytecode::ImmBorrowFieldGeneric(_)
+        | Bytecode::VecLen(_)
+        | Bytecode::VecPopBack(_) => (1, 1),
+        Bytecode::Add
+        | Bytecode::Sub
+        | Bytecode::Mul
+        | Bytecode::Div
+        | Bytecode::Mod
+        | Bytecode::BitOr
+        | Bytecode::BitAnd
+        | Bytecode::Xor
+        | Bytecode::Shl
+        | Bytecode::Shr
+        | Bytecode::Or
+        | Bytecode::And
+        | Bytecode::Eq
+        | Bytecode::Neq
+        | Bytecode::Lt
+        | Bytecode::Gt
+        | Bytecode::Le
+        | Bytecode::Ge
+        | Bytecode::VecImmBorrow(_)
+        | Bytecode::VecMutBorrow(_) => (2, 1),
+        Bytecode::WriteRef | Bytecode::VecPushBack(_) => (2, 0),
+        Bytecode::VecSwap(_) => (3, 0),
+        Bytecode::VecPack(_, count) => (*count as usize, 1),
+        Bytecode::VecUnpack(_, count) => (1, *count as usize),
+        Bytecode::Call(index) => call(*index),
+        Bytecode::CallGeneric(index) => call(module.function_instantiation_at(*index).handle),
+        Bytecode::Pack(index) => (fields(*index)?, 1),
+        Bytecode::PackGeneric(index) => (fields(module.struct_instantiation_at(*index).def)?, 1),
+        Bytecode::Unpack(index) => (1, fields(*index)?),
+        Bytecode::UnpackGeneric(index) => (1, fields(module.struct_instantiation_at(*index).def)?),
+        _ => return None,
+    })
+}
+
+/// Offset of the instruction pushing the value `depth` places below the top of the stack
+/// before `at`, `0` being the last argument of a call there. Only straight-line code before
+/// `at` is followed.
+pub(crate) fn producer(
+    module: &CompiledModule,
+    code: &[Bytecode],
+    at: usize,
+    depth: usize,
+) -> Option<usize> {
+    // Values on the stack down to the wanted one
+    let mut above = depth + 1;
+    for i in (0..at).rev() {
+        let (pops, pushes) = stack_effect(module, &code[i])?;
+        if pushes >= above {
+            return Some(i);
+        }
+        above = above - pushes + pops;
+    }
+    None
+}
+
+pub(crate) fn function_name(module: &CompiledModule, def: &FunctionDefinition) -> String {
+    module
+        .identifier_at(module.function_handle_at(def.function).name)
+        .to_string()
+}
+
+/// Whether the pattern is `address::module::name<..>`.
+pub(crate) fn is_datatype(
+    pattern: &Pattern,
+    address: AccountAddress,
+    module: &str,
+    name: &str,
+) -> bool {
+    matches!(
+        pattern,
+        Pattern::Datatype { address: a, module: m, name: n, .. }
+            if *a == address && m == module && n == name
+    )
+}
+
+/// Runs every rule over the package and replaces the findings stored for it.
+pub async fn run_rules(
+    package_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<Vec<Finding>> {
+    Package::load(package_id, client, db).await?.scan(db).await
+}
@@ -0,0 +1,50 @@
+use sui_types::SUI_FRAMEWORK_ADDRESS;
+
+use crate::db::{descriptions::SecurityLevel, finding::Finding};
+
+use super::{callee, function_name, is_datatype, Package, Rule};
+
+/// Sharing a `TreasuryCap` lets anyone mint the coin, diluting holders at will.
+pub struct SharedTreasuryCap;
+
+impl Rule for SharedTreasuryCap {
+    fn name(&self) -> &'static str {
+        "shared_treasury_cap"
+    }
+
+    fn severity(&self) -> SecurityLevel {
+        SecurityLevel::CriticalRisk
+    }
+
+    fn check(&self, package: &Package) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for module in &package.modules {
+            for def in module.function_defs() {
+                let Some(code) = &def.code else {
+                    continue;
+                };
+                let shared = code.code.iter().position(|instruction| {
+                    callee(module, instruction).is_some_and(|callee| {
+                        callee.is(
+                            SUI_FRAMEWORK_ADDRESS,
+                            "transfer",
+                            &["share_object", "public_share_object"],
+                        ) && callee.type_args.first().is_some_and(|shared| {
+                            is_datatype(shared, SUI_FRAMEWORK_ADDRESS, "coin", "TreasuryCap")
+                        })
+                    })
+                });
+                if let Some(at) = shared {
+                    findings.push(package.finding(
+                        self,
+                        module,
+                        &function_name(module, def),
+                        Some(at),
+                        "Shares the TreasuryCap, anyone can mint the coin".to_string(),
+                    ));
+                }
+            }
+        }
+        findings
+    }
+}
@@ -0,0 +1,416 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use move_core_types::language_storage::TypeTag;
+use serde::Serialize;
+use sui_protocol_config::{Chain, ProtocolConfig, ProtocolVersion};
+use sui_sdk::{
+    rpc_types::SuiObjectData,
+    types::base_types::{ObjectID, SequenceNumber},
+};
+use sui_types::{
+    base_types::ObjectRef,
+    committee::EpochId,
+    digests::TransactionDigest,
+    effects::{TransactionEffects, TransactionEffectsAPI},
+    error::SuiResult,
+    gas::SuiGasStatus,
+    inner_temporary_store::InnerTemporaryStore,
+    metrics::LimitsMetrics,
+    object::{Object, Owner},
+    storage::{BackingPackageStore, ChildObjectResolver, ObjectStore, PackageObject, ParentSync},
+    transaction::{
+        CheckedInputObjects, InputObjectKind, InputObjects, ObjectReadResult, ObjectReadResultKind,
+        TransactionData, TransactionDataAPI,
+    },
+    MOVE_STDLIB_PACKAGE_ID, SUI_FRAMEWORK_PACKAGE_ID, SUI_SYSTEM_PACKAGE_ID,
+};
+
+use crate::{
+    commands::download::{download_object, get_or_download_object},
+    db::Db,
+    sui_client::SuiClientWithNetwork,
+};
+
+/// Every round loads the objects the previous one missed, like dynamic fields nobody knew about.
+const MAX_ROUNDS: usize = 10;
+
+#[derive(Debug, Serialize)]
+pub struct Simulation {
+    /// `None` when the transaction executes successfully
+    pub error: Option<String>,
+    pub balance_changes: Vec<BalanceChange>,
+    pub object_changes: Vec<ObjectChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalanceChange {
+    pub owner: Owner,
+    pub coin_type: String,
+    /// Signed decimal, too large for JSON numbers
+    pub amount: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectChangeKind {
+    Created,
+    Transferred,
+    Wrapped,
+    Deleted,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectChange {
+    pub object_id: ObjectID,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub kind: ObjectChangeKind,
+    pub before: Option<Owner>,
+    pub after: Option<Owner>,
+}
+
+/// Local fork of the chain state the transaction touches. Reads of objects it does not have
+/// are remembered so the next round can load them.
+struct ForkStore<'a> {
+    objects: &'a BTreeMap<ObjectID, Object>,
+    missing: RefCell<BTreeSet<ObjectID>>,
+}
+
+impl ForkStore<'_> {
+    fn read(&self, object_id: &ObjectID) -> Option<Object> {
+        let object = self.objects.get(object_id).cloned();
+        if object.is_none() {
+            self.missing.borrow_mut().insert(*object_id);
+        }
+        object
+    }
+}
+
+impl BackingPackageStore for ForkStore<'_> {
+    fn get_package_object(&self, package_id: &ObjectID) -> SuiResult<Option<PackageObject>> {
+        Ok(self
+            .read(package_id)
+            .filter(|object| object.is_package())
+            .map(PackageObject::new))
+    }
+}
+
+impl ChildObjectResolver for ForkStore<'_> {
+    fn read_child_object(
+        &self,
+        _parent: &ObjectID,
+        child: &ObjectID,
+        _child_version_upper_bound: SequenceNumber,
+    ) -> SuiResult<Option<Object>> {
+        Ok(self.read(child))
+    }
+
+    fn get_object_received_at_version(
+        &self,
+        _owner: &ObjectID,
+        receiving_object_id: &ObjectID,
+        receive_object_at_version: SequenceNumber,
+        _epoch_id: EpochId,
+    ) -> SuiResult<Option<Object>> {
+        Ok(self
+            .read(receiving_object_id)
+            .filter(|object| object.version() == receive_object_at_version))
+    }
+}
+
+impl ObjectStore for ForkStore<'_> {
+    fn get_object(&self, object_id: &ObjectID) -> Option<Object> {
+        self.read(object_id)
+    }
+
+    fn get_object_by_key(&self, object_id: &ObjectID, version: SequenceNumber) -> Option<Object> {
+        self.read(object_id)
+            .filter(|object| object.version() == version)
+    }
+}
+
+impl ParentSync for ForkStore<'_> {
+    fn get_latest_parent_entry_ref_deprecated(&self, object_id: ObjectID) -> Option<ObjectRef> {
+        self.read(&object_id)
+            .map(|object| object.compute_object_reference())
+    }
+}
+
+/// Cached objects miss the previous transaction and the storage rebate, execution only
+/// needs them for the gas summary.
+fn into_object(mut data: SuiObjectData) -> Result<Object> {
+    data.previous_transaction
+        .get_or_insert(TransactionDigest::genesis_marker());
+    data.storage_rebate.get_or_insert(0);
+    data.try_into()
+}
+
+pub struct TransactionSimulator<'a> {
+    client: &'a SuiClientWithNetwork,
+    db: &'a Db,
+    objects: BTreeMap<ObjectID, Object>,
+    /// Read by the VM but neither cached nor on chain
+    unavailable: BTreeSet<ObjectID>,
+}
+
+impl<'a> TransactionSimulator<'a> {
+    pub fn new(client: &'a SuiClientWithNetwork, db: &'a Db) -> Self {
+        Self {
+            client,
+            db,
+            objects: BTreeMap::new(),
+            unavailable: BTreeSet::new(),
+        }
+    }
+
+    async fn load(&mut self, object_id: &ObjectID) -> Result<()> {
+        let object = get_or_download_object(object_id, self.client, self.db).await?;
+        self.objects.insert(*object_id, into_object(object)?);
+        Ok(())
+    }
+
+    /// Inputs change all the time, so they are refreshed when the fullnode is reachable.
+    async fn load_input(&mut self, object_id: &ObjectID) -> Result<()> {
+        match download_object(object_id, self.client, self.db).await {
+            Ok(object) => {
+                self.objects.insert(*object_id, into_object(object)?);
+                Ok(())
+            }
+            Err(err) => {
+                println!("Using cached input {}: {:?}", object_id, err);
+                self.load(object_id).await
+            }
+        }
+    }
+
+    /// Rules of the protocol version the chain is at, which decide how transactions execute.
+    /// Without the fullnode, the newest version this build knows is the closest guess.
+    async fn protocol_config(&self) -> Result<ProtocolConfig> {
+        let version = match self
+            .client
+            .client
+            .read_api()
+            .get_protocol_config(None)
+            .await
+        {
+            Ok(config) => config.protocol_version,
+            Err(err) => {
+                println!(
+                    "Using the newest protocol version, can not read the one of {}: {:?}",
+                    self.client.network, err
+                );
+                ProtocolVersion::MAX
+            }
+        };
+        let chain = match self.client.network.as_str() {
+            "mainnet" => Chain::Mainnet,
+            "testnet" => Chain::Testnet,
+            _ => Chain::Unknown,
+        };
+        ProtocolConfig::get_for_version_if_supported(version, chain)
+            .with_context(|| format!("Protocol version {} is not supported", version.as_u64()))
+    }
+
+    /// Executes the transaction with the Move VM against the local fork, nothing is sent
+    /// to the fullnode.
+    pub async fn simulate(&mut self, transaction: &TransactionData) -> Result<Simulation> {
+        let protocol_config = Arc::new(self.protocol_config().await?);
+        let input_kinds = transaction.input_objects()?;
+        for kind in &input_kinds {
+            match kind {
+                InputObjectKind::MovePackage(package_id) => self.load(package_id).await?,
+                _ => self.load_input(&kind.object_id()).await?,
+            }
+        }
+        for package_id in [
+            MOVE_STDLIB_PACKAGE_ID,
+            SUI_FRAMEWORK_PACKAGE_ID,
+            SUI_SYSTEM_PACKAGE_ID,
+        ] {
+            if !self.objects.contains_key(&package_id) {
+                self.load(&package_id).await?;
+            }
+        }
+
+        for _ in 0..MAX_ROUNDS {
+            // The VM runs synchronously, it gets the objects for the time it runs
+            let objects = std::mem::take(&mut self.objects);
+            let protocol_config = protocol_config.clone();
+            let (transaction, input_kinds) = (transaction.clone(), input_kinds.clone());
+            let (objects, executed) = tokio::task::spawn_blocking(move || {
+                let executed = execute(&objects, &protocol_config, &transaction, &input_kinds);
+                (objects, executed)
+            })
+            .await?;
+            self.objects = objects;
+            let (outcome, missing) = executed?;
+            let missing = missing
+                .into_iter()
+                .filter(|object_id| {
+                    !self.objects.contains_key(object_id) && !self.unavailable.contains(object_id)
+                })
+                .collect::<Vec<_>>();
+            if missing.is_empty() {
+                let (store, effects) = outcome;
+                return Ok(self.report(store, effects));
+            }
+            for object_id in missing {
+                // Reads of objects that do not exist are expected, like dynamic field lookups
+                if let Err(err) = self.load(&object_id).await {
+                    println!("Object {} is not available: {:?}", object_id, err);
+                    self.unavailable.insert(object_id);
+                }
+            }
+        }
+        bail!("Transaction needs too many objects to simulate")
+    }
+
+    fn report(&self, store: InnerTemporaryStore, effects: TransactionEffects) -> Simulation {
+        let error = if effects.status().is_ok() {
+            None
+        } else {
+            Some(format!("{:?}", effects.status()))
+        };
+
+        let mut object_changes = Vec::new();
+        let mut balances = BTreeMap::<(Owner, TypeTag), i128>::new();
+        let mut touched = BTreeSet::new();
+        for (object_id, after) in &store.written {
+            touched.insert(*object_id);
+            let before = self.objects.get(object_id);
+            let kind = match before {
+                None => ObjectChangeKind::Created,
+                Some(before) if before.owner() != after.owner() => ObjectChangeKind::Transferred,
+                Some(_) => continue,
+            };
+            object_changes.push(ObjectChange {
+                object_id: *object_id,
+                type_: after.type_().map(|type_| type_.to_canonical_string(true)),
+                kind,
+                before: before.map(|before| before.owner().clone()),
+                after: Some(after.owner().clone()),
+            });
+        }
+        for (removed, kind) in effects
+            .deleted()
+            .into_iter()
+            .map(|object| (object, ObjectChangeKind::Deleted))
+            .chain(
+                effects
+                    .wrapped()
+                    .into_iter()
+                    .map(|object| (object, ObjectChangeKind::Wrapped)),
+            )
+        {
+            let object_id = removed.0;
+            touched.insert(object_id);
+            let before = self.objects.get(&object_id);
+            object_changes.push(ObjectChange {
+                object_id,
+                type_: before
+                    .and_then(|before| before.type_())
+                    .map(|type_| type_.to_canonical_string(true)),
+                kind,
+                before: before.map(|before| before.owner().clone()),
+                after: None,
+            });
+        }
+
+        for object_id in &touched {
+            if let Some(before) = self.objects.get(object_id) {
+                if let (Some(coin), Some(coin_type)) =
+                    (before.as_coin_maybe(), before.coin_type_maybe())
+                {
+                    *balances
+                        .entry((before.owner().clone(), coin_type))
+                        .or_default() -= i128::from(coin.value());
+                }
+            }
+            if let Some(after) = store.written.get(object_id) {
+                if let (Some(coin), Some(coin_type)) =
+                    (after.as_coin_maybe(), after.coin_type_maybe())
+                {
+                    *balances
+                        .entry((after.owner().clone(), coin_type))
+                        .or_default() += i128::from(coin.value());
+                }
+            }
+        }
+        let balance_changes = balances
+            .into_iter()
+            .filter(|(_, amount)| *amount != 0)
+            .map(|((owner, coin_type), amount)| BalanceChange {
+                owner,
+                coin_type: coin_type.to_canonical_string(true),
+                amount: amount.to_string(),
+            })
+            .collect();
+
+        Simulation {
+            error,
+            balance_changes,
+            object_changes,
+        }
+    }
+}
+
+/// Runs the transaction once, returning the objects it read that the fork lacks.
+fn execute(
+    objects: &BTreeMap<ObjectID, Object>,
+    protocol_config: &ProtocolConfig,
+    transaction: &TransactionData,
+    input_kinds: &[InputObjectKind],
+) -> Result<(
+    (InnerTemporaryStore, TransactionEffects),
+    BTreeSet<ObjectID>,
+)> {
+    let executor = sui_execution::executor(protocol_config, true, None)?;
+    let metrics = Arc::new(LimitsMetrics::new(&prometheus::Registry::new()));
+
+    let inputs = input_kinds
+        .iter()
+        .filter_map(|kind| {
+            let object = objects.get(&kind.object_id())?.clone();
+            Some(ObjectReadResult::new(
+                *kind,
+                ObjectReadResultKind::Object(object),
+            ))
+        })
+        .collect();
+    let gas_status = SuiGasStatus::new(
+        transaction.gas_budget(),
+        transaction.gas_price(),
+        transaction.gas_price(),
+        protocol_config,
+    )?;
+    let store = ForkStore {
+        objects,
+        missing: RefCell::new(BTreeSet::new()),
+    };
+    let (inner, _, effects, _) = executor.execute_transaction_to_effects(
+        &store,
+        protocol_config,
+        metrics,
+        false,
+        &HashSet::new(),
+        &0,
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)?
+            .as_millis()
+            .try_into()?,
+        CheckedInputObjects::new_for_replay(InputObjects::new(inputs)),
+        transaction.gas_data().clone(),
+        gas_status,
+        transaction.kind().clone(),
+        transaction.sender(),
+        transaction.digest(),
+        &mut None,
+    );
+    Ok(((inner, effects), store.missing.into_inner()))
+}
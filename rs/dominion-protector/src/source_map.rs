@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use move_binary_format::{file_format::FunctionDefinitionIndex, CompiledModule};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::FileHash;
+use move_ir_types::location::Loc;
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, Acquire, Postgres};
+use sui_types::base_types::ObjectID;
+
+use crate::db::{module_source_map::ModuleSourceMap, sources::ModuleSource};
+
+/// Where the functions and instructions of a module are in its source, disassembly and Revela
+/// output, so one can be followed to the others.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceLinks {
+    /// Kind of the module source the spans point into
+    pub source_kind: String,
+    pub functions: Vec<FunctionLinks>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionLinks {
+    pub name: String,
+    /// Where the function is declared in the source
+    pub source: Option<Span>,
+    pub disassembly: Option<Lines>,
+    pub revela: Option<Lines>,
+    pub instructions: Vec<InstructionLinks>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstructionLinks {
+    pub offset: u16,
+    pub disassembly_line: Option<usize>,
+    /// `None` when the instruction comes from another file, like an inlined macro
+    pub source: Option<Span>,
+}
+
+/// Bytes `start..end` of the source, on lines `line..=end_line` counting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: u32,
+    pub end: u32,
+    pub line: usize,
+    pub end_line: usize,
+}
+
+/// Lines `first..=last` of a text, counting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Lines {
+    pub first: usize,
+    pub last: usize,
+}
+
+/// Links every function of the module and every instruction of their bodies. Functions are
+/// found in the disassembly and Revela output by their declarations, instructions in the
+/// disassembly by their offsets.
+pub fn link_module(
+    module: &CompiledModule,
+    source_map: &SourceMap,
+    source_kind: &str,
+    source: &str,
+    disassembly: Option<&str>,
+    revela: Option<&str>,
+) -> SourceLinks {
+    let file_hash = FileHash::new(source);
+    let span = |loc: Loc| {
+        (loc.file_hash() == file_hash && loc.end() as usize <= source.len()).then(|| Span {
+            start: loc.start(),
+            end: loc.end(),
+            line: line_of(source, loc.start() as usize),
+            end_line: line_of(source, loc.end() as usize),
+        })
+    };
+    let names: Vec<_> = module
+        .function_defs()
+        .iter()
+        .map(|def| {
+            module
+                .identifier_at(module.function_handle_at(def.function).name)
+                .to_string()
+        })
+        .collect();
+    let disassembly_lines = disassembly.map(|text| function_lines(text, &names));
+    let revela_lines = revela.map(|text| function_lines(text, &names));
+
+    let mut functions = vec![];
+    for (index, (def, name)) in module.function_defs().iter().zip(&names).enumerate() {
+        let index = FunctionDefinitionIndex(index as u16);
+        let function_map = source_map.get_function_source_map(index).ok();
+        let disassembly_range = disassembly_lines
+            .as_ref()
+            .and_then(|lines| lines.get(name).copied());
+        let offsets = match (disassembly, disassembly_range) {
+            (Some(text), Some(range)) => instruction_lines(text, range),
+            _ => BTreeMap::new(),
+        };
+        let instructions = def.code.as_ref().map_or(vec![], |code| {
+            (0..code.code.len() as u16)
+                .map(|offset| InstructionLinks {
+                    offset,
+                    disassembly_line: offsets.get(&offset).copied(),
+                    source: source_map
+                        .get_code_location(index, offset)
+                        .ok()
+                        .and_then(span),
+                })
+                .collect()
+        });
+        functions.push(FunctionLinks {
+            name: name.clone(),
+            source: function_map.and_then(|function_map| span(function_map.definition_location)),
+            disassembly: disassembly_range,
+            revela: revela_lines
+                .as_ref()
+                .and_then(|lines| lines.get(name).copied()),
+            instructions,
+        });
+    }
+    SourceLinks {
+        source_kind: source_kind.to_string(),
+        functions,
+    }
+}
+
+fn line_of(text: &str, offset: usize) -> usize {
+    text.as_bytes()[..offset.min(text.len())]
+        .iter()
+        .filter(|byte| **byte == b'\n')
+        .count()
+        + 1
+}
+
+/// Lines of every function declared in `text`, each running up to the next declaration.
+fn function_lines(text: &str, names: &[String]) -> BTreeMap<String, Lines> {
+    let lines: Vec<_> = text.lines().collect();
+    let mut starts = vec![];
+    for (number, line) in lines.iter().enumerate() {
+        if let Some(name) = names.iter().find(|name| declares(line, name)) {
+            if !starts.iter().any(|(declared, _)| declared == name) {
+                starts.push((name.clone(), number + 1));
+            }
+        }
+    }
+    let mut functions = BTreeMap::new();
+    for (i, (name, first)) in starts.iter().enumerate() {
+        let mut last = starts.get(i + 1).map_or(lines.len(), |(_, next)| next - 1);
+        while last > *first && lines[last - 1].trim().is_empty() {
+            last -= 1;
+        }
+        functions.insert(
+            name.clone(),
+            Lines {
+                first: *first,
+                last,
+            },
+        );
+    }
+    functions
+}
+
+/// Declarations look like `public fun name<T0>(` in Revela output and the disassembly, without
+/// `fun` in older disassemblers.
+fn declares(line: &str, name: &str) -> bool {
+    if line.starts_with(char::is_whitespace) && !line.trim_start().contains("fun ") {
+        return false;
+    }
+    line.match_indices(name).any(|(at, _)| {
+        let before = line[..at].chars().next_back();
+        let after = line[at + name.len()..].chars().next();
+        before.is_none_or(|c| c == ' ') && matches!(after, Some('(' | '<'))
+    })
+}
+
+/// Disassembly lines of the instructions in a function, printed as `offset: instruction`.
+fn instruction_lines(text: &str, range: Lines) -> BTreeMap<u16, usize> {
+    text.lines()
+        .enumerate()
+        .skip(range.first)
+        .take(range.last - range.first)
+        .filter_map(|(number, line)| {
+            let (offset, _) = line.trim_start().split_once(": ")?;
+            Some((offset.parse().ok()?, number + 1))
+        })
+        .collect()
+}
+
+/// Links the module with its verified source from the stored source map, disassembly and Revela
+/// output, and stores the links. `None` without a source map.
+pub async fn update_links<'a, A>(
+    db: A,
+    network: &str,
+    package_id: &ObjectID,
+    module: &CompiledModule,
+) -> Result<Option<SourceLinks>>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut db = db.acquire().await?;
+    let module_name = module.name().as_str();
+    let Some(mut stored) =
+        ModuleSourceMap::load(&mut *db, package_id, network, module_name).await?
+    else {
+        return Ok(None);
+    };
+    let Some(source) =
+        ModuleSource::load(&mut *db, package_id, network, module_name, &stored.kind).await?
+    else {
+        return Ok(None);
+    };
+    let disassembly =
+        ModuleSource::load(&mut *db, package_id, network, module_name, "disassembled").await?;
+    let revela = ModuleSource::load(&mut *db, package_id, network, module_name, "revela").await?;
+
+    let source_map: SourceMap = bcs::from_bytes(&stored.source_map)?;
+    let links = link_module(
+        module,
+        &source_map,
+        &stored.kind,
+        &source.source,
+        disassembly.as_ref().map(|source| source.source.as_str()),
+        revela.as_ref().map(|source| source.source.as_str()),
+    );
+    stored.links = Some(Json(links.clone()));
+    stored.save_links(&mut *db).await?;
+    Ok(Some(links))
+}
@@ -0,0 +1,65 @@
+use std::{
+    path::{Path, PathBuf},
+    str::from_utf8,
+};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use sui_types::base_types::ObjectID;
+use tempfile::tempdir;
+use tokio::process::Command;
+
+use super::{SourcePackage, SourceProvider};
+
+/// Local checkout of the Move package published as one package.
+pub struct Git {
+    pub path: PathBuf,
+    pub package_id: ObjectID,
+}
+
+#[async_trait]
+impl SourceProvider for Git {
+    fn kind(&self) -> &str {
+        "git"
+    }
+
+    async fn packages(&self, _network: &str) -> Result<Vec<ObjectID>> {
+        Ok(vec![self.package_id])
+    }
+
+    async fn fetch(&self, _network: &str, package_id: &ObjectID) -> Result<Option<SourcePackage>> {
+        if *package_id != self.package_id {
+            return Ok(None);
+        }
+        Ok(Some(SourcePackage::read(&self.path)?))
+    }
+}
+
+/// Shallow clone of `revision` of a repository, with the Move package in `path` inside it.
+/// The revision can be a commit as well as a branch or tag, which `git clone --branch` refuses.
+pub async fn clone(url: &str, revision: &str, path: &Path) -> Result<SourcePackage> {
+    let dir = tempdir()?;
+    let steps: [&[&str]; 4] = [
+        &["init", "--quiet"],
+        &["remote", "add", "origin", url],
+        &["fetch", "--quiet", "--depth", "1", "origin", revision],
+        &["checkout", "--quiet", "FETCH_HEAD"],
+    ];
+    for args in steps {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir.path())
+            .args(args)
+            .output()
+            .await?;
+        if !output.status.success() {
+            bail!(
+                "Failed to clone {} at {}: {}",
+                url,
+                revision,
+                from_utf8(&output.stderr)?
+            );
+        }
+    }
+    SourcePackage::read_temp(dir, path)
+}
@@ -0,0 +1,117 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use sui_types::base_types::ObjectID;
+use tempfile::TempDir;
+
+use crate::{
+    db::Db,
+    source_verification::{verify_package, Verification},
+};
+
+pub mod git;
+pub mod mvr;
+pub mod welldone;
+
+/// Third party claiming to have the sources of packages. Nothing it returns is trusted before
+/// it compiles to the stored bytecode.
+#[async_trait]
+pub trait SourceProvider: Send + Sync {
+    /// Kind the module sources are stored under
+    fn kind(&self) -> &str;
+
+    /// Packages the provider has sources for
+    async fn packages(&self, network: &str) -> Result<Vec<ObjectID>>;
+
+    /// Move package with the sources of `package_id`, `None` when the provider has none.
+    async fn fetch(&self, network: &str, package_id: &ObjectID) -> Result<Option<SourcePackage>>;
+}
+
+/// Move package on disk, with its `Move.toml`.
+pub struct SourcePackage {
+    pub dir: PathBuf,
+    /// Source file of every module
+    pub sources: BTreeMap<String, String>,
+    /// Keeps downloaded packages around until they are compiled
+    _temp: Option<TempDir>,
+}
+
+impl SourcePackage {
+    /// Package in `dir`, with every `.move` file under its `sources` directory.
+    pub fn read(dir: &Path) -> Result<Self> {
+        if !dir.join("Move.toml").is_file() {
+            bail!("{} is not a Move package", dir.display());
+        }
+        let mut sources = BTreeMap::new();
+        read_sources(&dir.join("sources"), &mut sources)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            sources,
+            _temp: None,
+        })
+    }
+
+    /// Package in `dir` inside `temp`, removed once the package is dropped.
+    pub fn read_temp(temp: TempDir, dir: &Path) -> Result<Self> {
+        let mut package = Self::read(&temp.path().join(dir))?;
+        package._temp = Some(temp);
+        Ok(package)
+    }
+}
+
+fn read_sources(dir: &Path, sources: &mut BTreeMap<String, String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            read_sources(&path, sources)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "move")
+        {
+            let source = fs::read_to_string(&path)?;
+            for module_name in module_names(&source) {
+                sources.insert(module_name, source.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Modules declared in a source file, as in `module my_package::my_module {`.
+pub fn module_names(source: &str) -> Vec<String> {
+    let mut tokens = source.split_whitespace();
+    let mut names = vec![];
+    while let Some(token) = tokens.next() {
+        if token != "module" {
+            continue;
+        }
+        let Some(name) = tokens.next() else {
+            break;
+        };
+        if let Some((_, name)) = name.trim_end_matches(['{', ';']).rsplit_once("::") {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Fetches the sources of a package and verifies them, `None` when the provider has no sources
+/// for the package.
+pub async fn ingest_package(
+    db: &Db,
+    network: &str,
+    provider: &dyn SourceProvider,
+    package_id: &ObjectID,
+) -> Result<Option<Verification>> {
+    let Some(package) = provider.fetch(network, package_id).await? else {
+        return Ok(None);
+    };
+    Ok(Some(
+        verify_package(db, network, package_id, provider.kind(), &package).await?,
+    ))
+}
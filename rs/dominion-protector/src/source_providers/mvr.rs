@@ -0,0 +1,89 @@
+use std::{path::Path, str::FromStr};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use sui_types::base_types::ObjectID;
+
+use super::{git, SourcePackage, SourceProvider};
+
+/// Move registry resolving names like `@mysten/kiosk` to packages and the git repository they
+/// were built from.
+pub struct Mvr {
+    pub names: Vec<String>,
+    /// Registry API, the public registry of the network by default
+    pub registry: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NameInfo {
+    pub name: String,
+    pub package_address: String,
+    pub git_info: Option<GitInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitInfo {
+    pub repository_url: String,
+    /// Directory of the Move package inside the repository
+    #[serde(default)]
+    pub path: String,
+    /// Tag, branch or commit the package was published from
+    pub tag: String,
+}
+
+impl Mvr {
+    fn registry(&self, network: &str) -> String {
+        self.registry
+            .clone()
+            .unwrap_or_else(|| format!("https://{}.mvr.mystenlabs.com", network))
+    }
+
+    async fn name_info(&self, network: &str, name: &str) -> Result<NameInfo> {
+        Ok(
+            reqwest::get(format!("{}/v1/names/{}", self.registry(network), name))
+                .await?
+                .error_for_status()?
+                .json::<NameInfo>()
+                .await?,
+        )
+    }
+}
+
+#[async_trait]
+impl SourceProvider for Mvr {
+    fn kind(&self) -> &str {
+        "mvr"
+    }
+
+    async fn packages(&self, network: &str) -> Result<Vec<ObjectID>> {
+        let mut packages = vec![];
+        for name in &self.names {
+            let info = self.name_info(network, name).await?;
+            packages.push(ObjectID::from_str(&info.package_address)?);
+        }
+        Ok(packages)
+    }
+
+    async fn fetch(&self, network: &str, package_id: &ObjectID) -> Result<Option<SourcePackage>> {
+        for name in &self.names {
+            let info = self.name_info(network, name).await?;
+            if ObjectID::from_str(&info.package_address)? != *package_id {
+                continue;
+            }
+            let Some(git_info) = info.git_info else {
+                println!("{} has no git repository registered", info.name);
+                return Ok(None);
+            };
+            return Ok(Some(
+                git::clone(
+                    &git_info.repository_url,
+                    &git_info.tag,
+                    Path::new(&git_info.path),
+                )
+                .await?,
+            ));
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,134 @@
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sui_types::base_types::ObjectID;
+use tempfile::tempdir;
+
+use super::{SourcePackage, SourceProvider};
+
+const API: &str = "https://api.welldonestudio.io/compiler/sui";
+const PAGE_SIZE: usize = 50;
+
+/// Sources verified by WELLDONE Code. Only module sources are published, so they are compiled
+/// as a package of their own depending on the Sui framework.
+pub struct Welldone;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuiPackageDto {
+    pub id: i64,
+    pub chain_id: String,
+    pub account: Option<String>,
+    pub package_id: String,
+    pub package_name: Option<String>,
+    pub is_verified: bool,
+    pub verified_src_url: Option<String>,
+    pub is_remix_src_uploaded: Option<bool>,
+    pub compiled_at: DateTime<Utc>,
+    pub deployed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuiModuleSourceCodeQueryResultDto {
+    is_success: bool,
+    err_msg: String,
+    source_codes: HashMap<String, String>,
+}
+
+#[async_trait]
+impl SourceProvider for Welldone {
+    fn kind(&self) -> &str {
+        "welldone"
+    }
+
+    async fn packages(&self, network: &str) -> Result<Vec<ObjectID>> {
+        let mut packages = vec![];
+        let mut offset = 0;
+        loop {
+            let page = reqwest::get(format!(
+                "{}/packages?isVerified=true&chainId={}&fetchSize={}&offset={}",
+                API, network, PAGE_SIZE, offset
+            ))
+            .await?
+            .json::<Vec<SuiPackageDto>>()
+            .await?;
+            if page.is_empty() {
+                break;
+            }
+            for package in &page {
+                packages.push(ObjectID::from_str(&package.package_id)?);
+            }
+            offset += PAGE_SIZE;
+        }
+        Ok(packages)
+    }
+
+    async fn fetch(&self, network: &str, package_id: &ObjectID) -> Result<Option<SourcePackage>> {
+        let sources = reqwest::get(format!(
+            "{}/verifications/module-sources/{}/{}",
+            API, network, package_id
+        ))
+        .await?
+        .json::<SuiModuleSourceCodeQueryResultDto>()
+        .await?;
+        if !sources.is_success || sources.source_codes.is_empty() {
+            return Ok(None);
+        }
+
+        let dir = tempdir()?;
+        let sources_dir = dir.path().join("sources");
+        fs::create_dir(&sources_dir)?;
+        for (module_name, source) in &sources.source_codes {
+            fs::write(sources_dir.join(format!("{}.move", module_name)), source)?;
+        }
+        fs::write(
+            dir.path().join("Move.toml"),
+            manifest(sources.source_codes.values()),
+        )?;
+        Ok(Some(SourcePackage::read_temp(dir, Path::new(""))?))
+    }
+}
+
+/// `Move.toml` for bare module sources: their named address is left unpublished and the
+/// framework comes in as an implicit dependency. Sources using `let mut` or `public struct`
+/// are in the 2024 edition, others in the legacy one.
+fn manifest<'a>(sources: impl Iterator<Item = &'a String> + Clone) -> String {
+    let mut addresses = sources
+        .clone()
+        .flat_map(|source| {
+            source
+                .split_whitespace()
+                .skip_while(|token| *token != "module")
+                .nth(1)
+                .and_then(|name| name.split_once("::"))
+                .map(|(address, _)| address.to_string())
+        })
+        .filter(|address| !address.starts_with("0x"))
+        .collect::<Vec<_>>();
+    addresses.sort();
+    addresses.dedup();
+    let edition = if sources
+        .into_iter()
+        .any(|source| source.contains("let mut ") || source.contains("public struct "))
+    {
+        "2024.beta"
+    } else {
+        "legacy"
+    };
+
+    let mut manifest = format!(
+        "[package]\nname = \"{}\"\nedition = \"{}\"\n\n[addresses]\n",
+        addresses
+            .first()
+            .map_or("welldone", |address| address.as_str()),
+        edition
+    );
+    for address in &addresses {
+        manifest.push_str(&format!("{} = \"0x0\"\n", address));
+    }
+    manifest
+}
@@ -0,0 +1,570 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use move_binary_format::{
+    file_format::{Bytecode, DatatypeHandleIndex, FunctionHandleIndex, SignatureToken},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_core_types::account_address::AccountAddress;
+use move_package::BuildConfig as MoveBuildConfig;
+use sqlx::{types::Json, Acquire, Postgres};
+use sui_move_build::BuildConfig;
+use sui_types::base_types::ObjectID;
+use tempfile::tempdir;
+
+use crate::{
+    db::{
+        module_source_map::ModuleSourceMap,
+        package_linkage::PackageLinkage,
+        package_module::PackageModule,
+        source_verification::{ModuleMatch, SourceVerification},
+        sources::ModuleSource,
+        Db,
+    },
+    source_providers::SourcePackage,
+};
+
+/// Module compiled from source, with where each of its instructions came from.
+pub struct CompiledSource {
+    pub module: CompiledModule,
+    pub source_map: SourceMap,
+}
+
+/// Compiles the Move package in `dir`, dependencies included, and returns its own modules.
+/// Dependencies are fetched into a temporary directory and no lock file is written.
+pub async fn compile_package(dir: &Path) -> Result<BTreeMap<String, CompiledSource>> {
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let install_dir = tempdir()?;
+        let config = BuildConfig {
+            config: MoveBuildConfig {
+                install_dir: Some(install_dir.path().to_path_buf()),
+                silence_warnings: true,
+                ..Default::default()
+            },
+            run_bytecode_verifier: false,
+            print_diags_to_stderr: false,
+            chain_id: None,
+        };
+        let compiled = config
+            .build(&dir)
+            .with_context(|| format!("Can not compile {}", dir.display()))?;
+        Ok(compiled
+            .package
+            .root_compiled_units
+            .iter()
+            .map(|unit| {
+                (
+                    unit.unit.name.to_string(),
+                    CompiledSource {
+                        module: unit.unit.module.clone(),
+                        source_map: unit.unit.source_map.clone(),
+                    },
+                )
+            })
+            .collect())
+    })
+    .await?
+}
+
+/// Outcome of verifying the sources of a package.
+pub struct Verification {
+    pub modules: BTreeMap<String, ModuleComparison>,
+    /// Why the sources did not compile, every module is left unverified then
+    pub compile_error: Option<String>,
+}
+
+/// Recompiles the sources of a package and stores, under `kind`, the outcome for every module
+/// with the source of the modules in the package. A source only counts as verified when it
+/// reproduces the stored bytecode, its source map is kept then.
+pub async fn verify_package(
+    db: &Db,
+    network: &str,
+    package_id: &ObjectID,
+    kind: &str,
+    package: &SourcePackage,
+) -> Result<Verification> {
+    let (compiled, compile_error) = match compile_package(&package.dir).await {
+        Ok(compiled) => (compiled, None),
+        Err(err) => (BTreeMap::new(), Some(format!("{:#}", err))),
+    };
+
+    let mut tx = db.pool.begin().await?;
+    let mut modules = compare_package(&mut *tx, network, package_id, &compiled).await?;
+    for (module_name, comparison) in &mut modules {
+        if let Some(err) = &compile_error {
+            comparison.differences.push(err.clone());
+        }
+        comparison
+            .record(package_id, network, module_name, kind)
+            .save(&mut *tx)
+            .await?;
+        let Some(source) = package.sources.get(module_name) else {
+            continue;
+        };
+        if comparison.result != ModuleMatch::Extra {
+            ModuleSource {
+                package_id: package_id.to_string(),
+                network: network.to_string(),
+                module_name: module_name.clone(),
+                source: source.clone(),
+                kind: kind.to_string(),
+                verified: comparison.result == ModuleMatch::Matches,
+            }
+            .save(&mut *tx)
+            .await?;
+        }
+        if comparison.result == ModuleMatch::Matches {
+            ModuleSourceMap {
+                package_id: package_id.to_string(),
+                network: network.to_string(),
+                module_name: module_name.clone(),
+                kind: kind.to_string(),
+                source_map: bcs::to_bytes(&compiled[module_name].source_map)?,
+                links: None,
+            }
+            .save(&mut *tx)
+            .await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(Verification {
+        modules,
+        compile_error,
+    })
+}
+
+/// Result of comparing one module, with what differs when it does not match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleComparison {
+    pub result: ModuleMatch,
+    pub differences: Vec<String>,
+}
+
+impl ModuleComparison {
+    pub fn record(
+        &self,
+        package_id: &ObjectID,
+        network: &str,
+        module_name: &str,
+        kind: &str,
+    ) -> SourceVerification {
+        SourceVerification {
+            package_id: package_id.to_string(),
+            network: network.to_string(),
+            module_name: module_name.to_string(),
+            kind: kind.to_string(),
+            result: self.result,
+            differences: Json(self.differences.clone()),
+            verified_at: Default::default(),
+        }
+    }
+}
+
+/// Compares the compiled modules with the bytecode stored for the package. Compiled addresses
+/// are mapped to the ones on chain first: the package's own address when compiled for `0x0`
+/// or its own id, and the original id of dependencies compiled for an upgraded version.
+pub async fn compare_package<'a, A>(
+    db: A,
+    network: &str,
+    package_id: &ObjectID,
+    compiled: &BTreeMap<String, CompiledSource>,
+) -> Result<BTreeMap<String, ModuleComparison>>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let mut db = db.acquire().await?;
+    let mut addresses = BTreeMap::new();
+    for linkage in PackageLinkage::load_all_by_package(&mut *db, package_id, network).await? {
+        addresses.insert(
+            AccountAddress::from_str(&linkage.upgraded_id)?,
+            AccountAddress::from_str(&linkage.dependency_id)?,
+        );
+    }
+
+    let mut comparisons = BTreeMap::new();
+    for stored in PackageModule::load_all_by_package(&mut *db, package_id, network).await? {
+        let on_chain = CompiledModule::deserialize_with_defaults(&stored.module_bytecode)?;
+        let comparison = match compiled.get(&stored.module_name) {
+            Some(compiled) => {
+                let module = relocate(&compiled.module, &on_chain, package_id, &addresses);
+                if module == on_chain {
+                    ModuleComparison {
+                        result: ModuleMatch::Matches,
+                        differences: vec![],
+                    }
+                } else {
+                    let mut differences = diff_modules(&on_chain, &module);
+                    if differences.is_empty() {
+                        differences
+                            .push("same declarations and code in another layout".to_string());
+                    }
+                    ModuleComparison {
+                        result: ModuleMatch::Differs,
+                        differences,
+                    }
+                }
+            }
+            None => ModuleComparison {
+                result: ModuleMatch::Missing,
+                differences: vec![],
+            },
+        };
+        comparisons.insert(stored.module_name, comparison);
+    }
+    for module_name in compiled.keys() {
+        comparisons
+            .entry(module_name.clone())
+            .or_insert(ModuleComparison {
+                result: ModuleMatch::Extra,
+                differences: vec![],
+            });
+    }
+    Ok(comparisons)
+}
+
+fn relocate(
+    compiled: &CompiledModule,
+    on_chain: &CompiledModule,
+    package_id: &ObjectID,
+    addresses: &BTreeMap<AccountAddress, AccountAddress>,
+) -> CompiledModule {
+    let mut compiled = compiled.clone();
+    let self_index = compiled.self_handle().address.0 as usize;
+    for (index, address) in compiled.address_identifiers.iter_mut().enumerate() {
+        if index == self_index
+            && (*address == AccountAddress::ZERO || *address == AccountAddress::from(*package_id))
+        {
+            *address = *on_chain.address();
+        } else if let Some(original) = addresses.get(address) {
+            *address = *original;
+        }
+    }
+    // The bytecode version only tells which serializer wrote the module
+    compiled.version = on_chain.version;
+    compiled
+}
+
+/// Differences between the stored and the compiled module, by name rather than by index so
+/// they read the same whatever order the compiler laid the tables out in.
+pub fn diff_modules(on_chain: &CompiledModule, compiled: &CompiledModule) -> Vec<String> {
+    let mut differences = vec![];
+    diff_sets(
+        &mut differences,
+        "dependency",
+        &dependencies(on_chain),
+        &dependencies(compiled),
+    );
+    diff_sets(
+        &mut differences,
+        "friend",
+        &friends(on_chain),
+        &friends(compiled),
+    );
+    diff_sets(
+        &mut differences,
+        "constant",
+        &constants(on_chain),
+        &constants(compiled),
+    );
+    diff_maps(
+        &mut differences,
+        "datatype",
+        &datatypes(on_chain),
+        &datatypes(compiled),
+    );
+    diff_maps(
+        &mut differences,
+        "function signature",
+        &function_signatures(on_chain),
+        &function_signatures(compiled),
+    );
+
+    let compiled_code = function_code(compiled);
+    for (name, code) in function_code(on_chain) {
+        let Some(other) = compiled_code.get(&name) else {
+            continue;
+        };
+        if let Some(offset) =
+            (0..code.len().max(other.len())).find(|i| code.get(*i) != other.get(*i))
+        {
+            differences.push(format!(
+                "function {} differs at instruction {}: {} on chain, {} compiled",
+                name,
+                offset,
+                code.get(offset).map_or("end", |i| i.as_str()),
+                other.get(offset).map_or("end", |i| i.as_str()),
+            ));
+        }
+    }
+    differences
+}
+
+fn diff_sets(
+    differences: &mut Vec<String>,
+    what: &str,
+    on_chain: &BTreeSet<String>,
+    compiled: &BTreeSet<String>,
+) {
+    for item in on_chain.difference(compiled) {
+        differences.push(format!("{} {} is only on chain", what, item));
+    }
+    for item in compiled.difference(on_chain) {
+        differences.push(format!("{} {} is only compiled", what, item));
+    }
+}
+
+fn diff_maps(
+    differences: &mut Vec<String>,
+    what: &str,
+    on_chain: &BTreeMap<String, String>,
+    compiled: &BTreeMap<String, String>,
+) {
+    for (name, declaration) in on_chain {
+        match compiled.get(name) {
+            None => differences.push(format!("{} {} is only on chain", what, name)),
+            Some(other) if other != declaration => differences.push(format!(
+                "{} {} is `{}` on chain, `{}` compiled",
+                what, name, declaration, other
+            )),
+            Some(_) => {}
+        }
+    }
+    for name in compiled.keys().filter(|name| !on_chain.contains_key(*name)) {
+        differences.push(format!("{} {} is only compiled", what, name));
+    }
+}
+
+fn dependencies(module: &CompiledModule) -> BTreeSet<String> {
+    module
+        .immediate_dependencies()
+        .iter()
+        .map(|id| format!("{}::{}", id.address().short_str_lossless(), id.name()))
+        .collect()
+}
+
+fn friends(module: &CompiledModule) -> BTreeSet<String> {
+    module
+        .immediate_friends()
+        .iter()
+        .map(|id| format!("{}::{}", id.address().short_str_lossless(), id.name()))
+        .collect()
+}
+
+fn constants(module: &CompiledModule) -> BTreeSet<String> {
+    module
+        .constant_pool()
+        .iter()
+        .map(|constant| {
+            format!(
+                "{}: 0x{}",
+                type_name(module, &constant.type_),
+                hex(&constant.data)
+            )
+        })
+        .collect()
+}
+
+fn datatypes(module: &CompiledModule) -> BTreeMap<String, String> {
+    let mut datatypes = BTreeMap::new();
+    for def in module.struct_defs() {
+        let handle = module.datatype_handle_at(def.struct_handle);
+        let fields = def.fields().map_or("native".to_string(), |fields| {
+            fields
+                .iter()
+                .map(|field| {
+                    format!(
+                        "{}: {}",
+                        module.identifier_at(field.name),
+                        type_name(module, &field.signature.0)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+        datatypes.insert(
+            module.identifier_at(handle.name).to_string(),
+            format!("struct has {:?} {{ {} }}", handle.abilities, fields),
+        );
+    }
+    for def in module.enum_defs() {
+        let handle = module.datatype_handle_at(def.enum_handle);
+        let variants = def
+            .variants
+            .iter()
+            .map(|variant| {
+                let fields = variant
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        format!(
+                            "{}: {}",
+                            module.identifier_at(field.name),
+                            type_name(module, &field.signature.0)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "{} {{ {} }}",
+                    module.identifier_at(variant.variant_name),
+                    fields
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        datatypes.insert(
+            module.identifier_at(handle.name).to_string(),
+            format!("enum has {:?} {{ {} }}", handle.abilities, variants),
+        );
+    }
+    datatypes
+}
+
+fn function_signatures(module: &CompiledModule) -> BTreeMap<String, String> {
+    module
+        .function_defs()
+        .iter()
+        .map(|def| {
+            let handle = module.function_handle_at(def.function);
+            let types = |index| {
+                module
+                    .signature_at(index)
+                    .0
+                    .iter()
+                    .map(|token| type_name(module, token))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            (
+                module.identifier_at(handle.name).to_string(),
+                format!(
+                    "{:?}{} fun<{}>({}): ({})",
+                    def.visibility,
+                    if def.is_entry { " entry" } else { "" },
+                    handle.type_parameters.len(),
+                    types(handle.parameters),
+                    types(handle.return_)
+                ),
+            )
+        })
+        .collect()
+}
+
+fn function_code(module: &CompiledModule) -> BTreeMap<String, Vec<String>> {
+    module
+        .function_defs()
+        .iter()
+        .map(|def| {
+            let name = module.identifier_at(module.function_handle_at(def.function).name);
+            let code = def.code.as_ref().map_or(vec![], |code| {
+                code.code
+                    .iter()
+                    .map(|instruction| instruction_name(module, instruction))
+                    .collect()
+            });
+            (name.to_string(), code)
+        })
+        .collect()
+}
+
+/// Instructions with the functions, datatypes and constants they refer to instead of indices.
+fn instruction_name(module: &CompiledModule, instruction: &Bytecode) -> String {
+    match instruction {
+        Bytecode::Call(index) => format!("Call({})", function_name(module, *index)),
+        Bytecode::CallGeneric(index) => format!(
+            "CallGeneric({})",
+            function_name(module, module.function_instantiation_at(*index).handle)
+        ),
+        Bytecode::Pack(index) => format!(
+            "Pack({})",
+            datatype_name(module, module.struct_def_at(*index).struct_handle)
+        ),
+        Bytecode::Unpack(index) => format!(
+            "Unpack({})",
+            datatype_name(module, module.struct_def_at(*index).struct_handle)
+        ),
+        Bytecode::PackGeneric(index) => format!(
+            "PackGeneric({})",
+            datatype_name(
+                module,
+                module
+                    .struct_def_at(module.struct_instantiation_at(*index).def)
+                    .struct_handle
+            )
+        ),
+        Bytecode::UnpackGeneric(index) => format!(
+            "UnpackGeneric({})",
+            datatype_name(
+                module,
+                module
+                    .struct_def_at(module.struct_instantiation_at(*index).def)
+                    .struct_handle
+            )
+        ),
+        Bytecode::LdConst(index) => {
+            format!("LdConst(0x{})", hex(&module.constant_at(*index).data))
+        }
+        instruction => format!("{:?}", instruction),
+    }
+}
+
+fn function_name(module: &CompiledModule, index: FunctionHandleIndex) -> String {
+    let handle = module.function_handle_at(index);
+    let id = module.module_id_for_handle(module.module_handle_at(handle.module));
+    format!(
+        "{}::{}::{}",
+        id.address().short_str_lossless(),
+        id.name(),
+        module.identifier_at(handle.name)
+    )
+}
+
+fn datatype_name(module: &CompiledModule, index: DatatypeHandleIndex) -> String {
+    let handle = module.datatype_handle_at(index);
+    let id = module.module_id_for_handle(module.module_handle_at(handle.module));
+    format!(
+        "{}::{}::{}",
+        id.address().short_str_lossless(),
+        id.name(),
+        module.identifier_at(handle.name)
+    )
+}
+
+fn type_name(module: &CompiledModule, token: &SignatureToken) -> String {
+    match token {
+        SignatureToken::Bool => "bool".to_string(),
+        SignatureToken::U8 => "u8".to_string(),
+        SignatureToken::U16 => "u16".to_string(),
+        SignatureToken::U32 => "u32".to_string(),
+        SignatureToken::U64 => "u64".to_string(),
+        SignatureToken::U128 => "u128".to_string(),
+        SignatureToken::U256 => "u256".to_string(),
+        SignatureToken::Address => "address".to_string(),
+        SignatureToken::Signer => "signer".to_string(),
+        SignatureToken::Vector(inner) => format!("vector<{}>", type_name(module, inner)),
+        SignatureToken::Datatype(index) => datatype_name(module, *index),
+        SignatureToken::DatatypeInstantiation(instantiation) => format!(
+            "{}<{}>",
+            datatype_name(module, instantiation.0),
+            instantiation
+                .1
+                .iter()
+                .map(|token| type_name(module, token))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        SignatureToken::Reference(inner) => format!("&{}", type_name(module, inner)),
+        SignatureToken::MutableReference(inner) => format!("&mut {}", type_name(module, inner)),
+        SignatureToken::TypeParameter(index) => format!("T{}", index),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
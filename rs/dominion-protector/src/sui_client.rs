@@ -30,24 +30,18 @@ impl SuiClientWithNetwork {
     }
 
     pub async fn with_default_network() -> Result<Self> {
-        let conf = sui_config_dir()?.join(SUI_CLIENT_CONFIG);
-        if !conf.exists() {
-            bail!("Wallet configuration file does not exist. Please create a wallet first.");
-        }
-        let client_config: SuiClientConfig = PersistedConfig::read(&conf)?;
-        let network = client_config
-            .active_env
-            .as_ref()
-            .context("No active environment")?;
-        let client = client_config
-            .get_active_env()?
-            .create_rpc_client(Some(std::time::Duration::from_secs(60)), None)
-            .await?;
-        Ok(SuiClientWithNetwork {
-            client,
-            network: network.clone(),
-        })
+        Self::new(&default_network()?).await
+    }
+}
+
+/// Active environment of the wallet configuration, read without connecting to it.
+pub fn default_network() -> Result<String> {
+    let conf = sui_config_dir()?.join(SUI_CLIENT_CONFIG);
+    if !conf.exists() {
+        bail!("Wallet configuration file does not exist. Please create a wallet first.");
     }
+    let client_config: SuiClientConfig = PersistedConfig::read(&conf)?;
+    client_config.active_env.context("No active environment")
 }
 
 /*
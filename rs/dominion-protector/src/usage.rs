@@ -0,0 +1,146 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use serde_json::json;
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::{
+    call_graph::{load_modules, CallGraph, Effect, Pattern, Trace},
+    db::{structure_usage::StructureUsage, structure_usage_package::StructureUsagePackage, Db},
+    sui_client::SuiClientWithNetwork,
+};
+
+/// Usage of the datatypes of the package, computed the first time it is asked for.
+pub async fn get_or_save_structures_usage(
+    package_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<Vec<StructureUsage>> {
+    if StructureUsagePackage::load(&db.pool, package_id, &client.network)
+        .await?
+        .is_some()
+    {
+        return StructureUsage::load_all_by_package(&db.pool, package_id, &client.network).await;
+    }
+    save_structures_usage(package_id, client, db).await
+}
+
+/// Records which functions of the package transfer, wrap, share, freeze or emit each of its
+/// datatypes, following calls into other packages. Replaces what was recorded before.
+pub async fn save_structures_usage(
+    package_id: &ObjectID,
+    client: &SuiClientWithNetwork,
+    db: &Db,
+) -> Result<Vec<StructureUsage>> {
+    let (own_modules, modules) = load_modules(package_id, client, db).await?;
+
+    // Types keep the address of the first version of the package after upgrades
+    let mut datatypes = BTreeSet::new();
+    for module in &own_modules {
+        let self_id = module.self_id();
+        for handle in module
+            .struct_defs()
+            .iter()
+            .map(|def| def.struct_handle)
+            .chain(module.enum_defs().iter().map(|def| def.enum_handle))
+        {
+            datatypes.insert((
+                *self_id.address(),
+                self_id.name().to_string(),
+                module
+                    .identifier_at(module.datatype_handle_at(handle).name)
+                    .to_string(),
+            ));
+        }
+    }
+
+    let mut usages = BTreeMap::<(String, String, String, String), StructureUsage>::new();
+    for module in &own_modules {
+        let target_module_name = module.self_id().name().to_string();
+        for def in module.function_defs() {
+            let function_name = module
+                .identifier_at(module.function_handle_at(def.function).name)
+                .to_string();
+            let mut graph = CallGraph::default();
+            graph.visit(&modules, module, def, Vec::new());
+            for unresolved in &graph.unresolved {
+                println!("{}::{}: {}", target_module_name, function_name, unresolved);
+            }
+
+            for ((effect, pattern), trace) in graph.effects {
+                // Nothing is recorded about datatypes being destroyed
+                if matches!(effect, Effect::Destroyed | Effect::Withdrawn) {
+                    continue;
+                }
+                let Pattern::Datatype {
+                    address,
+                    module: module_name,
+                    name: datatype_name,
+                    ..
+                } = &pattern
+                else {
+                    continue;
+                };
+                if !datatypes.contains(&(*address, module_name.clone(), datatype_name.clone())) {
+                    continue;
+                }
+                let usage = usages
+                    .entry((
+                        module_name.clone(),
+                        datatype_name.clone(),
+                        target_module_name.clone(),
+                        function_name.clone(),
+                    ))
+                    .or_insert_with(|| StructureUsage {
+                        package_id: package_id.to_string(),
+                        network: client.network.clone(),
+                        module_name: module_name.clone(),
+                        datatype_name: datatype_name.clone(),
+                        target_module_name: target_module_name.clone(),
+                        call_chain: trace.call_chain.clone(),
+                        function_name: function_name.clone(),
+                        address_owned: None,
+                        object_owned: None,
+                        wrapped: None,
+                        shared: None,
+                        immutable: None,
+                        emitted: None,
+                    });
+                record(usage, effect, &pattern, trace);
+            }
+        }
+    }
+
+    let usages = usages.into_values().collect::<Vec<_>>();
+    let mut tx = db.pool.begin().await?;
+    StructureUsage::delete_all_by_package(&mut *tx, package_id, &client.network).await?;
+    for usage in &usages {
+        usage.save(&mut *tx).await?;
+    }
+    StructureUsagePackage::save(&mut *tx, package_id, &client.network).await?;
+    tx.commit().await?;
+    Ok(usages)
+}
+
+fn record(usage: &mut StructureUsage, effect: Effect, pattern: &Pattern, trace: Trace) {
+    let column = match effect {
+        Effect::Transferred => &mut usage.address_owned,
+        Effect::ObjectOwned => &mut usage.object_owned,
+        Effect::Wrapped => &mut usage.wrapped,
+        Effect::Shared => &mut usage.shared,
+        Effect::Frozen => &mut usage.immutable,
+        Effect::Emitted => &mut usage.emitted,
+        Effect::Destroyed | Effect::Withdrawn => return,
+    };
+    if trace.call_chain.len() < usage.call_chain.len() {
+        usage.call_chain = trace.call_chain.clone();
+    }
+    let entry = json!({
+        "type": pattern.to_string(),
+        "via": trace.via,
+        "call_chain": trace.call_chain,
+    });
+    if let Some(entries) = column.get_or_insert_with(|| json!([])).as_array_mut() {
+        entries.push(entry);
+    }
+}
@@ -0,0 +1,464 @@
+use std::{collections::BTreeSet, fmt::Display, str::from_utf8, time::Duration};
+
+use anyhow::{bail, Result};
+use move_binary_format::{
+    file_format::{
+        Bytecode, DatatypeHandleIndex, FunctionDefinition, FunctionDefinitionIndex, SignatureToken,
+        StructDefinition, Visibility,
+    },
+    CompiledModule,
+};
+use move_core_types::account_address::AccountAddress;
+use sui_types::{base_types::ObjectID, BRIDGE_ADDRESS, SUI_FRAMEWORK_ADDRESS, SUI_SYSTEM_ADDRESS};
+use tempfile::tempdir;
+use tokio::{fs, process::Command};
+
+use crate::{
+    call_graph::is_framework_call,
+    decompiler::smt::{
+        decompile_reachability, decompile_symbol, field_symbol, function_relation, SYSTEM_THEORY,
+    },
+};
+
+/// Query asserting a bad state, instantiated for every function it applies to.
+pub struct Property {
+    pub name: &'static str,
+    template: &'static str,
+    /// Verdict meaning the bad state is reachable
+    violated_when: &'static str,
+}
+
+impl Property {
+    pub fn violated(&self, verdict: &str) -> Option<bool> {
+        match verdict {
+            "sat" | "unsat" => Some(verdict == self.violated_when),
+            _ => None,
+        }
+    }
+}
+
+pub static CAN_RETURN: Property = Property {
+    name: "can_return",
+    template: include_str!("../../../smt/properties/can_return.smt2"),
+    violated_when: "unsat",
+};
+
+pub static SHARED_WRITE_WITHOUT_CAPABILITY: Property = Property {
+    name: "shared_write_without_capability",
+    template: include_str!("../../../smt/properties/shared_write_without_capability.smt2"),
+    violated_when: "sat",
+};
+
+pub static SHARED_DECREASE_WITHOUT_CAPABILITY: Property = Property {
+    name: "shared_decrease_without_capability",
+    template: include_str!("../../../smt/properties/shared_decrease_without_capability.smt2"),
+    violated_when: "sat",
+};
+
+pub static COIN_TRANSFER_WITHOUT_RECEIPT: Property = Property {
+    name: "coin_transfer_without_receipt",
+    template: include_str!("../../../smt/properties/coin_transfer_without_receipt.smt2"),
+    violated_when: "sat",
+};
+
+/// Property instantiated for one function.
+pub struct Target {
+    pub property: &'static Property,
+    pub function_name: String,
+    /// Parameter or instruction the property is about, empty when it is about the whole function
+    pub subject: String,
+    /// Relation of the function in the module translation
+    function_relation: String,
+    /// Relation the query is about, the function's own or one defined by `definitions`
+    relation: String,
+    arguments: Vec<(String, String)>,
+    substitutions: Vec<(&'static str, String)>,
+    /// Relations the query needs besides the module translation
+    definitions: String,
+}
+
+impl Target {
+    /// Uninterpreted relations satisfy anything, checking them tells nothing.
+    pub fn is_defined_in(&self, module_smt: &str) -> bool {
+        module_smt.contains(&format!("(define-fun {} ", self.function_relation))
+    }
+
+    pub fn script(&self, module_smt: &str) -> String {
+        let call = format!(
+            "({} {})",
+            self.relation,
+            self.arguments
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        let mut query = self.property.template.replace("{call}", &call);
+        for (placeholder, value) in &self.substitutions {
+            query = query.replace(placeholder, value);
+        }
+        let constants = self
+            .arguments
+            .iter()
+            .map(|(name, sort)| format!("(declare-const {} {})\n", name, sort))
+            .collect::<String>();
+        format!(
+            "(set-option :produce-models true)\n{}\n{}\n{}{}{}\n(check-sat)\n(get-model)\n",
+            SYSTEM_THEORY, module_smt, self.definitions, constants, query
+        )
+    }
+}
+
+/// Instantiates every property for the public and entry functions of `module`, `shared`
+/// being the datatypes its package shares.
+pub fn targets(module: &CompiledModule, shared: &BTreeSet<DatatypeName>) -> Vec<Target> {
+    let mut targets = Vec::new();
+    for (index, def) in module.function_defs().iter().enumerate() {
+        if def.visibility != Visibility::Public && !def.is_entry {
+            continue;
+        }
+        let handle = module.function_handle_at(def.function);
+        let function_name = module.identifier_at(handle.name).to_string();
+        let (relation, arguments) = function_relation(module, def.function);
+        let target = |property, subject: &str, substitutions| Target {
+            property,
+            function_name: function_name.clone(),
+            subject: subject.to_string(),
+            function_relation: relation.clone(),
+            relation: relation.clone(),
+            arguments: arguments.clone(),
+            substitutions,
+            definitions: String::new(),
+        };
+
+        targets.push(target(&CAN_RETURN, "", Vec::new()));
+
+        for at in unreceived_coin_transfers(module, def) {
+            // Its body is not translated either then, there is nothing to check
+            let Ok((reaching, definition)) =
+                decompile_reachability(module, FunctionDefinitionIndex(index as u16), at)
+            else {
+                continue;
+            };
+            targets.push(Target {
+                relation: reaching,
+                definitions: definition + "\n",
+                ..target(
+                    &COIN_TRANSFER_WITHOUT_RECEIPT,
+                    &format!("offset {}", at),
+                    vec![("{offset}", at.to_string())],
+                )
+            });
+        }
+
+        let params = &module.signature_at(handle.parameters).0;
+        if params
+            .iter()
+            .any(|param| is_capability(module, param, shared))
+        {
+            continue;
+        }
+        for (i, param) in params.iter().enumerate() {
+            let SignatureToken::MutableReference(inner) = param else {
+                continue;
+            };
+            let Some(datatype) = datatype_index(inner) else {
+                continue;
+            };
+            if !shared.contains(&datatype_name(module, datatype)) {
+                continue;
+            }
+            let object = format!("$p{}", i);
+            let updated = format!("$m{}", i);
+            targets.push(target(
+                &SHARED_WRITE_WITHOUT_CAPABILITY,
+                &object,
+                vec![("{object}", object.clone()), ("{updated}", updated.clone())],
+            ));
+
+            // Fields of datatypes from other modules are not translated
+            let Some(struct_def) = own_struct(module, inner) else {
+                continue;
+            };
+            let decreases = struct_def
+                .fields()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|field| {
+                    let selector = field_symbol(module, struct_def, field);
+                    match &field.signature.0 {
+                        SignatureToken::U8
+                        | SignatureToken::U16
+                        | SignatureToken::U32
+                        | SignatureToken::U64
+                        | SignatureToken::U128
+                        | SignatureToken::U256 => Some(format!(
+                            "(< ({} {}) ({} {}))",
+                            selector, updated, selector, object
+                        )),
+                        token
+                            if framework_type_argument(module, token, "balance", "Balance")
+                                .is_some() =>
+                        {
+                            let value = balance_value();
+                            Some(format!(
+                                "(< ({} ({} {})) ({} ({} {})))",
+                                value, selector, updated, value, selector, object
+                            ))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<_>>();
+            let decreased = match decreases.len() {
+                0 => continue,
+                1 => decreases[0].clone(),
+                _ => format!("(or {})", decreases.join(" ")),
+            };
+            targets.push(target(
+                &SHARED_DECREASE_WITHOUT_CAPABILITY,
+                &object,
+                vec![("{object}", object.clone()), ("{decreased}", decreased)],
+            ));
+        }
+    }
+    targets
+}
+
+/// Selector of the amount in a `Balance`, declared by [`SYSTEM_THEORY`].
+fn balance_value() -> String {
+    decompile_symbol(
+        &ObjectID::from_address(AccountAddress::TWO),
+        "balance",
+        "Balance::value",
+    )
+}
+
+/// Offsets of the transfers of a `Coin<T>` in a function getting no `Coin<T>` or `Balance<T>`,
+/// which has to pay them out of something else: a shared pool, minting, another object.
+fn unreceived_coin_transfers(module: &CompiledModule, def: &FunctionDefinition) -> Vec<usize> {
+    let Some(code) = &def.code else {
+        return Vec::new();
+    };
+    let received = module
+        .signature_at(module.function_handle_at(def.function).parameters)
+        .0
+        .iter()
+        .filter_map(|param| {
+            let param = match param {
+                SignatureToken::Reference(inner) | SignatureToken::MutableReference(inner) => inner,
+                _ => param,
+            };
+            let param = match param {
+                SignatureToken::Vector(inner) => inner,
+                _ => param,
+            };
+            framework_type_argument(module, param, "coin", "Coin")
+                .or_else(|| framework_type_argument(module, param, "balance", "Balance"))
+        })
+        .collect::<Vec<_>>();
+    code.code
+        .iter()
+        .enumerate()
+        .filter_map(|(at, instruction)| {
+            let Bytecode::CallGeneric(index) = instruction else {
+                return None;
+            };
+            let instantiation = module.function_instantiation_at(*index);
+            if !is_framework_call(module, instantiation.handle, "transfer", "transfer")
+                && !is_framework_call(module, instantiation.handle, "transfer", "public_transfer")
+            {
+                return None;
+            }
+            let transferred = module
+                .signature_at(instantiation.type_parameters)
+                .0
+                .first()?;
+            let coin = framework_type_argument(module, transferred, "coin", "Coin")?;
+            (!received.contains(&coin)).then_some(at)
+        })
+        .collect()
+}
+
+/// `T` of a `0x2::<module_name>::<name><T>` type.
+fn framework_type_argument<'t>(
+    module: &CompiledModule,
+    token: &'t SignatureToken,
+    module_name: &str,
+    name: &str,
+) -> Option<&'t SignatureToken> {
+    let SignatureToken::DatatypeInstantiation(instantiation) = token else {
+        return None;
+    };
+    let (address, declared_in, declared_name) = datatype_name(module, instantiation.0);
+    if address != AccountAddress::TWO || declared_in != module_name || declared_name != name {
+        return None;
+    }
+    instantiation.1.first()
+}
+
+/// Datatype by its address, module and name, the same in every module referring to it.
+pub type DatatypeName = (AccountAddress, String, String);
+
+pub(crate) fn datatype_name(module: &CompiledModule, index: DatatypeHandleIndex) -> DatatypeName {
+    let handle = module.datatype_handle_at(index);
+    let m = module.module_handle_at(handle.module);
+    (
+        *module.address_identifier_at(m.address),
+        module.identifier_at(m.name).to_string(),
+        module.identifier_at(handle.name).to_string(),
+    )
+}
+
+pub(crate) fn datatype_index(token: &SignatureToken) -> Option<DatatypeHandleIndex> {
+    match token {
+        SignatureToken::Datatype(index) => Some(*index),
+        SignatureToken::DatatypeInstantiation(i) => Some(i.0),
+        _ => None,
+    }
+}
+
+/// Datatypes the modules of a package pass to `transfer::share_object` or
+/// `transfer::public_share_object`, including ones declared by other modules of the package.
+pub fn shared_types<'m>(
+    modules: impl IntoIterator<Item = &'m CompiledModule>,
+) -> BTreeSet<DatatypeName> {
+    let mut shared = BTreeSet::new();
+    for module in modules {
+        for code in module
+            .function_defs()
+            .iter()
+            .filter_map(|def| def.code.as_ref())
+        {
+            for instruction in &code.code {
+                let Bytecode::CallGeneric(index) = instruction else {
+                    continue;
+                };
+                let instantiation = module.function_instantiation_at(*index);
+                if !is_framework_call(module, instantiation.handle, "transfer", "share_object")
+                    && !is_framework_call(
+                        module,
+                        instantiation.handle,
+                        "transfer",
+                        "public_share_object",
+                    )
+                {
+                    continue;
+                }
+                if let Some(index) = module
+                    .signature_at(instantiation.type_parameters)
+                    .0
+                    .first()
+                    .and_then(datatype_index)
+                {
+                    shared.insert(datatype_name(module, index));
+                }
+            }
+        }
+    }
+    shared
+}
+
+pub(crate) fn own_struct<'m>(
+    module: &'m CompiledModule,
+    token: &SignatureToken,
+) -> Option<&'m StructDefinition> {
+    let index = datatype_index(token)?;
+    module
+        .struct_defs()
+        .iter()
+        .find(|struct_def| struct_def.struct_handle == index)
+}
+
+/// Objects the system shares, like the `Clock`, which anyone can pass.
+const SYSTEM_SHARED: &[(AccountAddress, &str, &str)] = &[
+    (SUI_FRAMEWORK_ADDRESS, "clock", "Clock"),
+    (SUI_FRAMEWORK_ADDRESS, "random", "Random"),
+    (SUI_FRAMEWORK_ADDRESS, "deny_list", "DenyList"),
+    (
+        SUI_FRAMEWORK_ADDRESS,
+        "authenticator_state",
+        "AuthenticatorState",
+    ),
+    (SUI_SYSTEM_ADDRESS, "sui_system", "SuiSystemState"),
+    (BRIDGE_ADDRESS, "bridge", "Bridge"),
+];
+
+/// Objects of types nobody shares, only their holders can pass them. Ones with `store` count
+/// too, holders can hand them on but whoever gets them is trusted all the same.
+pub(crate) fn is_capability_type(
+    module: &CompiledModule,
+    index: DatatypeHandleIndex,
+    shared: &BTreeSet<DatatypeName>,
+) -> bool {
+    let (address, module_name, name) = datatype_name(module, index);
+    module.datatype_handle_at(index).abilities.has_key()
+        && !shared.contains(&(address, module_name.clone(), name.clone()))
+        && !SYSTEM_SHARED.contains(&(address, module_name.as_str(), name.as_str()))
+}
+
+/// Capabilities prove who calls rather than hold anything: they are passed by reference.
+pub(crate) fn is_capability(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    shared: &BTreeSet<DatatypeName>,
+) -> bool {
+    let (SignatureToken::Reference(inner) | SignatureToken::MutableReference(inner)) = token else {
+        return false;
+    };
+    datatype_index(inner).is_some_and(|index| is_capability_type(module, index, shared))
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Solver {
+    Z3,
+    Cvc5,
+}
+
+impl Display for Solver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Solver::Z3 => write!(f, "z3"),
+            Solver::Cvc5 => write!(f, "cvc5"),
+        }
+    }
+}
+
+pub struct Outcome {
+    pub verdict: String,
+    pub model: Option<String>,
+}
+
+impl Solver {
+    pub async fn check(&self, script: &str, timeout: Duration) -> Result<Outcome> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("query.smt2");
+        fs::write(&file_path, script).await?;
+        let mut command = Command::new(self.to_string());
+        match self {
+            Solver::Z3 => command.arg(format!("-T:{}", timeout.as_secs().max(1))),
+            Solver::Cvc5 => command.arg(format!("--tlimit={}", timeout.as_millis())),
+        };
+        let output = command.arg(&file_path).output().await?;
+        fs::remove_file(file_path).await?;
+
+        let stdout = from_utf8(&output.stdout)?;
+        let (first, rest) = stdout.split_once('\n').unwrap_or((stdout, ""));
+        let verdict = match first.trim() {
+            verdict @ ("sat" | "unsat" | "unknown") => verdict,
+            "timeout" => "unknown",
+            _ => {
+                let stderr = from_utf8(&output.stderr)?;
+                if stdout.is_empty() && stderr.contains("timeout") {
+                    "unknown"
+                } else {
+                    bail!("{} failed: {}{}", self, stdout, stderr);
+                }
+            }
+        };
+        Ok(Outcome {
+            verdict: verdict.to_string(),
+            model: (verdict == "sat").then(|| rest.trim().to_string()),
+        })
+    }
+}
@@ -0,0 +1,273 @@
+//! Requests to models, answered by providers scripted in the tests.
+
+mod common;
+
+use std::{
+    collections::VecDeque,
+    env,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::{TestDb, NETWORK};
+use dominion_protector::{
+    ai::{
+        mock::Mock,
+        replay::{Recorder, Replay},
+        AiConfig, Call, Provider, ProviderKind, AI,
+    },
+    db::ai_call_total::AiCallTotal,
+    prompts::Prompts,
+};
+use openai_dive::v1::resources::chat::{
+    ChatCompletionParameters, ChatCompletionParametersBuilder, ChatCompletionResponse, ChatMessage,
+    ChatMessageContent,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sui_types::base_types::ObjectID;
+
+/// Answers with the given texts in turn, keeping the requests it got.
+struct Scripted {
+    answers: Mutex<VecDeque<&'static str>>,
+    requests: Arc<Mutex<Vec<ChatCompletionParameters>>>,
+}
+
+#[async_trait]
+impl Provider for Scripted {
+    async fn complete(&self, params: ChatCompletionParameters) -> Result<ChatCompletionResponse> {
+        let answer = self
+            .answers
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("No answer left");
+        let model = params.model.clone();
+        self.requests.lock().unwrap().push(params);
+        Ok(serde_json::from_value(json!({
+            "id": "scripted",
+            "object": "chat.completion",
+            "created": 0,
+            "model": model,
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": answer},
+                "finish_reason": "stop",
+            }],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15},
+        }))?)
+    }
+}
+
+async fn prompts() -> Prompts {
+    Prompts::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("prompts.yaml"))
+        .await
+        .unwrap()
+}
+
+/// AI answering with `answers` in turn, and the requests it made.
+async fn scripted(
+    answers: &[&'static str],
+    config: &AiConfig,
+) -> (AI, Arc<Mutex<Vec<ChatCompletionParameters>>>) {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let provider = Scripted {
+        answers: Mutex::new(answers.iter().copied().collect()),
+        requests: requests.clone(),
+    };
+    let ai = AI::with_provider(Box::new(provider), prompts().await, config);
+    (ai, requests)
+}
+
+fn call() -> Call {
+    Call::new(&ObjectID::ZERO, NETWORK, "vault", "module.warnings")
+}
+
+fn user(text: &str) -> ChatMessage {
+    ChatMessage::User {
+        content: ChatMessageContent::Text(text.to_string()),
+        name: None,
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Verdict {
+    level: String,
+}
+
+fn verdict_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": { "level": { "type": "string" } },
+        "required": ["level"],
+        "additionalProperties": false,
+    })
+}
+
+#[tokio::test]
+async fn replays_recorded_responses() {
+    let dir = tempfile::tempdir().unwrap();
+    let params = ChatCompletionParametersBuilder::default()
+        .model("model")
+        .messages(vec![user("Is it a scam?")])
+        .build()
+        .unwrap();
+
+    let recorded = Recorder::new(Box::new(Mock::new("Low Risk")), dir.path())
+        .complete(params.clone())
+        .await
+        .unwrap();
+    let replayed = Replay::new(dir.path())
+        .complete(params.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::to_value(&replayed).unwrap(),
+        serde_json::to_value(&recorded).unwrap()
+    );
+
+    // Any change to the request needs a new recording
+    let mut changed = params;
+    changed.messages.push(user("Really?"));
+    let err = Replay::new(dir.path()).complete(changed).await.unwrap_err();
+    assert!(err.to_string().contains("No recorded response"), "{}", err);
+}
+
+#[tokio::test]
+async fn loads_configs_over_the_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ai.yaml");
+    std::fs::write(&path, "provider: mock\nmodel: local\ncache: false\n").unwrap();
+    // The only test reading `AI_CONFIG`
+    env::set_var("AI_CONFIG", &path);
+    let config = AiConfig::load().await.unwrap();
+    env::remove_var("AI_CONFIG");
+
+    assert!(matches!(config.provider, ProviderKind::Mock));
+    assert_eq!(config.model, "local");
+    assert!(!config.cache);
+    let defaults = AiConfig::default();
+    assert_eq!(config.base_url, defaults.base_url);
+    assert_eq!(config.api_key_env, defaults.api_key_env);
+    assert_eq!(config.timeout_secs, defaults.timeout_secs);
+    assert_eq!(config.replay_dir, defaults.replay_dir);
+    assert_eq!(config.prompts, defaults.prompts);
+}
+
+#[tokio::test]
+async fn repairs_invalid_json() {
+    let (ai, requests) = scripted(
+        &["Low Risk", r#"{"level": "Low Risk"}"#],
+        &AiConfig::default(),
+    )
+    .await;
+    let mut messages = vec![user("Rate the risk")];
+
+    let verdict: Verdict = ai
+        .json_request(
+            &call(),
+            &mut messages,
+            "verdict",
+            "Risk level",
+            verdict_schema(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        verdict,
+        Verdict {
+            level: "Low Risk".to_string()
+        }
+    );
+    // Only the accepted answer is kept
+    assert_eq!(messages.len(), 2);
+
+    // The invalid answer is sent back with what is wrong with it
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    let repair = &requests[1].messages;
+    assert_eq!(repair.len(), 3);
+    let ChatMessage::User {
+        content: ChatMessageContent::Text(text),
+        ..
+    } = &repair[2]
+    else {
+        panic!("{:?}", repair[2]);
+    };
+    assert!(text.starts_with("Your answer is not valid"), "{}", text);
+}
+
+#[tokio::test]
+async fn gives_up_on_invalid_json() {
+    let (ai, requests) = scripted(&["{}", "[]", r#"{"level": 1}"#], &AiConfig::default()).await;
+    let mut messages = vec![user("Rate the risk")];
+
+    let err = ai
+        .json_request::<Verdict>(
+            &call(),
+            &mut messages,
+            "verdict",
+            "Risk level",
+            verdict_schema(),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("No valid verdict in 3 attempts"),
+        "{:#}",
+        err
+    );
+    assert_eq!(requests.lock().unwrap().len(), 3);
+    assert_eq!(messages.len(), 1);
+}
+
+async fn rate(ai: &AI) -> Verdict {
+    ai.json_request(
+        &call(),
+        &mut vec![user("Rate the risk")],
+        "verdict",
+        "Risk level",
+        verdict_schema(),
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn caches_only_valid_answers() {
+    let test = TestDb::new().await;
+    let config = AiConfig {
+        prompt_price: 1.0,
+        completion_price: 2.0,
+        ..AiConfig::default()
+    };
+
+    let (ai, _) = scripted(&["Low Risk", r#"{"level": "Low Risk"}"#], &config).await;
+    rate(&ai.with_db(&test.db)).await;
+    // The first request is asked again, its invalid answer was not cached
+    let (ai, requests) = scripted(&[r#"{"level": "High Risk"}"#], &config).await;
+    assert_eq!(rate(&ai.with_db(&test.db)).await.level, "High Risk");
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    // Now it is, no answer is left to give
+    let (ai, requests) = scripted(&[], &config).await;
+    assert_eq!(rate(&ai.with_db(&test.db)).await.level, "High Risk");
+    assert!(requests.lock().unwrap().is_empty());
+
+    let totals = AiCallTotal::load_all(&test.db.pool, Some(NETWORK), None)
+        .await
+        .unwrap();
+    let [total] = totals.as_slice() else {
+        panic!("{:?}", totals);
+    };
+    assert_eq!(total.prompt, "module.warnings");
+    assert_eq!((total.calls, total.cached), (4, 1));
+    // Cached answers are accounted for without tokens costing anything
+    assert_eq!((total.prompt_tokens, total.completion_tokens), (40, 20));
+    assert!(
+        (total.cost - 3.0 * 20.0 / 1_000_000.0).abs() < 1e-12,
+        "{}",
+        total.cost
+    );
+}
@@ -0,0 +1,232 @@
+//! What transaction analysis reports for calls into a package built by hand.
+
+mod builder;
+mod common;
+mod fullnode;
+
+use builder::{package, ModuleBuilder};
+use common::{TestDb, NETWORK};
+use dominion_protector::{
+    analyzer::{ObjectReport, TransactionAnalyzer, TransactionReport},
+    call_graph::Effect,
+    commands::import::import_package,
+    db::{descriptions::SecurityLevel, full_object::save_object},
+};
+use move_binary_format::file_format::{Ability, AbilitySet, Bytecode, SignatureToken, Visibility};
+use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    object::Object,
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::{Argument, Command, ObjectArg, TransactionData},
+};
+
+const GAS_BUDGET: u64 = 50_000_000;
+const GAS_PRICE: u64 = 1_000;
+
+/// Module `vault` with
+/// - `steal(coin: Coin<SUI>, to: address)` sending the coin away
+/// - `peek(coin: &Coin<SUI>)` doing nothing with it
+async fn publish_vault(test: &TestDb) -> ObjectID {
+    let two = AccountAddress::TWO;
+    let mut builder = ModuleBuilder::new(AccountAddress::ZERO, "vault");
+    let sui = builder.datatype(two, "sui", "SUI", AbilitySet::EMPTY | Ability::Drop, 0);
+    let coin = builder.datatype(
+        two,
+        "coin",
+        "Coin",
+        AbilitySet::EMPTY | Ability::Key | Ability::Store,
+        1,
+    );
+    let sui_coin = SignatureToken::DatatypeInstantiation(Box::new((
+        coin,
+        vec![SignatureToken::Datatype(sui)],
+    )));
+    let public_transfer = builder.function(
+        two,
+        "transfer",
+        "public_transfer",
+        vec![SignatureToken::TypeParameter(0), SignatureToken::Address],
+        vec![],
+        1,
+    );
+    let transfer_coin = builder.instantiation(public_transfer, vec![sui_coin.clone()]);
+    builder.add_function(
+        "steal",
+        Visibility::Public,
+        false,
+        vec![sui_coin.clone(), SignatureToken::Address],
+        vec![],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::MoveLoc(1),
+            Bytecode::CallGeneric(transfer_coin),
+            Bytecode::Ret,
+        ],
+    );
+    builder.add_function(
+        "peek",
+        Visibility::Public,
+        false,
+        vec![SignatureToken::Reference(Box::new(sui_coin))],
+        vec![],
+        vec![],
+        vec![Bytecode::MoveLoc(0), Bytecode::Pop, Bytecode::Ret],
+    );
+
+    let package_id = ObjectID::from_hex_literal("0x42").unwrap();
+    import_package(
+        &test.db.pool,
+        package_id,
+        NETWORK,
+        &package(&[&builder.build()]),
+    )
+    .await
+    .unwrap();
+    package_id
+}
+
+/// Coin of the sender, stored like one read from the fullnode.
+async fn save_coin(test: &TestDb, owner: SuiAddress) -> Object {
+    let coin = Object::with_id_owner_gas_for_testing(ObjectID::random(), owner, 1_000_000);
+    save_object(&test.db.pool, NETWORK, &coin).await.unwrap();
+    coin
+}
+
+async fn analyze(
+    test: &TestDb,
+    sender: SuiAddress,
+    builder: ProgrammableTransactionBuilder,
+) -> (TransactionReport, ObjectID) {
+    let client = fullnode::client(NETWORK).await;
+    let gas = Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, 10_000_000_000);
+    let transaction = TransactionData::new_programmable(
+        sender,
+        vec![gas.compute_object_reference()],
+        builder.finish(),
+        GAS_BUDGET,
+        GAS_PRICE,
+    );
+    let report = TransactionAnalyzer::new(&client, &test.db)
+        .analyze(&transaction)
+        .await
+        .unwrap();
+    (report, gas.id())
+}
+
+fn effects(objects: &[ObjectReport]) -> Vec<(ObjectID, Vec<Effect>)> {
+    objects
+        .iter()
+        .map(|object| (object.object_id, object.effects.clone()))
+        .collect()
+}
+
+#[tokio::test]
+async fn flags_calls_sending_objects_away() {
+    let test = TestDb::new().await;
+    let package_id = publish_vault(&test).await;
+    let sender = SuiAddress::random_for_testing_only();
+    let coin = save_coin(&test, sender).await;
+
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let input = builder
+        .obj(ObjectArg::ImmOrOwnedObject(coin.compute_object_reference()))
+        .unwrap();
+    let to = builder.pure(SuiAddress::random_for_testing_only()).unwrap();
+    builder.programmable_move_call(
+        package_id,
+        Identifier::new("vault").unwrap(),
+        Identifier::new("steal").unwrap(),
+        vec![],
+        vec![input, to],
+    );
+    let (report, _) = analyze(&test, sender, builder).await;
+
+    let [command] = report.commands.as_slice() else {
+        panic!("{:?}", report.commands);
+    };
+    assert_eq!(command.kind, "move_call");
+    assert_eq!(
+        command.target.as_deref(),
+        Some(format!("{}::vault::steal", package_id).as_str())
+    );
+    assert_eq!(
+        effects(&command.objects),
+        [(coin.id(), vec![Effect::Transferred])]
+    );
+    assert_eq!(
+        command.objects[0].type_,
+        coin.struct_tag().unwrap().to_canonical_string(true)
+    );
+    assert_eq!(command.security_level, SecurityLevel::HighRisk);
+    assert_eq!(report.security_level, SecurityLevel::HighRisk);
+    // Neither the gas coin nor the framework is stored, so the transaction can not run
+    assert!(report.simulation.is_none());
+    assert!(report
+        .warnings
+        .iter()
+        .any(|warning| warning.starts_with("Simulation failed")));
+}
+
+#[tokio::test]
+async fn follows_coins_through_commands() {
+    let test = TestDb::new().await;
+    let package_id = publish_vault(&test).await;
+    let sender = SuiAddress::random_for_testing_only();
+    let coin = save_coin(&test, sender).await;
+
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let input = builder
+        .obj(ObjectArg::ImmOrOwnedObject(coin.compute_object_reference()))
+        .unwrap();
+    let amount = builder.pure(5u64).unwrap();
+    let to = builder.pure(SuiAddress::random_for_testing_only()).unwrap();
+    let split = builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount]));
+    builder.command(Command::TransferObjects(vec![split], to));
+    builder.programmable_move_call(
+        package_id,
+        Identifier::new("vault").unwrap(),
+        Identifier::new("peek").unwrap(),
+        vec![],
+        vec![input],
+    );
+    builder.command(Command::MergeCoins(Argument::GasCoin, vec![input]));
+    let (report, gas) = analyze(&test, sender, builder).await;
+
+    let kinds = report
+        .commands
+        .iter()
+        .map(|command| command.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            "split_coins",
+            "transfer_objects",
+            "move_call",
+            "merge_coins"
+        ]
+    );
+    let objects = report
+        .commands
+        .iter()
+        .map(|command| effects(&command.objects))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        objects,
+        [
+            vec![],
+            // Split coins are reported as the coin they come from
+            vec![(gas, vec![Effect::Transferred])],
+            // References are only reported when something happens to them
+            vec![],
+            vec![(coin.id(), vec![Effect::Destroyed])],
+        ]
+    );
+    assert!(report
+        .commands
+        .iter()
+        .all(|command| command.security_level == SecurityLevel::LowRisk));
+    assert_eq!(report.security_level, SecurityLevel::LowRisk);
+}
@@ -0,0 +1,93 @@
+//! `bench-prompts` end to end on `scamtest`, with the mock provider instead of a model.
+
+mod common;
+
+use std::path::Path;
+
+use common::{publish_scamtest, TestDb, NETWORK};
+use dominion_protector::{
+    ai::{mock::Mock, AiConfig, AI},
+    commands::{
+        bench_prompts::{bench_package, scores, LabelledPackage},
+        decompile::Kind,
+    },
+    db::{descriptions::SecurityLevel, prompt_bench_result::PromptBenchResult},
+    prompts::Prompts,
+};
+
+async fn mock_ai(answer: &str) -> AI {
+    let prompts = Prompts::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("prompts.yaml"))
+        .await
+        .unwrap();
+    AI::with_provider(Box::new(Mock::new(answer)), prompts, &AiConfig::default())
+}
+
+fn scamtest(scam: bool) -> LabelledPackage {
+    LabelledPackage {
+        name: if scam { "scamtest" } else { "benign" }.to_string(),
+        scam,
+        path: None,
+        id: "0x0".to_string(),
+        network: Some(NETWORK.to_string()),
+    }
+}
+
+#[tokio::test]
+async fn scores_verdicts_against_labels() {
+    let test = TestDb::new().await;
+    publish_scamtest(&test.db).await;
+    let ai = mock_ai("High Risk").await;
+
+    let mut results = vec![];
+    for package in [scamtest(true), scamtest(false)] {
+        let result = bench_package(&test.db, &ai, &Kind::Disassembled, &package)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.security_level, SecurityLevel::HighRisk);
+        result.save(&test.db.pool).await.unwrap();
+        results.push(result);
+    }
+
+    let scores = scores(&PromptBenchResult::load_latest(&test.db.pool).await.unwrap());
+    assert_eq!(scores.len(), 1);
+    let score = &scores[0];
+    assert_eq!(score.description_version, results[0].description_version);
+    assert_eq!(
+        (
+            score.true_positives,
+            score.false_positives,
+            score.false_negatives,
+            score.true_negatives
+        ),
+        (1, 1, 0, 0)
+    );
+    assert_eq!(score.precision(), Some(0.5));
+    assert_eq!(score.recall(), Some(1.0));
+}
+
+#[tokio::test]
+async fn skips_packages_not_stored() {
+    let test = TestDb::new().await;
+    let ai = mock_ai("Low Risk").await;
+    let result = bench_package(&test.db, &ai, &Kind::Disassembled, &scamtest(true))
+        .await
+        .unwrap();
+    assert!(result.is_none());
+}
+
+#[tokio::test]
+async fn skips_local_packages_not_built() {
+    let test = TestDb::new().await;
+    let ai = mock_ai("Low Risk").await;
+    let empty = tempfile::tempdir().unwrap();
+    let package = LabelledPackage {
+        path: Some(empty.path().to_path_buf()),
+        network: None,
+        ..scamtest(true)
+    };
+    let result = bench_package(&test.db, &ai, &Kind::Disassembled, &package)
+        .await
+        .unwrap();
+    assert!(result.is_none());
+}
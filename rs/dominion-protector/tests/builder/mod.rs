@@ -0,0 +1,398 @@
+//! Modules assembled instruction by instruction, for tests that need bytecode the Move compiler
+//! would not produce or that must not depend on compiled fixtures.
+
+// Every test crate uses a different part of it
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+
+use move_binary_format::{
+    file_format::{
+        empty_module, AbilitySet, AddressIdentifierIndex, Bytecode, CodeOffset, CodeUnit, Constant,
+        ConstantPoolIndex, DatatypeHandle, DatatypeHandleIndex, DatatypeTyParameter,
+        EnumDefinition, EnumDefinitionIndex, FieldDefinition, FieldHandle, FieldHandleIndex,
+        FunctionDefinition, FunctionHandle, FunctionHandleIndex, FunctionInstantiation,
+        FunctionInstantiationIndex, IdentifierIndex, JumpTableInner, ModuleHandle,
+        ModuleHandleIndex, Signature, SignatureIndex, SignatureToken, StructDefInstantiation,
+        StructDefInstantiationIndex, StructDefinition, StructDefinitionIndex,
+        StructFieldInformation, TypeSignature, VariantDefinition, VariantHandle,
+        VariantHandleIndex, VariantJumpTable, VariantJumpTableIndex, VariantTag, Visibility,
+    },
+    CompiledModule,
+};
+use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+
+pub struct ModuleBuilder {
+    module: CompiledModule,
+}
+
+impl ModuleBuilder {
+    pub fn new(address: AccountAddress, name: &str) -> Self {
+        let mut module = empty_module();
+        module.address_identifiers[0] = address;
+        module.identifiers[0] = Identifier::new(name).unwrap();
+        Self { module }
+    }
+
+    pub fn identifier(&mut self, name: &str) -> IdentifierIndex {
+        let identifier = Identifier::new(name).unwrap();
+        let identifiers = &mut self.module.identifiers;
+        IdentifierIndex(match identifiers.iter().position(|i| *i == identifier) {
+            Some(index) => index,
+            None => {
+                identifiers.push(identifier);
+                identifiers.len() - 1
+            }
+        } as u16)
+    }
+
+    fn address(&mut self, address: AccountAddress) -> AddressIdentifierIndex {
+        let addresses = &mut self.module.address_identifiers;
+        AddressIdentifierIndex(match addresses.iter().position(|a| *a == address) {
+            Some(index) => index,
+            None => {
+                addresses.push(address);
+                addresses.len() - 1
+            }
+        } as u16)
+    }
+
+    pub fn module_handle(&mut self, address: AccountAddress, name: &str) -> ModuleHandleIndex {
+        let handle = ModuleHandle {
+            address: self.address(address),
+            name: self.identifier(name),
+        };
+        let handles = &mut self.module.module_handles;
+        ModuleHandleIndex(match handles.iter().position(|h| *h == handle) {
+            Some(index) => index,
+            None => {
+                handles.push(handle);
+                handles.len() - 1
+            }
+        } as u16)
+    }
+
+    pub fn signature(&mut self, tokens: Vec<SignatureToken>) -> SignatureIndex {
+        let signature = Signature(tokens);
+        let signatures = &mut self.module.signatures;
+        SignatureIndex(match signatures.iter().position(|s| *s == signature) {
+            Some(index) => index,
+            None => {
+                signatures.push(signature);
+                signatures.len() - 1
+            }
+        } as u16)
+    }
+
+    /// Datatype declared in another module, like `0x2::coin::Coin`.
+    pub fn datatype(
+        &mut self,
+        address: AccountAddress,
+        module_name: &str,
+        name: &str,
+        abilities: AbilitySet,
+        type_parameters: usize,
+    ) -> DatatypeHandleIndex {
+        let module = self.module_handle(address, module_name);
+        self.datatype_handle(module, name, abilities, type_parameters)
+    }
+
+    fn datatype_handle(
+        &mut self,
+        module: ModuleHandleIndex,
+        name: &str,
+        abilities: AbilitySet,
+        type_parameters: usize,
+    ) -> DatatypeHandleIndex {
+        let name = self.identifier(name);
+        let handles = &mut self.module.datatype_handles;
+        if let Some(index) = handles
+            .iter()
+            .position(|h| h.module == module && h.name == name)
+        {
+            return DatatypeHandleIndex(index as u16);
+        }
+        handles.push(DatatypeHandle {
+            module,
+            name,
+            abilities,
+            type_parameters: vec![
+                DatatypeTyParameter {
+                    constraints: AbilitySet::EMPTY,
+                    is_phantom: true,
+                };
+                type_parameters
+            ],
+        });
+        DatatypeHandleIndex(handles.len() as u16 - 1)
+    }
+
+    /// Struct of this module with phantom type parameters.
+    pub fn add_struct(
+        &mut self,
+        name: &str,
+        abilities: AbilitySet,
+        type_parameters: usize,
+        fields: Vec<(&str, SignatureToken)>,
+    ) -> (DatatypeHandleIndex, StructDefinitionIndex) {
+        let handle = self.datatype_handle(
+            self.module.self_handle_idx(),
+            name,
+            abilities,
+            type_parameters,
+        );
+        let fields = fields
+            .into_iter()
+            .map(|(name, signature)| FieldDefinition {
+                name: self.identifier(name),
+                signature: TypeSignature(signature),
+            })
+            .collect();
+        self.module.struct_defs.push(StructDefinition {
+            struct_handle: handle,
+            field_information: StructFieldInformation::Declared(fields),
+        });
+        (
+            handle,
+            StructDefinitionIndex(self.module.struct_defs.len() as u16 - 1),
+        )
+    }
+
+    /// Enum of this module without type parameters, with the fields of each variant.
+    pub fn add_enum(
+        &mut self,
+        name: &str,
+        abilities: AbilitySet,
+        variants: Vec<(&str, Vec<(&str, SignatureToken)>)>,
+    ) -> (DatatypeHandleIndex, EnumDefinitionIndex) {
+        let handle = self.datatype_handle(self.module.self_handle_idx(), name, abilities, 0);
+        let mut definitions = Vec::new();
+        for (name, fields) in variants {
+            let variant_name = self.identifier(name);
+            let fields = fields
+                .into_iter()
+                .map(|(name, signature)| FieldDefinition {
+                    name: self.identifier(name),
+                    signature: TypeSignature(signature),
+                })
+                .collect();
+            definitions.push(VariantDefinition {
+                variant_name,
+                fields,
+            });
+        }
+        self.module.enum_defs.push(EnumDefinition {
+            enum_handle: handle,
+            variants: definitions,
+        });
+        (
+            handle,
+            EnumDefinitionIndex(self.module.enum_defs.len() as u16 - 1),
+        )
+    }
+
+    pub fn variant(
+        &mut self,
+        enum_def: EnumDefinitionIndex,
+        variant: VariantTag,
+    ) -> VariantHandleIndex {
+        let handle = VariantHandle { enum_def, variant };
+        let handles = &mut self.module.variant_handles;
+        VariantHandleIndex(match handles.iter().position(|h| *h == handle) {
+            Some(index) => index,
+            None => {
+                handles.push(handle);
+                handles.len() - 1
+            }
+        } as u16)
+    }
+
+    /// Jump table of the body of `function` for `VariantSwitch`, with the offset each variant
+    /// of `head_enum` jumps to.
+    pub fn jump_table(
+        &mut self,
+        function: FunctionHandleIndex,
+        head_enum: EnumDefinitionIndex,
+        targets: Vec<CodeOffset>,
+    ) -> VariantJumpTableIndex {
+        let code = self
+            .module
+            .function_defs
+            .iter_mut()
+            .find(|def| def.function == function)
+            .and_then(|def| def.code.as_mut())
+            .expect("Function has no body yet");
+        code.jump_tables.push(VariantJumpTable {
+            head_enum,
+            jump_table: JumpTableInner::Full(targets),
+        });
+        VariantJumpTableIndex(code.jump_tables.len() as u16 - 1)
+    }
+
+    pub fn field(&mut self, owner: StructDefinitionIndex, field: u16) -> FieldHandleIndex {
+        let handle = FieldHandle { owner, field };
+        let handles = &mut self.module.field_handles;
+        FieldHandleIndex(match handles.iter().position(|h| *h == handle) {
+            Some(index) => index,
+            None => {
+                handles.push(handle);
+                handles.len() - 1
+            }
+        } as u16)
+    }
+
+    pub fn struct_instantiation(
+        &mut self,
+        def: StructDefinitionIndex,
+        type_arguments: Vec<SignatureToken>,
+    ) -> StructDefInstantiationIndex {
+        let type_parameters = self.signature(type_arguments);
+        self.module
+            .struct_def_instantiations
+            .push(StructDefInstantiation {
+                def,
+                type_parameters,
+            });
+        StructDefInstantiationIndex(self.module.struct_def_instantiations.len() as u16 - 1)
+    }
+
+    pub fn constant(&mut self, type_: SignatureToken, data: Vec<u8>) -> ConstantPoolIndex {
+        self.module.constant_pool.push(Constant { type_, data });
+        ConstantPoolIndex(self.module.constant_pool.len() as u16 - 1)
+    }
+
+    /// Function declared in another module, to be called.
+    pub fn function(
+        &mut self,
+        address: AccountAddress,
+        module_name: &str,
+        name: &str,
+        parameters: Vec<SignatureToken>,
+        returns: Vec<SignatureToken>,
+        type_parameters: usize,
+    ) -> FunctionHandleIndex {
+        let module = self.module_handle(address, module_name);
+        self.function_handle(module, name, parameters, returns, type_parameters)
+    }
+
+    fn function_handle(
+        &mut self,
+        module: ModuleHandleIndex,
+        name: &str,
+        parameters: Vec<SignatureToken>,
+        returns: Vec<SignatureToken>,
+        type_parameters: usize,
+    ) -> FunctionHandleIndex {
+        let name = self.identifier(name);
+        let parameters = self.signature(parameters);
+        let return_ = self.signature(returns);
+        let handles = &mut self.module.function_handles;
+        if let Some(index) = handles
+            .iter()
+            .position(|h| h.module == module && h.name == name)
+        {
+            return FunctionHandleIndex(index as u16);
+        }
+        handles.push(FunctionHandle {
+            module,
+            name,
+            parameters,
+            return_,
+            type_parameters: vec![AbilitySet::EMPTY; type_parameters],
+        });
+        FunctionHandleIndex(handles.len() as u16 - 1)
+    }
+
+    pub fn instantiation(
+        &mut self,
+        handle: FunctionHandleIndex,
+        type_arguments: Vec<SignatureToken>,
+    ) -> FunctionInstantiationIndex {
+        let type_parameters = self.signature(type_arguments);
+        let instantiation = FunctionInstantiation {
+            handle,
+            type_parameters,
+        };
+        let instantiations = &mut self.module.function_instantiations;
+        FunctionInstantiationIndex(
+            match instantiations.iter().position(|i| *i == instantiation) {
+                Some(index) => index,
+                None => {
+                    instantiations.push(instantiation);
+                    instantiations.len() - 1
+                }
+            } as u16,
+        )
+    }
+
+    /// Declares a function of this module, its body is set with [`ModuleBuilder::set_code`] so
+    /// that functions can call each other.
+    pub fn declare(
+        &mut self,
+        name: &str,
+        parameters: Vec<SignatureToken>,
+        returns: Vec<SignatureToken>,
+        type_parameters: usize,
+    ) -> FunctionHandleIndex {
+        let module = self.module.self_handle_idx();
+        self.function_handle(module, name, parameters, returns, type_parameters)
+    }
+
+    pub fn set_code(
+        &mut self,
+        function: FunctionHandleIndex,
+        visibility: Visibility,
+        is_entry: bool,
+        locals: Vec<SignatureToken>,
+        code: Vec<Bytecode>,
+    ) {
+        let locals = self.signature(locals);
+        self.module.function_defs.push(FunctionDefinition {
+            function,
+            visibility,
+            is_entry,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals,
+                code,
+                jump_tables: vec![],
+            }),
+        });
+    }
+
+    /// Declares a non-generic function with its body.
+    pub fn add_function(
+        &mut self,
+        name: &str,
+        visibility: Visibility,
+        is_entry: bool,
+        parameters: Vec<SignatureToken>,
+        returns: Vec<SignatureToken>,
+        locals: Vec<SignatureToken>,
+        code: Vec<Bytecode>,
+    ) -> FunctionHandleIndex {
+        let function = self.declare(name, parameters, returns, 0);
+        self.set_code(function, visibility, is_entry, locals, code);
+        function
+    }
+
+    pub fn build(self) -> CompiledModule {
+        self.module
+    }
+}
+
+pub fn bytecode(module: &CompiledModule) -> Vec<u8> {
+    let mut bytecode = Vec::new();
+    module
+        .serialize_with_version(module.version, &mut bytecode)
+        .unwrap();
+    bytecode
+}
+
+/// Modules by name, the way `import_package` takes them.
+pub fn package(modules: &[&CompiledModule]) -> BTreeMap<String, Vec<u8>> {
+    modules
+        .iter()
+        .map(|module| (module.name().to_string(), bytecode(module)))
+        .collect()
+}
@@ -0,0 +1,220 @@
+//! Who gets the capabilities of a package built by hand.
+
+mod builder;
+mod common;
+mod fullnode;
+
+use std::collections::BTreeMap;
+
+use builder::{package, ModuleBuilder};
+use common::{TestDb, NETWORK};
+use dominion_protector::{
+    capabilities::{capability_graph, Recipient},
+    commands::import::import_package,
+};
+use move_binary_format::file_format::{Ability, AbilitySet, Bytecode, SignatureToken, Visibility};
+use move_core_types::account_address::AccountAddress;
+use sui_types::base_types::ObjectID;
+
+/// Module `admin` with an `AdminCap` its `init` gives to the publisher, and functions taking
+/// `&AdminCap` that mint more of them:
+/// - `mint_overwritten(cap, to)` storing the sender in a local, then `to` over it
+/// - `mint_branch(cap, flag)` storing the sender on one branch and a constant on the other
+/// - `mint_both(cap, flag)` storing the sender on both branches
+/// - `mint_const(cap)` giving it to a constant address
+async fn publish_admin(test: &TestDb, constant: AccountAddress) -> ObjectID {
+    let two = AccountAddress::TWO;
+    let mut builder = ModuleBuilder::new(AccountAddress::ZERO, "admin");
+    let (cap, cap_def) = builder.add_struct(
+        "AdminCap",
+        AbilitySet::EMPTY | Ability::Key,
+        0,
+        vec![("value", SignatureToken::U64)],
+    );
+    let cap = SignatureToken::Datatype(cap);
+    let cap_ref = SignatureToken::Reference(Box::new(cap.clone()));
+    let tx_context = builder.datatype(
+        two,
+        "tx_context",
+        "TxContext",
+        AbilitySet::EMPTY | Ability::Drop,
+        0,
+    );
+    let tx_context = SignatureToken::Datatype(tx_context);
+    let ctx = SignatureToken::MutableReference(Box::new(tx_context.clone()));
+    let sender = builder.function(
+        two,
+        "tx_context",
+        "sender",
+        vec![SignatureToken::Reference(Box::new(tx_context))],
+        vec![SignatureToken::Address],
+        0,
+    );
+    let transfer = builder.function(
+        two,
+        "transfer",
+        "transfer",
+        vec![SignatureToken::TypeParameter(0), SignatureToken::Address],
+        vec![],
+        1,
+    );
+    let transfer = builder.instantiation(transfer, vec![cap.clone()]);
+    let constant = builder.constant(SignatureToken::Address, constant.to_vec());
+
+    // Stores the sender from the context in `ctx` into `local`
+    let store_sender = |ctx, local| {
+        vec![
+            Bytecode::CopyLoc(ctx),
+            Bytecode::FreezeRef,
+            Bytecode::Call(sender),
+            Bytecode::StLoc(local),
+        ]
+    };
+    // Gives a new cap to the address in `local`
+    let give = |local| {
+        vec![
+            Bytecode::LdU64(1),
+            Bytecode::Pack(cap_def),
+            Bytecode::MoveLoc(local),
+            Bytecode::CallGeneric(transfer),
+            Bytecode::Ret,
+        ]
+    };
+
+    builder.add_function(
+        "init",
+        Visibility::Private,
+        false,
+        vec![ctx.clone()],
+        vec![],
+        vec![SignatureToken::Address],
+        [store_sender(0, 1), give(1)].concat(),
+    );
+    builder.add_function(
+        "mint_overwritten",
+        Visibility::Public,
+        false,
+        vec![cap_ref.clone(), SignatureToken::Address, ctx.clone()],
+        vec![],
+        vec![SignatureToken::Address],
+        [
+            store_sender(2, 3),
+            vec![Bytecode::MoveLoc(1), Bytecode::StLoc(3)],
+            give(3),
+        ]
+        .concat(),
+    );
+    // 0: flag, 1: jump to the sender branch at 5, 2-4: constant, then to the join at 9
+    builder.add_function(
+        "mint_branch",
+        Visibility::Public,
+        false,
+        vec![cap_ref.clone(), SignatureToken::Bool, ctx.clone()],
+        vec![],
+        vec![SignatureToken::Address],
+        [
+            vec![
+                Bytecode::MoveLoc(1),
+                Bytecode::BrTrue(5),
+                Bytecode::LdConst(constant),
+                Bytecode::StLoc(3),
+                Bytecode::Branch(9),
+            ],
+            store_sender(2, 3),
+            give(3),
+        ]
+        .concat(),
+    );
+    // 0: flag, 1: jump to the second branch at 7, 2-6: sender, then to the join at 11
+    builder.add_function(
+        "mint_both",
+        Visibility::Public,
+        false,
+        vec![cap_ref.clone(), SignatureToken::Bool, ctx],
+        vec![],
+        vec![SignatureToken::Address],
+        [
+            vec![Bytecode::MoveLoc(1), Bytecode::BrTrue(7)],
+            store_sender(2, 3),
+            vec![Bytecode::Branch(11)],
+            store_sender(2, 3),
+            give(3),
+        ]
+        .concat(),
+    );
+    builder.add_function(
+        "mint_const",
+        Visibility::Public,
+        false,
+        vec![cap_ref],
+        vec![],
+        vec![],
+        vec![
+            Bytecode::LdU64(1),
+            Bytecode::Pack(cap_def),
+            Bytecode::LdConst(constant),
+            Bytecode::CallGeneric(transfer),
+            Bytecode::Ret,
+        ],
+    );
+
+    let package_id = ObjectID::from_hex_literal("0x42").unwrap();
+    import_package(
+        &test.db.pool,
+        package_id,
+        NETWORK,
+        &package(&[&builder.build()]),
+    )
+    .await
+    .unwrap();
+    package_id
+}
+
+#[tokio::test]
+async fn follows_the_sender_through_locals() {
+    let test = TestDb::new().await;
+    let client = fullnode::client(NETWORK).await;
+    let constant = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let package_id = publish_admin(&test, constant).await;
+
+    let graph = capability_graph(&package_id, &client, &test.db)
+        .await
+        .unwrap();
+    let [capability] = graph.capabilities.as_slice() else {
+        panic!("{:?}", graph.capabilities);
+    };
+    assert_eq!(capability.name, "AdminCap");
+    assert!(!capability.transferable);
+    let [holder] = capability.initial_holders.as_slice() else {
+        panic!("{:?}", capability.initial_holders);
+    };
+    assert_eq!(holder.recipient, Recipient::Sender);
+    assert_eq!(holder.call_chain, ["0x42::admin::init"]);
+
+    let minted_by = capability
+        .minted_by
+        .iter()
+        .map(|minter| {
+            let recipients = minter
+                .grants
+                .iter()
+                .map(|grant| grant.recipient.clone())
+                .collect::<Vec<_>>();
+            (minter.function_name.as_str(), recipients)
+        })
+        .collect::<BTreeMap<_, _>>();
+    // A local only holds the sender when nothing else was stored in it on any path
+    assert_eq!(
+        minted_by,
+        BTreeMap::from([
+            ("mint_both", vec![Recipient::Sender]),
+            ("mint_branch", vec![Recipient::Computed]),
+            (
+                "mint_const",
+                vec![Recipient::Address(constant.to_hex_literal())]
+            ),
+            ("mint_overwritten", vec![Recipient::Computed]),
+        ])
+    );
+    assert_eq!(capability.gates.len(), 4);
+}
@@ -0,0 +1,143 @@
+// Every test crate uses a different part of it
+#![allow(dead_code)]
+
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Mutex,
+};
+
+use dominion_protector::{
+    commands::import::{import_package, read_modules},
+    db::Db,
+};
+use move_binary_format::CompiledModule;
+use sui_sdk::types::base_types::ObjectID;
+use tempfile::TempDir;
+
+pub const NETWORK: &str = "test";
+
+/// Postgres cluster living in a temporary directory, reachable only through a socket there.
+struct EphemeralPostgres {
+    dir: TempDir,
+}
+
+impl EphemeralPostgres {
+    fn start() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        run(Command::new("initdb")
+            .arg("-D")
+            .arg(dir.path().join("data"))
+            .args(["-U", "postgres", "--auth=trust", "--no-sync"]));
+        run(Command::new("pg_ctl")
+            .arg("-D")
+            .arg(dir.path().join("data"))
+            .arg("-l")
+            .arg(dir.path().join("log"))
+            .arg("-o")
+            .arg(format!(
+                "-k {} -c listen_addresses='' -c fsync=off",
+                dir.path().display()
+            ))
+            .args(["-w", "start"]));
+        Self { dir }
+    }
+
+    fn url(&self) -> String {
+        format!(
+            "postgres://postgres@localhost/postgres?host={}",
+            self.dir.path().display()
+        )
+    }
+}
+
+impl Drop for EphemeralPostgres {
+    fn drop(&mut self) {
+        let _ = Command::new("pg_ctl")
+            .arg("-D")
+            .arg(self.dir.path().join("data"))
+            .args(["-m", "immediate", "stop"])
+            .output();
+    }
+}
+
+fn run(command: &mut Command) {
+    let output = command
+        .output()
+        .unwrap_or_else(|err| panic!("Can not run {:?}: {}", command, err));
+    assert!(
+        output.status.success(),
+        "{:?} failed: {}",
+        command,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Database for one test, dropped with it. `TEST_DATABASE_URL` points the tests at an
+/// existing empty database instead, for environments where `initdb` can not run.
+pub struct TestDb {
+    pub db: Db,
+    _postgres: Option<EphemeralPostgres>,
+}
+
+impl TestDb {
+    pub async fn new() -> Self {
+        if let Ok(url) = env::var("TEST_DATABASE_URL") {
+            return Self {
+                db: Db::connect(&url).await.unwrap(),
+                _postgres: None,
+            };
+        }
+        let postgres = EphemeralPostgres::start();
+        Self {
+            db: Db::connect(&postgres.url()).await.unwrap(),
+            _postgres: Some(postgres),
+        }
+    }
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/scamtest")
+}
+
+/// Held while the fixtures are built, tests of one crate run in parallel.
+static BUILD: Mutex<()> = Mutex::new(());
+
+/// Bytecode of the `sui/scamtest` modules. When `tests/fixtures/scamtest` has none, they are
+/// built there with its `regenerate.sh`, which needs the `sui` CLI and the network once to
+/// fetch the framework; commit them afterwards so that the tests need neither.
+pub fn scamtest_modules() -> BTreeMap<String, Vec<u8>> {
+    let fixtures = fixtures_dir();
+    {
+        let _build = BUILD
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !fixtures.join("scamtest.mv").exists() {
+            run(Command::new("sh").arg(fixtures.join("regenerate.sh")));
+        }
+    }
+    read_modules(&fixtures).unwrap_or_else(|err| {
+        panic!(
+            "No compiled scamtest modules in {}: {:#}",
+            fixtures.display(),
+            err
+        )
+    })
+}
+
+/// Imports `sui/scamtest` like the `import` command does. Compiled packages keep the `0x0`
+/// address, which becomes the package id.
+pub async fn publish_scamtest(db: &Db) -> (ObjectID, Vec<CompiledModule>) {
+    let package_id = ObjectID::ZERO;
+    let modules = scamtest_modules();
+    import_package(&db.pool, package_id, NETWORK, &modules)
+        .await
+        .unwrap();
+    let compiled = modules
+        .values()
+        .map(|bytecode| CompiledModule::deserialize_with_defaults(bytecode).unwrap())
+        .collect();
+    (package_id, compiled)
+}
@@ -0,0 +1,13 @@
+#!/bin/sh
+# Rebuilds the bytecode of `sui/scamtest` the tests load, which they also run when it is
+# missing. Needs the sui CLI, and the network once to fetch the framework. Commit the .mv
+# files it leaves here.
+set -e
+
+here=$(cd "$(dirname "$0")" && pwd)
+build=$(mktemp -d)
+trap 'rm -rf "$build"' EXIT
+
+sui move build --path "$here/../../../../../sui/scamtest" --install-dir "$build"
+rm -f "$here"/*.mv
+cp "$build"/build/scamtest/bytecode_modules/*.mv "$here"
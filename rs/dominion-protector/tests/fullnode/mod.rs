@@ -0,0 +1,91 @@
+//! Fullnode that knows no object, for tests of code falling back to the network for whatever
+//! the database misses.
+
+// Not every test crate takes the fullnode down
+#![allow(dead_code)]
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use dominion_protector::sui_client::SuiClientWithNetwork;
+use serde_json::{json, Value};
+use sui_protocol_config::ProtocolVersion;
+use sui_sdk::SuiClientBuilder;
+
+const METHODS: &[&str] = &["rpc.discover", "sui_getObject", "sui_getProtocolConfig"];
+
+/// Client of a fullnode serving on a local port until the test ends. The chain is at the
+/// newest protocol version this build supports.
+pub async fn client(network: &str) -> SuiClientWithNetwork {
+    serve(network).await.0
+}
+
+/// Client of a fullnode that went down right after the client connected, every request fails.
+pub async fn unreachable_client(network: &str) -> SuiClientWithNetwork {
+    let (client, down) = serve(network).await;
+    down.store(true, Ordering::SeqCst);
+    client
+}
+
+async fn serve(network: &str) -> (SuiClientWithNetwork, Arc<AtomicBool>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let down = Arc::new(AtomicBool::new(false));
+    let router = Router::new()
+        .route("/", post(handle))
+        .with_state(down.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    let client = SuiClientWithNetwork {
+        client: SuiClientBuilder::default().build(url).await.unwrap(),
+        network: network.to_string(),
+    };
+    (client, down)
+}
+
+async fn handle(
+    State(down): State<Arc<AtomicBool>>,
+    Json(request): Json<Value>,
+) -> Result<Json<Value>, StatusCode> {
+    if down.load(Ordering::SeqCst) {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    let version = ProtocolVersion::MAX.as_u64().to_string();
+    let outcome = match request["method"].as_str() {
+        Some("rpc.discover") => json!({
+            "result": {
+                "openrpc": "1.2.6",
+                "info": { "version": "1.0.0" },
+                "methods": METHODS
+                    .iter()
+                    .map(|name| json!({ "name": name }))
+                    .collect::<Vec<_>>(),
+            }
+        }),
+        Some("sui_getObject") => json!({
+            "result": {
+                "error": { "code": "notExists", "object_id": request["params"][0] }
+            }
+        }),
+        Some("sui_getProtocolConfig") => json!({
+            "result": {
+                "minSupportedProtocolVersion": "1",
+                "maxSupportedProtocolVersion": version,
+                "protocolVersion": version,
+                "featureFlags": {},
+                "attributes": {},
+            }
+        }),
+        method => json!({
+            "error": { "code": -32601, "message": format!("{:?} is not served", method) }
+        }),
+    };
+    let mut response = json!({ "jsonrpc": "2.0", "id": request["id"] });
+    response
+        .as_object_mut()
+        .unwrap()
+        .extend(outcome.as_object().unwrap().clone());
+    Ok(Json(response))
+}
@@ -0,0 +1,149 @@
+//! Differences between versions of a package built by hand.
+
+mod builder;
+mod common;
+mod fullnode;
+
+use builder::{package, ModuleBuilder};
+use common::{TestDb, NETWORK};
+use dominion_protector::{
+    commands::import::import_package,
+    db::descriptions::SecurityLevel,
+    lineage::{diff, ChangeKind, PackageDiff},
+};
+use move_binary_format::{
+    file_format::{Ability, AbilitySet, Bytecode, FunctionHandleIndex, SignatureToken, Visibility},
+    CompiledModule,
+};
+use move_core_types::account_address::AccountAddress;
+use sui_types::base_types::ObjectID;
+
+/// Module `vault` with bodyless `functions`, in that order, and an `init` whose code is made
+/// from the handle of `keep`.
+fn vault(
+    functions: &[(&str, Visibility)],
+    init: Option<fn(FunctionHandleIndex) -> Vec<Bytecode>>,
+) -> CompiledModule {
+    let mut builder = ModuleBuilder::new(AccountAddress::ZERO, "vault");
+    let mut keep = None;
+    for (name, visibility) in functions {
+        let handle = builder.add_function(
+            name,
+            *visibility,
+            false,
+            vec![],
+            vec![],
+            vec![],
+            vec![Bytecode::Ret],
+        );
+        if *name == "keep" {
+            keep = Some(handle);
+        }
+    }
+    if let Some(init) = init {
+        let tx_context = builder.datatype(
+            AccountAddress::TWO,
+            "tx_context",
+            "TxContext",
+            AbilitySet::EMPTY | Ability::Drop,
+            0,
+        );
+        builder.add_function(
+            "init",
+            Visibility::Private,
+            false,
+            vec![SignatureToken::MutableReference(Box::new(
+                SignatureToken::Datatype(tx_context),
+            ))],
+            vec![],
+            vec![],
+            init(keep.unwrap()),
+        );
+    }
+    builder.build()
+}
+
+fn calls_keep(keep: FunctionHandleIndex) -> Vec<Bytecode> {
+    vec![
+        Bytecode::Call(keep),
+        Bytecode::MoveLoc(0),
+        Bytecode::Pop,
+        Bytecode::Ret,
+    ]
+}
+
+fn loads_and_calls_keep(keep: FunctionHandleIndex) -> Vec<Bytecode> {
+    let mut code = vec![Bytecode::LdU64(1), Bytecode::Pop];
+    code.extend(calls_keep(keep));
+    code
+}
+
+const V1: &[(&str, Visibility)] = &[
+    ("keep", Visibility::Public),
+    ("gone", Visibility::Public),
+    ("grow", Visibility::Private),
+];
+
+async fn diff_versions(old: CompiledModule, new: CompiledModule) -> PackageDiff {
+    let test = TestDb::new().await;
+    let client = fullnode::client(NETWORK).await;
+    let ids = ["0x42", "0x43"].map(|id| ObjectID::from_hex_literal(id).unwrap());
+    for (package_id, module) in ids.iter().zip([old, new]) {
+        import_package(&test.db.pool, *package_id, NETWORK, &package(&[&module]))
+            .await
+            .unwrap();
+    }
+    diff(&ids[0], &ids[1], &client, &test.db).await.unwrap()
+}
+
+fn changes(diff: &PackageDiff) -> Vec<(ChangeKind, &str)> {
+    diff.changes
+        .iter()
+        .map(|change| (change.kind, change.name.as_deref().unwrap_or_default()))
+        .collect()
+}
+
+#[tokio::test]
+async fn reports_function_changes() {
+    // Declaring `fresh` first moves every handle `init` refers to without changing it
+    let diff = diff_versions(
+        vault(V1, Some(calls_keep)),
+        vault(
+            &[
+                ("fresh", Visibility::Public),
+                ("keep", Visibility::Public),
+                ("grow", Visibility::Public),
+            ],
+            Some(calls_keep),
+        ),
+    )
+    .await;
+    assert_eq!(
+        changes(&diff),
+        [
+            (ChangeKind::FunctionAdded, "fresh"),
+            (ChangeKind::VisibilityChanged, "grow"),
+            (ChangeKind::FunctionRemoved, "gone"),
+        ]
+    );
+    assert_eq!(diff.changes[1].security_level, SecurityLevel::HighRisk);
+    assert_eq!(diff.security_level, SecurityLevel::HighRisk);
+}
+
+#[tokio::test]
+async fn reports_init_changes() {
+    let diff = diff_versions(
+        vault(V1, Some(calls_keep)),
+        vault(V1, Some(loads_and_calls_keep)),
+    )
+    .await;
+    assert_eq!(changes(&diff), [(ChangeKind::InitChanged, "init")]);
+    assert_eq!(diff.security_level, SecurityLevel::MediumRisk);
+
+    let diff = diff_versions(vault(V1, None), vault(V1, Some(calls_keep))).await;
+    assert_eq!(changes(&diff), [(ChangeKind::InitAdded, "init")]);
+
+    let diff = diff_versions(vault(V1, Some(calls_keep)), vault(V1, Some(calls_keep))).await;
+    assert!(diff.changes.is_empty(), "{:?}", diff.changes);
+    assert_eq!(diff.security_level, SecurityLevel::LowRisk);
+}
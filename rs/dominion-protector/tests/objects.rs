@@ -0,0 +1,257 @@
+//! Contents of Move objects, decoded with the layouts of the packages of their types.
+
+mod builder;
+mod common;
+
+use std::collections::BTreeMap;
+
+use builder::{bytecode, ModuleBuilder};
+use chrono::Utc;
+use common::{TestDb, NETWORK};
+use dominion_protector::{
+    db::{
+        full_module::save_module,
+        full_object::{decode_saved_objects, save_objects},
+        move_object::MoveObject,
+        object::{Object as DbObject, OwnerType},
+        package_linkage::PackageLinkage,
+    },
+    layout::PackageLayouts,
+};
+use move_binary_format::{
+    file_format::{Ability, AbilitySet, SignatureToken},
+    CompiledModule,
+};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::StructTag,
+};
+use serde_json::{json, Value};
+use sui_framework::BuiltInFramework;
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    digests::ObjectDigest,
+    object::Object,
+    MOVE_STDLIB_PACKAGE_ID, SUI_FRAMEWORK_PACKAGE_ID,
+};
+
+/// Packages `0x2::coin::Coin<0x2::sui::SUI>` is made of.
+fn framework() -> Vec<Object> {
+    BuiltInFramework::iter_system_packages()
+        .filter(|package| [MOVE_STDLIB_PACKAGE_ID, SUI_FRAMEWORK_PACKAGE_ID].contains(&package.id))
+        .map(|package| package.genesis_object())
+        .collect()
+}
+
+fn coin() -> Object {
+    Object::with_id_owner_gas_for_testing(
+        ObjectID::random(),
+        SuiAddress::random_for_testing_only(),
+        1_000,
+    )
+}
+
+async fn contents(test: &TestDb, object: &Object) -> Option<serde_json::Value> {
+    MoveObject::load(&test.db.pool, &object.id(), NETWORK)
+        .await
+        .unwrap()
+        .unwrap()
+        .contents
+}
+
+#[tokio::test]
+async fn saves_packages_before_objects_of_the_batch() {
+    let test = TestDb::new().await;
+    let coin = coin();
+    let mut batch = vec![coin.clone()];
+    batch.extend(framework());
+
+    save_objects(&test.db.pool, NETWORK, &batch).await.unwrap();
+    let contents = contents(&test, &coin).await.unwrap();
+    assert_eq!(contents["balance"]["value"], "1000", "{}", contents);
+}
+
+#[tokio::test]
+async fn decodes_objects_saved_before_their_package() {
+    let test = TestDb::new().await;
+    let coin = coin();
+    save_objects(&test.db.pool, NETWORK, &[coin.clone()])
+        .await
+        .unwrap();
+    assert_eq!(contents(&test, &coin).await, None);
+    assert_eq!(
+        decode_saved_objects(&test.db.pool, NETWORK).await.unwrap(),
+        0
+    );
+
+    save_objects(&test.db.pool, NETWORK, &framework())
+        .await
+        .unwrap();
+    assert_eq!(
+        decode_saved_objects(&test.db.pool, NETWORK).await.unwrap(),
+        1
+    );
+    assert!(contents(&test, &coin).await.is_some());
+    // Nothing is left to decode
+    assert_eq!(
+        decode_saved_objects(&test.db.pool, NETWORK).await.unwrap(),
+        0
+    );
+}
+
+/// Objects holding a `u64` after their `UID`: `0x42::m::S`, and `0x42::m::T` from the upgrade
+/// at `0x43` when `upgraded`.
+fn release(upgraded: bool) -> CompiledModule {
+    let mut builder = ModuleBuilder::new(AccountAddress::from_hex_literal("0x42").unwrap(), "m");
+    let uid = SignatureToken::Datatype(builder.datatype(
+        AccountAddress::TWO,
+        "object",
+        "UID",
+        AbilitySet::EMPTY | Ability::Store,
+        0,
+    ));
+    let object = AbilitySet::EMPTY | Ability::Key | Ability::Store;
+    let fields = |name| vec![("id", uid.clone()), (name, SignatureToken::U64)];
+    builder.add_struct("S", object, 0, fields("value"));
+    if upgraded {
+        builder.add_struct("T", object, 0, fields("count"));
+    }
+    builder.build()
+}
+
+/// Module `0x44::n` wrapping the `T` of the upgrade into `Wrapper`.
+fn wrapper() -> CompiledModule {
+    let mut builder = ModuleBuilder::new(AccountAddress::from_hex_literal("0x44").unwrap(), "n");
+    let store = AbilitySet::EMPTY | Ability::Store;
+    let uid = builder.datatype(AccountAddress::TWO, "object", "UID", store, 0);
+    let t = builder.datatype(
+        AccountAddress::from_hex_literal("0x42").unwrap(),
+        "m",
+        "T",
+        store | Ability::Key,
+        0,
+    );
+    builder.add_struct(
+        "Wrapper",
+        store | Ability::Key,
+        0,
+        vec![
+            ("id", SignatureToken::Datatype(uid)),
+            ("inner", SignatureToken::Datatype(t)),
+        ],
+    );
+    builder.build()
+}
+
+/// Stores the package the way downloading it does, with the package each of its types
+/// originates from and the versions of its dependencies.
+async fn save_package(
+    test: &TestDb,
+    package_id: &str,
+    module: &CompiledModule,
+    origins: &[(&str, &str)],
+    linkage: &[(&str, &str)],
+) {
+    let package_id = ObjectID::from_hex_literal(package_id).unwrap();
+    DbObject {
+        object_id: package_id.to_string(),
+        network: NETWORK.to_string(),
+        version: 1,
+        digest: ObjectDigest::new([0; 32]).to_string(),
+        object_type: "package".to_string(),
+        owner_type: OwnerType::Immutable,
+        owner: None,
+        initial_shared_version: None,
+        read_at: Utc::now(),
+    }
+    .save(&test.db.pool)
+    .await
+    .unwrap();
+    let module_name = module.name().to_string();
+    let origins: BTreeMap<_, _> = origins
+        .iter()
+        .map(|(name, origin)| {
+            (
+                (module_name.clone(), name.to_string()),
+                ObjectID::from_hex_literal(origin).unwrap(),
+            )
+        })
+        .collect();
+    save_module(
+        &test.db.pool,
+        package_id,
+        NETWORK,
+        &module_name,
+        &bytecode(module),
+        &origins,
+    )
+    .await
+    .unwrap();
+    for (dependency_id, upgraded_id) in linkage {
+        PackageLinkage {
+            package_id: package_id.to_string(),
+            network: NETWORK.to_string(),
+            dependency_id: ObjectID::from_hex_literal(dependency_id)
+                .unwrap()
+                .to_string(),
+            upgraded_id: ObjectID::from_hex_literal(upgraded_id).unwrap().to_string(),
+            upgraded_version: 2,
+        }
+        .save(&test.db.pool)
+        .await
+        .unwrap();
+    }
+}
+
+/// Contents of `0x<address>::<module>::<name>` read from `bcs`.
+async fn decode(test: &TestDb, address: &str, module: &str, name: &str, bcs: &[u8]) -> Value {
+    let type_ = StructTag {
+        address: AccountAddress::from_hex_literal(address).unwrap(),
+        module: Identifier::new(module).unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params: vec![],
+    };
+    PackageLayouts::load(&test.db.pool, NETWORK, &type_)
+        .await
+        .unwrap()
+        .to_json(&type_, bcs)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn decodes_types_added_by_upgrades() {
+    let test = TestDb::new().await;
+    save_package(&test, "0x42", &release(false), &[("S", "0x42")], &[]).await;
+    // Upgrades keep the original id in their bytecode
+    save_package(
+        &test,
+        "0x43",
+        &release(true),
+        &[("S", "0x42"), ("T", "0x43")],
+        &[],
+    )
+    .await;
+    save_package(
+        &test,
+        "0x44",
+        &wrapper(),
+        &[("Wrapper", "0x44")],
+        &[("0x42", "0x43")],
+    )
+    .await;
+
+    let id = AccountAddress::random();
+    let mut object = id.to_vec();
+    object.extend(7u64.to_le_bytes());
+    let contents = json!({ "id": { "id": id.to_hex_literal() }, "count": "7" });
+    assert_eq!(decode(&test, "0x43", "m", "T", &object).await, contents);
+    let original = json!({ "id": { "id": id.to_hex_literal() }, "value": "7" });
+    assert_eq!(decode(&test, "0x42", "m", "S", &object).await, original);
+
+    // `0x44` names `T` by the original id, its linkage says where `T` is
+    let mut wrapped = id.to_vec();
+    wrapped.extend(&object);
+    assert_eq!(
+        decode(&test, "0x44", "n", "Wrapper", &wrapped).await,
+        json!({ "id": { "id": id.to_hex_literal() }, "inner": contents })
+    );
+}
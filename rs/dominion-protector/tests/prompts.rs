@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use dominion_protector::prompts::{Prompt, Prompts};
+
+const PROMPTS: &str = "
+developer: You audit Move code
+module:
+  input:
+    version: v2
+    template: '{dependencies}{source}'
+  description: Describe {module_name}
+  security_level: Rate it
+  warnings: List warnings
+structure:
+  usage: '{usage_facts}'
+  description: Describe {struct_name}
+  ownership: Ownership of {struct_name}
+  warnings: Warnings about {struct_name}
+";
+
+fn prompt(template: &str) -> Prompt {
+    Prompt {
+        version: "1".to_string(),
+        template: template.to_string(),
+    }
+}
+
+#[test]
+fn renders_variables_without_touching_their_values() {
+    let rendered = prompt("Struct `{struct_name}` of ```move {source}```")
+        .render(&[("struct_name", "Pool"), ("source", "struct Pool {balance}")])
+        .unwrap();
+    assert_eq!(
+        rendered,
+        "Struct `Pool` of ```move struct Pool {balance}```"
+    );
+}
+
+#[test]
+fn keeps_braces_around_anything_else() {
+    let rendered = prompt(r#"Return {"a": 1} or { } for {name}"#)
+        .render(&[("name", "x")])
+        .unwrap();
+    assert_eq!(rendered, r#"Return {"a": 1} or { } for x"#);
+}
+
+#[test]
+fn rejects_unknown_variables() {
+    assert!(prompt("{missing}").render(&[]).is_err());
+}
+
+#[test]
+fn versions_prompts_by_content_unless_set() {
+    let prompts = Prompts::from_yaml(PROMPTS.as_bytes()).unwrap();
+    assert_eq!(prompts.get("module.input").unwrap().version, "v2");
+
+    let edited = Prompts::from_yaml(
+        PROMPTS
+            .replace("Describe {module_name}", "Sum up {module_name}")
+            .as_bytes(),
+    )
+    .unwrap();
+    let names = ["developer", "module.description"];
+    assert_eq!(
+        edited.versions(&names)["developer"],
+        prompts.versions(&names)["developer"]
+    );
+    assert_ne!(
+        edited.versions(&names)["module.description"],
+        prompts.versions(&names)["module.description"]
+    );
+}
+
+#[test]
+fn requires_the_describe_prompts() {
+    let missing = PROMPTS.replace("  security_level: Rate it\n", "");
+    assert!(Prompts::from_yaml(missing.as_bytes()).is_err());
+}
+
+#[tokio::test]
+async fn loads_the_shipped_prompts() {
+    Prompts::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("prompts.yaml"))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,230 @@
+//! Rules run over packages built by hand.
+
+mod builder;
+mod common;
+
+use std::collections::BTreeSet;
+
+use builder::{package, ModuleBuilder};
+use common::{TestDb, NETWORK};
+use dominion_protector::{
+    commands::import::import_package,
+    rules::{admin_withdraw::AdminWithdraw, Package, Rule},
+};
+use move_binary_format::{
+    file_format::{Ability, AbilitySet, Bytecode, SignatureToken, Visibility},
+    CompiledModule,
+};
+use move_core_types::account_address::AccountAddress;
+use sui_types::base_types::ObjectID;
+
+fn reference(token: SignatureToken) -> SignatureToken {
+    SignatureToken::Reference(Box::new(token))
+}
+
+fn mutable(token: SignatureToken) -> SignatureToken {
+    SignatureToken::MutableReference(Box::new(token))
+}
+
+/// Module `vault` with a `Vault` and a `Treasury` holding `Balance<TOKEN>`, and functions
+/// splitting balances:
+/// - `drain(cap: &AdminCap, vault: &mut Vault, amount: u64)` out of the vault
+/// - `drain_kept(cap: &AdminCap, vault: &mut Vault, amount: u64)` through a local
+/// - `skim(cap: &AdminCap, treasury: &mut Treasury, amount: u64)` out of the treasury
+/// - `split_own(cap: &AdminCap, funds: &mut Balance<TOKEN>, amount: u64)` out of the caller's
+/// - `withdraw(vault: &mut Vault, amount: u64)` without a capability
+/// - `ticket_drain(ticket: &Ticket, vault: &mut Vault, amount: u64)`, `Ticket` having `store`,
+///   which makes it no less of a capability
+fn vault() -> CompiledModule {
+    let two = AccountAddress::TWO;
+    let mut builder = ModuleBuilder::new(AccountAddress::ZERO, "vault");
+    let (token, _) = builder.add_struct(
+        "TOKEN",
+        AbilitySet::EMPTY | Ability::Drop,
+        0,
+        vec![("dummy_field", SignatureToken::Bool)],
+    );
+    let token = SignatureToken::Datatype(token);
+    let balance = builder.datatype(
+        two,
+        "balance",
+        "Balance",
+        AbilitySet::EMPTY | Ability::Store,
+        1,
+    );
+    let balance_of =
+        |t: SignatureToken| SignatureToken::DatatypeInstantiation(Box::new((balance, vec![t])));
+    let mut object = |name: &str, abilities: AbilitySet, fields: Vec<(&str, SignatureToken)>| {
+        builder.add_struct(name, abilities, 0, fields)
+    };
+    let key = AbilitySet::EMPTY | Ability::Key;
+    let funds = vec![("funds", balance_of(token.clone()))];
+    let (vault, vault_def) = object("Vault", key, funds.clone());
+    let (treasury, treasury_def) = object("Treasury", key, funds);
+    let flag = vec![("dummy_field", SignatureToken::Bool)];
+    let (admin_cap, _) = object("AdminCap", key, flag.clone());
+    let (ticket, _) = object("Ticket", key | Ability::Store, flag);
+    let [vault, treasury, admin_cap, ticket] =
+        [vault, treasury, admin_cap, ticket].map(SignatureToken::Datatype);
+    let vault_funds = builder.field(vault_def, 0);
+    let treasury_funds = builder.field(treasury_def, 0);
+    let split = builder.function(
+        two,
+        "balance",
+        "split",
+        vec![
+            mutable(balance_of(SignatureToken::TypeParameter(0))),
+            SignatureToken::U64,
+        ],
+        vec![balance_of(SignatureToken::TypeParameter(0))],
+        1,
+    );
+    let split = builder.instantiation(split, vec![token.clone()]);
+
+    let mut add = |name: &str, params: Vec<SignatureToken>, locals, code| {
+        builder.add_function(
+            name,
+            Visibility::Public,
+            false,
+            params,
+            vec![balance_of(token.clone())],
+            locals,
+            code,
+        );
+    };
+    let split_field = |field| {
+        vec![
+            Bytecode::MoveLoc(1),
+            Bytecode::MutBorrowField(field),
+            Bytecode::MoveLoc(2),
+            Bytecode::CallGeneric(split),
+            Bytecode::Ret,
+        ]
+    };
+    let cap = reference(admin_cap);
+    add(
+        "drain",
+        vec![cap.clone(), mutable(vault.clone()), SignatureToken::U64],
+        vec![],
+        split_field(vault_funds),
+    );
+    add(
+        "drain_kept",
+        vec![cap.clone(), mutable(vault.clone()), SignatureToken::U64],
+        vec![mutable(balance_of(token.clone()))],
+        vec![
+            Bytecode::MoveLoc(1),
+            Bytecode::MutBorrowField(vault_funds),
+            Bytecode::StLoc(3),
+            Bytecode::MoveLoc(3),
+            Bytecode::MoveLoc(2),
+            Bytecode::CallGeneric(split),
+            Bytecode::Ret,
+        ],
+    );
+    add(
+        "skim",
+        vec![cap.clone(), mutable(treasury), SignatureToken::U64],
+        vec![],
+        split_field(treasury_funds),
+    );
+    add(
+        "split_own",
+        vec![cap, mutable(balance_of(token.clone())), SignatureToken::U64],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(1),
+            Bytecode::MoveLoc(2),
+            Bytecode::CallGeneric(split),
+            Bytecode::Ret,
+        ],
+    );
+    add(
+        "withdraw",
+        vec![mutable(vault.clone()), SignatureToken::U64],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::MutBorrowField(vault_funds),
+            Bytecode::MoveLoc(1),
+            Bytecode::CallGeneric(split),
+            Bytecode::Ret,
+        ],
+    );
+    add(
+        "ticket_drain",
+        vec![reference(ticket), mutable(vault), SignatureToken::U64],
+        vec![],
+        split_field(vault_funds),
+    );
+    builder.build()
+}
+
+/// Module `setup` sharing the `Vault` of its sibling module.
+fn setup() -> CompiledModule {
+    let mut builder = ModuleBuilder::new(AccountAddress::ZERO, "setup");
+    let vault = SignatureToken::Datatype(builder.datatype(
+        AccountAddress::ZERO,
+        "vault",
+        "Vault",
+        AbilitySet::EMPTY | Ability::Key,
+        0,
+    ));
+    let share_object = builder.function(
+        AccountAddress::TWO,
+        "transfer",
+        "share_object",
+        vec![SignatureToken::TypeParameter(0)],
+        vec![],
+        1,
+    );
+    let share_object = builder.instantiation(share_object, vec![vault.clone()]);
+    builder.add_function(
+        "share",
+        Visibility::Public,
+        false,
+        vec![vault],
+        vec![],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::CallGeneric(share_object),
+            Bytecode::Ret,
+        ],
+    );
+    builder.build()
+}
+
+#[tokio::test]
+async fn finds_capability_holders_draining_shared_objects() {
+    let test = TestDb::new().await;
+    let package_id = ObjectID::from_hex_literal("0x42").unwrap();
+    import_package(
+        &test.db.pool,
+        package_id,
+        NETWORK,
+        &package(&[&vault(), &setup()]),
+    )
+    .await
+    .unwrap();
+    let package = Package::load_stored(&package_id, NETWORK, &test.db)
+        .await
+        .unwrap();
+
+    let findings = AdminWithdraw.check(&package);
+    // `Vault` is shared by `setup`, `Treasury` by nobody
+    assert_eq!(
+        findings
+            .iter()
+            .map(|finding| (finding.member_name.as_str(), finding.code_offset))
+            .collect::<BTreeSet<_>>(),
+        BTreeSet::from([
+            ("drain", Some(3)),
+            ("drain_kept", Some(5)),
+            ("ticket_drain", Some(3))
+        ])
+    );
+    assert!(findings
+        .iter()
+        .all(|finding| finding.message.ends_with("the shared Vault")));
+}
@@ -0,0 +1,431 @@
+//! Ground truth from `sui/scamtest`, a package written to behave like a scam.
+
+mod common;
+mod fullnode;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
+
+use common::{publish_scamtest, TestDb, NETWORK};
+use dominion_protector::{
+    capabilities::{capability_graph, Recipient},
+    db::{
+        finding::Finding,
+        full_object::load_object,
+        function::{Function, Visibility},
+        structure::Structure,
+    },
+    decompiler::{decompile_module_to_smt, decompile_module_with_disasm},
+    rules::{all_rules, Package},
+    verifier::{shared_types, targets, Solver, SHARED_WRITE_WITHOUT_CAPABILITY},
+};
+use sui_sdk::rpc_types::SuiRawData;
+
+fn names<'a>(items: impl IntoIterator<Item = (&'a str, &'a str)>) -> BTreeSet<String> {
+    items
+        .into_iter()
+        .map(|(module, member)| format!("{}::{}", module, member))
+        .collect()
+}
+
+#[tokio::test]
+async fn saves_structures_and_functions() {
+    let test = TestDb::new().await;
+    let (package_id, _) = publish_scamtest(&test.db).await;
+
+    let structures =
+        Structure::load_all_by_package(&test.db.pool, &package_id.to_string(), NETWORK)
+            .await
+            .unwrap();
+    let objects = names(
+        structures
+            .iter()
+            .filter(|structure| structure.has_key)
+            .map(|structure| {
+                (
+                    structure.module_name.as_str(),
+                    structure.datatype_name.as_str(),
+                )
+            }),
+    );
+    assert_eq!(
+        objects,
+        names([
+            ("scamtest", "Scamtest"),
+            ("scamtest", "AdminCap"),
+            ("scamtest", "OperatorCap"),
+        ])
+    );
+    let witnesses = structures
+        .iter()
+        .filter(|structure| structure.datatype_name == "TST" || structure.datatype_name == "WIN")
+        .collect::<Vec<_>>();
+    assert_eq!(witnesses.len(), 2);
+    assert!(witnesses
+        .iter()
+        .all(|witness| witness.has_drop && !witness.has_store && witness.field_count == 1));
+
+    let functions = Function::load_all_by_package(&test.db.pool, &package_id.to_string(), NETWORK)
+        .await
+        .unwrap();
+    let function = |module: &str, name: &str| {
+        functions
+            .iter()
+            .find(|function| function.module_name == module && function.function_name == name)
+            .unwrap_or_else(|| panic!("{}::{} is not stored", module, name))
+    };
+    assert_eq!(
+        names(
+            functions
+                .iter()
+                .filter(|function| function.is_initializer)
+                .map(|function| (
+                    function.module_name.as_str(),
+                    function.function_name.as_str()
+                ))
+        ),
+        names([("tst", "init"), ("win", "init")])
+    );
+    let place_bet_to = function("scamtest", "place_bet_to");
+    assert!(place_bet_to.is_entry);
+    assert_eq!(place_bet_to.visibility, Visibility::Private);
+    assert_eq!(place_bet_to.type_argument_count, 2);
+    assert_eq!(place_bet_to.parameter_count, 5);
+    let mint_to = function("tst", "mint_to");
+    assert!(mint_to.is_entry);
+    assert_eq!(mint_to.visibility, Visibility::Public);
+    let new = function("scamtest", "new");
+    assert!(!new.is_entry);
+    assert_eq!(new.return_count, 2);
+
+    // Stored packages load back byte for byte
+    let object = load_object(&test.db.pool, NETWORK, &package_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let Some(SuiRawData::Package(package)) = object.bcs else {
+        panic!("Stored package is not loaded as a package");
+    };
+    assert_eq!(package.module_map, common::scamtest_modules());
+    assert_eq!(package.type_origin_table.len(), structures.len());
+}
+
+#[tokio::test]
+async fn disassembles_modules() {
+    let test = TestDb::new().await;
+    let (package_id, modules) = publish_scamtest(&test.db).await;
+    for module in &modules {
+        let source = decompile_module_with_disasm(&test.db.pool, NETWORK, package_id, module)
+            .await
+            .unwrap();
+        assert_eq!(source.kind, "disassembled");
+        for def in module.function_defs() {
+            let name = module.identifier_at(module.function_handle_at(def.function).name);
+            assert!(
+                source.source.contains(name.as_str()),
+                "{} is missing from the disassembly of {}",
+                name,
+                module.self_id().name()
+            );
+        }
+    }
+}
+
+/// Whether each property is violated, by function, for the scam modules. `win` has no public
+/// or entry function to check. Calls into generic functions are uninterpreted, anything can
+/// come out of them.
+const VERDICTS: &[(&str, &str, &[(&str, bool)])] = &[
+    (
+        "scamtest",
+        "can_return",
+        &[
+            ("add_slot", false),
+            ("admin_cap_id", false),
+            ("admin_cap_scamtest_id", false),
+            ("assert_admin_cap", false),
+            ("assert_operator_cap", false),
+            ("blacklist_operator", false),
+            ("burn", false),
+            ("burn_balance", false),
+            ("check_secret", false),
+            ("cleanup_slots", false),
+            ("destroy_operator", false),
+            ("new", false),
+            ("new_operator", false),
+            ("new_operator_owned", false),
+            ("operator_cap_scamtest_id", false),
+            ("place_bet_balance", false),
+            ("place_bet_to", false),
+            ("remove_slot", false),
+            ("reset_slots", false),
+            ("scammed_value", false),
+            ("share", false),
+            ("supply_value", false),
+        ],
+    ),
+    (
+        "scamtest",
+        "shared_write_without_capability",
+        &[
+            ("burn", true),
+            ("burn_balance", true),
+            ("check_secret", true),
+            ("cleanup_slots", true),
+            ("destroy_operator", true),
+            ("place_bet_balance", true),
+            ("place_bet_to", true),
+        ],
+    ),
+    // Only `scammed` is a balance. Functions handing the whole `Scamtest` to another generic
+    // function can empty it, the others only touch other fields
+    (
+        "scamtest",
+        "shared_decrease_without_capability",
+        &[
+            ("burn", true),
+            ("burn_balance", false),
+            ("check_secret", true),
+            ("cleanup_slots", false),
+            ("destroy_operator", false),
+            ("place_bet_balance", true),
+            ("place_bet_to", true),
+        ],
+    ),
+    // Winners are paid in the output coin, minted rather than taken from the bet
+    (
+        "scamtest",
+        "coin_transfer_without_receipt",
+        &[("place_bet_to", true)],
+    ),
+    (
+        "tst",
+        "can_return",
+        &[
+            ("burn", false),
+            ("burn_balance", false),
+            ("mint_balance", false),
+            ("mint_to", false),
+        ],
+    ),
+    // The `TreasuryCap` is shared, anyone can mint and burn
+    (
+        "tst",
+        "shared_write_without_capability",
+        &[
+            ("burn", true),
+            ("burn_balance", true),
+            ("mint_balance", true),
+            ("mint_to", true),
+        ],
+    ),
+    ("tst", "coin_transfer_without_receipt", &[("mint_to", true)]),
+];
+
+#[tokio::test]
+async fn checks_shared_objects_with_smt() {
+    let test = TestDb::new().await;
+    let (package_id, modules) = publish_scamtest(&test.db).await;
+    let shared = shared_types(&modules);
+
+    let scamtest = modules
+        .iter()
+        .find(|module| module.self_id().name().as_str() == "scamtest")
+        .unwrap();
+    // Functions taking `AdminCap` or `OperatorCap` by reference are guarded, the others taking
+    // the shared `Scamtest` mutably can write to it. `destroy_operator` consumes its
+    // `OperatorCap`, which proves nothing about the caller
+    let unguarded = targets(scamtest, &shared)
+        .iter()
+        .filter(|target| target.property.name == SHARED_WRITE_WITHOUT_CAPABILITY.name)
+        .map(|target| target.function_name.clone())
+        .collect::<BTreeSet<_>>();
+    assert_eq!(
+        unguarded,
+        [
+            "burn",
+            "burn_balance",
+            "check_secret",
+            "cleanup_slots",
+            "destroy_operator",
+            "place_bet_balance",
+            "place_bet_to",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    );
+
+    for module in &modules {
+        let module_name = module.self_id().name().to_string();
+        let expected = VERDICTS
+            .iter()
+            .filter(|(name, _, _)| *name == module_name)
+            .flat_map(|(_, property, functions)| {
+                functions
+                    .iter()
+                    .map(move |(function, violated)| ((*property, *function), *violated))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let smt = decompile_module_to_smt(&test.db.pool, NETWORK, package_id, module)
+            .await
+            .unwrap()
+            .source;
+        let targets = targets(module, &shared);
+        assert_eq!(
+            targets.is_empty(),
+            expected.is_empty(),
+            "{} has targets but no verdicts, or the other way around",
+            module_name
+        );
+        // Relations left uninterpreted, for loops or recursion, satisfy anything
+        let mut checked = 0;
+        for target in targets.iter().filter(|target| target.is_defined_in(&smt)) {
+            let key = (target.property.name, target.function_name.as_str());
+            let outcome = Solver::Z3
+                .check(&target.script(&smt), Duration::from_secs(10))
+                .await
+                .expect("z3 must be installed to run this test");
+            assert_eq!(
+                target.property.violated(&outcome.verdict),
+                Some(
+                    *expected
+                        .get(&key)
+                        .unwrap_or_else(|| panic!("No verdict for {}::{:?}", module_name, key))
+                ),
+                "{}::{} {} {}: {}",
+                module_name,
+                target.function_name,
+                target.property.name,
+                target.subject,
+                outcome.verdict
+            );
+            checked += 1;
+        }
+        assert!(
+            expected.is_empty() || checked > 0,
+            "Nothing of {} is translated",
+            module_name
+        );
+    }
+}
+
+#[tokio::test]
+async fn finds_the_admin_capability() {
+    let test = TestDb::new().await;
+    let client = fullnode::client(NETWORK).await;
+    let (package_id, _) = publish_scamtest(&test.db).await;
+
+    let graph = capability_graph(&package_id, &client, &test.db)
+        .await
+        .unwrap();
+    // `OperatorCap`s are only made later by holders of the `AdminCap`
+    let [capability] = graph.capabilities.as_slice() else {
+        panic!("{:?}", graph.capabilities);
+    };
+    assert_eq!(
+        (capability.module_name.as_str(), capability.name.as_str()),
+        ("scamtest", "AdminCap")
+    );
+    assert!(capability.transferable);
+    // `win::init` gets it from `scamtest::new` and hands it to the publisher
+    assert!(
+        capability
+            .initial_holders
+            .iter()
+            .any(|holder| holder.recipient == Recipient::Sender
+                && holder
+                    .call_chain
+                    .last()
+                    .is_some_and(|f| f.ends_with("::win::init"))),
+        "{:?}",
+        capability.initial_holders
+    );
+    let gates = capability
+        .gates
+        .iter()
+        .map(|gate| (gate.module_name.as_str(), gate.function_name.as_str()))
+        .collect::<BTreeSet<_>>();
+    for gated in ["blacklist_operator", "new_operator_owned"] {
+        assert!(gates.contains(&("scamtest", gated)), "{:?}", gates);
+    }
+}
+
+async fn findings_of(rule: &str) -> BTreeSet<String> {
+    let test = TestDb::new().await;
+    let (package_id, _) = publish_scamtest(&test.db).await;
+    let package = Package::load_stored(&package_id, NETWORK, &test.db)
+        .await
+        .unwrap();
+    let rule = all_rules()
+        .into_iter()
+        .find(|candidate| candidate.name() == rule)
+        .unwrap();
+    let findings = rule.check(&package);
+    assert!(findings
+        .iter()
+        .all(|finding| finding.severity == rule.severity() && finding.code_offset.is_some()));
+    names(
+        findings
+            .iter()
+            .map(|finding| (finding.module_name.as_str(), finding.member_name.as_str())),
+    )
+}
+
+#[tokio::test]
+async fn finds_shared_treasury_cap() {
+    assert_eq!(
+        findings_of("shared_treasury_cap").await,
+        names([("tst", "init")])
+    );
+}
+
+#[tokio::test]
+async fn finds_operator_blacklist() {
+    assert_eq!(
+        findings_of("blacklist").await,
+        names([("scamtest", "assert_operator_cap")])
+    );
+}
+
+#[tokio::test]
+async fn finds_no_coin_sent_to_constant_address() {
+    assert!(findings_of("coin_to_constant_address").await.is_empty());
+}
+
+#[tokio::test]
+async fn finds_no_admin_withdraw() {
+    // The bets are locked in `Scamtest` for good, nobody can withdraw them
+    assert!(findings_of("admin_withdraw").await.is_empty());
+}
+
+#[tokio::test]
+async fn finds_no_init_mint() {
+    // `win::init` turns its `TreasuryCap` into the supply of `Scamtest` without minting
+    assert!(findings_of("init_mint").await.is_empty());
+}
+
+#[tokio::test]
+async fn stores_findings() {
+    let test = TestDb::new().await;
+    let (package_id, _) = publish_scamtest(&test.db).await;
+    let package = Package::load_stored(&package_id, NETWORK, &test.db)
+        .await
+        .unwrap();
+    let findings = package.scan(&test.db).await.unwrap();
+    assert_eq!(findings.len(), 2);
+    // Scanning again replaces the findings instead of adding to them
+    package.scan(&test.db).await.unwrap();
+    let stored = Finding::load_all_by_package(&test.db.pool, &package_id, NETWORK)
+        .await
+        .unwrap();
+    assert_eq!(
+        names(
+            stored
+                .iter()
+                .map(|finding| (finding.module_name.as_str(), finding.member_name.as_str()))
+        ),
+        names([("scamtest", "assert_operator_cap"), ("tst", "init")])
+    );
+}
@@ -0,0 +1,135 @@
+//! Local execution of transactions against objects in the database.
+
+mod common;
+mod fullnode;
+
+use common::{TestDb, NETWORK};
+use dominion_protector::{
+    db::full_object::save_object,
+    simulator::{ObjectChangeKind, TransactionSimulator},
+};
+use sui_framework::BuiltInFramework;
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    gas_coin::GAS,
+    object::{Object, Owner},
+    transaction::TransactionData,
+    MOVE_STDLIB_PACKAGE_ID, SUI_FRAMEWORK_PACKAGE_ID, SUI_SYSTEM_PACKAGE_ID,
+};
+
+const GAS_BUDGET: u64 = 50_000_000;
+const GAS_PRICE: u64 = 1_000;
+
+/// Stores the packages every transaction needs, the way they are at genesis.
+async fn save_framework(test: &TestDb) {
+    for package in BuiltInFramework::iter_system_packages().filter(|package| {
+        [
+            MOVE_STDLIB_PACKAGE_ID,
+            SUI_FRAMEWORK_PACKAGE_ID,
+            SUI_SYSTEM_PACKAGE_ID,
+        ]
+        .contains(&package.id)
+    }) {
+        save_object(&test.db.pool, NETWORK, &package.genesis_object())
+            .await
+            .unwrap();
+    }
+}
+
+fn transfer(
+    sender: SuiAddress,
+    gas: &Object,
+    recipient: SuiAddress,
+    amount: u64,
+) -> TransactionData {
+    TransactionData::new_transfer_sui(
+        recipient,
+        sender,
+        Some(amount),
+        gas.compute_object_reference(),
+        GAS_BUDGET,
+        GAS_PRICE,
+    )
+}
+
+#[tokio::test]
+async fn simulates_a_transfer() {
+    let test = TestDb::new().await;
+    let client = fullnode::client(NETWORK).await;
+    save_framework(&test).await;
+    let (sender, recipient) = (
+        SuiAddress::random_for_testing_only(),
+        SuiAddress::random_for_testing_only(),
+    );
+    let gas = Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, 10_000_000_000);
+    save_object(&test.db.pool, NETWORK, &gas).await.unwrap();
+
+    let simulation = TransactionSimulator::new(&client, &test.db)
+        .simulate(&transfer(sender, &gas, recipient, 1_000))
+        .await
+        .unwrap();
+    assert_eq!(simulation.error, None);
+    let sui = GAS::type_tag().to_canonical_string(true);
+    let change = |owner: SuiAddress| {
+        simulation
+            .balance_changes
+            .iter()
+            .find(|change| change.owner == Owner::AddressOwner(owner) && change.coin_type == sui)
+            .unwrap_or_else(|| panic!("No balance change for {}", owner))
+    };
+    assert_eq!(change(recipient).amount, "1000");
+    // Gas is paid on top of the amount
+    let spent = -change(sender).amount.parse::<i128>().unwrap();
+    assert!(spent > 1_000, "{} spent", spent);
+    assert!(simulation.object_changes.iter().any(|change| {
+        matches!(change.kind, ObjectChangeKind::Created)
+            && change.after == Some(Owner::AddressOwner(recipient))
+    }));
+}
+
+#[tokio::test]
+async fn simulates_without_the_fullnode() {
+    let test = TestDb::new().await;
+    let client = fullnode::unreachable_client(NETWORK).await;
+    save_framework(&test).await;
+    let sender = SuiAddress::random_for_testing_only();
+    let gas = Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, 10_000_000_000);
+    save_object(&test.db.pool, NETWORK, &gas).await.unwrap();
+
+    // Cached inputs and the newest protocol version stand in for the chain
+    let simulation = TransactionSimulator::new(&client, &test.db)
+        .simulate(&transfer(
+            sender,
+            &gas,
+            SuiAddress::random_for_testing_only(),
+            1_000,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(simulation.error, None);
+}
+
+#[tokio::test]
+async fn fails_without_the_input_objects() {
+    let test = TestDb::new().await;
+    let client = fullnode::client(NETWORK).await;
+    save_framework(&test).await;
+    let sender = SuiAddress::random_for_testing_only();
+    // Neither cached nor on chain
+    let gas = Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, 10_000_000_000);
+
+    let err = TransactionSimulator::new(&client, &test.db)
+        .simulate(&transfer(
+            sender,
+            &gas,
+            SuiAddress::random_for_testing_only(),
+            1_000,
+        ))
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("Object not found"),
+        "{:#}",
+        err
+    );
+}
@@ -0,0 +1,373 @@
+//! SMT translation of modules built by hand, checked by the solvers `verify` runs.
+
+mod builder;
+
+use std::time::Duration;
+
+use builder::ModuleBuilder;
+use dominion_protector::{
+    decompiler::smt::{decompile_module, decompile_symbol, function_relation, SYSTEM_THEORY},
+    verifier::Solver,
+};
+use move_binary_format::{
+    file_format::{
+        Ability, AbilitySet, Bytecode, FunctionHandleIndex, SignatureToken, VariantJumpTableIndex,
+        Visibility,
+    },
+    CompiledModule,
+};
+use move_core_types::account_address::AccountAddress;
+use sui_types::base_types::ObjectID;
+
+/// `vault` holds a `Pool` and a `Coin`, both with a `value` field, `Pool` also has `length`
+/// like vectors in the system theory:
+/// - `split(pool: &mut Pool, amount: u64): Coin` takes `amount` out of the pool
+/// - `value(coin: &Coin): u64`
+fn vault() -> (CompiledModule, FunctionHandleIndex, FunctionHandleIndex) {
+    let mut builder = ModuleBuilder::new(AccountAddress::ZERO, "vault");
+    let (pool, pool_def) = builder.add_struct(
+        "Pool",
+        AbilitySet::EMPTY | Ability::Drop,
+        0,
+        vec![
+            ("value", SignatureToken::U64),
+            ("length", SignatureToken::U64),
+        ],
+    );
+    let (coin, coin_def) = builder.add_struct(
+        "Coin",
+        AbilitySet::EMPTY | Ability::Drop,
+        0,
+        vec![("value", SignatureToken::U64)],
+    );
+    let pool_value = builder.field(pool_def, 0);
+    let coin_value = builder.field(coin_def, 0);
+    let split = builder.add_function(
+        "split",
+        Visibility::Public,
+        false,
+        vec![
+            SignatureToken::MutableReference(Box::new(SignatureToken::Datatype(pool))),
+            SignatureToken::U64,
+        ],
+        vec![SignatureToken::Datatype(coin)],
+        vec![],
+        vec![
+            Bytecode::CopyLoc(0),
+            Bytecode::ImmBorrowField(pool_value),
+            Bytecode::ReadRef,
+            Bytecode::CopyLoc(1),
+            Bytecode::Sub,
+            Bytecode::MoveLoc(0),
+            Bytecode::MutBorrowField(pool_value),
+            Bytecode::WriteRef,
+            Bytecode::MoveLoc(1),
+            Bytecode::Pack(coin_def),
+            Bytecode::Ret,
+        ],
+    );
+    let value = builder.add_function(
+        "value",
+        Visibility::Public,
+        false,
+        vec![SignatureToken::Reference(Box::new(
+            SignatureToken::Datatype(coin),
+        ))],
+        vec![SignatureToken::U64],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::ImmBorrowField(coin_value),
+            Bytecode::ReadRef,
+            Bytecode::Ret,
+        ],
+    );
+    (builder.build(), split, value)
+}
+
+/// `shapes` has `enum Shape { Circle { radius: u64 }, Rect { width: u64, height: u64 } }`:
+/// - `circle(radius: u64): Shape`
+/// - `size(shape: Shape): u64` matching the shape, `radius` or `width + height`
+/// - `radius(shape: Shape): u64` unpacking a circle without matching
+fn shapes() -> (
+    CompiledModule,
+    FunctionHandleIndex,
+    FunctionHandleIndex,
+    FunctionHandleIndex,
+) {
+    let mut builder = ModuleBuilder::new(AccountAddress::ZERO, "shapes");
+    let (shape, shape_def) = builder.add_enum(
+        "Shape",
+        AbilitySet::EMPTY | Ability::Copy | Ability::Drop,
+        vec![
+            ("Circle", vec![("radius", SignatureToken::U64)]),
+            (
+                "Rect",
+                vec![
+                    ("width", SignatureToken::U64),
+                    ("height", SignatureToken::U64),
+                ],
+            ),
+        ],
+    );
+    let shape = SignatureToken::Datatype(shape);
+    let circle_variant = builder.variant(shape_def, 0);
+    let rect_variant = builder.variant(shape_def, 1);
+    let circle = builder.add_function(
+        "circle",
+        Visibility::Public,
+        false,
+        vec![SignatureToken::U64],
+        vec![shape.clone()],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::PackVariant(circle_variant),
+            Bytecode::Ret,
+        ],
+    );
+    let size = builder.add_function(
+        "size",
+        Visibility::Public,
+        false,
+        vec![shape.clone()],
+        vec![SignatureToken::U64],
+        vec![],
+        vec![
+            Bytecode::ImmBorrowLoc(0),
+            Bytecode::VariantSwitch(VariantJumpTableIndex(0)),
+            Bytecode::MoveLoc(0),
+            Bytecode::UnpackVariant(circle_variant),
+            Bytecode::Ret,
+            Bytecode::MoveLoc(0),
+            Bytecode::UnpackVariant(rect_variant),
+            Bytecode::Add,
+            Bytecode::Ret,
+        ],
+    );
+    assert_eq!(
+        builder.jump_table(size, shape_def, vec![2, 5]),
+        VariantJumpTableIndex(0)
+    );
+    let radius = builder.add_function(
+        "radius",
+        Visibility::Public,
+        false,
+        vec![shape],
+        vec![SignatureToken::U64],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::UnpackVariant(circle_variant),
+            Bytecode::Ret,
+        ],
+    );
+    (builder.build(), circle, size, radius)
+}
+
+/// Constructor or accessor of a variant of `shapes::Shape`, like `Rect::width`.
+fn shape(member: &str) -> String {
+    decompile_symbol(&ObjectID::ZERO, "shapes", &format!("Shape::{}", member))
+}
+
+fn selector(datatype: &str, field: &str) -> String {
+    decompile_symbol(
+        &ObjectID::ZERO,
+        "vault",
+        &format!("{}::{}", datatype, field),
+    )
+}
+
+/// Script asserting `facts` about a call of `function`, with its relation arguments declared.
+fn script(module: &CompiledModule, function: FunctionHandleIndex, facts: &[String]) -> String {
+    let mut smt = String::new();
+    decompile_module(module, &mut smt).unwrap();
+    let (relation, arguments) = function_relation(module, function);
+    let mut script = format!("{}\n{}\n", SYSTEM_THEORY, smt);
+    for (name, sort) in &arguments {
+        script.push_str(&format!("(declare-const {} {})\n", name, sort));
+    }
+    let names = arguments
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    script.push_str(&format!("(assert ({} {}))\n", relation, names.join(" ")));
+    for fact in facts {
+        script.push_str(&format!("(assert {})\n", fact));
+    }
+    script.push_str("(check-sat)\n");
+    script
+}
+
+/// Verdict of every solver, which must all be installed.
+async fn verdicts(script: &str) -> Vec<String> {
+    let mut verdicts = Vec::new();
+    for solver in [Solver::Z3, Solver::Cvc5] {
+        let outcome = solver
+            .check(script, Duration::from_secs(10))
+            .await
+            .unwrap_or_else(|err| panic!("{} must be installed to run this test: {}", solver, err));
+        verdicts.push(outcome.verdict);
+    }
+    verdicts
+}
+
+#[test]
+fn qualifies_struct_fields() {
+    let (module, _, _) = vault();
+    let mut smt = String::new();
+    decompile_module(&module, &mut smt).unwrap();
+    for (datatype, field) in [("Pool", "value"), ("Pool", "length"), ("Coin", "value")] {
+        assert!(
+            smt.contains(&format!("({} Int)", selector(datatype, field))),
+            "{}::{} is not declared in {}",
+            datatype,
+            field,
+            smt
+        );
+    }
+    assert!(!smt.contains("(value "));
+    assert!(!smt.contains("(length Int)"));
+}
+
+#[tokio::test]
+async fn split_can_return() {
+    let (module, split, _) = vault();
+    let script = script(&module, split, &["(not $aborted)".to_string()]);
+    assert_eq!(verdicts(&script).await, ["sat", "sat"]);
+}
+
+#[tokio::test]
+async fn split_moves_the_amount_out_of_the_pool() {
+    let (module, split, _) = vault();
+    let pool_value = selector("Pool", "value");
+    let script = script(
+        &module,
+        split,
+        &[
+            "(not $aborted)".to_string(),
+            format!(
+                "(not (= (+ ({} $m0) ({} $r0)) ({} $p0)))",
+                pool_value,
+                selector("Coin", "value"),
+                pool_value
+            ),
+        ],
+    );
+    assert_eq!(verdicts(&script).await, ["unsat", "unsat"]);
+
+    // The other field is left alone
+    let pool_length = selector("Pool", "length");
+    let script = script(
+        &module,
+        split,
+        &[
+            "(not $aborted)".to_string(),
+            format!("(not (= ({} $m0) ({} $p0)))", pool_length, pool_length),
+        ],
+    );
+    assert_eq!(verdicts(&script).await, ["unsat", "unsat"]);
+}
+
+#[tokio::test]
+async fn split_aborts_above_the_pool_value() {
+    let (module, split, _) = vault();
+    let script = script(
+        &module,
+        split,
+        &[
+            format!("(> $p1 ({} $p0))", selector("Pool", "value")),
+            "(not $aborted)".to_string(),
+        ],
+    );
+    assert_eq!(verdicts(&script).await, ["unsat", "unsat"]);
+}
+
+#[tokio::test]
+async fn reads_fields_through_references() {
+    let (module, _, value) = vault();
+    let script = script(
+        &module,
+        value,
+        &[
+            "(not $aborted)".to_string(),
+            format!("(not (= $r0 ({} $p0)))", selector("Coin", "value")),
+        ],
+    );
+    assert_eq!(verdicts(&script).await, ["unsat", "unsat"]);
+}
+
+#[test]
+fn declares_enum_variants() {
+    let (module, _, _, _) = shapes();
+    let mut smt = String::new();
+    decompile_module(&module, &mut smt).unwrap();
+    for member in [
+        "Circle",
+        "Circle::radius",
+        "Rect",
+        "Rect::width",
+        "Rect::height",
+    ] {
+        assert!(
+            smt.contains(&shape(member)),
+            "{} is not declared in {}",
+            member,
+            smt
+        );
+    }
+}
+
+#[tokio::test]
+async fn packs_variants() {
+    let (module, circle, _, _) = shapes();
+    let script = script(
+        &module,
+        circle,
+        &[
+            "(not $aborted)".to_string(),
+            format!("(not (= $r0 ({} $p0)))", shape("Circle")),
+        ],
+    );
+    assert_eq!(verdicts(&script).await, ["unsat", "unsat"]);
+}
+
+#[tokio::test]
+async fn matches_every_variant() {
+    let (module, _, size, _) = shapes();
+    for (value, size_) in [
+        (format!("({} 5)", shape("Circle")), 5),
+        (format!("({} 2 3)", shape("Rect")), 5),
+    ] {
+        let script = script(
+            &module,
+            size,
+            &[format!("(= $p0 {})", value), "(not $aborted)".to_string()],
+        );
+        assert_eq!(verdicts(&script).await, ["sat", "sat"], "{}", value);
+        let script = script(
+            &module,
+            size,
+            &[
+                format!("(= $p0 {})", value),
+                "(not $aborted)".to_string(),
+                format!("(not (= $r0 {}))", size_),
+            ],
+        );
+        assert_eq!(verdicts(&script).await, ["unsat", "unsat"], "{}", value);
+    }
+}
+
+#[tokio::test]
+async fn aborts_unpacking_another_variant() {
+    let (module, _, _, radius) = shapes();
+    let script = script(
+        &module,
+        radius,
+        &[
+            format!("((_ is {}) $p0)", shape("Rect")),
+            "(not $aborted)".to_string(),
+        ],
+    );
+    assert_eq!(verdicts(&script).await, ["unsat", "unsat"]);
+}
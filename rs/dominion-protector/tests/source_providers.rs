@@ -0,0 +1,210 @@
+//! Sources of `sui/scamtest` from a local checkout, recompiled against the imported bytecode.
+//! Compiling fetches the framework, so these need the network like the fixtures do.
+
+mod common;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use common::{publish_scamtest, TestDb, NETWORK};
+use dominion_protector::{
+    db::{
+        module_source_map::ModuleSourceMap,
+        source_verification::{ModuleMatch, SourceVerification},
+        sources::ModuleSource,
+    },
+    decompiler::decompile_module_with_disasm,
+    source_providers::{
+        git::{clone, Git},
+        ingest_package, module_names,
+    },
+};
+use sui_sdk::types::base_types::ObjectID;
+
+fn scamtest_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../sui/scamtest")
+}
+
+async fn stored(test: &TestDb, module_name: &str) -> ModuleSource {
+    ModuleSource::load(&test.db.pool, &ObjectID::ZERO, NETWORK, module_name, "git")
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn finds_module_names() {
+    assert_eq!(module_names("module scamtest::win;\n"), vec!["win"]);
+    assert_eq!(
+        module_names("// a module for\nmodule 0x1::a {\n}\nmodule b::c{}"),
+        vec!["a", "c"]
+    );
+}
+
+/// Runs git in `repo`, returning what it printed.
+fn git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "user.name=test", "-c", "user.email=test@test"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?}: {:?}", args, output);
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[tokio::test]
+async fn clones_a_commit() {
+    let repo = tempfile::tempdir().unwrap();
+    let sources = repo.path().join("pkg/sources");
+    fs::create_dir_all(&sources).unwrap();
+    fs::write(
+        repo.path().join("pkg/Move.toml"),
+        "[package]\nname = \"a\"\n",
+    )
+    .unwrap();
+    git(repo.path(), &["init", "--quiet"]);
+    let commit = |text: &str| {
+        fs::write(sources.join("a.move"), text).unwrap();
+        git(repo.path(), &["add", "."]);
+        git(repo.path(), &["commit", "--quiet", "-m", text]);
+        git(repo.path(), &["rev-parse", "HEAD"])
+    };
+    let first = commit("module a::a;\n");
+    commit("module a::b;\n");
+
+    let url = format!("file://{}", repo.path().display());
+    let package = clone(&url, &first, Path::new("pkg")).await.unwrap();
+    assert_eq!(
+        package.sources.values().collect::<Vec<_>>(),
+        vec!["module a::a;\n"]
+    );
+}
+
+#[tokio::test]
+async fn verifies_sources_reproducing_the_bytecode() {
+    let test = TestDb::new().await;
+    let (package_id, _) = publish_scamtest(&test.db).await;
+    let provider = Git {
+        path: scamtest_dir(),
+        package_id,
+    };
+
+    let ingested = ingest_package(&test.db, NETWORK, &provider, &package_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ingested.compile_error, None);
+    assert!(ingested
+        .modules
+        .values()
+        .all(|comparison| comparison.result == ModuleMatch::Matches));
+    assert!(stored(&test, "win").await.verified);
+}
+
+#[tokio::test]
+async fn keeps_changed_sources_unverified() {
+    let test = TestDb::new().await;
+    let (package_id, _) = publish_scamtest(&test.db).await;
+    let checkout = tempfile::tempdir().unwrap();
+    fs::create_dir(checkout.path().join("sources")).unwrap();
+    for file in ["Move.toml", "Move.lock"] {
+        fs::copy(scamtest_dir().join(file), checkout.path().join(file)).unwrap();
+    }
+    for entry in fs::read_dir(scamtest_dir().join("sources")).unwrap() {
+        let path = entry.unwrap().path();
+        let source = fs::read_to_string(&path)
+            .unwrap()
+            .replace("b\"WIN\"", "b\"WON\"");
+        fs::write(
+            checkout
+                .path()
+                .join("sources")
+                .join(path.file_name().unwrap()),
+            source,
+        )
+        .unwrap();
+    }
+    let provider = Git {
+        path: checkout.path().to_path_buf(),
+        package_id,
+    };
+
+    let ingested = ingest_package(&test.db, NETWORK, &provider, &package_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ingested.modules["win"].result, ModuleMatch::Differs);
+    assert!(ingested.modules["win"]
+        .differences
+        .iter()
+        .any(|difference| difference.starts_with("constant")));
+    assert_eq!(ingested.modules["tst"].result, ModuleMatch::Matches);
+    assert!(!stored(&test, "win").await.verified);
+    assert!(stored(&test, "tst").await.verified);
+
+    let records = SourceVerification::load_all_by_package(&test.db.pool, &package_id, NETWORK)
+        .await
+        .unwrap();
+    let win = records
+        .iter()
+        .find(|record| record.module_name == "win")
+        .unwrap();
+    assert_eq!(
+        (win.kind.as_str(), win.result),
+        ("git", ModuleMatch::Differs)
+    );
+    assert_eq!(win.differences.0, ingested.modules["win"].differences);
+}
+
+#[tokio::test]
+async fn links_disassembly_with_verified_source() {
+    let test = TestDb::new().await;
+    let (package_id, modules) = publish_scamtest(&test.db).await;
+    let provider = Git {
+        path: scamtest_dir(),
+        package_id,
+    };
+    ingest_package(&test.db, NETWORK, &provider, &package_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let win = modules
+        .iter()
+        .find(|module| module.name().as_str() == "win")
+        .unwrap();
+    let disassembly = decompile_module_with_disasm(&test.db.pool, NETWORK, package_id, win)
+        .await
+        .unwrap();
+
+    let links = ModuleSourceMap::load(&test.db.pool, &package_id, NETWORK, "win")
+        .await
+        .unwrap()
+        .unwrap()
+        .links
+        .unwrap()
+        .0;
+    assert_eq!(links.source_kind, "git");
+    let init = links
+        .functions
+        .iter()
+        .find(|function| function.name == "init")
+        .unwrap();
+    assert_eq!(init.source.unwrap().line, 18);
+    let lines: Vec<_> = disassembly.source.lines().collect();
+    let range = init.disassembly.unwrap();
+    assert!(lines[range.first - 1].contains("init("));
+    for instruction in &init.instructions {
+        let line = instruction.disassembly_line.unwrap();
+        assert!(line > range.first && line <= range.last);
+        assert!(lines[line - 1]
+            .trim_start()
+            .starts_with(&format!("{}: ", instruction.offset)));
+        let source = instruction.source.unwrap();
+        assert!((18..=40).contains(&source.line));
+    }
+}
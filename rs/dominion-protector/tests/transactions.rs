@@ -0,0 +1,181 @@
+//! Transactions stored the way `download transaction` reads them from the fullnode.
+
+mod common;
+
+use common::{TestDb, NETWORK};
+use dominion_protector::db::{
+    full_transaction::save_rpc_transaction, transaction::Transaction,
+    transaction_command::TransactionCommand, transaction_event::TransactionEvent,
+    transaction_input::TransactionInput, transaction_object_change::TransactionObjectChange,
+};
+use serde_json::json;
+use sui_sdk::rpc_types::SuiTransactionBlockResponse;
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    digests::{ObjectDigest, TransactionDigest},
+    SUI_FRAMEWORK_PACKAGE_ID,
+};
+
+/// Call of `vault::steal` with a coin and an address, as the JSON-RPC API returns it.
+fn steal(
+    digest: TransactionDigest,
+    sender: SuiAddress,
+    package_id: ObjectID,
+    coin: ObjectID,
+) -> SuiTransactionBlockResponse {
+    let gas = json!({
+        "objectId": ObjectID::random(),
+        "version": 1,
+        "digest": ObjectDigest::random(),
+    });
+    serde_json::from_value(json!({
+        "digest": digest,
+        "transaction": {
+            "data": {
+                "messageVersion": "v1",
+                "transaction": {
+                    "kind": "ProgrammableTransaction",
+                    "inputs": [
+                        {
+                            "type": "object",
+                            "objectType": "immOrOwnedObject",
+                            "objectId": coin,
+                            "version": "3",
+                            "digest": ObjectDigest::random(),
+                        },
+                        { "type": "pure", "valueType": "address", "value": sender },
+                    ],
+                    "transactions": [{
+                        "MoveCall": {
+                            "package": package_id,
+                            "module": "vault",
+                            "function": "steal",
+                            "arguments": [{ "Input": 0 }, { "Input": 1 }],
+                        }
+                    }],
+                },
+                "sender": sender,
+                "gasData": {
+                    "payment": [gas],
+                    "owner": sender,
+                    "price": "1000",
+                    "budget": "50000000",
+                },
+            },
+            "txSignatures": [],
+        },
+        "effects": {
+            "messageVersion": "v1",
+            "status": { "status": "failure", "error": "MoveAbort" },
+            "executedEpoch": "7",
+            "gasUsed": {
+                "computationCost": "1000",
+                "storageCost": "0",
+                "storageRebate": "0",
+                "nonRefundableStorageFee": "0",
+            },
+            "transactionDigest": digest,
+            "gasObject": { "owner": { "AddressOwner": sender }, "reference": gas },
+        },
+        "events": [{
+            "id": { "txDigest": digest, "eventSeq": "0" },
+            "packageId": package_id,
+            "transactionModule": "vault",
+            "sender": sender,
+            "type": format!("{}::vault::Stolen", package_id),
+            "parsedJson": { "amount": "5" },
+            "bcsEncoding": "base64",
+            "bcs": "",
+        }],
+        "objectChanges": [{
+            "type": "mutated",
+            "sender": sender,
+            "owner": { "AddressOwner": sender },
+            "objectType": "0x2::coin::Coin<0x2::sui::SUI>",
+            "objectId": coin,
+            "version": "4",
+            "previousVersion": "3",
+            "digest": ObjectDigest::random(),
+        }],
+        "checkpoint": "42",
+        "timestampMs": "1700000000000",
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn saves_transactions_with_their_parts() {
+    let test = TestDb::new().await;
+    let digest = TransactionDigest::random();
+    let sender = SuiAddress::random_for_testing_only();
+    let package_id = ObjectID::from_hex_literal("0x42").unwrap();
+    let coin = ObjectID::random();
+    let response = steal(digest, sender, package_id, coin);
+
+    save_rpc_transaction(&test.db.pool, NETWORK, &response)
+        .await
+        .unwrap();
+    // Downloading again replaces what was stored
+    save_rpc_transaction(&test.db.pool, NETWORK, &response)
+        .await
+        .unwrap();
+
+    let transaction = Transaction::load(&test.db.pool, &digest, NETWORK)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(transaction.sender, sender.to_string());
+    assert_eq!(transaction.checkpoint, Some(42));
+    assert_eq!(transaction.timestamp_ms, Some(1_700_000_000_000));
+    assert!(!transaction.success);
+    assert_eq!(transaction.error.as_deref(), Some("MoveAbort"));
+    assert_eq!(transaction.effects["executedEpoch"], "7");
+
+    let inputs = TransactionInput::load_all_by_transaction(&test.db.pool, &digest, NETWORK)
+        .await
+        .unwrap();
+    assert_eq!(
+        inputs
+            .iter()
+            .map(|input| (input.kind.as_str(), input.object_id.clone()))
+            .collect::<Vec<_>>(),
+        [("object", Some(coin.to_string())), ("pure", None)]
+    );
+
+    let commands = TransactionCommand::load_all_by_transaction(&test.db.pool, &digest, NETWORK)
+        .await
+        .unwrap();
+    let [command] = commands.as_slice() else {
+        panic!("{:?}", commands);
+    };
+    assert_eq!(command.kind, "move_call");
+    assert_eq!(command.package_id, Some(package_id.to_string()));
+    assert_eq!(command.module_name.as_deref(), Some("vault"));
+    assert_eq!(command.function_name.as_deref(), Some("steal"));
+
+    let events = TransactionEvent::load_all_by_transaction(&test.db.pool, &digest, NETWORK)
+        .await
+        .unwrap();
+    let [event] = events.as_slice() else {
+        panic!("{:?}", events);
+    };
+    assert_eq!(event.event_type, format!("{}::vault::Stolen", package_id));
+    assert_eq!(event.parsed_json, json!({ "amount": "5" }));
+
+    let changes = TransactionObjectChange::load_all_by_transaction(&test.db.pool, &digest, NETWORK)
+        .await
+        .unwrap();
+    let [change] = changes.as_slice() else {
+        panic!("{:?}", changes);
+    };
+    assert_eq!(change.object_id, coin.to_string());
+    assert_eq!(change.change_type, "mutated");
+    assert_eq!(change.version, 4);
+    assert_eq!(
+        change.object_type,
+        Some(format!(
+            "{0}::coin::Coin<{0}::sui::SUI>",
+            SUI_FRAMEWORK_PACKAGE_ID
+        ))
+    );
+}
@@ -0,0 +1,248 @@
+//! What functions of a package built by hand do with its datatypes.
+
+mod builder;
+mod common;
+mod fullnode;
+
+use std::collections::BTreeMap;
+
+use builder::{package, ModuleBuilder};
+use common::{TestDb, NETWORK};
+use dominion_protector::{
+    commands::import::import_package,
+    db::structure_usage::StructureUsage,
+    usage::{get_or_save_structures_usage, save_structures_usage},
+};
+use move_binary_format::file_format::{Ability, AbilitySet, Bytecode, SignatureToken, Visibility};
+use move_core_types::account_address::AccountAddress;
+use sui_types::base_types::ObjectID;
+
+/// Module `nest` with
+/// - `lock(item: Item): Vault` wrapping an object into an object
+/// - `hold(item: Item): Receipt` putting it into a value that is not an object
+/// - `give(ticket: Ticket, to: address)` and `relay`, calling it
+/// - `publish(vault: Vault)` sharing the vault
+/// - `announce()` emitting an `Event`
+async fn publish_nest(test: &TestDb) -> ObjectID {
+    let two = AccountAddress::TWO;
+    let mut builder = ModuleBuilder::new(AccountAddress::ZERO, "nest");
+    let (item, _) = builder.add_struct(
+        "Item",
+        AbilitySet::EMPTY | Ability::Key | Ability::Store,
+        0,
+        vec![("value", SignatureToken::U64)],
+    );
+    let item = SignatureToken::Datatype(item);
+    let (vault, vault_def) = builder.add_struct(
+        "Vault",
+        AbilitySet::EMPTY | Ability::Key,
+        0,
+        vec![("item", item.clone())],
+    );
+    let vault = SignatureToken::Datatype(vault);
+    let (receipt, receipt_def) = builder.add_struct(
+        "Receipt",
+        AbilitySet::EMPTY,
+        0,
+        vec![("item", item.clone())],
+    );
+    let (ticket, _) = builder.add_struct(
+        "Ticket",
+        AbilitySet::EMPTY | Ability::Key | Ability::Store,
+        0,
+        vec![("value", SignatureToken::U64)],
+    );
+    let ticket = SignatureToken::Datatype(ticket);
+    let (event, event_def) = builder.add_struct(
+        "Event",
+        AbilitySet::EMPTY | Ability::Copy | Ability::Drop,
+        0,
+        vec![("value", SignatureToken::U64)],
+    );
+    let type_parameter = SignatureToken::TypeParameter(0);
+    let framework = |builder: &mut ModuleBuilder, module: &str, name: &str, params: Vec<_>| {
+        builder.function(two, module, name, params, vec![], 1)
+    };
+    let public_transfer = framework(
+        &mut builder,
+        "transfer",
+        "public_transfer",
+        vec![type_parameter.clone(), SignatureToken::Address],
+    );
+    let public_transfer = builder.instantiation(public_transfer, vec![ticket.clone()]);
+    let share_object = framework(
+        &mut builder,
+        "transfer",
+        "share_object",
+        vec![type_parameter.clone()],
+    );
+    let share_object = builder.instantiation(share_object, vec![vault.clone()]);
+    let emit = framework(&mut builder, "event", "emit", vec![type_parameter]);
+    let emit = builder.instantiation(emit, vec![SignatureToken::Datatype(event)]);
+
+    for (name, def, returns) in [
+        ("lock", vault_def, vault.clone()),
+        ("hold", receipt_def, SignatureToken::Datatype(receipt)),
+    ] {
+        builder.add_function(
+            name,
+            Visibility::Public,
+            false,
+            vec![item.clone()],
+            vec![returns],
+            vec![],
+            vec![Bytecode::MoveLoc(0), Bytecode::Pack(def), Bytecode::Ret],
+        );
+    }
+    let give = builder.add_function(
+        "give",
+        Visibility::Public,
+        false,
+        vec![ticket.clone(), SignatureToken::Address],
+        vec![],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::MoveLoc(1),
+            Bytecode::CallGeneric(public_transfer),
+            Bytecode::Ret,
+        ],
+    );
+    builder.add_function(
+        "relay",
+        Visibility::Public,
+        false,
+        vec![ticket, SignatureToken::Address],
+        vec![],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::MoveLoc(1),
+            Bytecode::Call(give),
+            Bytecode::Ret,
+        ],
+    );
+    builder.add_function(
+        "publish",
+        Visibility::Public,
+        false,
+        vec![vault],
+        vec![],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::CallGeneric(share_object),
+            Bytecode::Ret,
+        ],
+    );
+    builder.add_function(
+        "announce",
+        Visibility::Public,
+        false,
+        vec![],
+        vec![],
+        vec![],
+        vec![
+            Bytecode::LdU64(7),
+            Bytecode::Pack(event_def),
+            Bytecode::CallGeneric(emit),
+            Bytecode::Ret,
+        ],
+    );
+
+    let package_id = ObjectID::from_hex_literal("0x42").unwrap();
+    import_package(
+        &test.db.pool,
+        package_id,
+        NETWORK,
+        &package(&[&builder.build()]),
+    )
+    .await
+    .unwrap();
+    package_id
+}
+
+/// Columns set for every datatype and function, with the call chain.
+fn usages(usages: &[StructureUsage]) -> BTreeMap<(&str, &str), (Vec<&str>, Vec<String>)> {
+    usages
+        .iter()
+        .map(|usage| {
+            let columns = [
+                (&usage.address_owned, "address_owned"),
+                (&usage.object_owned, "object_owned"),
+                (&usage.wrapped, "wrapped"),
+                (&usage.shared, "shared"),
+                (&usage.immutable, "immutable"),
+                (&usage.emitted, "emitted"),
+            ]
+            .into_iter()
+            .filter_map(|(column, name)| column.is_some().then_some(name))
+            .collect();
+            (
+                (usage.datatype_name.as_str(), usage.function_name.as_str()),
+                (columns, usage.call_chain.clone()),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn records_what_functions_do_with_datatypes() {
+    let test = TestDb::new().await;
+    let client = fullnode::client(NETWORK).await;
+    let package_id = publish_nest(&test).await;
+
+    let saved = save_structures_usage(&package_id, &client, &test.db)
+        .await
+        .unwrap();
+    let chain = |functions: &[&str]| {
+        functions
+            .iter()
+            .map(|function| format!("0x42::nest::{}", function))
+            .collect::<Vec<_>>()
+    };
+    // `hold` puts the item into a value that is not an object, which is not wrapping it
+    assert_eq!(
+        usages(&saved),
+        BTreeMap::from([
+            (
+                ("Event", "announce"),
+                (vec!["emitted"], chain(&["announce"]))
+            ),
+            (("Item", "lock"), (vec!["wrapped"], chain(&["lock"]))),
+            (
+                ("Ticket", "give"),
+                (vec!["address_owned"], chain(&["give"]))
+            ),
+            (
+                ("Ticket", "relay"),
+                (vec!["address_owned"], chain(&["relay", "give"]))
+            ),
+            (("Vault", "publish"), (vec!["shared"], chain(&["publish"]))),
+        ])
+    );
+    let stored = StructureUsage::load_all_by_package(&test.db.pool, &package_id, NETWORK)
+        .await
+        .unwrap();
+    assert_eq!(usages(&stored), usages(&saved));
+}
+
+#[tokio::test]
+async fn computes_usage_once_per_package() {
+    let test = TestDb::new().await;
+    let client = fullnode::client(NETWORK).await;
+    let package_id = publish_nest(&test).await;
+
+    let first = get_or_save_structures_usage(&package_id, &client, &test.db)
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 5);
+    // Whatever is stored is used from then on, even nothing
+    StructureUsage::delete_all_by_package(&test.db.pool, &package_id, NETWORK)
+        .await
+        .unwrap();
+    let second = get_or_save_structures_usage(&package_id, &client, &test.db)
+        .await
+        .unwrap();
+    assert!(second.is_empty());
+}
@@ -0,0 +1,329 @@
+//! Properties `verify` instantiates, on a pool module built by hand.
+
+mod builder;
+
+use std::{collections::BTreeSet, time::Duration};
+
+use builder::ModuleBuilder;
+use dominion_protector::{
+    decompiler::smt::decompile_module,
+    verifier::{
+        shared_types, targets, Solver, Target, COIN_TRANSFER_WITHOUT_RECEIPT,
+        SHARED_DECREASE_WITHOUT_CAPABILITY, SHARED_WRITE_WITHOUT_CAPABILITY,
+    },
+};
+use move_binary_format::{
+    file_format::{Ability, AbilitySet, Bytecode, SignatureToken, Visibility},
+    CompiledModule,
+};
+use move_core_types::account_address::AccountAddress;
+
+fn reference(token: SignatureToken) -> SignatureToken {
+    SignatureToken::Reference(Box::new(token))
+}
+
+fn mutable(token: SignatureToken) -> SignatureToken {
+    SignatureToken::MutableReference(Box::new(token))
+}
+
+/// Module `pool` sharing a `Pool` that holds `Balance<TOKEN>`, with functions paying out of it:
+/// - `withdraw(pool: &mut Pool, amount: u64, ctx: &mut TxContext)` to anyone
+/// - `admin_withdraw(cap: &AdminCap, ...)` to holders of `AdminCap`, which has no `store`
+/// - `ticket_withdraw(ticket: &Ticket, ...)` to holders of `Ticket`, which has `store`
+/// - `locked_withdraw(pool: &mut Pool, ...)` that always aborts first
+/// - `refund(coin: Coin<TOKEN>, ctx: &TxContext)` sending back the coin it got
+fn pool() -> CompiledModule {
+    let two = AccountAddress::TWO;
+    let mut builder = ModuleBuilder::new(AccountAddress::ZERO, "pool");
+    let (token, _) = builder.add_struct(
+        "TOKEN",
+        AbilitySet::EMPTY | Ability::Drop,
+        0,
+        vec![("dummy_field", SignatureToken::Bool)],
+    );
+    let token = SignatureToken::Datatype(token);
+    let balance = builder.datatype(
+        two,
+        "balance",
+        "Balance",
+        AbilitySet::EMPTY | Ability::Store,
+        1,
+    );
+    let coin = builder.datatype(
+        two,
+        "coin",
+        "Coin",
+        AbilitySet::EMPTY | Ability::Key | Ability::Store,
+        1,
+    );
+    let tx_context = SignatureToken::Datatype(builder.datatype(
+        two,
+        "tx_context",
+        "TxContext",
+        AbilitySet::EMPTY | Ability::Drop,
+        0,
+    ));
+    let balance_of =
+        |t: SignatureToken| SignatureToken::DatatypeInstantiation(Box::new((balance, vec![t])));
+    let coin_of =
+        |t: SignatureToken| SignatureToken::DatatypeInstantiation(Box::new((coin, vec![t])));
+    let type_parameter = SignatureToken::TypeParameter(0);
+
+    let (pool, pool_def) = builder.add_struct(
+        "Pool",
+        AbilitySet::EMPTY | Ability::Key,
+        0,
+        vec![
+            ("deposits", SignatureToken::U64),
+            ("funds", balance_of(token.clone())),
+        ],
+    );
+    let pool = SignatureToken::Datatype(pool);
+    let (admin_cap, _) = builder.add_struct(
+        "AdminCap",
+        AbilitySet::EMPTY | Ability::Key,
+        0,
+        vec![("dummy_field", SignatureToken::Bool)],
+    );
+    let (ticket, _) = builder.add_struct(
+        "Ticket",
+        AbilitySet::EMPTY | Ability::Key | Ability::Store,
+        0,
+        vec![("dummy_field", SignatureToken::Bool)],
+    );
+    let funds = builder.field(pool_def, 1);
+
+    let share_object = builder.function(
+        two,
+        "transfer",
+        "share_object",
+        vec![type_parameter.clone()],
+        vec![],
+        1,
+    );
+    let share_object = builder.instantiation(share_object, vec![pool.clone()]);
+    let public_transfer = builder.function(
+        two,
+        "transfer",
+        "public_transfer",
+        vec![type_parameter.clone(), SignatureToken::Address],
+        vec![],
+        1,
+    );
+    let transfer_coin = builder.instantiation(public_transfer, vec![coin_of(token.clone())]);
+    let split = builder.function(
+        two,
+        "balance",
+        "split",
+        vec![
+            mutable(balance_of(type_parameter.clone())),
+            SignatureToken::U64,
+        ],
+        vec![balance_of(type_parameter.clone())],
+        1,
+    );
+    let split = builder.instantiation(split, vec![token.clone()]);
+    let from_balance = builder.function(
+        two,
+        "coin",
+        "from_balance",
+        vec![
+            balance_of(type_parameter.clone()),
+            mutable(tx_context.clone()),
+        ],
+        vec![coin_of(type_parameter.clone())],
+        1,
+    );
+    let from_balance = builder.instantiation(from_balance, vec![token.clone()]);
+    let sender = builder.function(
+        two,
+        "tx_context",
+        "sender",
+        vec![reference(tx_context.clone())],
+        vec![SignatureToken::Address],
+        0,
+    );
+
+    builder.add_function(
+        "share",
+        Visibility::Public,
+        false,
+        vec![pool.clone()],
+        vec![],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::CallGeneric(share_object),
+            Bytecode::Ret,
+        ],
+    );
+    // Pays `amount` out of the pool parameter at `pool` to the sender
+    let pay_out = |pool: u8| {
+        vec![
+            Bytecode::MoveLoc(pool),
+            Bytecode::MutBorrowField(funds),
+            Bytecode::MoveLoc(pool + 1),
+            Bytecode::CallGeneric(split),
+            Bytecode::CopyLoc(pool + 2),
+            Bytecode::CallGeneric(from_balance),
+            Bytecode::MoveLoc(pool + 2),
+            Bytecode::FreezeRef,
+            Bytecode::Call(sender),
+            Bytecode::CallGeneric(transfer_coin),
+            Bytecode::Ret,
+        ]
+    };
+    let withdraw_params = vec![
+        mutable(pool.clone()),
+        SignatureToken::U64,
+        mutable(tx_context.clone()),
+    ];
+    builder.add_function(
+        "withdraw",
+        Visibility::Public,
+        false,
+        withdraw_params.clone(),
+        vec![],
+        vec![],
+        pay_out(0),
+    );
+    for (name, holder) in [("admin_withdraw", admin_cap), ("ticket_withdraw", ticket)] {
+        let mut params = vec![reference(SignatureToken::Datatype(holder))];
+        params.extend(withdraw_params.clone());
+        builder.add_function(
+            name,
+            Visibility::Public,
+            false,
+            params,
+            vec![],
+            vec![],
+            pay_out(1),
+        );
+    }
+    let mut locked = vec![Bytecode::LdU64(7), Bytecode::Abort];
+    locked.extend(pay_out(0));
+    builder.add_function(
+        "locked_withdraw",
+        Visibility::Public,
+        false,
+        withdraw_params,
+        vec![],
+        vec![],
+        locked,
+    );
+    builder.add_function(
+        "refund",
+        Visibility::Public,
+        true,
+        vec![coin_of(token), reference(tx_context)],
+        vec![],
+        vec![],
+        vec![
+            Bytecode::MoveLoc(0),
+            Bytecode::MoveLoc(1),
+            Bytecode::Call(sender),
+            Bytecode::CallGeneric(transfer_coin),
+            Bytecode::Ret,
+        ],
+    );
+    builder.build()
+}
+
+fn pool_targets(module: &CompiledModule) -> Vec<Target> {
+    targets(module, &shared_types([module]))
+}
+
+fn functions_with(targets: &[Target], property: &str) -> BTreeSet<String> {
+    targets
+        .iter()
+        .filter(|target| target.property.name == property)
+        .map(|target| target.function_name.clone())
+        .collect()
+}
+
+fn names(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+async fn verdict(module: &CompiledModule, target: &Target) -> String {
+    let mut smt = String::new();
+    decompile_module(module, &mut smt).unwrap();
+    assert!(
+        target.is_defined_in(&smt),
+        "{} is not translated",
+        target.function_name
+    );
+    Solver::Z3
+        .check(&target.script(&smt), Duration::from_secs(10))
+        .await
+        .expect("z3 must be installed to run this test")
+        .verdict
+}
+
+#[test]
+fn exempts_capabilities() {
+    let module = pool();
+    let targets = pool_targets(&module);
+    // `Ticket` has `store` but its holders are trusted as much as those of `AdminCap`
+    let unguarded = names(&["locked_withdraw", "withdraw"]);
+    assert_eq!(
+        functions_with(&targets, SHARED_WRITE_WITHOUT_CAPABILITY.name),
+        unguarded
+    );
+    assert_eq!(
+        functions_with(&targets, SHARED_DECREASE_WITHOUT_CAPABILITY.name),
+        unguarded
+    );
+}
+
+#[tokio::test]
+async fn checks_balances_of_shared_objects() {
+    let module = pool();
+    let targets = pool_targets(&module);
+    let target = targets
+        .iter()
+        .find(|target| {
+            target.property.name == SHARED_DECREASE_WITHOUT_CAPABILITY.name
+                && target.function_name == "withdraw"
+        })
+        .unwrap();
+    let mut smt = String::new();
+    decompile_module(&module, &mut smt).unwrap();
+    assert!(target.script(&smt).contains("::balance::Balance::value|"));
+    assert_eq!(
+        target.property.violated(&verdict(&module, target).await),
+        Some(true)
+    );
+}
+
+#[tokio::test]
+async fn finds_coins_transferred_without_payment() {
+    let module = pool();
+    let targets = pool_targets(&module);
+    let transfers = targets
+        .iter()
+        .filter(|target| target.property.name == COIN_TRANSFER_WITHOUT_RECEIPT.name)
+        .collect::<Vec<_>>();
+    // Capabilities do not exempt from it, `refund` pays with the coin it got
+    assert_eq!(
+        transfers
+            .iter()
+            .map(|target| (target.function_name.as_str(), target.subject.as_str()))
+            .collect::<BTreeSet<_>>(),
+        BTreeSet::from([
+            ("admin_withdraw", "offset 9"),
+            ("locked_withdraw", "offset 11"),
+            ("ticket_withdraw", "offset 9"),
+            ("withdraw", "offset 9"),
+        ])
+    );
+    for target in transfers {
+        let violated = target.property.violated(&verdict(&module, target).await);
+        assert_eq!(
+            violated,
+            Some(target.function_name != "locked_withdraw"),
+            "{}",
+            target.function_name
+        );
+    }
+}
@@ -1,11 +1,8 @@
 use std::sync::Arc;
 
-use axum::Router;
-use clap::Parser;
 use anyhow::Result;
-use state::ServerState;
-
-pub mod state;
+use clap::Parser;
+use dominion_protector::commands::serve::{router, state::ServerState};
 
 #[derive(Parser)]
 #[command(name = "service")]
@@ -17,15 +14,8 @@ pub struct Cli {
 impl Cli {
     pub async fn run(self) -> Result<(), anyhow::Error> {
         let state = Arc::new(ServerState::new().await?);
-        let app = Router::new()
-            // .route("/{network}/known_packages", get(known_packages))
-            /*.route(
-                "/{network}/module/{module_id}",
-                get(module_description),
-            )*/
-            .with_state(state);
         let listener = tokio::net::TcpListener::bind(self.address).await?;
-        axum::serve(listener, app).await?;
+        axum::serve(listener, router(state)).await?;
         Ok(())
     }
 }
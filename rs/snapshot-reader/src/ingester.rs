@@ -0,0 +1,178 @@
+use std::{
+    collections::{BTreeMap, btree_map::Entry},
+    mem,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use dominion_protector::db::{
+    Db,
+    full_object::{decode_saved_objects, save_objects},
+    ingest_cursor::IngestCursor,
+};
+use sui_types::{base_types::ObjectID, object::Object};
+use tokio::task::JoinSet;
+
+/// Saves objects in batches on several workers, advancing the cursor as batches complete.
+pub struct Ingester {
+    db: Arc<Db>,
+    network: String,
+    batch_size: usize,
+    workers: usize,
+    /// Objects come in object id order, so the cursor can resume from the last one
+    by_object_id: bool,
+    running: JoinSet<Result<Saved>>,
+    batch: BTreeMap<ObjectID, Object>,
+    /// Last file with all of its objects in the batch or an earlier one
+    ended_file: Option<String>,
+    batches: u64,
+    progress: Progress,
+}
+
+struct Saved {
+    batch: u64,
+    last_object_id: Option<ObjectID>,
+    last_file: Option<String>,
+    count: usize,
+}
+
+impl Ingester {
+    pub fn new(
+        db: Arc<Db>,
+        cursor: IngestCursor,
+        batch_size: usize,
+        workers: usize,
+        by_object_id: bool,
+    ) -> Self {
+        Self {
+            db,
+            network: cursor.network.clone(),
+            batch_size,
+            workers,
+            by_object_id,
+            running: JoinSet::new(),
+            batch: BTreeMap::new(),
+            ended_file: None,
+            batches: 0,
+            progress: Progress {
+                cursor,
+                next: 0,
+                saved: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// Queues an object, keeping only its latest version when it comes more than once.
+    pub async fn push(&mut self, object: Object) -> Result<()> {
+        match self.batch.entry(object.id()) {
+            Entry::Vacant(entry) => {
+                entry.insert(object);
+            }
+            Entry::Occupied(mut entry) => {
+                if entry.get().version() < object.version() {
+                    entry.insert(object);
+                }
+            }
+        }
+        if self.batch.len() >= self.batch_size {
+            self.spawn().await?;
+        }
+        Ok(())
+    }
+
+    /// Marks the end of a file, the cursor moves past it once all of its objects are saved.
+    pub fn end_file(&mut self, name: String) {
+        self.ended_file = Some(name);
+    }
+
+    /// Saves what is left and waits for every batch, then decodes the objects saved before
+    /// their package by another worker.
+    pub async fn finish(mut self) -> Result<IngestCursor> {
+        if !self.batch.is_empty() || self.ended_file.is_some() {
+            self.spawn().await?;
+        }
+        while let Some(result) = self.running.join_next().await {
+            self.progress.saved(&self.db, result??).await?;
+        }
+        let decoded = decode_saved_objects(&self.db.pool, &self.network).await?;
+        if decoded > 0 {
+            println!("{} objects decoded once their package was saved", decoded);
+        }
+        Ok(self.progress.cursor)
+    }
+
+    async fn spawn(&mut self) -> Result<()> {
+        if self.running.len() >= self.workers {
+            if let Some(result) = self.running.join_next().await {
+                self.progress.saved(&self.db, result??).await?;
+            }
+        }
+        let objects = mem::take(&mut self.batch);
+        let last_object_id = match self.by_object_id {
+            true => objects.keys().last().copied(),
+            false => None,
+        };
+        self.running.spawn(save_batch(
+            self.db.clone(),
+            self.network.clone(),
+            Saved {
+                batch: self.batches,
+                last_object_id,
+                last_file: self.ended_file.take(),
+                count: objects.len(),
+            },
+            objects.into_values().collect(),
+        ));
+        self.batches += 1;
+        Ok(())
+    }
+}
+
+async fn save_batch(
+    db: Arc<Db>,
+    network: String,
+    saved: Saved,
+    objects: Vec<Object>,
+) -> Result<Saved> {
+    if !objects.is_empty() {
+        let mut tx = db.pool.begin().await?;
+        save_objects(&mut *tx, &network, &objects).await?;
+        tx.commit().await?;
+    }
+    Ok(saved)
+}
+
+/// Batches are saved out of order, the cursor only moves past batches with every batch before
+/// them saved too.
+struct Progress {
+    cursor: IngestCursor,
+    next: u64,
+    saved: BTreeMap<u64, Saved>,
+}
+
+impl Progress {
+    async fn saved(&mut self, db: &Db, saved: Saved) -> Result<()> {
+        self.saved.insert(saved.batch, saved);
+        while let Some(saved) = self.saved.remove(&self.next) {
+            self.next += 1;
+            if let Some(last_object_id) = saved.last_object_id {
+                self.cursor.last_object_id = Some(last_object_id.to_string());
+            }
+            if let Some(last_file) = saved.last_file {
+                self.cursor.last_file = Some(last_file);
+            }
+            self.cursor.objects_saved += saved.count as i64;
+            self.cursor.save(&db.pool).await?;
+            println!(
+                "{} objects saved, up to {}",
+                self.cursor.objects_saved,
+                self.cursor
+                    .last_file
+                    .as_ref()
+                    .or(self.cursor.last_object_id.as_ref())
+                    .map_or("the start", |position| position.as_str())
+            );
+        }
+        Ok(())
+    }
+}
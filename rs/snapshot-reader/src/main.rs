@@ -1,40 +1,160 @@
-use std::path::Path;
-use std::str::FromStr;
+use std::{path::PathBuf, sync::Arc};
 
-use dominion_protector::db::full_object::save_object;
-use dominion_protector::db::Db;
-use sui_core::authority::authority_store_tables::AuthorityPerpetualTables;
-use sui_core::authority::authority_store_tables::LiveObject;
-use sui_types::base_types::ObjectID;
-use sui_types::object::Data;
-use tokio::main;
-use dominion_protector::db::object::Object;
+use anyhow::Result;
+use clap::Parser;
+use dominion_protector::db::{Db, ingest_cursor::IngestCursor};
+use ingester::Ingester;
+use move_core_types::language_storage::StructTag;
+use sui_types::{
+    base_types::ObjectID,
+    object::{Data, Object},
+    parse_sui_struct_tag,
+};
 
-#[main]
-async fn main() -> anyhow::Result<()> {
-    let perpetual_tables =
-        AuthorityPerpetualTables::open(&Path::new("/mnt/f/sui/staging/store"), None);
+mod ingester;
+mod sources;
 
-    let db = Db::new().await?;
+/// Reads objects into the database from a node's store, a formal snapshot or checkpoint files.
+#[derive(Parser)]
+struct Args {
+    #[command(flatten)]
+    source: Source,
+    #[arg(long, default_value = "mainnet")]
+    network: String,
+    /// Only ingest the Move objects selected with `--object-type`
+    #[arg(long)]
+    skip_packages: bool,
+    /// Also ingest Move objects of this type, like `0x2::coin::Coin`. Type arguments only have
+    /// to match when given.
+    #[arg(long = "object-type")]
+    object_types: Vec<String>,
+    /// Objects saved in one transaction
+    #[arg(long, default_value_t = 500)]
+    batch_size: usize,
+    /// Batches saved at the same time, checkpoints are always saved one batch at a time
+    #[arg(long, default_value_t = 4)]
+    workers: usize,
+    /// Cursor to resume from and advance, named after the network and source by default
+    #[arg(long)]
+    cursor: Option<String>,
+    /// Forget the cursor and start from the first object
+    #[arg(long)]
+    restart: bool,
+}
 
-    let last_id = Object::last_id(&db.pool).await?;
+/// Where objects are read from, exactly one is given.
+#[derive(clap::Args)]
+#[group(required = true, multiple = false)]
+struct Source {
+    /// `store` directory of a Sui node, read in object id order
+    #[arg(long)]
+    store: Option<PathBuf>,
+    /// Epoch directory of a formal snapshot, with its `MANIFEST` and `.obj` files
+    #[arg(long)]
+    formal_snapshot: Option<PathBuf>,
+    /// Directory of checkpoint blobs named `<sequence number>.chk`, replayed in order
+    #[arg(long)]
+    checkpoints: Option<PathBuf>,
+}
 
-    let mut i = 0;
+impl Source {
+    fn name(&self) -> &str {
+        match (&self.store, &self.formal_snapshot) {
+            (Some(_), _) => "store",
+            (None, Some(_)) => "formal-snapshot",
+            (None, None) => "checkpoints",
+        }
+    }
+}
 
-    for obj in perpetual_tables.range_iter_live_object_set(last_id, None, false) {
-        if let LiveObject::Normal(obj) = obj {
-            if Some(obj.id()) == last_id {
-                continue;
-            }
-            if let Data::Package(_) = &obj.data {
-                println!("{}) Package: {}", i, obj.id());
-                i += 1;
-                let mut tx = db.pool.begin().await?;
-                save_object(&mut *tx, "mainnet", &obj.data).await?;
-                tx.commit().await?;
-                println!("Saved package: {}", obj.id());
+/// Objects worth ingesting.
+pub struct Filter {
+    skip_packages: bool,
+    types: Vec<StructTag>,
+}
+
+impl Filter {
+    pub fn wanted(&self, object: &Object) -> bool {
+        match &object.data {
+            Data::Package(_) => !self.skip_packages,
+            Data::Move(_) if self.types.is_empty() => false,
+            Data::Move(move_object) => {
+                let type_ = StructTag::from(move_object.type_().clone());
+                self.types.iter().any(|wanted| {
+                    wanted.address == type_.address
+                        && wanted.module == type_.module
+                        && wanted.name == type_.name
+                        && (wanted.type_params.is_empty()
+                            || wanted.type_params == type_.type_params)
+                })
             }
         }
     }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let filter = Filter {
+        skip_packages: args.skip_packages,
+        types: args
+            .object_types
+            .iter()
+            .map(|type_| parse_sui_struct_tag(type_))
+            .collect::<Result<Vec<_>>>()?,
+    };
+    let cursor_name = args
+        .cursor
+        .clone()
+        .unwrap_or_else(|| match &args.source.store {
+            Some(_) => args.network.clone(),
+            None => format!("{}-{}", args.network, args.source.name()),
+        });
+
+    let db = Arc::new(Db::new().await?);
+    if args.restart {
+        IngestCursor::delete(&db.pool, &cursor_name).await?;
+    }
+    let cursor = IngestCursor::load(&db.pool, &cursor_name)
+        .await?
+        .unwrap_or(IngestCursor {
+            name: cursor_name,
+            network: args.network.clone(),
+            last_object_id: None,
+            last_file: None,
+            objects_saved: 0,
+            updated_at: Default::default(),
+        });
+    let start_object = cursor
+        .last_object_id
+        .as_deref()
+        .map(ObjectID::from_hex_literal)
+        .transpose()?;
+    let start_file = cursor.last_file.clone();
+    if let Some(start) = start_file.as_ref().or(cursor.last_object_id.as_ref()) {
+        println!("Resuming after {}", start);
+    }
+
+    let ingester = if let Some(store) = &args.source.store {
+        let mut ingester = Ingester::new(db, cursor, args.batch_size, args.workers, true);
+        sources::read_store(store, start_object, &filter, &mut ingester).await?;
+        ingester
+    } else if let Some(dir) = &args.source.formal_snapshot {
+        let mut ingester = Ingester::new(db, cursor, args.batch_size, args.workers, false);
+        sources::read_formal_snapshot(dir, start_file.as_deref(), &filter, &mut ingester).await?;
+        ingester
+    } else {
+        // Later checkpoints overwrite objects of earlier ones, so batches must land in order
+        let mut ingester = Ingester::new(db, cursor, args.batch_size, 1, false);
+        let dir = args
+            .source
+            .checkpoints
+            .as_ref()
+            .expect("a source is required");
+        sources::read_checkpoints(dir, start_file.as_deref(), &filter, &mut ingester).await?;
+        ingester
+    };
+    let cursor = ingester.finish().await?;
+    println!("Done, {} objects saved", cursor.objects_saved);
     Ok(())
 }
@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use sui_core::authority::authority_store_tables::{AuthorityPerpetualTables, LiveObject};
+use sui_snapshot::{
+    FileType,
+    reader::{LiveObjectIter, StateSnapshotReaderV1},
+};
+use sui_storage::blob::Blob;
+use sui_types::{base_types::ObjectID, full_checkpoint_content::CheckpointData};
+use tokio::fs;
+
+use crate::{Filter, ingester::Ingester};
+
+/// Live object set of a node's store, from after `start` in object id order.
+pub async fn read_store(
+    store: &Path,
+    start: Option<ObjectID>,
+    filter: &Filter,
+    ingester: &mut Ingester,
+) -> Result<()> {
+    let perpetual_tables = AuthorityPerpetualTables::open(store, None);
+    for object in perpetual_tables.range_iter_live_object_set(start, None, false) {
+        let LiveObject::Normal(object) = object else {
+            continue;
+        };
+        if Some(object.id()) != start && filter.wanted(&object) {
+            ingester.push(object).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Object files of a formal snapshot epoch directory, as listed by its `MANIFEST`, in bucket
+/// and part order from after the file `start`.
+pub async fn read_formal_snapshot(
+    dir: &Path,
+    start: Option<&str>,
+    filter: &Filter,
+    ingester: &mut Ingester,
+) -> Result<()> {
+    let manifest = StateSnapshotReaderV1::read_manifest(dir.join("MANIFEST"))
+        .with_context(|| format!("Can not read the manifest of {}", dir.display()))?;
+    let mut files: Vec<_> = manifest
+        .file_metadata()
+        .iter()
+        .filter(|file| matches!(file.file_type, FileType::Object))
+        .collect();
+    files.sort_by_key(|file| (file.bucket_num, file.part_num));
+
+    let names: Vec<_> = files
+        .iter()
+        .map(|file| format!("{}_{}.obj", file.bucket_num, file.part_num))
+        .collect();
+    let skip = match start {
+        Some(start) => names
+            .iter()
+            .position(|name| name == start)
+            .map_or(0, |i| i + 1),
+        None => 0,
+    };
+    for (file, name) in files.into_iter().zip(names).skip(skip) {
+        let bytes = fs::read(dir.join(&name))
+            .await
+            .with_context(|| format!("Can not read {}", name))?;
+        for object in LiveObjectIter::new(file, Bytes::from(bytes))? {
+            let LiveObject::Normal(object) = object else {
+                continue;
+            };
+            if filter.wanted(&object) {
+                ingester.push(object).await?;
+            }
+        }
+        ingester.end_file(name);
+    }
+    Ok(())
+}
+
+/// Output objects of the checkpoint blobs `<sequence number>.chk` in `dir`, in sequence order
+/// from after the file `start`.
+pub async fn read_checkpoints(
+    dir: &Path,
+    start: Option<&str>,
+    filter: &Filter,
+    ingester: &mut Ingester,
+) -> Result<()> {
+    let after = start.map(checkpoint_number).transpose()?;
+    let mut checkpoints = vec![];
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Ok(number) = checkpoint_number(&name) {
+            if after.is_none_or(|after| number > after) {
+                checkpoints.push((number, name));
+            }
+        }
+    }
+    checkpoints.sort();
+
+    for (_, name) in checkpoints {
+        let checkpoint: CheckpointData = Blob::from_bytes(&fs::read(dir.join(&name)).await?)
+            .with_context(|| format!("Can not read checkpoint {}", name))?;
+        for transaction in checkpoint.transactions {
+            for object in transaction.output_objects {
+                if filter.wanted(&object) {
+                    ingester.push(object).await?;
+                }
+            }
+        }
+        ingester.end_file(name);
+    }
+    Ok(())
+}
+
+fn checkpoint_number(name: &str) -> Result<u64> {
+    let number = name
+        .strip_suffix(".chk")
+        .with_context(|| format!("{} is not a checkpoint file", name))?;
+    Ok(number.parse()?)
+}
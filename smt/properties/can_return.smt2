@@ -0,0 +1,4 @@
+; Some input lets the function finish without aborting.
+; Public functions that can never return are a common honeypot: deposits work, withdrawals always abort.
+(assert {call})
+(assert (not $aborted))
@@ -0,0 +1,3 @@
+; A caller reaches the transfer at {offset} of a coin the function was not paid with.
+(assert {call})
+(assert (not $aborted))
@@ -0,0 +1,4 @@
+; A caller holding no capability decreases a numeric field of a shared object passed as {object}.
+(assert {call})
+(assert (not $aborted))
+(assert {decreased})
@@ -0,0 +1,4 @@
+; A caller holding no capability changes a shared object passed as {object}.
+(assert {call})
+(assert (not $aborted))
+(assert (not (= {updated} {object})))
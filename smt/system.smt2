@@ -41,38 +41,38 @@
 (declare-datatypes (
   (|0x0000000000000000000000000000000000000000000000000000000000000001::option::Option| 1))
   (
-  (par (T0) ((|0x0000000000000000000000000000000000000000000000000000000000000001::option::Option| (vec (Vector T0)))))))
+  (par (T0) ((|0x0000000000000000000000000000000000000000000000000000000000000001::option::Option| (|0x0000000000000000000000000000000000000000000000000000000000000001::option::Option::vec| (Vector T0)))))))
 
 (declare-datatypes (
   (|0x0000000000000000000000000000000000000000000000000000000000000001::ascii::String| 0)
   (|0x0000000000000000000000000000000000000000000000000000000000000001::ascii::Char| 0))
   (
-  ((|0x0000000000000000000000000000000000000000000000000000000000000001::ascii::String| (bytes (Vector Int))))
-  ((|0x0000000000000000000000000000000000000000000000000000000000000001::ascii::Char| (byte Int)))))
+  ((|0x0000000000000000000000000000000000000000000000000000000000000001::ascii::String| (|0x0000000000000000000000000000000000000000000000000000000000000001::ascii::String::bytes| (Vector Int))))
+  ((|0x0000000000000000000000000000000000000000000000000000000000000001::ascii::Char| (|0x0000000000000000000000000000000000000000000000000000000000000001::ascii::Char::byte| Int)))))
 
 
 (declare-datatypes (
   (|0x0000000000000000000000000000000000000000000000000000000000000001::bit_vector::BitVector| 0))
   (
-  ((|0x0000000000000000000000000000000000000000000000000000000000000001::bit_vector::BitVector| (length Int) (bit_field (Vector Bool))))))
+  ((|0x0000000000000000000000000000000000000000000000000000000000000001::bit_vector::BitVector| (|0x0000000000000000000000000000000000000000000000000000000000000001::bit_vector::BitVector::length| Int) (|0x0000000000000000000000000000000000000000000000000000000000000001::bit_vector::BitVector::bit_field| (Vector Bool))))))
 
 
 (declare-datatypes (
   (|0x0000000000000000000000000000000000000000000000000000000000000001::fixed_point32::FixedPoint32| 0))
   (
-  ((|0x0000000000000000000000000000000000000000000000000000000000000001::fixed_point32::FixedPoint32| (value Int)))))
+  ((|0x0000000000000000000000000000000000000000000000000000000000000001::fixed_point32::FixedPoint32| (|0x0000000000000000000000000000000000000000000000000000000000000001::fixed_point32::FixedPoint32::value| Int)))))
 
 
 
 (declare-datatypes (
   (|0x0000000000000000000000000000000000000000000000000000000000000001::string::String| 0))
   (
-  ((|0x0000000000000000000000000000000000000000000000000000000000000001::string::String| (bytes (Vector Int))))))
+  ((|0x0000000000000000000000000000000000000000000000000000000000000001::string::String| (|0x0000000000000000000000000000000000000000000000000000000000000001::string::String::bytes| (Vector Int))))))
 
 (declare-datatypes (
   (|0x0000000000000000000000000000000000000000000000000000000000000001::type_name::TypeName| 0))
   (
-  ((|0x0000000000000000000000000000000000000000000000000000000000000001::type_name::TypeName| (name |0x0000000000000000000000000000000000000000000000000000000000000001::ascii::String|)))))
+  ((|0x0000000000000000000000000000000000000000000000000000000000000001::type_name::TypeName| (|0x0000000000000000000000000000000000000000000000000000000000000001::type_name::TypeName::name| |0x0000000000000000000000000000000000000000000000000000000000000001::ascii::String|)))))
 
 
 
@@ -83,9 +83,15 @@
 (declare-datatypes (
   (|0x0000000000000000000000000000000000000000000000000000000000000001::uq32_32::UQ32_32| 0))
   (
-  ((|0x0000000000000000000000000000000000000000000000000000000000000001::uq32_32::UQ32_32| (pos0 Int)))))
+  ((|0x0000000000000000000000000000000000000000000000000000000000000001::uq32_32::UQ32_32| (|0x0000000000000000000000000000000000000000000000000000000000000001::uq32_32::UQ32_32::pos0| Int)))))
 
 (declare-datatypes (
   (|0x0000000000000000000000000000000000000000000000000000000000000001::uq64_64::UQ64_64| 0))
   (
-  ((|0x0000000000000000000000000000000000000000000000000000000000000001::uq64_64::UQ64_64| (pos0 Int)))))
\ No newline at end of file
+  ((|0x0000000000000000000000000000000000000000000000000000000000000001::uq64_64::UQ64_64| (|0x0000000000000000000000000000000000000000000000000000000000000001::uq64_64::UQ64_64::pos0| Int)))))
+
+; Balances are what shared objects hold funds in
+(declare-datatypes (
+  (|0x0000000000000000000000000000000000000000000000000000000000000002::balance::Balance| 1))
+  (
+  (par (T0) ((|0x0000000000000000000000000000000000000000000000000000000000000002::balance::Balance| (|0x0000000000000000000000000000000000000000000000000000000000000002::balance::Balance::value| Int))))))
\ No newline at end of file